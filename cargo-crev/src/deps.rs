@@ -7,6 +7,7 @@ use crate::{opts::*, prelude::*, shared::*, term};
 use cargo::core::PackageId;
 use std::{
     collections::{HashMap, HashSet},
+    fmt,
     ops::Add,
 };
 
@@ -53,6 +54,50 @@ where
     }
 }
 
+/// Verification result of a crate, as displayed
+///
+/// Ordered from the worst, so the status of a crate with
+/// its dependencies is the `min` of their statuses.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, PartialOrd, Ord)]
+pub enum TrustStatus {
+    Negative,
+    Insufficient,
+    Verified,
+    /// Local source code, not verified at all
+    Local,
+}
+
+impl TrustStatus {
+    pub fn is_verified(self) -> bool {
+        self == TrustStatus::Verified
+    }
+}
+
+impl From<&VerificationStatus> for TrustStatus {
+    fn from(status: &VerificationStatus) -> Self {
+        match status {
+            VerificationStatus::Verified => TrustStatus::Verified,
+            VerificationStatus::Distrusted { .. } | VerificationStatus::OpenIssues { .. } => {
+                TrustStatus::Negative
+            }
+            VerificationStatus::DigestMismatch
+            | VerificationStatus::NoReviews
+            | VerificationStatus::InsufficientReviews { .. } => TrustStatus::Insufficient,
+        }
+    }
+}
+
+impl fmt::Display for TrustStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrustStatus::Local => f.pad("locl"),
+            TrustStatus::Verified => f.pad("pass"),
+            TrustStatus::Insufficient => f.pad("none"),
+            TrustStatus::Negative => f.pad("warn"),
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 pub struct DownloadsStats {
     pub version: u64,
@@ -129,7 +174,7 @@ impl std::ops::Add<OwnerSetSet> for OwnerSetSet {
 /// by recursively including dependencies
 #[derive(Clone, Debug)]
 pub struct AccumulativeCrateDetails {
-    pub trust: VerificationStatus,
+    pub trust: TrustStatus,
     pub trusted_issues: CountWithTotal,
     pub verified: bool,
     pub loc: Option<u64>,
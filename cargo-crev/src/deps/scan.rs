@@ -2,6 +2,7 @@ use crate::{
     crates_io,
     deps::{
         AccumulativeCrateDetails, CountWithTotal, CrateDetails, CrateInfo, CrateStats, OwnerSetSet,
+        TrustStatus,
     },
     opts::{CargoOpts, CrateSelector, CrateVerify},
     prelude::*,
@@ -14,7 +15,6 @@ use crate::{
 use cargo::core::PackageId;
 use crev_common::convert::OptionDeref;
 use crev_data::proof::{self, CommonOps};
-use crev_lib::{self, VerificationStatus};
 use crev_wot::{self, *};
 use crossbeam::{
     self,
@@ -252,9 +252,14 @@ impl Scanner {
             .map(|digest| !is_digest_clean(&self.db, &pkg_name, &pkg_version, &digest))
             .unwrap_or(false);
         let verification_result = if let Some(digest) = digest.as_ref() {
-            crev_lib::verify_package_digest(&digest, &self.trust_set, &self.requirements, &self.db)
+            TrustStatus::from(&crev_lib::verify_package_digest(
+                &digest,
+                &self.trust_set,
+                &self.requirements,
+                &self.db,
+            ))
         } else {
-            VerificationStatus::Local
+            TrustStatus::Local
         };
         let verified = verification_result.is_verified();
 
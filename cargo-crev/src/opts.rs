@@ -128,6 +128,7 @@ impl From<TrustDistanceParams> for crev_lib::TrustDistanceParams {
             high_trust_distance: params.high_cost,
             medium_trust_distance: params.medium_cost,
             low_trust_distance: params.low_cost,
+            ..Default::default()
         }
     }
 }
@@ -183,10 +184,13 @@ pub struct VerificationRequirements {
 impl From<VerificationRequirements> for crev_lib::VerificationRequirements {
     fn from(req: VerificationRequirements) -> Self {
         crev_lib::VerificationRequirements {
-            trust_level: req.trust_level.trust_level,
-            redundancy: req.redundancy,
+            trust_level: req.trust_level.trust_level.into(),
+            redundancy: req.redundancy as usize,
             understanding: req.understanding_level,
             thoroughness: req.thoroughness_level,
+            // open issues are reported on their own
+            max_issue_severity: Level::High,
+            ..Default::default()
         }
     }
 }
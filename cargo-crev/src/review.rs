@@ -134,7 +134,10 @@ pub fn create_review_proof(
         .cloned()
         .unwrap_or_default();
 
-    review.alternatives = db.get_pkg_alternatives_by_author(&id.id.id, &review.package.id.id);
+    review.alternatives = db
+        .get_pkg_alternatives_by_author(&id.id.id, &review.package.id.id)
+        .into_iter()
+        .collect();
 
     let review = edit::edit_proof_content_iteractively(
         &review,
@@ -1,4 +1,4 @@
-use crev_lib::VerificationStatus;
+use crate::deps::TrustStatus;
 use std::{
     fmt::Arguments,
     io::{self, Write},
@@ -9,8 +9,8 @@ use term::{
     StderrTerminal, StdoutTerminal,
 };
 
-pub fn verification_status_color(s: VerificationStatus) -> Option<color::Color> {
-    use VerificationStatus::*;
+pub fn verification_status_color(s: TrustStatus) -> Option<color::Color> {
+    use TrustStatus::*;
     match s {
         Verified | Local => Some(term::color::GREEN),
         Insufficient => None,
@@ -5,12 +5,11 @@ use termimad::{
 };
 
 use crate::{
-    deps::{latest_trusted_version_string, CrateStats, Progress},
+    deps::{latest_trusted_version_string, CrateStats, Progress, TrustStatus},
     opts::CargoOpts,
     prelude::*,
     repo::Repo,
 };
-use crev_lib::VerificationStatus;
 use lazy_static::lazy_static;
 
 /// the styles that can be applied to cells of the dep list
@@ -111,12 +110,10 @@ impl<'t> VerifyScreen<'t> {
                 6,
                 6,
                 Box::new(|dep: &CrateStats| match dep.details.accumulative.trust {
-                    VerificationStatus::Local => ListViewCell::new("locl".to_owned(), &TS.good),
-                    VerificationStatus::Verified => ListViewCell::new("pass".to_owned(), &TS.good),
-                    VerificationStatus::Insufficient => {
-                        ListViewCell::new("none".to_owned(), &TS.none)
-                    }
-                    VerificationStatus::Negative => ListViewCell::new("fail".to_owned(), &TS.bad),
+                    TrustStatus::Local => ListViewCell::new("locl".to_owned(), &TS.good),
+                    TrustStatus::Verified => ListViewCell::new("pass".to_owned(), &TS.good),
+                    TrustStatus::Insufficient => ListViewCell::new("none".to_owned(), &TS.none),
+                    TrustStatus::Negative => ListViewCell::new("fail".to_owned(), &TS.bad),
                 }),
             ),
             ListViewColumn::new(
@@ -85,6 +85,30 @@ where
     serializer.serialize_str(&key.to_rfc3339())
 }
 
+/// Like `from_rfc3339_fixed`, for optional fields (with `#[serde(default)]`)
+pub fn from_rfc3339_fixed_opt<'d, D>(
+    deserializer: D,
+) -> Result<Option<chrono::DateTime<FixedOffset>>, D::Error>
+where
+    D: serde::Deserializer<'d>,
+{
+    from_rfc3339_fixed(deserializer).map(Some)
+}
+
+/// Like `as_rfc3339_fixed`, for optional fields (with `skip_serializing_if = "Option::is_none"`)
+pub fn as_rfc3339_fixed_opt<S>(
+    key: &Option<chrono::DateTime<FixedOffset>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    match key {
+        Some(key) => as_rfc3339_fixed(key, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
 impl MyTryFromBytes for Vec<u8> {
     type Err = io::Error;
     fn try_from(slice: &[u8]) -> Result<Self, Self::Err> {
@@ -1,6 +1,6 @@
 use std::fmt;
 
-#[derive(Eq, PartialEq, Debug, Clone)]
+#[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Clone)]
 pub struct Digest(Vec<u8>);
 
 impl Digest {
@@ -158,6 +158,18 @@ impl PublicId {
             .map_err(|e| crate::Error::BuildingProof(e.into()))?)
     }
 
+    /// Declare that this Id has been replaced by `superseded_by`
+    pub fn create_supersession_proof(
+        &self,
+        superseded_by: &PublicId,
+    ) -> crate::Result<proof::IdSupersession> {
+        Ok(proof::IdSupersessionBuilder::default()
+            .from(self.clone())
+            .superseded_by(superseded_by.clone())
+            .build()
+            .map_err(|e| crate::Error::BuildingProof(e.into()))?)
+    }
+
     pub fn create_package_review_proof(
         &self,
         package: proof::PackageInfo,
@@ -252,4 +264,13 @@ impl UnlockedId {
     ) -> crate::Result<proof::Proof> {
         self.id.create_trust_proof(ids, trust_level)?.sign_by(&self)
     }
+
+    pub fn create_signed_supersession_proof(
+        &self,
+        superseded_by: &PublicId,
+    ) -> crate::Result<proof::Proof> {
+        self.id
+            .create_supersession_proof(superseded_by)?
+            .sign_by(&self)
+    }
 }
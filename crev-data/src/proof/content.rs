@@ -98,6 +98,8 @@ pub enum ValidationError {
     AdvisoriesWithNoIDSAreNotAllowed,
     #[error("Advisories with an empty `id` field are not allowed")]
     AdvisoriesWithAnEmptyIDFieldAreNotAllowed,
+    #[error("Retractions can't contain a review, issues or advisories")]
+    RetractionsCanNotContainReviews,
 }
 
 pub type ValidationResult<T> = std::result::Result<T, ValidationError>;
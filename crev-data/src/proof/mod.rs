@@ -13,12 +13,14 @@ use std::{
     io::{self, BufRead},
     mem,
 };
+pub use supersession::*;
 pub use trust::*;
 
 pub mod content;
 pub mod package_info;
 pub mod review;
 pub mod revision;
+pub mod supersession;
 pub mod trust;
 
 const MAX_PROOF_BODY_LENGTH: usize = 32_000;
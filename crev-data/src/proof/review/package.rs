@@ -85,6 +85,24 @@ pub struct Package {
     #[serde(skip_serializing_if = "String::is_empty", default = "Default::default")]
     #[builder(default = "Default::default()")]
     pub comment: String,
+    /// The author no longer stands behind any review of this package version
+    ///
+    /// A retraction replaces the author's previous review, just like any newer
+    /// review would, but it doesn't carry any review, issues or advisories itself.
+    #[serde(default = "Default::default", skip_serializing_if = "is_equal_default")]
+    #[builder(default = "Default::default()")]
+    pub retracted: bool,
+    /// The review is no longer in effect from this date on
+    ///
+    /// Like any review, it is replaced by a newer review of the version by the same author.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default = "Default::default",
+        serialize_with = "crev_common::serde::as_rfc3339_fixed_opt",
+        deserialize_with = "crev_common::serde::from_rfc3339_fixed_opt"
+    )]
+    #[builder(default = "Default::default()")]
+    pub expires: Option<proof::Date>,
 }
 
 impl PackageBuilder {
@@ -202,6 +220,12 @@ impl proof::Content for Package {
                 }
             }
         }
+
+        if self.retracted
+            && (!self.review.is_none() || !self.issues.is_empty() || !self.advisories.is_empty())
+        {
+            Err(ValidationError::RetractionsCanNotContainReviews)?;
+        }
         Ok(())
     }
 
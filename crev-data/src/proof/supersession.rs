@@ -0,0 +1,74 @@
+use crate::{
+    proof::{self, content::ValidationResult, CommonOps, Content},
+    serde_content_serialize,
+};
+
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+
+const CURRENT_ID_SUPERSESSION_PROOF_SERIALIZATION_VERSION: i64 = -1;
+
+fn cur_version() -> i64 {
+    CURRENT_ID_SUPERSESSION_PROOF_SERIALIZATION_VERSION
+}
+
+/// Body of an Id Supersession Proof
+///
+/// Signed by the old Id (`from`), to let others know it has been replaced
+/// by `superseded_by`, e.g. after rotating the key.
+#[derive(Clone, Debug, Builder, Serialize, Deserialize)]
+pub struct IdSupersession {
+    #[serde(flatten)]
+    pub common: proof::Common,
+    #[serde(rename = "superseded-by")]
+    pub superseded_by: crate::PublicId,
+    #[serde(skip_serializing_if = "String::is_empty", default = "Default::default")]
+    #[builder(default = "Default::default()")]
+    pub comment: String,
+}
+
+impl IdSupersessionBuilder {
+    pub fn from<VALUE: Into<crate::PublicId>>(&mut self, value: VALUE) -> &mut Self {
+        if let Some(ref mut common) = self.common {
+            common.from = value.into();
+        } else {
+            self.common = Some(proof::Common {
+                kind: Some(IdSupersession::KIND.into()),
+                version: cur_version(),
+                date: crev_common::now(),
+                from: value.into(),
+            });
+        }
+        self
+    }
+}
+
+impl fmt::Display for IdSupersession {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.serialize_to(f).map_err(|_| fmt::Error)
+    }
+}
+
+impl proof::CommonOps for IdSupersession {
+    fn common(&self) -> &proof::Common {
+        &self.common
+    }
+}
+
+impl IdSupersession {
+    pub const KIND: &'static str = "id supersession";
+}
+
+impl proof::Content for IdSupersession {
+    fn serialize_to(&self, fmt: &mut dyn std::fmt::Write) -> fmt::Result {
+        serde_content_serialize!(self, fmt);
+        Ok(())
+    }
+
+    fn validate_data(&self) -> ValidationResult<()> {
+        self.ensure_kind_is(Self::KIND)?;
+        Ok(())
+    }
+}
@@ -79,6 +79,21 @@ pub struct Trust {
     #[serde(skip_serializing_if = "String::is_empty", default = "Default::default")]
     #[builder(default = "Default::default()")]
     pub comment: String,
+    /// Package sources (like `https://crates.io`) the trust is limited to;
+    /// if empty, the trust is not limited
+    #[serde(skip_serializing_if = "Vec::is_empty", default = "Default::default")]
+    #[builder(default = "Default::default()")]
+    pub sources: Vec<String>,
+    /// The trust is no longer in effect from this date on, unless re-affirmed
+    /// by a newer trust proof
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default = "Default::default",
+        serialize_with = "crev_common::serde::as_rfc3339_fixed_opt",
+        deserialize_with = "crev_common::serde::from_rfc3339_fixed_opt"
+    )]
+    #[builder(default = "Default::default()")]
+    pub expires: Option<proof::Date>,
 }
 
 impl TrustBuilder {
@@ -124,6 +139,8 @@ pub struct Draft {
     pub trust: TrustLevel,
     #[serde(default = "Default::default", skip_serializing_if = "String::is_empty")]
     comment: String,
+    #[serde(default = "Default::default", skip_serializing_if = "Vec::is_empty")]
+    sources: Vec<String>,
 }
 
 impl From<Trust> for Draft {
@@ -131,6 +148,7 @@ impl From<Trust> for Draft {
         Draft {
             trust: trust.trust,
             comment: trust.comment,
+            sources: trust.sources,
         }
     }
 }
@@ -183,6 +201,7 @@ impl proof::ContentWithDraft for Trust {
         let mut copy = self.clone();
         copy.trust = draft.trust;
         copy.comment = draft.comment;
+        copy.sources = draft.sources;
 
         copy.validate_data()?;
         Ok(copy)
@@ -1,6 +1,6 @@
 use crate::{
     id::UnlockedId,
-    proof::{self, ContentExt, Proof},
+    proof::{self, CommonOps, ContentExt, Proof},
     Error, Result, Url,
 };
 use semver::Version;
@@ -240,3 +240,18 @@ pub fn ensure_serializes_to_valid_proof_works() -> Result<()> {
     assert!(package.ensure_serializes_to_valid_proof().is_err());
     Ok(())
 }
+
+#[test]
+pub fn supersession_proof_roundtrip() -> Result<()> {
+    let old = UnlockedId::generate_for_git_url("https://old");
+    let new = UnlockedId::generate_for_git_url("https://new");
+
+    let proof = old.create_signed_supersession_proof(new.as_public_id())?;
+    proof.verify()?;
+    assert_eq!(proof.kind(), proof::IdSupersession::KIND);
+
+    let parsed: proof::IdSupersession = proof.parse_content()?;
+    assert_eq!(parsed.common.from.id, old.id.id);
+    assert_eq!(parsed.superseded_by, *new.as_public_id());
+    Ok(())
+}
@@ -9,20 +9,14 @@ pub mod staging;
 pub mod util;
 pub use crate::local::Local;
 pub use activity::{ReviewActivity, ReviewMode};
-use crev_data::{
-    self,
-    id::IdError,
-    proof::{
-        review::{self, Rating},
-        trust::TrustLevel,
-        CommonOps,
-    },
-    Digest, Id,
+use crev_data::{self, id::IdError, Digest};
+pub use crev_wot::{
+    verification::{VerificationRequirements, VerificationStatus},
+    TrustDistanceParams,
 };
-pub use crev_wot::TrustDistanceParams;
 use semver::Version;
 use std::{
-    collections::{HashMap, HashSet},
+    collections::HashSet,
     fmt,
     path::{Path, PathBuf},
 };
@@ -167,106 +161,16 @@ impl TrustProofType {
     }
 }
 
-/// Verification requirements
-#[derive(Clone, Debug)]
-pub struct VerificationRequirements {
-    pub trust_level: crev_data::Level,
-    pub understanding: crev_data::Level,
-    pub thoroughness: crev_data::Level,
-    pub redundancy: u64,
-}
-
-impl Default for VerificationRequirements {
-    fn default() -> Self {
-        VerificationRequirements {
-            trust_level: Default::default(),
-            understanding: Default::default(),
-            thoroughness: Default::default(),
-            redundancy: 1,
-        }
-    }
-}
-/// Result of verification
+/// Check whether the package with a given `digest` meets the `requirements`
 ///
-/// Not named `Result` to avoid confusion with `Result` type.
-#[derive(Copy, Clone, PartialEq, Eq, Debug, PartialOrd, Ord)]
-pub enum VerificationStatus {
-    Negative,
-    Insufficient,
-    Verified,
-    Local,
-}
-
-impl VerificationStatus {
-    pub fn is_verified(self) -> bool {
-        match self {
-            VerificationStatus::Verified => true,
-            _ => false,
-        }
-    }
-
-    pub fn min(self, other: Self) -> Self {
-        if self < other {
-            self
-        } else if other < self {
-            other
-        } else {
-            self
-        }
-    }
-}
-
-impl fmt::Display for VerificationStatus {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            VerificationStatus::Local => f.pad("locl"),
-            VerificationStatus::Verified => f.pad("pass"),
-            VerificationStatus::Insufficient => f.pad("none"),
-            VerificationStatus::Negative => f.pad("warn"),
-        }
-    }
-}
-
+/// See `crev_wot::ProofDB::verify_package_digest`.
 pub fn verify_package_digest(
     digest: &Digest,
     trust_set: &crev_wot::TrustSet,
     requirements: &VerificationRequirements,
     db: &crev_wot::ProofDB,
 ) -> VerificationStatus {
-    let reviews: HashMap<Id, review::Package> = db
-        .get_package_reviews_by_digest(digest)
-        .map(|review| (review.from().id.clone(), review))
-        .collect();
-    // Faster somehow maybe?
-    let reviews_by: HashSet<Id, _> = reviews.keys().cloned().collect();
-    let trusted_ids: HashSet<_> = trust_set.trusted_ids().cloned().collect();
-    let matching_reviewers = trusted_ids.intersection(&reviews_by);
-    let mut trust_count = 0;
-    let mut negative_count = 0;
-    for matching_reviewer in matching_reviewers {
-        let review = &reviews[matching_reviewer].review_possibly_none();
-        if !review.is_none()
-            && Rating::Neutral <= review.rating
-            && requirements.thoroughness <= review.thoroughness
-            && requirements.understanding <= review.understanding
-        {
-            if TrustLevel::from(requirements.trust_level)
-                <= trust_set.get_effective_trust_level(matching_reviewer)
-            {
-                trust_count += 1;
-            }
-        } else if review.rating <= Rating::Negative {
-            negative_count += 1;
-        }
-    }
-
-    if negative_count > 0 {
-        VerificationStatus::Negative
-    } else if trust_count >= requirements.redundancy {
-        VerificationStatus::Verified
-    } else {
-        VerificationStatus::Insufficient
-    }
+    db.verify_package_digest(digest, trust_set, requirements)
 }
 
 pub fn find_latest_trusted_version(
@@ -1,14 +1,22 @@
 use super::*;
 use crev_data::{
-    proof::{self, trust::TrustLevel, ContentExt},
-    Digest, Level, UnlockedId, Url,
+    proof::{self, trust::TrustLevel, CommonOps, ContentExt},
+    Digest, Id, Level, UnlockedId, Url,
 };
 use crev_wot::{FetchSource, ProofDB};
 use default::default;
 use semver::Version;
 use std::{str::FromStr, sync::Arc};
 
+mod compat;
 mod issues;
+mod merge;
+mod ordering;
+mod reviews;
+mod sources;
+mod trust;
+mod urls;
+mod verification;
 
 // Basic liftime of an `LockedId`:
 //
@@ -114,6 +122,7 @@ fn proofdb_distance() -> Result<()> {
         medium_trust_distance: 10,
         low_trust_distance: 100,
         max_distance: 111,
+        ..default()
     };
 
     let a_to_b = a.create_signed_trust_proof(vec![b.as_public_id()], TrustLevel::High)?;
@@ -199,7 +208,7 @@ fn overwritting_reviews() -> Result<()> {
         assert_eq!(
             trustdb
                 .get_package_reviews_by_digest(&Digest::from_vec(digest.clone()))
-                .map(|r| r.comment)
+                .map(|r| r.comment.clone())
                 .collect::<Vec<_>>(),
             vec!["b".to_string()]
         );
@@ -264,8 +273,9 @@ fn dont_consider_an_empty_review_as_valid() -> Result<()> {
     let verification_reqs = VerificationRequirements {
         thoroughness: Level::None,
         understanding: Level::None,
-        trust_level: Level::None,
+        trust_level: TrustLevel::None,
         redundancy: 1,
+        ..default()
     };
     assert!(!verify_package_digest(
         &Digest::from_vec(digest),
@@ -292,6 +302,7 @@ fn proofdb_distrust() -> Result<()> {
         medium_trust_distance: 10,
         low_trust_distance: 100,
         max_distance: 10000,
+        ..default()
     };
 
     let a_to_bc =
@@ -320,8 +331,9 @@ fn proofdb_distrust() -> Result<()> {
     assert!(trust_set.contains(d.as_ref()));
     assert!(!trust_set.contains(e.as_ref()));
 
-    // This introduces a tie between nodes banning each other.
-    // Both should be removed from the trust_set.
+    // Now c and d ban each other (through e). Nodes with higher trust
+    // are visited first, so e (high trust) bans d (low trust), before
+    // d gets a chance to ban c.
     let e_to_d = e.create_signed_trust_proof(vec![d.as_public_id()], TrustLevel::Distrust)?;
 
     trustdb.import_from_iter(vec![(e_to_d, url)].into_iter());
@@ -334,9 +346,9 @@ fn proofdb_distrust() -> Result<()> {
 
     assert!(trust_set.contains(a.as_ref()));
     assert!(trust_set.contains(b.as_ref()));
-    assert!(!trust_set.contains(c.as_ref()));
+    assert!(trust_set.contains(c.as_ref()));
     assert!(!trust_set.contains(d.as_ref()));
-    assert!(!trust_set.contains(e.as_ref()));
+    assert!(trust_set.contains(e.as_ref()));
 
     Ok(())
 }
@@ -0,0 +1,202 @@
+use super::*;
+
+use crev_data::{proof::CommonOps, TrustLevel, UnlockedId};
+use crev_wot::{FetchSource, ProofDB};
+
+/// A validly signed proof of a kind no crev version knows about
+fn build_future_proof(id: &UnlockedId, other: &UnlockedId) -> Result<proof::Proof> {
+    let trust = id.create_signed_trust_proof(vec![other.as_public_id()], TrustLevel::High)?;
+    let body = trust
+        .body()
+        .replace("kind: trust", "kind: package review v3");
+    let signature = crev_common::base64_encode(&id.sign(body.as_bytes()));
+    let proof = proof::Proof::from_parts(body, signature)?;
+    assert_eq!(proof.kind(), "package review v3");
+    proof.verify()?;
+    Ok(proof)
+}
+
+#[test]
+fn unknown_proof_kinds_are_stored_on_request() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+
+    let proofs = vec![
+        build_future_proof(&a, &b)?,
+        build_future_proof(&b, &a)?,
+        a.create_signed_trust_proof(vec![b.as_public_id()], TrustLevel::High)?,
+    ];
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(proofs.clone().into_iter().map(|x| (x, url.clone())));
+    assert!(trustdb.unknown_proofs().is_empty());
+    assert_eq!(trustdb.unique_trust_proof_count(), 1);
+
+    let mut trustdb = ProofDB::new_storing_unknown_proofs();
+    trustdb.import_from_iter(proofs.into_iter().map(|x| (x, url.clone())));
+    assert_eq!(trustdb.unknown_proofs().len(), 2);
+    assert_eq!(
+        trustdb
+            .unknown_proof_count_by_kind()
+            .into_iter()
+            .collect::<Vec<_>>(),
+        vec![("package review v3", 2)]
+    );
+    // known proofs are handled as usual
+    assert_eq!(trustdb.unique_trust_proof_count(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn unknown_proofs_can_be_taken_for_reimport() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let c = UnlockedId::generate_for_git_url("https://c");
+
+    let known = a.create_signed_trust_proof(vec![b.as_public_id()], TrustLevel::High)?;
+    let future = vec![build_future_proof(&a, &b)?, build_future_proof(&c, &a)?];
+
+    let mut trustdb = ProofDB::new();
+    trustdb.set_store_unknown_proofs(true);
+    let import = |trustdb: &mut ProofDB, proofs: Vec<proof::Proof>| {
+        trustdb.import_from_iter(proofs.into_iter().map(|x| (x, url.clone())));
+    };
+    import(&mut trustdb, vec![known.clone()]);
+    import(&mut trustdb, future.clone());
+    // importing the same proofs again doesn't store them twice
+    import(&mut trustdb, future.clone());
+    assert_eq!(
+        trustdb
+            .unknown_proof_count_by_kind()
+            .into_iter()
+            .collect::<Vec<_>>(),
+        vec![("package review v3", 2)]
+    );
+    // `c` is only known from its proof of an unknown kind
+    assert_eq!(
+        trustdb.lookup_url(c.as_ref()).from_self(),
+        c.id.url.as_ref()
+    );
+
+    assert!(trustdb.take_unknown_proofs("repo review").is_empty());
+    let taken = trustdb.take_unknown_proofs("package review v3");
+    assert_eq!(taken.len(), 2);
+    assert!(trustdb.unknown_proofs().is_empty());
+    // known proofs are not affected
+    assert_eq!(trustdb.unique_trust_proof_count(), 1);
+    assert_eq!(
+        trustdb
+            .get_direct_trust_for_id(a.as_ref(), None)
+            .map(|direct| direct.id.clone())
+            .collect::<Vec<_>>(),
+        vec![b.id.id.clone()]
+    );
+
+    // taken proofs can be imported again
+    import(
+        &mut trustdb,
+        taken.into_iter().map(|(proof, _)| proof).collect(),
+    );
+    assert_eq!(trustdb.unknown_proofs().len(), 2);
+
+    // unless unknown proofs are not stored anymore
+    trustdb.take_unknown_proofs("package review v3");
+    trustdb.set_store_unknown_proofs(false);
+    import(&mut trustdb, future);
+    assert!(trustdb.unknown_proofs().is_empty());
+    assert_eq!(trustdb.unique_trust_proof_count(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn import_report_lists_rejected_proofs() -> Result<()> {
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let mirror = FetchSource::Url(Arc::new(crev_data::Url::new_git("https://mirror")));
+
+    let valid = a.create_signed_trust_proof(vec![b.as_public_id()], TrustLevel::High)?;
+    let forged = {
+        let other = a.create_signed_trust_proof(vec![a.as_public_id()], TrustLevel::Low)?;
+        proof::Proof::from_parts(valid.body().to_owned(), other.signature().to_owned())?
+    };
+
+    let mut trustdb = ProofDB::new();
+    let report = trustdb.import_from_iter_with_report(
+        vec![
+            (valid, FetchSource::LocalUser),
+            (build_future_proof(&a, &b)?, mirror.clone()),
+            (forged, mirror),
+        ]
+        .into_iter(),
+    );
+
+    assert_eq!(
+        report.accepted.into_iter().collect::<Vec<_>>(),
+        vec![("trust".to_owned(), 1)]
+    );
+    assert!(report.unknown.is_empty());
+    assert_eq!(report.self_trust, 0);
+    assert_eq!(report.rejected.len(), 2);
+    assert!(report
+        .rejected
+        .iter()
+        .all(|(source, _)| source.name() == "https://mirror"));
+    assert!(matches!(
+        &report.rejected[0].1,
+        crev_wot::Error::UnknownProofType(kind) if &**kind == "package review v3"
+    ));
+    assert!(matches!(&report.rejected[1].1, crev_wot::Error::Data(_)));
+
+    assert_eq!(trustdb.unique_trust_proof_count(), 1);
+    assert_eq!(
+        trustdb
+            .get_direct_trust_for_id(a.as_ref(), None)
+            .map(|direct| direct.id.clone())
+            .collect::<Vec<_>>(),
+        vec![b.id.id.clone()]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn import_report_counts_unknown_and_self_trust_proofs() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+
+    let proofs = vec![
+        build_future_proof(&a, &b)?,
+        build_future_proof(&b, &a)?,
+        a.create_signed_trust_proof(vec![b.as_public_id()], TrustLevel::High)?,
+        b.create_signed_trust_proof(vec![a.as_public_id(), b.as_public_id()], TrustLevel::Low)?,
+    ];
+
+    let mut trustdb = ProofDB::new_storing_unknown_proofs();
+    let report =
+        trustdb.import_from_iter_with_report(proofs.clone().into_iter().map(|x| (x, url.clone())));
+    assert_eq!(
+        report.accepted.into_iter().collect::<Vec<_>>(),
+        vec![("trust".to_owned(), 2)]
+    );
+    assert_eq!(
+        report.unknown.into_iter().collect::<Vec<_>>(),
+        vec![("package review v3".to_owned(), 2)]
+    );
+    assert_eq!(report.self_trust, 1);
+    assert!(report.rejected.is_empty());
+
+    // without storing them, proofs of unknown kinds are rejected instead
+    let mut trustdb = ProofDB::new();
+    let report = trustdb.import_from_iter_with_report(proofs.into_iter().map(|x| (x, url.clone())));
+    assert_eq!(report.accepted.values().sum::<usize>(), 2);
+    assert!(report.unknown.is_empty());
+    assert_eq!(report.self_trust, 1);
+    assert_eq!(report.rejected.len(), 2);
+
+    Ok(())
+}
@@ -5,11 +5,13 @@ use crev_data::{
     review::{Advisory, Issue, VersionRange},
     TrustLevel, UnlockedId,
 };
-use crev_wot::FetchSource;
+use crev_wot::{FetchSource, ReviewFilter};
 use semver::Version;
+use std::collections::BTreeSet;
 
 const SOURCE: &str = "SOURCE_ID";
 const NAME: &str = "name";
+const DIGEST: [u8; 32] = [1; 32];
 
 fn build_advisory(id: impl Into<String>, range: VersionRange) -> Advisory {
     let id = id.into();
@@ -35,7 +37,7 @@ fn build_proof_with_advisories(
 ) -> proof::Proof {
     let package_info = proof::PackageInfo {
         id: proof::PackageVersionId::new(SOURCE.into(), NAME.into(), version),
-        digest: vec![0, 1, 2, 3],
+        digest: DIGEST.to_vec(),
         digest_type: proof::default_digest_type(),
         revision: "".into(),
         revision_type: proof::default_revision_type(),
@@ -54,7 +56,7 @@ fn build_proof_with_advisories(
 fn build_proof_with_issues(id: &UnlockedId, version: Version, issues: Vec<Issue>) -> proof::Proof {
     let package_info = proof::PackageInfo {
         id: proof::PackageVersionId::new("SOURCE_ID".to_owned(), NAME.into(), version),
-        digest: vec![0, 1, 2, 3],
+        digest: DIGEST.to_vec(),
         digest_type: proof::default_digest_type(),
         revision: "".into(),
         revision_type: proof::default_revision_type(),
@@ -328,3 +330,676 @@ fn issues_sanity() -> Result<()> {
     );
     Ok(())
 }
+
+#[test]
+fn issues_for_digest_with_disagreeing_versions() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let id = UnlockedId::generate_for_git_url("https://a");
+    let mut trustdb = ProofDB::new();
+    let trust_set = trustdb.calculate_trust_set(id.as_ref(), &TrustDistanceParams::new_no_wot());
+
+    // both reviews are of the same digest, but claim different versions
+    trustdb.import_from_iter(
+        vec![
+            build_proof_with_issues(
+                &id,
+                Version::parse("1.0.0").unwrap(),
+                vec![build_issue("issueA")],
+            ),
+            build_proof_with_issues(
+                &id,
+                Version::parse("2.0.0").unwrap(),
+                vec![build_issue("issueB")],
+            ),
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+    let digest = Digest::from_vec(DIGEST.to_vec());
+
+    let versions = trustdb.lookup_package_version_by_digest(&digest);
+    assert_eq!(
+        versions
+            .iter()
+            .map(|v| v.version.to_string())
+            .collect::<Vec<_>>(),
+        vec!["1.0.0", "2.0.0"]
+    );
+    assert!(versions.iter().all(|v| v.id.name == NAME));
+
+    let issues_v1 = trustdb.get_open_issues_for_version(
+        SOURCE,
+        NAME,
+        &Version::parse("1.0.0").unwrap(),
+        &trust_set,
+        TrustLevel::Medium,
+    );
+    assert_eq!(issues_v1.keys().collect::<Vec<_>>(), vec!["issueA"]);
+
+    let found = trustdb.get_open_issues_for_digest(&digest, &trust_set, TrustLevel::Medium);
+    assert!(found.version_mismatch());
+    assert_eq!(found.versions, versions);
+    assert_eq!(
+        found.issues.keys().collect::<Vec<_>>(),
+        vec!["issueA", "issueB"]
+    );
+    assert_eq!(found.issues["issueA"].issues.len(), 1);
+
+    let unknown = Digest::from_vec(vec![9; 32]);
+    assert!(trustdb
+        .lookup_package_version_by_digest(&unknown)
+        .is_empty());
+    let found = trustdb.get_open_issues_for_digest(&unknown, &trust_set, TrustLevel::Medium);
+    assert!(found.issues.is_empty());
+    assert!(!found.version_mismatch());
+
+    Ok(())
+}
+
+#[test]
+fn advisories_affecting_matches_open_issues() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let id = UnlockedId::generate_for_git_url("https://a");
+    let mut trustdb = ProofDB::new();
+    let trust_set = trustdb.calculate_trust_set(id.as_ref(), &TrustDistanceParams::new_no_wot());
+
+    // same advisories as in `issues_sanity`
+    trustdb.import_from_iter(
+        vec![
+            build_proof_with_advisories(
+                &id,
+                Version::parse("1.2.3").unwrap(),
+                vec![build_advisory("issueX", VersionRange::Major)],
+            ),
+            build_proof_with_advisories(
+                &id,
+                Version::parse("2.0.1").unwrap(),
+                vec![build_advisory("issueY", VersionRange::All)],
+            ),
+            build_proof_with_advisories(
+                &id,
+                Version::parse("3.1.0").unwrap(),
+                vec![build_advisory("issueX", VersionRange::Major)],
+            ),
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+
+    let advisory_ids = |db: &ProofDB, version: &Version| {
+        db.advisories_affecting(SOURCE, NAME, version, &trust_set, TrustLevel::Medium)
+            .into_iter()
+            .flat_map(|summary| summary.ids.iter().cloned())
+            .collect::<BTreeSet<_>>()
+    };
+    let open_ids = |db: &ProofDB, version: &Version| {
+        db.get_open_issues_for_version(SOURCE, NAME, version, &trust_set, TrustLevel::Medium)
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect::<BTreeSet<_>>()
+    };
+
+    let versions: Vec<_> = vec![
+        "0.0.1", "1.0.1", "1.2.3", "1.3.0", "2.0.0", "2.0.1", "3.0.4", "3.0.5", "3.0.7", "3.1.0",
+        "4.0.0",
+    ]
+    .into_iter()
+    .map(|v| Version::parse(v).unwrap())
+    .collect();
+    for version in &versions {
+        assert_eq!(
+            advisory_ids(&trustdb, version),
+            open_ids(&trustdb, version),
+            "{}",
+            version
+        );
+    }
+
+    let affecting = trustdb.advisories_affecting(
+        SOURCE,
+        NAME,
+        &Version::parse("1.0.1").unwrap(),
+        &trust_set,
+        TrustLevel::Medium,
+    );
+    let mut fixed_in: Vec<_> = affecting
+        .iter()
+        .map(|summary| (summary.ids[0].as_str(), summary.fixed_in.to_string()))
+        .collect();
+    fixed_in.sort();
+    assert_eq!(
+        fixed_in,
+        vec![
+            ("issueX", "1.2.3".to_owned()),
+            ("issueY", "2.0.1".to_owned())
+        ]
+    );
+    let review = affecting[0].review;
+    assert!(trustdb.is_version_affected_by_advisory(review, 0, &Version::parse("1.0.1").unwrap()));
+    assert!(!trustdb.is_version_affected_by_advisory(review, 1, &Version::parse("1.0.1").unwrap()));
+
+    // issue reported in 3.0.5 is fixed by the 3.1.0 advisory
+    trustdb.import_from_iter(
+        vec![(
+            build_proof_with_issues(
+                &id,
+                Version::parse("3.0.5").unwrap(),
+                vec![build_issue("issueX")],
+            ),
+            url,
+        )]
+        .into_iter(),
+    );
+    for version in &versions {
+        assert!(advisory_ids(&trustdb, version).is_subset(&open_ids(&trustdb, version)));
+    }
+    assert!(open_ids(&trustdb, &Version::parse("3.0.7").unwrap()).contains("issueX"));
+    assert!(open_ids(&trustdb, &Version::parse("3.1.0").unwrap()).is_empty());
+    assert!(advisory_ids(&trustdb, &Version::parse("3.1.0").unwrap()).is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn open_advisories_across_trust_set() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let c = UnlockedId::generate_for_git_url("https://c");
+    let version = |v: &str| Version::parse(v).unwrap();
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            a.as_public_id()
+                .create_trust_proof(vec![b.as_public_id()], TrustLevel::Low)?
+                .sign_by(&a)?,
+            a.as_public_id()
+                .create_trust_proof(vec![c.as_public_id()], TrustLevel::Distrust)?
+                .sign_by(&a)?,
+            build_proof_with_advisories(&a, version("1.0.0"), vec![]),
+            build_proof_with_advisories(
+                &a,
+                version("1.1.0"),
+                vec![build_advisory("issueY", VersionRange::All)],
+            ),
+            build_proof_with_advisories(
+                &a,
+                version("1.2.0"),
+                vec![build_advisory("issueX", VersionRange::Major)],
+            ),
+            build_proof_with_advisories(
+                &b,
+                version("1.2.1"),
+                vec![build_advisory("issueX", VersionRange::Major)],
+            ),
+            // reported again after the advisory, so it's not fixed anymore
+            build_proof_with_issues(&a, version("1.3.0"), vec![build_issue("issueY")]),
+            build_proof_with_advisories(
+                &c,
+                version("1.3.0"),
+                vec![build_advisory("issueW", VersionRange::All)],
+            ),
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+    let trust_set = trustdb.calculate_trust_set(a.as_ref(), &default());
+
+    let open = trustdb.get_open_advisories(SOURCE, &trust_set, TrustLevel::Low);
+    assert_eq!(open.keys().collect::<Vec<_>>(), vec![NAME]);
+    let advisories = &open[NAME];
+    let ids: Vec<_> = advisories.iter().map(|a| a.ids[0].as_str()).collect();
+    assert_eq!(ids, vec!["issueX", "issueY"]);
+
+    // open below the fix, cancelled above it
+    let issue_x = &advisories[0];
+    assert_eq!(issue_x.reporter, &a.id.id);
+    assert_eq!(issue_x.effective_trust_level, TrustLevel::High);
+    assert_eq!(issue_x.fixed_in, &version("1.2.0"));
+    assert_eq!(issue_x.range, VersionRange::Major);
+    assert_eq!(issue_x.severity, Advisory::default().severity);
+    assert!(issue_x.fixed);
+    let open_ids = |v: &str| {
+        trustdb
+            .get_open_issues_for_version(SOURCE, NAME, &version(v), &trust_set, TrustLevel::Low)
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(open_ids("1.0.0"), vec!["issueX", "issueY"]);
+    assert_eq!(open_ids("1.3.0"), vec!["issueY"]);
+
+    let issue_y = &advisories[1];
+    assert_eq!(issue_y.range, VersionRange::All);
+    assert!(!issue_y.fixed);
+
+    // only the most trusted reporters count
+    let open = trustdb.get_open_advisories(SOURCE, &trust_set, TrustLevel::Medium);
+    assert_eq!(open[NAME].len(), 2);
+    assert!(open[NAME]
+        .iter()
+        .all(|advisory| advisory.reporter == &a.id.id));
+    assert!(trustdb
+        .get_open_advisories(SOURCE, &trust_set, TrustLevel::Distrust)
+        .values()
+        .flatten()
+        .all(|advisory| advisory.reporter != &c.id.id));
+
+    Ok(())
+}
+
+#[test]
+fn stale_reviews_filtered_by_age() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let version = Version::parse("1.0.0").unwrap();
+    let years_ago = chrono::Utc::now() - chrono::Duration::days(4 * 365);
+
+    let mut trust = a
+        .as_public_id()
+        .create_trust_proof(vec![b.as_public_id()], TrustLevel::High)?;
+    trust.common.date = years_ago.into();
+    let mut old_review = proof::review::PackageBuilder::default()
+        .from(b.id.to_owned())
+        .package(proof::PackageInfo {
+            id: proof::PackageVersionId::new(SOURCE.into(), NAME.into(), version.clone()),
+            digest: DIGEST.to_vec(),
+            digest_type: proof::default_digest_type(),
+            revision: "".into(),
+            revision_type: proof::default_revision_type(),
+        })
+        .comment("old".into())
+        .issues(vec![build_issue("old-issue")])
+        .build()
+        .unwrap();
+    old_review.common.date = years_ago.into();
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            trust.sign_by(&a)?,
+            old_review.sign_by(&b)?,
+            build_proof_with_issues(&a, version.clone(), vec![build_issue("new-issue")]),
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+    let trust_set = trustdb.calculate_trust_set(a.as_ref(), &default());
+    // the trust proof is just as old, but still counts
+    assert_eq!(
+        trust_set.get_effective_trust_level(b.as_ref()),
+        TrustLevel::High
+    );
+
+    let reviewers = |filter: &ReviewFilter| {
+        trustdb
+            .get_pkg_reviews_for_version_filtered(SOURCE, NAME, &version, filter)
+            .map(|review| review.from().id.clone())
+            .collect::<BTreeSet<_>>()
+    };
+    let open_ids = |filter: &ReviewFilter| {
+        trustdb
+            .get_open_issues_for_version_filtered(
+                SOURCE,
+                NAME,
+                &version,
+                &trust_set,
+                TrustLevel::Low,
+                filter,
+            )
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>()
+    };
+
+    // the default filter changes nothing
+    assert_eq!(
+        reviewers(&ReviewFilter::default()),
+        trustdb
+            .get_pkg_reviews_for_version(SOURCE, NAME, &version)
+            .map(|review| review.from().id.clone())
+            .collect()
+    );
+    assert_eq!(reviewers(&ReviewFilter::default()).len(), 2);
+    assert_eq!(
+        open_ids(&ReviewFilter::default()),
+        vec!["new-issue", "old-issue"]
+    );
+
+    let fresh = ReviewFilter {
+        max_age: Some(chrono::Duration::days(365)),
+        ..ReviewFilter::default()
+    };
+    assert_eq!(
+        reviewers(&fresh),
+        vec![a.id.id.clone()].into_iter().collect()
+    );
+    assert_eq!(
+        trustdb
+            .get_pkg_reviews_for_name_filtered(SOURCE, NAME, &fresh)
+            .count(),
+        1
+    );
+    assert_eq!(open_ids(&fresh), vec!["new-issue"]);
+
+    let since = ReviewFilter {
+        min_date: Some(years_ago - chrono::Duration::days(1)),
+        ..ReviewFilter::default()
+    };
+    assert_eq!(reviewers(&since).len(), 2);
+
+    Ok(())
+}
+
+#[test]
+fn open_issue_severity_and_fix() -> Result<()> {
+    use crev_data::Level;
+
+    let url = FetchSource::LocalUser;
+    let root = UnlockedId::generate_for_git_url("https://root");
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let c = UnlockedId::generate_for_git_url("https://c");
+    let v = |s| Version::parse(s).unwrap();
+    let issue = |severity| Issue::builder().id("X".into()).severity(severity).build();
+    let advisory = |severity| {
+        Advisory::builder()
+            .ids(vec!["X".into()])
+            .severity(severity)
+            .build()
+    };
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            root.create_signed_trust_proof(
+                vec![a.as_public_id(), b.as_public_id(), c.as_public_id()],
+                TrustLevel::High,
+            )?,
+            build_proof_with_issues(&a, v("1.0.0"), vec![issue(Level::Low)]),
+            build_proof_with_issues(&b, v("1.0.0"), vec![issue(Level::High)]),
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+    let trust_set = trustdb.calculate_trust_set(root.as_ref(), &default());
+    let open = |trustdb: &ProofDB, version| {
+        trustdb.get_open_issues_for_version(SOURCE, NAME, &v(version), &trust_set, TrustLevel::Low)
+    };
+
+    let issues = open(&trustdb, "1.1.0");
+    let details = &issues["X"];
+    assert_eq!(details.severity, Level::High);
+    assert_eq!(details.fixed_in, None);
+    assert_eq!(
+        details.reporting_ids,
+        vec![a.id.id.clone(), b.id.id.clone()].into_iter().collect()
+    );
+
+    // fixed twice; the oldest fix counts
+    trustdb.import_from_iter(
+        vec![
+            build_proof_with_advisories(&c, v("2.0.0"), vec![advisory(Level::Medium)]),
+            build_proof_with_advisories(&a, v("1.5.0"), vec![advisory(Level::Low)]),
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+    let issues = open(&trustdb, "1.1.0");
+    let details = &issues["X"];
+    // the issue reports are superseded by the advisories
+    assert_eq!(details.severity, Level::Medium);
+    assert_eq!(details.fixed_in, Some(v("1.5.0")));
+    assert!(details.reporting_ids.contains(c.as_ref()));
+    assert!(!details.reporting_ids.contains(b.as_ref()));
+    assert_eq!(open(&trustdb, "1.7.0")["X"].fixed_in, Some(v("2.0.0")));
+    assert!(open(&trustdb, "2.0.0").is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn build_metadata_version_matching() -> Result<()> {
+    use crev_wot::VersionMatchPolicy;
+
+    let url = FetchSource::LocalUser;
+    let root = UnlockedId::generate_for_git_url("https://root");
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let v = |s| Version::parse(s).unwrap();
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            root.create_signed_trust_proof(vec![a.as_public_id()], TrustLevel::High)?,
+            build_proof_with_issues(&a, v("1.2.3+foo"), vec![build_issue("X")]),
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+    let trust_set = trustdb.calculate_trust_set(root.as_ref(), &default());
+    let open = |version, policy| {
+        trustdb
+            .get_open_issues_for_version_matching(
+                SOURCE,
+                NAME,
+                &v(version),
+                &trust_set,
+                TrustLevel::Low,
+                policy,
+            )
+            .len()
+    };
+    let gte = |version, policy| {
+        trustdb
+            .get_pkg_reviews_gte_version_matching(SOURCE, NAME, &v(version), policy)
+            .count()
+    };
+
+    // by default, build metadata is ignored
+    assert_eq!(open("1.2.3", VersionMatchPolicy::default()), 1);
+    assert_eq!(
+        trustdb
+            .get_open_issues_for_version(SOURCE, NAME, &v("1.2.3"), &trust_set, TrustLevel::Low)
+            .len(),
+        1
+    );
+    assert_eq!(
+        open("1.2.3+bar", VersionMatchPolicy::IgnoreBuildMetadata),
+        1
+    );
+    assert_eq!(gte("1.2.3", VersionMatchPolicy::IgnoreBuildMetadata), 1);
+
+    // strictly, only the same build matches
+    assert_eq!(open("1.2.3", VersionMatchPolicy::Strict), 0);
+    assert_eq!(open("1.2.3+bar", VersionMatchPolicy::Strict), 0);
+    assert_eq!(open("1.2.3+foo", VersionMatchPolicy::Strict), 1);
+    assert_eq!(gte("1.2.3", VersionMatchPolicy::Strict), 0);
+    assert_eq!(gte("1.2.2", VersionMatchPolicy::Strict), 1);
+    // other versions are still ordered just like without the metadata
+    assert_eq!(open("1.3.0", VersionMatchPolicy::Strict), 1);
+
+    Ok(())
+}
+
+#[test]
+fn pre_release_advisory_window() -> Result<()> {
+    use crev_wot::VersionMatchPolicy;
+
+    let url = FetchSource::LocalUser;
+    let root = UnlockedId::generate_for_git_url("https://root");
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let v = |s| Version::parse(s).unwrap();
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            root.create_signed_trust_proof(
+                vec![a.as_public_id(), b.as_public_id()],
+                TrustLevel::High,
+            )?,
+            build_proof_with_advisories(
+                &a,
+                v("2.0.0"),
+                vec![build_advisory("X", VersionRange::All)],
+            ),
+            build_proof_with_issues(&b, v("1.0.0-rc.1"), vec![build_issue("Y")]),
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+    let trust_set = trustdb.calculate_trust_set(root.as_ref(), &default());
+    let advisories = |version, policy| {
+        trustdb
+            .get_advisories_for_version_matching(SOURCE, NAME, &v(version), policy)
+            .count()
+    };
+    let open = |version, policy| -> BTreeSet<String> {
+        trustdb
+            .get_open_issues_for_version_matching(
+                SOURCE,
+                NAME,
+                &v(version),
+                &trust_set,
+                TrustLevel::Low,
+                policy,
+            )
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect()
+    };
+    let ids = |ids: &[&str]| ids.iter().map(|id| id.to_string()).collect::<BTreeSet<_>>();
+    let default = VersionMatchPolicy::default();
+    let as_release = VersionMatchPolicy::PreReleaseAsRelease;
+
+    // a pre-release precedes the release fixing the problem...
+    assert_eq!(advisories("2.0.0-rc.1", default), 1);
+    assert_eq!(open("2.0.0-rc.1", default), ids(&["X", "Y"]));
+    // ...unless it's treated as the release itself
+    assert_eq!(advisories("2.0.0-rc.1", as_release), 0);
+    assert_eq!(open("2.0.0-rc.1", as_release), ids(&["Y"]));
+    assert_eq!(advisories("1.9.9", as_release), 1);
+    assert_eq!(advisories("2.0.0", as_release), 0);
+
+    // an issue reported in a pre-release is open in pre-releases before it
+    // only if they are treated as the release
+    assert_eq!(open("1.0.0-beta", default), ids(&["X"]));
+    assert_eq!(open("1.0.0-beta", as_release), ids(&["X", "Y"]));
+    assert_eq!(open("1.0.0", default), ids(&["X", "Y"]));
+    assert_eq!(open("1.0.0", as_release), ids(&["X", "Y"]));
+
+    Ok(())
+}
+
+#[test]
+fn issue_timeline_across_versions() {
+    use crev_data::Level;
+    use crev_wot::{
+        testing::{fabricated_date, fabricated_id, package_review_builder, ProofDBBuilder},
+        timeline::IssueEvent,
+    };
+
+    let [root, a, b, c, d, e] = [0, 1, 2, 3, 4, 5].map(fabricated_id);
+    let issue = |id: &str, severity| Issue::new_with_severity(id.into(), severity);
+
+    let mut builder = ProofDBBuilder::new();
+    for id in &[&a, &b, &c, &e] {
+        builder.trust(&root, id, TrustLevel::High, fabricated_date(0));
+    }
+    let reviews = vec![
+        (
+            &a,
+            "1.0.0",
+            0,
+            vec![issue("X", Level::High), issue("Y", Level::Low)],
+            vec![],
+        ),
+        // not trusted by the root
+        (&d, "1.3.0", 1, vec![issue("X", Level::Low)], vec![]),
+        (&b, "1.1.0", 2, vec![issue("X", Level::Medium)], vec![]),
+        (&e, "1.0.5", 2, vec![issue("X", Level::Medium)], vec![]),
+        // one advisory for many issues, published on the same day as some reports
+        (
+            &c,
+            "1.2.0",
+            2,
+            vec![],
+            vec![Advisory::builder()
+                .ids(vec!["X".into(), "Y".into(), "Z".into()])
+                .severity(Level::High)
+                .build()],
+        ),
+    ];
+    for (from, version, days, issues, advisories) in reviews {
+        builder.package_review(
+            &package_review_builder(from, SOURCE, NAME, version, fabricated_date(days))
+                .issues(issues)
+                .advisories(advisories)
+                .build()
+                .unwrap(),
+        );
+    }
+    let trustdb = builder.build();
+
+    let reported =
+        |who: &crev_data::PublicId, version: &str, days, severity| IssueEvent::IssueReported {
+            version: Version::parse(version).unwrap(),
+            reporter: who.id.clone(),
+            date: fabricated_date(days),
+            severity,
+        };
+    let advisory = IssueEvent::AdvisoryPublished {
+        ids: vec!["X".into(), "Y".into(), "Z".into()],
+        affected_range: VersionRange::All,
+        fixed_in: Version::parse("1.2.0").unwrap(),
+        reporter: c.id.clone(),
+        date: fabricated_date(2),
+        severity: Level::High,
+    };
+
+    let timeline = trustdb.get_issue_timeline(SOURCE, NAME, "X", None);
+    assert_eq!(timeline.issue_id, "X");
+    assert_eq!(
+        timeline.events,
+        vec![
+            reported(&a, "1.0.0", 0, Level::High),
+            reported(&d, "1.3.0", 1, Level::Low),
+            // same date: reports first, by version, then advisories
+            reported(&e, "1.0.5", 2, Level::Medium),
+            reported(&b, "1.1.0", 2, Level::Medium),
+            advisory.clone(),
+        ]
+    );
+    assert_eq!(timeline.first_reported(), Some(&timeline.events[0]));
+    assert_eq!(timeline.fixed_in(), Some(&Version::parse("1.2.0").unwrap()));
+
+    let trust_set = trustdb.calculate_trust_set(&root.id, &default());
+    let trusted_timeline = trustdb.get_issue_timeline(SOURCE, NAME, "X", Some(&trust_set));
+    assert!(trusted_timeline
+        .events
+        .iter()
+        .all(|event| event.reporter() != &d.id));
+    assert_eq!(trusted_timeline.events.len(), 4);
+
+    assert_eq!(
+        trustdb.get_issue_timeline(SOURCE, NAME, "Y", None).events,
+        vec![reported(&a, "1.0.0", 0, Level::Low), advisory.clone()]
+    );
+    let z = trustdb.get_issue_timeline(SOURCE, NAME, "Z", None);
+    assert_eq!(z.events, vec![advisory]);
+    assert_eq!(z.first_reported(), None);
+    assert!(trustdb
+        .get_issue_timeline(SOURCE, "other", "X", None)
+        .events
+        .is_empty());
+
+    assert_eq!(
+        trustdb.list_issue_ids_for_package(SOURCE, NAME),
+        vec!["X", "Y", "Z"]
+            .into_iter()
+            .map(String::from)
+            .collect::<BTreeSet<_>>()
+    );
+}
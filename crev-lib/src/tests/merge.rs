@@ -0,0 +1,704 @@
+use super::*;
+
+use crev_data::{
+    proof::{self, CommonOps, ContentExt},
+    Digest, TrustLevel, UnlockedId,
+};
+use crev_wot::{FetchSource, LatestBy, ProofDB, ReviewOrder};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use semver::Version;
+
+const SOURCE: &str = "SOURCE_ID";
+const NAMES: &[&str] = &["foo", "bar"];
+const VERSIONS: &[&str] = &["1.0.0", "1.1.0"];
+const ISSUES: &[&str] = &["RUSTSEC-0001", "RUSTSEC-0002"];
+
+fn base_date() -> chrono::DateTime<chrono::Utc> {
+    chrono::DateTime::parse_from_rfc3339("2020-01-01T00:00:00+00:00")
+        .unwrap()
+        .with_timezone(&chrono::Utc)
+}
+
+fn pkg_id(name: &str) -> proof::PackageId {
+    proof::PackageId {
+        source: SOURCE.into(),
+        name: name.into(),
+    }
+}
+
+/// A random proof, with a few possible dates, so some of them are equally recent
+fn random_proof(rng: &mut StdRng, ids: &[UnlockedId]) -> Result<(proof::Proof, FetchSource)> {
+    let date = base_date() + chrono::Duration::days(rng.gen_range(0, 3));
+    random_proof_on(rng, ids, date)
+}
+
+fn random_proof_on(
+    rng: &mut StdRng,
+    ids: &[UnlockedId],
+    date: chrono::DateTime<chrono::Utc>,
+) -> Result<(proof::Proof, FetchSource)> {
+    let from = ids.choose(rng).unwrap();
+
+    let proof = if rng.gen() {
+        let count = rng.gen_range(1, 3);
+        let to: Vec<_> = ids
+            .choose_multiple(rng, count)
+            .map(|id| id.as_public_id())
+            .collect();
+        let level = *[
+            TrustLevel::Distrust,
+            TrustLevel::None,
+            TrustLevel::Low,
+            TrustLevel::Medium,
+            TrustLevel::High,
+        ]
+        .choose(rng)
+        .unwrap();
+        let mut trust = from.as_public_id().create_trust_proof(to, level)?;
+        trust.comment = ["", "a", "b"].choose(rng).unwrap().to_string();
+        trust.common.date = date.into();
+        trust.sign_by(from)?
+    } else {
+        let name = *NAMES.choose(rng).unwrap();
+        let package_info = proof::PackageInfo {
+            id: proof::PackageVersionId::new(
+                SOURCE.into(),
+                name.into(),
+                Version::parse(VERSIONS.choose(rng).unwrap()).unwrap(),
+            ),
+            digest: vec![rng.gen_range(0, 2); 32],
+            digest_type: proof::default_digest_type(),
+            revision: "".into(),
+            revision_type: proof::default_revision_type(),
+        };
+        let mut review = proof::review::PackageBuilder::default()
+            .from(from.id.to_owned())
+            .package(package_info)
+            .comment(format!("{}", rng.gen_range(0, 1000)))
+            .flags(proof::Flags {
+                unmaintained: rng.gen(),
+            })
+            .alternatives(
+                NAMES
+                    .choose(rng)
+                    .filter(|alt| **alt != name)
+                    .map(|alt| pkg_id(alt))
+                    .into_iter()
+                    .collect(),
+            )
+            .issues(
+                ISSUES
+                    .choose(rng)
+                    .filter(|_| rng.gen())
+                    .map(|id| proof::review::package::Issue::new(id.to_string()))
+                    .into_iter()
+                    .collect(),
+            )
+            .build()
+            .unwrap();
+        review.common.date = date.into();
+        review.sign_by(from)?
+    };
+
+    let fetched_from = match rng.gen_range(0, 3) {
+        0 => FetchSource::LocalUser,
+        1 => FetchSource::Url(Arc::new(from.id.url.clone().unwrap())),
+        _ => FetchSource::Url(Arc::new(crev_data::Url::new_git("https://mirror"))),
+    };
+    Ok((proof, fetched_from))
+}
+
+/// Results of all the queries affected by merging
+fn query_snapshot(db: &ProofDB, ids: &[UnlockedId]) -> Vec<String> {
+    let mut out = vec![];
+    for id in ids {
+        let id = id.as_ref();
+        out.push(format!("url {} {:?}", id, db.lookup_url(id).into_owned()));
+        for direct in db.get_direct_trust_for_id(id, None) {
+            out.push(format!(
+                "trust {} {} {} {} {}",
+                id,
+                direct.id,
+                direct.level,
+                direct.date,
+                direct.comment()
+            ));
+        }
+        out.push(format!(
+            "trust set {} {:?}",
+            id,
+            db.calculate_trust_set(id, &default())
+        ));
+        for review in db.get_pkg_reviews_by_author(id) {
+            out.push(format!("by author {} {}", id, review.comment));
+        }
+    }
+    let trust_set = db.calculate_trust_set(ids[0].as_ref(), &default());
+    for name in NAMES {
+        for version in VERSIONS {
+            let issues = db.get_open_issues_for_version(
+                SOURCE,
+                name,
+                &Version::parse(version).unwrap(),
+                &trust_set,
+                TrustLevel::None,
+            );
+            for (id, details) in issues {
+                let mut reporting_ids: Vec<_> = details.reporting_ids.into_iter().collect();
+                reporting_ids.sort();
+                out.push(format!(
+                    "issue {} {} {} {:?} {:?}",
+                    name, version, id, details.issues, reporting_ids
+                ));
+            }
+        }
+        for review in db.get_pkg_reviews_for_name(SOURCE, name) {
+            out.push(format!("name {} {}", review.from().id, review.comment));
+        }
+        let (page, total) =
+            db.get_pkg_reviews_for_name_page(SOURCE, name, 0, usize::MAX, ReviewOrder::ByDateDesc);
+        for review in page {
+            out.push(format!("by date {} {}", review.from().id, review.comment));
+        }
+        out.push(format!("total {}", total));
+        for by in &[LatestBy::Date, LatestBy::Version] {
+            for review in db.get_latest_review_per_author(SOURCE, name, *by) {
+                out.push(format!("latest {} {}", review.from().id, review.comment));
+            }
+        }
+        for (id, flags) in db.get_pkg_flags(&pkg_id(name)) {
+            out.push(format!("flags {} {}", id, flags.unmaintained));
+        }
+        for (id, alt) in db.get_pkg_alternatives(&pkg_id(name)) {
+            out.push(format!("alt {} {}", id, alt.name));
+        }
+    }
+    for digest in 0..2 {
+        let mut comments: Vec<_> = db
+            .get_package_reviews_by_digest(&Digest::from_vec(vec![digest; 32]))
+            .map(|review| review.comment.clone())
+            .collect();
+        comments.sort();
+        out.push(format!("digest {} {:?}", digest, comments));
+    }
+    out.push(format!("{:?}", db.proof_count_by_fetch_source()));
+    out.push(format!("{:?}", db.all_author_ids()));
+    out.push(format!("{:?}", db.approximate_memory_usage()));
+    out
+}
+
+#[test]
+fn merge_is_order_independent() -> Result<()> {
+    let ids: Vec<_> = (0..4)
+        .map(|i| UnlockedId::generate_for_git_url(&format!("https://{}", i)))
+        .collect();
+
+    for seed in 0..20 {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let shared: Vec<_> = (0..5)
+            .map(|_| random_proof(&mut rng, &ids))
+            .collect::<Result<_>>()?;
+        let build = |rng: &mut StdRng| -> Result<Vec<_>> {
+            let mut proofs = shared.clone();
+            for _ in 0..15 {
+                proofs.push(random_proof(rng, &ids)?);
+            }
+            proofs.shuffle(rng);
+            Ok(proofs)
+        };
+        let proofs_a = build(&mut rng)?;
+        let proofs_b = build(&mut rng)?;
+
+        let db = |proofs: &Vec<_>| {
+            let mut db = ProofDB::new();
+            db.import_from_iter(proofs.clone().into_iter());
+            db
+        };
+
+        let mut a_then_b = db(&proofs_a);
+        a_then_b.merge(db(&proofs_b));
+        let mut b_then_a = db(&proofs_b);
+        b_then_a.extend(vec![db(&proofs_a)]);
+
+        assert_eq!(
+            query_snapshot(&a_then_b, &ids),
+            query_snapshot(&b_then_a, &ids),
+            "seed {}",
+            seed
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn merge_matches_sequential_import() -> Result<()> {
+    let ids: Vec<_> = (0..4)
+        .map(|i| UnlockedId::generate_for_git_url(&format!("https://{}", i)))
+        .collect();
+
+    for seed in 0..20 {
+        let mut rng = StdRng::seed_from_u64(seed);
+        // with no equally recent proofs, which one is imported first doesn't matter
+        let mut proofs: Vec<_> = (0..40)
+            .map(|i| random_proof_on(&mut rng, &ids, base_date() + chrono::Duration::hours(i)))
+            .collect::<Result<_>>()?;
+        proofs.shuffle(&mut rng);
+        let (proofs_a, proofs_b) = proofs.split_at(rng.gen_range(0, proofs.len()));
+        // some proofs are in both
+        let shared = rng.gen_range(0, proofs_a.len() + 1);
+        let proofs_b: Vec<_> = proofs_b
+            .iter()
+            .chain(&proofs_a[..shared])
+            .cloned()
+            .collect();
+
+        let mut sequential = ProofDB::new();
+        sequential.import_from_iter(proofs_a.iter().cloned());
+        sequential.import_from_iter(proofs_b.iter().cloned());
+
+        let mut merged = ProofDB::new();
+        merged.import_from_iter(proofs_a.iter().cloned());
+        let mut other = ProofDB::new();
+        other.import_from_iter(proofs_b.iter().cloned());
+        merged.merge(other);
+
+        assert_eq!(
+            query_snapshot(&merged, &ids),
+            query_snapshot(&sequential, &ids),
+            "seed {}",
+            seed
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn merge_keeps_most_recent_data() -> Result<()> {
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let mirror = FetchSource::Url(Arc::new(crev_data::Url::new_git("https://mirror")));
+
+    let trust = |level, days| -> Result<proof::Proof> {
+        let mut trust = a
+            .as_public_id()
+            .create_trust_proof(vec![b.as_public_id()], level)?;
+        trust.common.date = (base_date() + chrono::Duration::days(days)).into();
+        Ok(trust.sign_by(&a)?)
+    };
+
+    let mut old = ProofDB::new();
+    old.import_from_iter(vec![(trust(TrustLevel::High, 0)?, FetchSource::LocalUser)].into_iter());
+    let review = {
+        let package_info = proof::PackageInfo {
+            id: proof::PackageVersionId::new(
+                SOURCE.into(),
+                "foo".into(),
+                Version::parse("1.0.0").unwrap(),
+            ),
+            digest: vec![0; 32],
+            digest_type: proof::default_digest_type(),
+            revision: "".into(),
+            revision_type: proof::default_revision_type(),
+        };
+        proof::review::PackageBuilder::default()
+            .from(b.id.to_owned())
+            .package(package_info)
+            .alternatives(vec![pkg_id("bar")].into_iter().collect())
+            .build()
+            .unwrap()
+            .sign_by(&b)?
+    };
+    let mut new = ProofDB::new();
+    new.import_from_iter(
+        vec![
+            (trust(TrustLevel::Low, 1)?, mirror.clone()),
+            (review, mirror),
+        ]
+        .into_iter(),
+    );
+
+    // cache of derived data has to be invalidated
+    assert!(old.get_pkg_alternatives(&pkg_id("foo")).is_empty());
+
+    old.merge(new);
+    let direct: Vec<_> = old.get_direct_trust_for_id(a.as_ref(), None).collect();
+    assert_eq!(direct.len(), 1);
+    assert_eq!(direct[0].level, TrustLevel::Low);
+    // verified by the local proof, even though the newer one came from a mirror
+    assert!(old.lookup_url(a.as_ref()).verified().is_some());
+    assert_eq!(old.unique_trust_proof_count(), 1);
+    assert_eq!(old.get_pkg_alternatives(&pkg_id("foo")).len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn snapshot_round_trip() -> Result<()> {
+    let ids: Vec<_> = (0..4)
+        .map(|i| UnlockedId::generate_for_git_url(&format!("https://{}", i)))
+        .collect();
+    let mut rng = StdRng::seed_from_u64(0);
+    let proofs: Vec<_> = (0..30)
+        .map(|_| random_proof(&mut rng, &ids))
+        .collect::<Result<_>>()?;
+
+    let mut db = ProofDB::new();
+    db.import_from_iter(proofs.clone().into_iter());
+
+    let mut snapshot = vec![];
+    db.serialize_snapshot(&mut snapshot).unwrap();
+
+    let header = ProofDB::read_snapshot_header(&snapshot[..]).unwrap();
+    assert_eq!(header.version, crev_wot::SNAPSHOT_VERSION);
+    assert_eq!(
+        header.proof_set_digest,
+        crev_wot::proof_set_digest(proofs.iter().map(|(proof, _)| proof.signature()))
+    );
+
+    let mut restored = ProofDB::deserialize_snapshot(&snapshot[..]).unwrap();
+    assert_eq!(query_snapshot(&restored, &ids), query_snapshot(&db, &ids));
+    for name in NAMES {
+        for version in VERSIONS {
+            let version = Version::parse(version).unwrap();
+            let comments = |db: &ProofDB| -> Vec<_> {
+                db.get_pkg_reviews_for_version(SOURCE, name, &version)
+                    .map(|review| review.comment.clone())
+                    .collect()
+            };
+            assert_eq!(comments(&restored), comments(&db));
+        }
+    }
+
+    // the restored db keeps working as usual
+    let more: Vec<_> = (0..10)
+        .map(|_| random_proof(&mut rng, &ids))
+        .collect::<Result<_>>()?;
+    restored.import_from_iter(more.clone().into_iter());
+    db.import_from_iter(more.into_iter());
+    assert_eq!(query_snapshot(&restored, &ids), query_snapshot(&db, &ids));
+    assert_ne!(restored.proof_set_digest(), header.proof_set_digest);
+
+    Ok(())
+}
+
+#[test]
+fn parallel_import_matches_sequential() -> Result<()> {
+    let ids: Vec<_> = (0..4)
+        .map(|i| UnlockedId::generate_for_git_url(&format!("https://{}", i)))
+        .collect();
+    let mut rng = StdRng::seed_from_u64(0);
+
+    // with no equally recent proofs the order of importing doesn't matter at all
+    let mut proofs: Vec<_> = (0..40)
+        .map(|i| random_proof_on(&mut rng, &ids, base_date() + chrono::Duration::hours(i)))
+        .collect::<Result<_>>()?;
+    // signature of a different proof
+    let forged = proof::Proof::from_parts(
+        proofs[0].0.body().to_owned(),
+        proofs[1].0.signature().to_owned(),
+    )?;
+    proofs.push((forged, FetchSource::LocalUser));
+    proofs.shuffle(&mut rng);
+
+    let mut sequential = ProofDB::new();
+    let sequential_report = sequential.import_from_iter_with_report(proofs.clone().into_iter());
+    let mut parallel = ProofDB::new();
+    let parallel_report = parallel.import_par_from_vec(proofs);
+
+    assert_eq!(parallel_report.accepted, sequential_report.accepted);
+    assert_eq!(parallel_report.rejected.len(), 1);
+    assert_eq!(sequential_report.rejected.len(), 1);
+    assert_eq!(
+        query_snapshot(&parallel, &ids),
+        query_snapshot(&sequential, &ids)
+    );
+
+    // otherwise proofs are imported in the order of dates and signatures
+    let mut proofs: Vec<_> = (0..40)
+        .map(|_| random_proof(&mut rng, &ids))
+        .collect::<Result<_>>()?;
+    let mut parallel = ProofDB::new();
+    parallel.import_par_from_vec(proofs.clone());
+    proofs.shuffle(&mut rng);
+    let mut reshuffled = ProofDB::new();
+    reshuffled.import_par_from_vec(proofs.clone());
+    assert_eq!(
+        query_snapshot(&reshuffled, &ids),
+        query_snapshot(&parallel, &ids)
+    );
+
+    proofs.sort_by(|a, b| {
+        a.0.date_utc()
+            .cmp(&b.0.date_utc())
+            .then_with(|| a.0.signature().cmp(b.0.signature()))
+    });
+    let mut sequential = ProofDB::new();
+    sequential.import_from_iter(proofs.into_iter());
+    assert_eq!(
+        query_snapshot(&parallel, &ids),
+        query_snapshot(&sequential, &ids)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn staged_import_leaves_clones_unaffected() -> Result<()> {
+    let ids: Vec<_> = (0..4)
+        .map(|i| UnlockedId::generate_for_git_url(&format!("https://{}", i)))
+        .collect();
+    let mut rng = StdRng::seed_from_u64(0);
+    let proofs: Vec<_> = (0..30)
+        .map(|_| random_proof(&mut rng, &ids))
+        .collect::<Result<_>>()?;
+    let more: Vec<_> = (0..20)
+        .map(|_| random_proof(&mut rng, &ids))
+        .collect::<Result<_>>()?;
+
+    let mut db = ProofDB::new();
+    db.import_from_iter(proofs.clone().into_iter());
+    let before = query_snapshot(&db, &ids);
+
+    let batch = db.stage_import(more.clone().into_iter());
+    assert_eq!(batch.len(), more.len());
+    // staging doesn't change anything yet
+    assert_eq!(query_snapshot(&db, &ids), before);
+
+    let snapshot = db.clone();
+    let report = db.apply(batch);
+    assert!(report.rejected.is_empty());
+    assert_eq!(report.accepted.values().sum::<usize>(), more.len());
+    assert_eq!(query_snapshot(&snapshot, &ids), before);
+
+    let mut sequential = ProofDB::new();
+    sequential.import_from_iter(proofs.into_iter().chain(more));
+    assert_eq!(query_snapshot(&db, &ids), query_snapshot(&sequential, &ids));
+
+    Ok(())
+}
+
+#[test]
+fn import_with_progress_can_be_cancelled() -> Result<()> {
+    use crev_wot::ImportProgress;
+    use std::ops::ControlFlow;
+
+    let ids: Vec<_> = (0..4)
+        .map(|i| UnlockedId::generate_for_git_url(&format!("https://{}", i)))
+        .collect();
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut proofs: Vec<_> = (0..40)
+        .map(|_| random_proof(&mut rng, &ids))
+        .collect::<Result<_>>()?;
+    // signature of a different proof
+    let forged = proof::Proof::from_parts(
+        proofs[0].0.body().to_owned(),
+        proofs[1].0.signature().to_owned(),
+    )?;
+    proofs.insert(5, (forged, FetchSource::LocalUser));
+
+    // every proof, once
+    let mut reported = vec![];
+    let mut db = ProofDB::new();
+    let report = db.import_with_progress_every(proofs.clone().into_iter(), 7, &mut |progress| {
+        reported.push(progress);
+        ControlFlow::Continue(())
+    });
+    assert_eq!(
+        reported.iter().map(|p| p.processed).collect::<Vec<_>>(),
+        [7, 14, 21, 28, 35, 41]
+    );
+    let last = reported.last().unwrap();
+    assert_eq!((last.accepted, last.rejected), (40, 1));
+    assert_eq!(last.last_kind.as_deref(), Some(proofs[40].0.kind()));
+    assert_eq!(report.accepted.values().sum::<usize>(), 40);
+    let mut sequential = ProofDB::new();
+    sequential.import_from_iter(proofs.clone().into_iter());
+    assert_eq!(query_snapshot(&db, &ids), query_snapshot(&sequential, &ids));
+
+    // halfway
+    let mut calls = 0;
+    let mut db = ProofDB::new();
+    let report = db.import_with_progress_every(proofs.clone().into_iter(), 7, &mut |progress| {
+        calls += 1;
+        if progress.processed >= 20 {
+            assert_eq!(
+                progress,
+                ImportProgress {
+                    processed: 21,
+                    accepted: 20,
+                    rejected: 1,
+                    last_kind: Some(proofs[20].0.kind().to_owned()),
+                }
+            );
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    });
+    assert_eq!(calls, 3);
+    assert_eq!(report.accepted.values().sum::<usize>(), 20);
+    assert_eq!(report.rejected.len(), 1);
+    assert_eq!(
+        db.proof_count_by_fetch_source()
+            .values()
+            .map(|stats| stats.proof_count)
+            .sum::<usize>(),
+        20
+    );
+    let mut sequential = ProofDB::new();
+    sequential.import_from_iter(proofs[..21].iter().cloned());
+    assert_eq!(query_snapshot(&db, &ids), query_snapshot(&sequential, &ids));
+
+    // the rest can be imported later
+    db.import_with_progress(proofs[21..].iter().cloned(), &mut |_| {
+        ControlFlow::Continue(())
+    });
+    let mut sequential = ProofDB::new();
+    sequential.import_from_iter(proofs.into_iter());
+    assert_eq!(query_snapshot(&db, &ids), query_snapshot(&sequential, &ids));
+
+    Ok(())
+}
+
+#[test]
+fn export_round_trip() -> Result<()> {
+    use crev_wot::{export::ExportFilter, ProofKind};
+    use std::collections::BTreeSet;
+
+    let ids: Vec<_> = (0..4)
+        .map(|i| UnlockedId::generate_for_git_url(&format!("https://{}", i)))
+        .collect();
+    let mut rng = StdRng::seed_from_u64(3);
+    let mut proofs: Vec<_> = (0..40)
+        .map(|_| random_proof(&mut rng, &ids))
+        .collect::<Result<_>>()?;
+    // the order proofs are exported in, which matters for equally recent ones
+    proofs.sort_by(|a, b| {
+        a.0.date_utc()
+            .cmp(&b.0.date_utc())
+            .then_with(|| a.0.signature().cmp(b.0.signature()))
+    });
+
+    let mut db = ProofDB::new();
+    db.set_keep_signed_proofs(true);
+    db.import_from_iter(proofs.clone().into_iter());
+
+    let export = |db: &ProofDB, filter: &ExportFilter| -> Result<(usize, Vec<proof::Proof>)> {
+        let mut out = vec![];
+        let count = db.export_proofs(filter, &mut out).unwrap();
+        Ok((count, proof::Proof::parse_from(&out[..])?))
+    };
+    let import = |proofs: Vec<proof::Proof>| {
+        let mut db = ProofDB::new();
+        db.import_from_iter(
+            proofs
+                .into_iter()
+                .map(|proof| (proof, FetchSource::LocalUser)),
+        );
+        db
+    };
+    let trust_sets = |db: &ProofDB| -> Vec<String> {
+        ids.iter()
+            .map(|id| format!("{:?}", db.calculate_trust_set(id.as_ref(), &default())))
+            .collect()
+    };
+    let reviews = |db: &ProofDB, name: &str| -> Vec<String> {
+        let trust_set = db.calculate_trust_set(ids[0].as_ref(), &default());
+        let mut out = vec![];
+        for version in VERSIONS {
+            let version = Version::parse(version).unwrap();
+            let mut comments: Vec<_> = db
+                .get_pkg_reviews_for_version(SOURCE, name, &version)
+                .map(|review| format!("{} {}", review.from().id, review.comment))
+                .collect();
+            comments.sort();
+            out.extend(comments);
+            let issues = db.get_open_issues_for_version(
+                SOURCE,
+                name,
+                &version,
+                &trust_set,
+                TrustLevel::None,
+            );
+            out.push(format!("{:?}", issues.keys().collect::<Vec<_>>()));
+        }
+        out
+    };
+
+    // everything
+    let (count, all) = export(&db, &ExportFilter::default())?;
+    assert_eq!(count, all.len());
+    assert_eq!(count, db.approximate_memory_usage().signed_proofs);
+    assert!(all
+        .windows(2)
+        .all(|pair| pair[0].date_utc() <= pair[1].date_utc()));
+    let imported = import(all);
+    assert_eq!(trust_sets(&imported), trust_sets(&db));
+    for name in NAMES {
+        assert_eq!(reviews(&imported, name), reviews(&db, name));
+    }
+
+    // the trust, and reviews of one package
+    let filter = ExportFilter {
+        packages: vec![pkg_id("foo")].into_iter().collect(),
+        kinds: vec![ProofKind::Trust, ProofKind::PackageReview]
+            .into_iter()
+            .collect(),
+        ..ExportFilter::default()
+    };
+    let (_, slice) = export(&db, &filter)?;
+    let imported = import(slice);
+    assert_eq!(trust_sets(&imported), trust_sets(&db));
+    assert_eq!(reviews(&imported, "foo"), reviews(&db, "foo"));
+    assert_eq!(imported.get_pkg_reviews_for_name(SOURCE, "bar").count(), 0);
+
+    // by author and date
+    let since = base_date() + chrono::Duration::days(1);
+    let filter = ExportFilter {
+        authors: vec![ids[1].id.id.clone()].into_iter().collect(),
+        since: Some(since),
+        ..ExportFilter::default()
+    };
+    let (count, slice) = export(&db, &filter)?;
+    assert!(count > 0);
+    assert!(slice
+        .iter()
+        .all(|proof| proof.author_id() == &ids[1].id.id && since <= proof.date_utc()));
+    let expected = proofs
+        .iter()
+        .filter(|(proof, _)| proof.author_id() == &ids[1].id.id && since <= proof.date_utc())
+        .map(|(proof, _)| proof.signature())
+        .collect::<BTreeSet<_>>();
+    assert_eq!(
+        slice
+            .iter()
+            .map(|proof| proof.signature())
+            .collect::<BTreeSet<_>>(),
+        expected
+    );
+
+    // signed proofs survive snapshots, and are kept only when asked to
+    let mut snapshot = vec![];
+    db.serialize_snapshot(&mut snapshot).unwrap();
+    let restored = ProofDB::deserialize_snapshot(&snapshot[..]).unwrap();
+    let mut before = vec![];
+    let mut after = vec![];
+    db.export_proofs(&ExportFilter::default(), &mut before)
+        .unwrap();
+    restored
+        .export_proofs(&ExportFilter::default(), &mut after)
+        .unwrap();
+    assert_eq!(before, after);
+    assert_eq!(import(vec![]).export_proofs(&filter, vec![]).unwrap(), 0);
+    let not_kept = import(proofs.into_iter().map(|(proof, _)| proof).collect());
+    assert_eq!(
+        not_kept
+            .export_proofs(&ExportFilter::default(), vec![])
+            .unwrap(),
+        0
+    );
+
+    Ok(())
+}
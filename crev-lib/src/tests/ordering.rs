@@ -0,0 +1,300 @@
+use super::*;
+
+use crev_data::{
+    proof::{self, ContentExt},
+    Digest, TrustLevel, UnlockedId,
+};
+use crev_wot::{FetchSource, ProofDB, ReviewOrder};
+use semver::Version;
+
+const SOURCE: &str = "SOURCE_ID";
+const NAME: &str = "name";
+
+fn build_review(
+    id: &UnlockedId,
+    version: &str,
+    days: i64,
+    unmaintained: bool,
+    alternative: &str,
+) -> proof::Proof {
+    let package_info = proof::PackageInfo {
+        id: proof::PackageVersionId::new(
+            SOURCE.into(),
+            NAME.into(),
+            Version::parse(version).unwrap(),
+        ),
+        digest: vec![0; 32],
+        digest_type: proof::default_digest_type(),
+        revision: "".into(),
+        revision_type: proof::default_revision_type(),
+    };
+    let mut review = proof::review::PackageBuilder::default()
+        .from(id.id.to_owned())
+        .package(package_info)
+        .comment(format!("{} {}", version, days))
+        .flags(proof::Flags { unmaintained })
+        .alternatives(
+            vec![proof::PackageId {
+                source: SOURCE.into(),
+                name: alternative.into(),
+            }]
+            .into_iter()
+            .collect(),
+        )
+        .build()
+        .unwrap();
+    // fixed base date, so reviews from the same day have equal dates
+    review.common.date = chrono::DateTime::parse_from_rfc3339("2020-01-01T00:00:00+00:00").unwrap()
+        + chrono::Duration::days(days);
+
+    review.sign_by(&id).unwrap()
+}
+
+/// Everything a report would print, in the order it would print it
+fn query_snapshot(db: &ProofDB, root: &UnlockedId) -> Vec<String> {
+    let pkg_id = proof::PackageId {
+        source: SOURCE.into(),
+        name: NAME.into(),
+    };
+    let mut out = vec![];
+
+    for (id, flags) in db.get_pkg_flags(&pkg_id) {
+        out.push(format!("flags {} {}", id, flags.unmaintained));
+    }
+    for (id, alt) in db.get_pkg_alternatives(&pkg_id) {
+        out.push(format!("alt {} {}", id, alt.name));
+    }
+    for review in db.get_package_reviews_by_digest(&Digest::from_vec(vec![0; 32])) {
+        out.push(format!("digest {} {}", review.from().id, review.comment));
+    }
+    for review in db.get_pkg_reviews_for_name(SOURCE, NAME) {
+        out.push(format!("name {} {}", review.from().id, review.comment));
+    }
+    for id in db
+        .calculate_trust_set(root.as_ref(), &default())
+        .trusted_ids()
+    {
+        out.push(format!("trusted {}", id));
+    }
+
+    out
+}
+
+#[test]
+fn query_order_does_not_depend_on_import_order() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let ids: Vec<_> = (0..5)
+        .map(|i| UnlockedId::generate_for_git_url(&format!("https://{}", i)))
+        .collect();
+
+    let mut proofs = vec![ids[0].create_signed_trust_proof(
+        ids.iter().skip(1).map(|id| id.as_public_id()),
+        TrustLevel::High,
+    )?];
+    for (i, id) in ids.iter().enumerate() {
+        proofs.push(build_review(id, "1.0.0", 0, false, &format!("old{}", i)));
+        proofs.push(build_review(
+            id,
+            "1.1.0",
+            1,
+            i % 2 == 0,
+            &format!("alt{}", i),
+        ));
+        // overwrites the previous review of `1.1.0`
+        proofs.push(build_review(
+            id,
+            "1.1.0",
+            2,
+            i % 2 == 1,
+            &format!("new{}", i),
+        ));
+    }
+
+    let mut snapshots = vec![];
+    for order in vec![
+        proofs.clone(),
+        proofs.iter().rev().cloned().collect(),
+        proofs
+            .iter()
+            .skip(3)
+            .chain(proofs.iter().take(3))
+            .cloned()
+            .collect(),
+    ] {
+        let mut trustdb = ProofDB::new();
+        trustdb.import_from_iter(order.into_iter().map(|x| (x, url.clone())));
+        snapshots.push(query_snapshot(&trustdb, &ids[0]));
+    }
+
+    assert_eq!(snapshots[0], snapshots[1]);
+    assert_eq!(snapshots[0], snapshots[2]);
+
+    // latest-wins semantics are preserved
+    let snapshot = &snapshots[0];
+    assert_eq!(
+        snapshot.iter().filter(|l| l.starts_with("flags ")).count(),
+        ids.len()
+    );
+    for (i, id) in ids.iter().enumerate() {
+        assert!(snapshot.contains(&format!("flags {} {}", id.id.id, i % 2 == 1)));
+        assert!(snapshot.contains(&format!("name {} 1.1.0 2", id.id.id)));
+        assert!(!snapshot.contains(&format!("name {} 1.1.0 1", id.id.id)));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn memory_usage_and_shrinking() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            a.create_signed_trust_proof(vec![b.as_public_id()], TrustLevel::High)?,
+            build_review(&a, "1.0.0", 0, false, "alt"),
+            // overwritten by the next one
+            build_review(&b, "1.0.0", 0, true, "alt"),
+            build_review(&b, "1.0.0", 1, false, "other"),
+            build_review(&b, "1.1.0", 0, false, "alt"),
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+
+    let stats = trustdb.approximate_memory_usage();
+    assert_eq!(stats.trust_edges, 1);
+    assert_eq!(stats.urls_self_reported, 2);
+    assert_eq!(stats.package_reviews, 4);
+    assert_eq!(stats.package_reviews_by_pkg_review_id, 3);
+    assert_eq!(stats.package_reviews_by_version, 3);
+    assert_eq!(stats.package_flags, 2);
+    assert_eq!(stats.fetch_sources, 5);
+    assert_eq!(stats.derived_alternatives, 0);
+
+    let pkg_id = proof::PackageId {
+        source: SOURCE.into(),
+        name: NAME.into(),
+    };
+    let alternatives = trustdb.get_pkg_alternatives(&pkg_id);
+    assert_eq!(trustdb.approximate_memory_usage().derived_alternatives, 4);
+
+    trustdb.shrink_to_fit();
+    assert_eq!(
+        trustdb.approximate_memory_usage(),
+        crev_wot::MemoryStats {
+            derived_alternatives: 4,
+            ..stats.clone()
+        }
+    );
+
+    trustdb.clear_derived_caches();
+    assert_eq!(trustdb.approximate_memory_usage(), stats);
+    // recalculated on demand
+    assert_eq!(trustdb.get_pkg_alternatives(&pkg_id), alternatives);
+
+    Ok(())
+}
+
+#[test]
+fn paginated_reviews() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let mut ids: Vec<_> = (0..3)
+        .map(|i| UnlockedId::generate_for_git_url(&format!("https://{}", i)))
+        .collect();
+    ids.sort_by(|a, b| a.id.id.cmp(&b.id.id));
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            build_review(&ids[0], "1.0.0", 0, false, "a"),
+            build_review(&ids[1], "1.0.0", 3, false, "a"),
+            build_review(&ids[2], "1.2.0", 1, false, "a"),
+            // same date as the one above
+            build_review(&ids[0], "1.1.0", 1, false, "a"),
+            build_review(&ids[1], "1.1.0", 2, false, "a"),
+            // replaces the previous review, and moves it to the top
+            build_review(&ids[0], "1.0.0", 5, false, "a"),
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+
+    let page = |offset, limit, order| -> (Vec<(usize, String)>, usize) {
+        let (reviews, total) =
+            trustdb.get_pkg_reviews_for_name_page(SOURCE, NAME, offset, limit, order);
+        let reviews = reviews
+            .into_iter()
+            .map(|r| {
+                (
+                    ids.iter().position(|id| id.id.id == r.from().id).unwrap(),
+                    r.package.id.version.to_string(),
+                )
+            })
+            .collect();
+        (reviews, total)
+    };
+    let v = |i: usize, version: &str| (i, version.to_owned());
+
+    assert_eq!(
+        page(0, 10, ReviewOrder::ByDateDesc),
+        (
+            vec![
+                v(0, "1.0.0"),
+                v(1, "1.0.0"),
+                v(1, "1.1.0"),
+                v(0, "1.1.0"),
+                v(2, "1.2.0"),
+            ],
+            5
+        )
+    );
+    assert_eq!(
+        page(0, 10, ReviewOrder::ByVersionDesc).0,
+        vec![
+            v(2, "1.2.0"),
+            v(0, "1.1.0"),
+            v(1, "1.1.0"),
+            v(0, "1.0.0"),
+            v(1, "1.0.0"),
+        ]
+    );
+    assert_eq!(
+        page(0, 10, ReviewOrder::ByAuthor).0,
+        vec![
+            v(0, "1.1.0"),
+            v(0, "1.0.0"),
+            v(1, "1.1.0"),
+            v(1, "1.0.0"),
+            v(2, "1.2.0"),
+        ]
+    );
+
+    // pages put together are the same as everything at once
+    for &order in &[
+        ReviewOrder::ByDateDesc,
+        ReviewOrder::ByVersionDesc,
+        ReviewOrder::ByAuthor,
+    ] {
+        let all = page(0, 10, order).0;
+        let mut paged = vec![];
+        for offset in (0..6).step_by(2) {
+            let (reviews, total) = page(offset, 2, order);
+            assert_eq!(total, 5);
+            paged.extend(reviews);
+        }
+        assert_eq!(paged, all);
+        assert_eq!(page(10, 2, order), (vec![], 5));
+    }
+
+    assert_eq!(
+        trustdb
+            .get_pkg_reviews_for_name_page(SOURCE, "unknown", 0, 10, ReviewOrder::ByDateDesc)
+            .1,
+        0
+    );
+
+    Ok(())
+}
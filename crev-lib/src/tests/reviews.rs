@@ -0,0 +1,2619 @@
+use super::*;
+
+use crev_data::{
+    proof::{self, ContentExt},
+    Digest, Id, TrustLevel, UnlockedId,
+};
+use crev_wot::{FetchSource, LatestBy, ProofDB};
+use semver::Version;
+
+const SOURCE: &str = "SOURCE_ID";
+
+fn build_review(id: &UnlockedId, name: &str, version: &str, digest: Vec<u8>) -> proof::Proof {
+    build_review_dated(id, name, version, digest, 0)
+}
+
+fn build_review_dated(
+    id: &UnlockedId,
+    name: &str,
+    version: &str,
+    digest: Vec<u8>,
+    days: i64,
+) -> proof::Proof {
+    build_review_full(id, SOURCE, name, version, digest, days)
+}
+
+fn build_review_full(
+    id: &UnlockedId,
+    source: &str,
+    name: &str,
+    version: &str,
+    digest: Vec<u8>,
+    days: i64,
+) -> proof::Proof {
+    let date = chrono::Utc::now() + chrono::Duration::days(days);
+    build_review_on(id, source, name, version, digest, date)
+}
+
+fn build_review_on(
+    id: &UnlockedId,
+    source: &str,
+    name: &str,
+    version: &str,
+    digest: Vec<u8>,
+    date: chrono::DateTime<chrono::Utc>,
+) -> proof::Proof {
+    build_package_review(id, source, name, version, digest, date, false)
+}
+
+fn build_retraction_on(
+    id: &UnlockedId,
+    name: &str,
+    version: &str,
+    digest: Vec<u8>,
+    date: chrono::DateTime<chrono::Utc>,
+) -> proof::Proof {
+    build_package_review(id, SOURCE, name, version, digest, date, true)
+}
+
+fn build_package_review(
+    id: &UnlockedId,
+    source: &str,
+    name: &str,
+    version: &str,
+    digest: Vec<u8>,
+    date: chrono::DateTime<chrono::Utc>,
+    retracted: bool,
+) -> proof::Proof {
+    let package_info = proof::PackageInfo {
+        id: proof::PackageVersionId::new(
+            source.into(),
+            name.into(),
+            Version::parse(version).unwrap(),
+        ),
+        digest,
+        digest_type: proof::default_digest_type(),
+        revision: "".into(),
+        revision_type: proof::default_revision_type(),
+    };
+    let mut review = proof::review::PackageBuilder::default()
+        .from(id.id.to_owned())
+        .package(package_info)
+        .comment(if retracted { "" } else { "comment" }.into())
+        .retracted(retracted)
+        .build()
+        .unwrap();
+    review.common.date = date.into();
+
+    review.sign_by(&id).unwrap()
+}
+
+fn digest_with_prefix(prefix: &[u8]) -> Vec<u8> {
+    let mut digest = prefix.to_vec();
+    digest.resize(32, 0);
+    digest
+}
+
+#[test]
+fn find_reviews_by_digest_prefix() -> Result<(), crev_wot::Error> {
+    let url = FetchSource::LocalUser;
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+
+    let digest1 = digest_with_prefix(&[0xab, 0xcd, 0xef, 0x01, 0x11]);
+    let digest2 = digest_with_prefix(&[0xab, 0xcd, 0xef, 0x01, 0x22]);
+    let digest3 = digest_with_prefix(&[0x12, 0x34]);
+    // "abcdef0111" in base64 too
+    let digest4 = digest_with_prefix(&crev_common::base64_decode("abcdef0111xx").unwrap());
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            build_review(&a, "one", "1.0.0", digest1.clone()),
+            build_review(&b, "one", "1.0.0", digest1.clone()),
+            build_review(&a, "two", "1.0.0", digest2.clone()),
+            build_review(&a, "three", "1.0.0", digest3.clone()),
+            build_review(&a, "four", "1.0.0", digest4.clone()),
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+
+    // read as hex first, even though it's a base64 prefix of another digest
+    let found = trustdb.find_reviews_by_digest_prefix("abcdef0111")?;
+    assert_eq!(found.len(), 2);
+    assert!(found.iter().all(|r| r.package.digest == digest1));
+    let found = trustdb.find_reviews_by_digest_prefix("abcdef0111xx")?;
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].package.id.id.name, "four");
+
+    // hex is case insensitive, and can have an odd length
+    let found = trustdb.find_reviews_by_digest_prefix("ABCDEF0122")?;
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].package.id.id.name, "two");
+    assert_eq!(trustdb.find_reviews_by_digest_prefix("abcdef012")?.len(), 1);
+
+    // base64, as displayed by crev
+    let base64 = crev_common::base64_encode(&digest3);
+    let found = trustdb.find_reviews_by_digest_prefix(&base64[..10])?;
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].package.id.id.name, "three");
+
+    match trustdb.find_reviews_by_digest_prefix("abcdef01") {
+        Err(crev_wot::Error::AmbiguousDigestPrefix(candidates)) => {
+            assert_eq!(candidates, vec![digest1, digest2])
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    assert!(matches!(
+        trustdb.find_reviews_by_digest_prefix("abcdef"),
+        Err(crev_wot::Error::DigestPrefixTooShort(_))
+    ));
+    assert!(trustdb
+        .find_reviews_by_digest_prefix("99999999")?
+        .is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn best_review_for_digest_tie_breaking() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let root = UnlockedId::generate_for_git_url("https://root");
+    let mut high: Vec<_> = (0..3)
+        .map(|i| UnlockedId::generate_for_git_url(&format!("https://high{}", i)))
+        .collect();
+    high.sort_by(|a, b| a.id.id.cmp(&b.id.id));
+    let medium = UnlockedId::generate_for_git_url("https://medium");
+    let distrusted = UnlockedId::generate_for_git_url("https://distrusted");
+    let stranger = UnlockedId::generate_for_git_url("https://stranger");
+
+    let digest = vec![1; 32];
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            root.create_signed_trust_proof(
+                high.iter().map(|id| id.as_public_id()),
+                TrustLevel::High,
+            )?,
+            root.create_signed_trust_proof(vec![medium.as_public_id()], TrustLevel::Medium)?,
+            root.create_signed_trust_proof(vec![distrusted.as_public_id()], TrustLevel::Distrust)?,
+            build_review_dated(&high[0], "one", "1.0.0", digest.clone(), -2),
+            build_review_dated(&high[1], "one", "1.0.0", digest.clone(), -1),
+            build_review_dated(&high[2], "one", "1.0.0", digest.clone(), -2),
+            build_review_dated(&medium, "one", "1.0.0", digest.clone(), 0),
+            build_review_dated(&distrusted, "one", "1.0.0", digest.clone(), 1),
+            build_review_dated(&stranger, "one", "1.0.0", digest.clone(), 1),
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+    let trust_set = trustdb.calculate_trust_set(root.as_ref(), &default());
+    let digest = Digest::from_vec(digest);
+
+    // highest trust level beats the newest review
+    let best = trustdb
+        .best_review_for_digest(&digest, &trust_set, TrustLevel::Low)
+        .unwrap();
+    assert_eq!(best.from().id, high[1].id.id);
+    assert_eq!(
+        trustdb.trusted_review_count_for_digest(&digest, &trust_set, TrustLevel::Low),
+        4
+    );
+    assert_eq!(
+        trustdb.trusted_review_count_for_digest(&digest, &trust_set, TrustLevel::High),
+        3
+    );
+    // untrusted authors count only if asked for, distrusted never
+    assert_eq!(
+        trustdb.trusted_review_count_for_digest(&digest, &trust_set, TrustLevel::None),
+        5
+    );
+
+    assert!(trustdb
+        .best_review_for_digest(&Digest::from_vec(vec![2; 32]), &trust_set, TrustLevel::Low)
+        .is_none());
+
+    // same trust level and date: lowest Id wins
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            root.create_signed_trust_proof(
+                vec![high[0].as_public_id(), high[2].as_public_id()],
+                TrustLevel::High,
+            )?,
+            build_review_dated(&high[2], "one", "1.0.0", digest.as_slice().to_vec(), -2),
+            build_review_dated(&high[0], "one", "1.0.0", digest.as_slice().to_vec(), -2),
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+    let trust_set = trustdb.calculate_trust_set(root.as_ref(), &default());
+    let best = trustdb
+        .best_review_for_digest(&digest, &trust_set, TrustLevel::Low)
+        .unwrap();
+    assert_eq!(best.from().id, high[0].id.id);
+
+    Ok(())
+}
+
+fn build_range_review(
+    id: &UnlockedId,
+    base: Option<&str>,
+    version: &str,
+    days: i64,
+) -> proof::Proof {
+    let package_info = |version: &str| proof::PackageInfo {
+        id: proof::PackageVersionId::new(
+            SOURCE.into(),
+            "range".into(),
+            Version::parse(version).unwrap(),
+        ),
+        digest: vec![0; 32],
+        digest_type: proof::default_digest_type(),
+        revision: "".into(),
+        revision_type: proof::default_revision_type(),
+    };
+    let mut review = proof::review::PackageBuilder::default()
+        .from(id.id.to_owned())
+        .package(package_info(version))
+        .diff_base(base.map(package_info))
+        .build()
+        .unwrap();
+    review.common.date = review.common.date + chrono::Duration::days(days);
+
+    review.sign_by(&id).unwrap()
+}
+
+#[test]
+fn reviews_covering_version() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let c = UnlockedId::generate_for_git_url("https://c");
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            build_range_review(&a, Some("1.0.0"), "1.2.0", 0),
+            build_range_review(&b, Some("1.1.0"), "1.3.0", 0),
+            build_range_review(&c, None, "1.1.5", 0),
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+
+    let covering = |db: &ProofDB, version: &str| -> Vec<(Id, String)> {
+        db.get_pkg_reviews_covering_version(SOURCE, "range", &Version::parse(version).unwrap())
+            .map(|r| (r.from().id.clone(), r.package.id.version.to_string()))
+            .collect()
+    };
+
+    let mut expected = vec![
+        (a.id.id.clone(), "1.2.0".to_owned()),
+        (b.id.id.clone(), "1.3.0".to_owned()),
+    ];
+    expected.sort();
+    expected.insert(0, (c.id.id.clone(), "1.1.5".to_owned()));
+    assert_eq!(covering(&trustdb, "1.1.5"), expected);
+
+    // base version is covered too
+    assert_eq!(
+        covering(&trustdb, "1.0.0"),
+        vec![(a.id.id.clone(), "1.2.0".to_owned())]
+    );
+    // reviewed version is returned once
+    assert_eq!(
+        covering(&trustdb, "1.3.0"),
+        vec![(b.id.id.clone(), "1.3.0".to_owned())]
+    );
+    assert!(covering(&trustdb, "0.9.0").is_empty());
+    assert!(covering(&trustdb, "1.3.1").is_empty());
+
+    // `a` re-reviews `1.2.0`, this time fully
+    trustdb.import_from_iter(
+        vec![build_range_review(&a, None, "1.2.0", 1)]
+            .into_iter()
+            .map(|x| (x, url.clone())),
+    );
+    assert!(covering(&trustdb, "1.0.0").is_empty());
+    assert_eq!(covering(&trustdb, "1.1.5").len(), 2);
+
+    Ok(())
+}
+
+#[test]
+fn owned_reviews_outlive_db_mutation() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let version = Version::parse("1.0.0").unwrap();
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![build_review(&a, "foo", "1.0.0", vec![1; 32])]
+            .into_iter()
+            .map(|x| (x, url.clone())),
+    );
+
+    let owned = trustdb.get_pkg_reviews_for_version_owned(SOURCE, "foo", &version);
+    assert_eq!(owned.len(), 1);
+
+    // newer review from the same author replaces the old one
+    trustdb.import_from_iter(
+        vec![
+            build_review_dated(&a, "foo", "1.0.0", vec![2; 32], 1),
+            build_review(&b, "foo", "1.0.0", vec![2; 32]),
+            build_review(&b, "bar", "1.0.0", vec![3; 32]),
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+
+    assert_eq!(owned.len(), 1);
+    assert_eq!(owned[0].package.digest, vec![1; 32]);
+    assert_eq!(owned[0].from().id, a.id.id);
+
+    let current = trustdb.get_pkg_reviews_for_version_owned(SOURCE, "foo", &version);
+    assert_eq!(current.len(), 2);
+    assert!(current.iter().all(|r| r.package.digest == vec![2; 32]));
+    assert_eq!(
+        trustdb.get_pkg_reviews_for_name_owned(SOURCE, "foo").len(),
+        2
+    );
+    assert_eq!(trustdb.get_pkg_reviews_for_source_owned(SOURCE).len(), 3);
+
+    let by_digest: Vec<_> = trustdb
+        .get_package_reviews_by_digest(&Digest::from_vec(vec![2; 32]))
+        .collect();
+    assert_eq!(by_digest.len(), 2);
+    assert!(!by_digest.iter().any(|r| std::ptr::eq(*r, &*owned[0])));
+
+    Ok(())
+}
+
+#[test]
+fn crates_io_package_names_are_normalized() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let crates_io = crev_wot::CRATES_IO_SOURCE;
+    let version = Version::parse("1.0.0").unwrap();
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            build_review_full(&a, crates_io, "Tokio_Util", "1.0.0", vec![1; 32], 0),
+            build_review_full(&a, crates_io, "tokio", "1.0.0", vec![2; 32], 0),
+            build_review_full(&a, SOURCE, "Tokio_Util", "1.0.0", vec![3; 32], 0),
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+
+    for name in &["tokio-util", "tokio_util", "TOKIO-UTIL", "Tokio_Util"] {
+        let reviews: Vec<_> = trustdb.get_pkg_reviews_for_name(crates_io, name).collect();
+        assert_eq!(reviews.len(), 1, "{}", name);
+        // original spelling is preserved
+        assert_eq!(reviews[0].package.id.id.name, "Tokio_Util");
+        assert_eq!(
+            trustdb
+                .get_pkg_reviews_for_version(crates_io, name, &version)
+                .count(),
+            1
+        );
+    }
+
+    // other sources keep exact matching
+    assert_eq!(
+        trustdb
+            .get_pkg_reviews_for_name(SOURCE, "Tokio_Util")
+            .count(),
+        1
+    );
+    assert_eq!(
+        trustdb
+            .get_pkg_reviews_for_name(SOURCE, "tokio-util")
+            .count(),
+        0
+    );
+    assert_eq!(
+        trustdb
+            .get_pkg_reviews_for_name(SOURCE, "tokio_util")
+            .count(),
+        0
+    );
+
+    assert_eq!(
+        trustdb.find_package_names(crates_io, "Tokio"),
+        vec!["tokio", "tokio-util"]
+    );
+    assert_eq!(
+        trustdb.find_package_names(crates_io, "tokio_"),
+        vec!["tokio-util"]
+    );
+    assert_eq!(
+        trustdb.find_package_names(SOURCE, "tokio"),
+        Vec::<&str>::new()
+    );
+    assert_eq!(
+        trustdb.find_package_names(SOURCE, "Tokio"),
+        vec!["Tokio_Util"]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn crates_io_name_spellings_supersede_each_other() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let crates_io = crev_wot::CRATES_IO_SOURCE;
+    let version = Version::parse("1.0.0").unwrap();
+    let older = build_review_full(&a, crates_io, "Tokio_Util", "1.0.0", vec![1; 32], 0);
+    let newer = build_review_full(&a, crates_io, "tokio-util", "1.0.0", vec![2; 32], 1);
+
+    for proofs in vec![
+        vec![older.clone(), newer.clone()],
+        vec![newer.clone(), older.clone()],
+    ] {
+        let mut trustdb = ProofDB::new();
+        trustdb.import_from_iter(proofs.into_iter().map(|x| (x, url.clone())));
+
+        let names = |reviews: Vec<&proof::review::Package>| -> Vec<String> {
+            reviews
+                .into_iter()
+                .map(|review| review.package.id.id.name.clone())
+                .collect()
+        };
+        assert_eq!(
+            names(
+                trustdb
+                    .get_pkg_reviews_for_version(crates_io, "tokio_util", &version)
+                    .collect()
+            ),
+            vec!["tokio-util"]
+        );
+        assert_eq!(
+            names(
+                trustdb
+                    .get_pkg_reviews_for_name(crates_io, "Tokio_Util")
+                    .collect()
+            ),
+            vec!["tokio-util"]
+        );
+        // digests still tell the package version the way the review spelled it
+        assert_eq!(
+            trustdb
+                .lookup_package_version_by_digest(&Digest::from_vec(vec![2; 32]))
+                .into_iter()
+                .map(|id| id.id.name)
+                .collect::<Vec<_>>(),
+            vec!["tokio-util"]
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn latest_review_per_author_tie_breaking() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let date = chrono::Utc::now();
+    let day = chrono::Duration::days(1);
+    let review = |id, source, name, version, date| {
+        build_review_on(id, source, name, version, vec![0; 32], date)
+    };
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            // a: newer version reviewed earlier
+            review(&a, SOURCE, "foo", "1.0.0", date + day + day),
+            review(&a, SOURCE, "foo", "2.0.0", date + day),
+            // b: two versions reviewed at the same time
+            review(&b, SOURCE, "foo", "1.1.0", date),
+            review(&b, SOURCE, "foo", "1.0.0", date),
+            // the same version of the same crate, under two spellings of the name
+            review(
+                &a,
+                crev_wot::CRATES_IO_SOURCE,
+                "foo-bar",
+                "1.0.0",
+                date + day,
+            ),
+            review(&a, crev_wot::CRATES_IO_SOURCE, "foo_bar", "1.0.0", date),
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+
+    let latest = |source, name, by| -> Vec<(Id, String, String)> {
+        trustdb
+            .get_latest_review_per_author(source, name, by)
+            .map(|review| {
+                (
+                    review.from().id.clone(),
+                    review.package.id.id.name.clone(),
+                    review.package.id.version.to_string(),
+                )
+            })
+            .collect()
+    };
+    let mut expected_by_date = vec![
+        (a.id.id.clone(), "foo".to_owned(), "1.0.0".to_owned()),
+        (b.id.id.clone(), "foo".to_owned(), "1.1.0".to_owned()),
+    ];
+    let mut expected_by_version = vec![
+        (a.id.id.clone(), "foo".to_owned(), "2.0.0".to_owned()),
+        (b.id.id.clone(), "foo".to_owned(), "1.1.0".to_owned()),
+    ];
+    expected_by_date.sort();
+    expected_by_version.sort();
+    assert_eq!(latest(SOURCE, "foo", LatestBy::Date), expected_by_date);
+    assert_eq!(
+        latest(SOURCE, "foo", LatestBy::Version),
+        expected_by_version
+    );
+
+    let expected = vec![(a.id.id.clone(), "foo-bar".to_owned(), "1.0.0".to_owned())];
+    for by in &[LatestBy::Date, LatestBy::Version] {
+        assert_eq!(latest(crev_wot::CRATES_IO_SOURCE, "Foo_Bar", *by), expected);
+    }
+    assert_eq!(latest(SOURCE, "bar", LatestBy::Date), vec![]);
+
+    Ok(())
+}
+
+#[test]
+fn flags_and_alternatives_use_canonical_names() {
+    use crev_data::proof::review::Flags;
+    use crev_wot::{
+        testing::{fabricated_date, fabricated_id, package_review_builder, ProofDBBuilder},
+        TrustSet, CRATES_IO_SOURCE,
+    };
+
+    let (a, b) = (fabricated_id(1), fabricated_id(2));
+    let pkg_id = |name: &str| proof::PackageId {
+        source: CRATES_IO_SOURCE.into(),
+        name: name.into(),
+    };
+    let mut builder = ProofDBBuilder::new();
+    builder
+        .package_review(
+            &package_review_builder(&a, CRATES_IO_SOURCE, "Foo_Bar", "1.0.0", fabricated_date(0))
+                .flags(Flags { unmaintained: true })
+                .alternatives(vec![pkg_id("Baz_Quux")].into_iter().collect())
+                .build()
+                .unwrap(),
+        )
+        .package_review(
+            &package_review_builder(&b, CRATES_IO_SOURCE, "foo-bar", "1.1.0", fabricated_date(0))
+                .build()
+                .unwrap(),
+        );
+    let trustdb = builder.build();
+
+    for name in &["Foo_Bar", "foo-bar", "FOO_BAR"] {
+        let flags: Vec<_> = trustdb
+            .get_pkg_flags(&pkg_id(name))
+            .map(|(id, flags)| (id.clone(), flags.unmaintained))
+            .collect();
+        assert_eq!(
+            flags,
+            {
+                let mut expected = vec![(a.id.clone(), true), (b.id.clone(), false)];
+                expected.sort();
+                expected
+            },
+            "{}",
+            name
+        );
+        assert_eq!(
+            trustdb.get_pkg_flags_by_author(&a.id, &pkg_id(name)),
+            Some(&Flags { unmaintained: true })
+        );
+        assert_eq!(
+            trustdb
+                .get_pkg_flags_summary(&pkg_id(name), &TrustSet::default(), TrustLevel::None)
+                .unmaintained
+                .count,
+            1
+        );
+        assert_eq!(
+            trustdb.get_pkg_alternatives(&pkg_id(name)),
+            vec![(a.id.clone(), pkg_id("baz-quux"))]
+                .into_iter()
+                .collect()
+        );
+    }
+    // and the other way around
+    assert_eq!(
+        trustdb.get_pkg_alternatives_by_author(&a.id, &pkg_id("baz_quux")),
+        vec![pkg_id("foo-bar")].into_iter().collect()
+    );
+}
+
+#[test]
+fn retracted_reviews_are_hidden() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let date = |days| {
+        chrono::DateTime::parse_from_rfc3339("2020-01-01T00:00:00+00:00")
+            .unwrap()
+            .with_timezone(&chrono::Utc)
+            + chrono::Duration::days(days)
+    };
+    let digest = vec![0; 32];
+    let version = Version::parse("1.0.0").unwrap();
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            build_review_on(&a, SOURCE, "foo", "1.0.0", digest.clone(), date(0)),
+            build_retraction_on(&a, "foo", "1.0.0", digest.clone(), date(1)),
+            // a retraction older than the review doesn't affect it
+            build_retraction_on(&b, "foo", "1.0.0", digest.clone(), date(0)),
+            build_review_on(&b, SOURCE, "foo", "1.0.0", digest.clone(), date(1)),
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+
+    let authors = |reviews: Vec<&proof::review::Package>| -> Vec<Id> {
+        reviews.into_iter().map(|r| r.from().id.clone()).collect()
+    };
+    let visible = vec![b.id.id.clone()];
+    assert_eq!(
+        authors(
+            trustdb
+                .get_pkg_reviews_for_version(SOURCE, "foo", &version)
+                .collect()
+        ),
+        visible
+    );
+    assert_eq!(
+        authors(trustdb.get_pkg_reviews_for_name(SOURCE, "foo").collect()),
+        visible
+    );
+    let by_digest: Vec<_> = trustdb
+        .get_package_reviews_by_digest(&Digest::from_vec(digest.clone()))
+        .map(|r| r.from().id.clone())
+        .collect();
+    assert_eq!(by_digest, visible);
+    assert!(trustdb
+        .get_pkg_review(SOURCE, "foo", &version, &a.id.id)
+        .is_none());
+
+    let retracted: Vec<_> = trustdb
+        .get_pkg_reviews_for_version_including_retracted(SOURCE, "foo", &version)
+        .filter(|r| r.retracted)
+        .map(|r| r.from().id.clone())
+        .collect();
+    assert_eq!(retracted, vec![a.id.id.clone()]);
+    assert_eq!(
+        trustdb
+            .get_package_reviews_by_digest_including_retracted(&Digest::from_vec(digest))
+            .count(),
+        2
+    );
+
+    Ok(())
+}
+
+#[test]
+fn retracting_the_latest_review_keeps_older_ones() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let date = |days| {
+        chrono::DateTime::parse_from_rfc3339("2020-01-01T00:00:00+00:00")
+            .unwrap()
+            .with_timezone(&chrono::Utc)
+            + chrono::Duration::days(days)
+    };
+    let digest = vec![0; 32];
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            build_review_on(&a, SOURCE, "foo", "1.0.0", digest.clone(), date(0)),
+            build_review_on(&a, SOURCE, "foo", "1.1.0", digest.clone(), date(1)),
+            build_retraction_on(&a, "foo", "1.1.0", digest.clone(), date(2)),
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+
+    for by in &[LatestBy::Date, LatestBy::Version] {
+        let latest: Vec<_> = trustdb
+            .get_latest_review_per_author(SOURCE, "foo", *by)
+            .map(|review| {
+                (
+                    review.from().id.clone(),
+                    review.package.id.version.to_string(),
+                )
+            })
+            .collect();
+        assert_eq!(latest, vec![(a.id.id.clone(), "1.0.0".to_owned())]);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn review_history_keeps_superseded_reviews() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let date = |days| {
+        chrono::DateTime::parse_from_rfc3339("2020-01-01T00:00:00+00:00")
+            .unwrap()
+            .with_timezone(&chrono::Utc)
+            + chrono::Duration::days(days)
+    };
+    let version = Version::parse("1.0.0").unwrap();
+
+    // three successive edits, imported out of order
+    let edits = vec![
+        build_review_on(&a, SOURCE, "foo", "1.0.0", vec![1; 32], date(2)),
+        build_review_on(&a, SOURCE, "foo", "1.0.0", vec![0; 32], date(0)),
+        build_review_on(&a, SOURCE, "foo", "1.0.0", vec![2; 32], date(5)),
+    ];
+    // two proofs with the same date
+    let same_date = vec![
+        build_review_on(&b, SOURCE, "foo", "1.0.0", vec![3; 32], date(1)),
+        build_review_on(&b, SOURCE, "foo", "1.0.0", vec![4; 32], date(1)),
+    ];
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        edits
+            .iter()
+            .chain(&same_date)
+            .cloned()
+            .chain(vec![build_review_on(
+                &a,
+                SOURCE,
+                "foo",
+                "2.0.0",
+                vec![9; 32],
+                date(3),
+            )])
+            .map(|x| (x, url.clone())),
+    );
+
+    let digests = |id: &UnlockedId| -> Vec<u8> {
+        trustdb
+            .get_pkg_review_history(SOURCE, "foo", &version, &id.id.id)
+            .into_iter()
+            .map(|r| r.package.digest[0])
+            .collect()
+    };
+    assert_eq!(digests(&a), vec![0, 1, 2]);
+    // only the newest one is returned by other getters
+    assert_eq!(
+        trustdb
+            .get_pkg_review(SOURCE, "foo", &version, &a.id.id)
+            .unwrap()
+            .package
+            .digest[0],
+        2
+    );
+
+    let mut same_date_digests = digests(&b);
+    assert_eq!(same_date_digests.len(), 2);
+    // deterministic, no matter the order of importing
+    let mut reversed = ProofDB::new();
+    reversed.import_from_iter(same_date.into_iter().rev().map(|x| (x, url.clone())));
+    let reversed_digests: Vec<u8> = reversed
+        .get_pkg_review_history(SOURCE, "foo", &version, &b.id.id)
+        .into_iter()
+        .map(|r| r.package.digest[0])
+        .collect();
+    assert_eq!(same_date_digests, reversed_digests);
+    same_date_digests.sort_unstable();
+    assert_eq!(same_date_digests, vec![3, 4]);
+
+    assert!(trustdb
+        .get_pkg_review_history(SOURCE, "bar", &version, &a.id.id)
+        .is_empty());
+    Ok(())
+}
+
+#[test]
+fn reviews_by_author() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            build_review_dated(&a, "foo", "1.0.0", vec![0; 32], 0),
+            build_review_dated(&a, "foo", "1.0.0", vec![1; 32], 1),
+            build_review_dated(&a, "foo", "1.1.0", vec![0; 32], 0),
+            build_review_dated(&a, "bar", "1.0.0", vec![0; 32], 0),
+            build_review_dated(&b, "foo", "1.0.0", vec![0; 32], 2),
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+
+    let reviewed: Vec<_> = trustdb
+        .get_pkg_reviews_by_author(&a.id.id)
+        .map(|r| {
+            (
+                r.package.id.id.name.as_str(),
+                r.package.id.version.to_string(),
+                r.package.digest[0],
+            )
+        })
+        .collect();
+    assert_eq!(
+        reviewed,
+        vec![
+            ("bar", "1.0.0".to_string(), 0),
+            ("foo", "1.0.0".to_string(), 1),
+            ("foo", "1.1.0".to_string(), 0),
+        ]
+    );
+    assert_eq!(trustdb.get_pkg_reviews_by_author(&b.id.id).count(), 1);
+    assert_eq!(
+        trustdb
+            .get_pkg_reviews_by_author(&UnlockedId::generate_for_git_url("https://c").id.id)
+            .count(),
+        0
+    );
+
+    Ok(())
+}
+
+fn build_code_review(
+    id: &UnlockedId,
+    version: &str,
+    files: &[(&str, u8)],
+    days: i64,
+) -> proof::Proof {
+    build_code_review_of(id, "foo", version, files, days)
+}
+
+fn build_code_review_of(
+    id: &UnlockedId,
+    name: &str,
+    version: &str,
+    files: &[(&str, u8)],
+    days: i64,
+) -> proof::Proof {
+    let package_info = proof::PackageInfo {
+        id: proof::PackageVersionId::new(
+            SOURCE.into(),
+            name.into(),
+            Version::parse(version).unwrap(),
+        ),
+        digest: vec![0; 32],
+        digest_type: proof::default_digest_type(),
+        revision: "".into(),
+        revision_type: proof::default_revision_type(),
+    };
+    let mut review = proof::review::CodeBuilder::default()
+        .from(id.id.to_owned())
+        .package(package_info)
+        .files(
+            files
+                .iter()
+                .map(|(path, digest)| proof::review::code::File {
+                    path: path.into(),
+                    digest: vec![*digest; 32],
+                    digest_type: proof::default_digest_type(),
+                })
+                .collect(),
+        )
+        .build()
+        .unwrap();
+    review.common.date = review.common.date + chrono::Duration::days(days);
+
+    review.sign_by(&id).unwrap()
+}
+
+#[test]
+fn reviewed_files_from_code_reviews() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let root = UnlockedId::generate_for_git_url("https://root");
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let stranger = UnlockedId::generate_for_git_url("https://stranger");
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            root.create_signed_trust_proof(vec![a.as_public_id()], TrustLevel::High)?,
+            root.create_signed_trust_proof(vec![b.as_public_id()], TrustLevel::Low)?,
+            build_code_review(&a, "1.0.0", &[("src/lib.rs", 1), ("src/a.rs", 1)], 0),
+            // newer review of the same file wins
+            build_code_review(&a, "1.1.0", &[("src/lib.rs", 2)], 1),
+            build_code_review(&b, "1.0.0", &[("src/b.rs", 1)], 0),
+            build_code_review(&stranger, "1.0.0", &[("src/c.rs", 1)], 0),
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+
+    let pkg_id = proof::PackageId {
+        source: SOURCE.into(),
+        name: "foo".into(),
+    };
+    let all: Vec<_> = trustdb
+        .get_code_reviews_for_package(&pkg_id)
+        .map(|(path, _, file_review)| {
+            (
+                path.to_str().unwrap(),
+                file_review.value().version.to_string(),
+                file_review.value().digest[0],
+            )
+        })
+        .collect();
+    assert_eq!(
+        all,
+        vec![
+            ("src/a.rs", "1.0.0".to_string(), 1),
+            ("src/b.rs", "1.0.0".to_string(), 1),
+            ("src/c.rs", "1.0.0".to_string(), 1),
+            ("src/lib.rs", "1.1.0".to_string(), 2),
+        ]
+    );
+
+    let trust_set = trustdb.calculate_trust_set(root.as_ref(), &default());
+    let files = |min_level| -> Vec<(String, Vec<Id>)> {
+        trustdb
+            .get_reviewed_files(&pkg_id, &trust_set, min_level)
+            .into_iter()
+            .map(|(path, reviews)| {
+                (
+                    path.to_str().unwrap().to_owned(),
+                    reviews.keys().map(|id| (*id).clone()).collect(),
+                )
+            })
+            .collect()
+    };
+    assert_eq!(
+        files(TrustLevel::Low),
+        vec![
+            ("src/a.rs".to_owned(), vec![a.id.id.clone()]),
+            ("src/b.rs".to_owned(), vec![b.id.id.clone()]),
+            ("src/lib.rs".to_owned(), vec![a.id.id.clone()]),
+        ]
+    );
+    assert_eq!(files(TrustLevel::High).len(), 2);
+
+    Ok(())
+}
+
+#[test]
+fn file_reviews_across_packages() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let root = UnlockedId::generate_for_git_url("https://root");
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let stranger = UnlockedId::generate_for_git_url("https://stranger");
+
+    let proofs = vec![
+        root.create_signed_trust_proof(vec![a.as_public_id()], TrustLevel::High)?,
+        root.create_signed_trust_proof(vec![b.as_public_id()], TrustLevel::Low)?,
+        build_code_review_of(
+            &a,
+            "foo",
+            "1.0.0",
+            &[("src/lib.rs", 1), ("src/gen.rs", 7)],
+            0,
+        ),
+        // newer review of the same file wins
+        build_code_review_of(&a, "foo", "1.1.0", &[("src/lib.rs", 2)], 1),
+        // the same file, vendored in other packages
+        build_code_review_of(&b, "bar", "2.0.0", &[("vendor/gen.rs", 7)], 0),
+        build_code_review_of(&stranger, "baz", "0.1.0", &[("gen.rs", 7)], 0),
+    ];
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(proofs.iter().cloned().map(|x| (x, url.clone())));
+    let trust_set = trustdb.calculate_trust_set(root.as_ref(), &default());
+
+    let reviews = |db: &ProofDB, digest: u8, trust_set, min_level| -> Vec<(Id, String, String)> {
+        db.get_file_reviews_by_digest(&[digest; 32], trust_set, min_level)
+            .into_iter()
+            .map(|(id, pkg_version_id, path)| {
+                (
+                    id.clone(),
+                    format!("{} {}", pkg_version_id.id.name, pkg_version_id.version),
+                    path.to_str().unwrap().to_owned(),
+                )
+            })
+            .collect()
+    };
+    let mut everyone = vec![
+        (a.id.id.clone(), "foo 1.0.0".into(), "src/gen.rs".into()),
+        (b.id.id.clone(), "bar 2.0.0".into(), "vendor/gen.rs".into()),
+        (stranger.id.id.clone(), "baz 0.1.0".into(), "gen.rs".into()),
+    ];
+    everyone.sort();
+    assert_eq!(reviews(&trustdb, 7, None, TrustLevel::High), everyone);
+    assert_eq!(
+        reviews(&trustdb, 7, Some(&trust_set), TrustLevel::Low).len(),
+        2
+    );
+    assert_eq!(
+        reviews(&trustdb, 7, Some(&trust_set), TrustLevel::High),
+        vec![(a.id.id.clone(), "foo 1.0.0".into(), "src/gen.rs".into())]
+    );
+    assert_eq!(reviews(&trustdb, 1, None, TrustLevel::None), vec![]);
+    assert_eq!(
+        reviews(&trustdb, 2, None, TrustLevel::None),
+        vec![(a.id.id.clone(), "foo 1.1.0".into(), "src/lib.rs".into())]
+    );
+
+    let digests: Vec<_> = [7, 2, 1, 9]
+        .iter()
+        .map(|digest| vec![*digest; 32])
+        .collect();
+    assert_eq!(
+        trustdb.count_reviewed_files_matching(&digests, Some(&trust_set), TrustLevel::Low),
+        2
+    );
+    assert_eq!(
+        trustdb.count_reviewed_files_matching(&digests, Some(&trust_set), TrustLevel::High),
+        2
+    );
+    assert_eq!(
+        trustdb.count_reviewed_files_matching(&digests[2..], None, TrustLevel::None),
+        0
+    );
+
+    // merging gives the same index
+    let (first, second) = proofs.split_at(3);
+    let mut merged = ProofDB::new();
+    merged.import_from_iter(first.iter().cloned().map(|x| (x, url.clone())));
+    let mut other = ProofDB::new();
+    other.import_from_iter(second.iter().cloned().map(|x| (x, url.clone())));
+    merged.merge(other);
+    assert_eq!(reviews(&merged, 7, None, TrustLevel::None), everyone);
+    assert_eq!(reviews(&merged, 1, None, TrustLevel::None), vec![]);
+    assert_eq!(
+        merged.approximate_memory_usage().file_reviews_by_digest,
+        trustdb.approximate_memory_usage().file_reviews_by_digest
+    );
+
+    Ok(())
+}
+
+#[test]
+fn reviews_for_version_req() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let root = UnlockedId::generate_for_git_url("https://root");
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let stranger = UnlockedId::generate_for_git_url("https://stranger");
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            root.create_signed_trust_proof(vec![a.as_public_id()], TrustLevel::Medium)?,
+            build_review(&a, "foo", "1.1.0", vec![0; 32]),
+            build_review(&a, "foo", "1.2.3", vec![0; 32]),
+            build_review(&a, "foo", "1.3.0-beta", vec![0; 32]),
+            build_review(&a, "foo", "2.0.0", vec![0; 32]),
+            build_review(&stranger, "foo", "1.2.5", vec![0; 32]),
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+
+    let versions = |req: &str| -> Vec<String> {
+        let req = semver::VersionReq::parse(req).unwrap();
+        trustdb
+            .get_pkg_reviews_for_req(SOURCE, "foo", &req)
+            .map(|review| review.package.id.version.to_string())
+            .collect()
+    };
+    assert_eq!(versions("^1.2"), vec!["1.2.3", "1.2.5"]);
+    assert_eq!(versions("^1"), vec!["1.1.0", "1.2.3", "1.2.5"]);
+    // pre-releases only when asked for
+    assert_eq!(versions("^1.3.0-alpha"), vec!["1.3.0-beta"]);
+    assert!(versions("^3").is_empty());
+
+    let trust_set = trustdb.calculate_trust_set(root.as_ref(), &default());
+    let nearest = |version: &str, min_level| {
+        trustdb
+            .get_nearest_reviewed_version(
+                SOURCE,
+                "foo",
+                &Version::parse(version).unwrap(),
+                &trust_set,
+                min_level,
+            )
+            .map(|version| version.to_string())
+    };
+    // 1.2.5 is closer, but reviewed only by an untrusted Id
+    assert_eq!(nearest("1.2.4", TrustLevel::Low).as_deref(), Some("1.2.3"));
+    assert_eq!(nearest("1.2.3", TrustLevel::Low).as_deref(), Some("1.2.3"));
+    // same major version is preferred over a closer major version
+    assert_eq!(
+        nearest("1.9.0", TrustLevel::Low).as_deref(),
+        Some("1.3.0-beta")
+    );
+    assert_eq!(nearest("2.5.0", TrustLevel::Low).as_deref(), Some("2.0.0"));
+    assert_eq!(nearest("1.2.4", TrustLevel::High), None);
+
+    Ok(())
+}
+
+#[test]
+fn wot_as_of_past_date() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let root = UnlockedId::generate_for_git_url("https://root");
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let date = |days| {
+        chrono::DateTime::parse_from_rfc3339("2020-01-01T00:00:00+00:00")
+            .unwrap()
+            .with_timezone(&chrono::Utc)
+            + chrono::Duration::days(days)
+    };
+    let trust = |level, days| -> Result<proof::Proof> {
+        let mut trust = root
+            .as_public_id()
+            .create_trust_proof(vec![a.as_public_id()], level)?;
+        trust.common.date = date(days).into();
+        Ok(trust.sign_by(&root)?)
+    };
+    let version = Version::parse("1.0.0").unwrap();
+
+    let mut trustdb = ProofDB::new();
+    trustdb.set_keep_trust_history(true);
+    // newest first, to make sure the import order doesn't matter
+    trustdb.import_from_iter(
+        vec![
+            build_retraction_on(&a, "foo", "1.0.0", vec![2; 32], date(70)),
+            // distrusted in March
+            trust(TrustLevel::Distrust, 60)?,
+            build_review_on(&a, SOURCE, "foo", "1.0.0", vec![1; 32], date(20)),
+            build_review_on(&a, SOURCE, "foo", "1.0.0", vec![0; 32], date(10)),
+            trust(TrustLevel::High, 0)?,
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+
+    let params = default();
+    let trust_at = |days| {
+        trustdb
+            .calculate_trust_set_at(root.as_ref(), &params, date(days))
+            .get_effective_trust_level_opt(a.as_ref())
+    };
+    let digests_at = |days| -> Vec<u8> {
+        trustdb
+            .get_pkg_reviews_for_version_at(SOURCE, "foo", &version, date(days))
+            .map(|review| review.package.digest[0])
+            .collect()
+    };
+
+    // February: still trusted, and the newer review is in effect
+    assert_eq!(trust_at(40), Some(TrustLevel::High));
+    assert_eq!(digests_at(40), vec![1]);
+    assert_eq!(
+        trustdb
+            .get_pkg_reviews_for_name_at(SOURCE, "foo", date(40))
+            .count(),
+        1
+    );
+    // before the review was superseded
+    assert_eq!(digests_at(15), vec![0]);
+    assert!(digests_at(5).is_empty());
+
+    // after the distrust and the retraction, just like now
+    assert_eq!(trust_at(80), Some(TrustLevel::Distrust));
+    assert!(digests_at(80).is_empty());
+    assert_eq!(
+        trustdb
+            .calculate_trust_set(root.as_ref(), &params)
+            .get_effective_trust_level_opt(a.as_ref()),
+        Some(TrustLevel::Distrust)
+    );
+    assert_eq!(
+        trustdb
+            .get_pkg_reviews_for_version(SOURCE, "foo", &version)
+            .count(),
+        0
+    );
+    assert_eq!(trustdb.approximate_memory_usage().package_review_history, 3);
+
+    Ok(())
+}
+
+#[test]
+fn digests_across_packages() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let c = UnlockedId::generate_for_git_url("https://c");
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            build_review(&a, "foo", "1.0.0", vec![1; 32]),
+            build_review(&b, "foo", "1.0.0", vec![1; 32]),
+            // claims to be the same version, but it's different code
+            build_review(&c, "foo", "1.0.0", vec![2; 32]),
+            build_review(&a, "foo", "1.1.0", vec![3; 32]),
+            // same code as foo 1.0.0, renamed and vendored
+            build_review_full(&b, "OTHER_SOURCE", "foo-fork", "0.1.0", vec![1; 32], 0),
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+
+    let ids: Vec<_> = trustdb
+        .get_package_ids_by_digest(&Digest::from_vec(vec![1; 32]))
+        .into_iter()
+        .map(|id| format!("{}/{}/{}", id.id.source, id.id.name, id.version))
+        .collect();
+    assert_eq!(
+        ids,
+        vec!["OTHER_SOURCE/foo-fork/0.1.0", "SOURCE_ID/foo/1.0.0"]
+    );
+    assert_eq!(
+        trustdb
+            .get_package_reviews_by_digest(&Digest::from_vec(vec![1; 32]))
+            .count(),
+        3
+    );
+
+    let version = Version::parse("1.0.0").unwrap();
+    assert_eq!(
+        trustdb.get_digests_for_package(SOURCE, "foo", &version),
+        vec![Digest::from_vec(vec![1; 32]), Digest::from_vec(vec![2; 32])]
+            .into_iter()
+            .collect()
+    );
+
+    let conflicts = trustdb.find_digest_conflicts(SOURCE, "foo");
+    assert_eq!(conflicts.keys().collect::<Vec<_>>(), vec![&version]);
+    let reviewers: Vec<Vec<Id>> = conflicts[&version]
+        .values()
+        .map(|ids| ids.iter().cloned().collect())
+        .collect();
+    let mut expected = vec![a.id.id.clone(), b.id.id.clone()];
+    expected.sort();
+    assert_eq!(reviewers, vec![expected, vec![c.id.id.clone()]]);
+    assert!(trustdb
+        .find_digest_conflicts("OTHER_SOURCE", "foo-fork")
+        .is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn package_names_by_prefix() -> Result<()> {
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let crates_io = crev_wot::CRATES_IO_SOURCE;
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            build_review(&a, "tower", "1.0.0", vec![1; 32]),
+            build_review(&a, "tokio", "1.0.0", vec![2; 32]),
+            build_review(&a, "tokio-util", "1.0.0", vec![3; 32]),
+            build_review(&a, "Tokio-Big", "1.0.0", vec![4; 32]),
+            build_review(&a, "toml", "1.0.0", vec![5; 32]),
+            build_review_full(&a, "OTHER_SOURCE", "tokio-other", "1.0.0", vec![6; 32], 0),
+            build_review_full(&a, crates_io, "serde_json", "1.0.0", vec![7; 32], 0),
+            build_review_full(&a, crates_io, "serde", "1.0.0", vec![8; 32], 0),
+        ]
+        .into_iter()
+        .map(|x| (x, FetchSource::LocalUser)),
+    );
+
+    let search = |source, prefix| {
+        trustdb
+            .search_package_names(source, prefix)
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(search(SOURCE, "tok"), vec!["tokio", "tokio-util"]);
+    assert_eq!(search(SOURCE, "Tok"), vec!["Tokio-Big"]);
+    assert_eq!(
+        search(SOURCE, ""),
+        vec!["Tokio-Big", "tokio", "tokio-util", "toml", "tower"]
+    );
+    assert_eq!(search(SOURCE, "tokio-"), vec!["tokio-util"]);
+    assert!(search(SOURCE, "tokyo").is_empty());
+    assert!(search("UNKNOWN_SOURCE", "tok").is_empty());
+    // crates.io names are canonicalized
+    assert_eq!(search(crates_io, "Serde_"), vec!["serde-json"]);
+
+    Ok(())
+}
+
+fn build_flagged_review(id: &UnlockedId, unmaintained: bool, days: i64) -> proof::Proof {
+    let package_info = proof::PackageInfo {
+        id: proof::PackageVersionId::new(
+            SOURCE.into(),
+            "foo".into(),
+            Version::parse("1.0.0").unwrap(),
+        ),
+        digest: vec![0; 32],
+        digest_type: proof::default_digest_type(),
+        revision: "".into(),
+        revision_type: proof::default_revision_type(),
+    };
+    let mut review = proof::review::PackageBuilder::default()
+        .from(id.id.to_owned())
+        .package(package_info)
+        .flags(proof::Flags { unmaintained })
+        .build()
+        .unwrap();
+    review.common.date = review.common.date + chrono::Duration::days(days);
+    review.sign_by(id).unwrap()
+}
+
+#[test]
+fn flags_summary_counts_trusted_ids() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let root = UnlockedId::generate_for_git_url("https://root");
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let evil = UnlockedId::generate_for_git_url("https://evil");
+    let stale = UnlockedId::generate_for_git_url("https://stale");
+
+    let a_flag = build_flagged_review(&a, true, -1);
+    let b_flag = build_flagged_review(&b, true, -2);
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            root.create_signed_trust_proof(
+                vec![a.as_public_id(), stale.as_public_id()],
+                TrustLevel::High,
+            )?,
+            root.create_signed_trust_proof(vec![b.as_public_id()], TrustLevel::Low)?,
+            root.create_signed_trust_proof(vec![evil.as_public_id()], TrustLevel::Distrust)?,
+            a_flag.clone(),
+            b_flag,
+            build_flagged_review(&evil, true, 0),
+            // no longer thinks so
+            build_flagged_review(&stale, true, -3),
+            build_flagged_review(&stale, false, 0),
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+
+    let pkg_id = proof::PackageId {
+        source: SOURCE.into(),
+        name: "foo".into(),
+    };
+    let trust_set = trustdb.calculate_trust_set(root.as_ref(), &default());
+    let summary = trustdb.get_pkg_flags_summary(&pkg_id, &trust_set, TrustLevel::Low);
+    assert_eq!(summary.unmaintained.count, 2);
+    assert_eq!(summary.unmaintained.max_trust_level, Some(TrustLevel::High));
+    assert_eq!(summary.unmaintained.latest, Some(a_flag.date_utc()));
+
+    let summary = trustdb.get_pkg_flags_summary(&pkg_id, &trust_set, TrustLevel::Medium);
+    assert_eq!(summary.unmaintained.count, 1);
+
+    // the distrusted Id doesn't count, even with no trust required
+    let summary = trustdb.get_pkg_flags_summary(&pkg_id, &trust_set, TrustLevel::None);
+    assert_eq!(summary.unmaintained.count, 2);
+
+    Ok(())
+}
+
+#[test]
+fn effective_flags_by_majority() -> Result<()> {
+    use crev_wot::{FlagPolicy, FlagRule};
+    use std::collections::BTreeSet;
+
+    let url = FetchSource::LocalUser;
+    let root = UnlockedId::generate_for_git_url("https://root");
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let c = UnlockedId::generate_for_git_url("https://c");
+
+    let mut trustdb = ProofDB::new();
+    let import = |trustdb: &mut ProofDB, proofs: Vec<proof::Proof>| {
+        trustdb.import_from_iter(proofs.into_iter().map(|x| (x, url.clone())));
+        trustdb.calculate_trust_set(root.as_ref(), &default())
+    };
+    let trust_set = import(
+        &mut trustdb,
+        vec![
+            root.create_signed_trust_proof(
+                vec![a.as_public_id(), b.as_public_id(), c.as_public_id()],
+                TrustLevel::High,
+            )?,
+            build_flagged_review(&a, true, 0),
+            build_flagged_review(&b, false, 0),
+            build_flagged_review(&c, false, -1),
+        ],
+    );
+
+    let pkg_id = proof::PackageId {
+        source: SOURCE.into(),
+        name: "foo".into(),
+    };
+    let any = FlagPolicy::default();
+    let majority = FlagPolicy {
+        rule: FlagRule::Majority,
+        ..FlagPolicy::default()
+    };
+    let flagged_reviewers = |trustdb: &ProofDB, trust_set, policy| {
+        trustdb
+            .get_pkg_reviews_with_issues_or_flags_for_name(
+                SOURCE,
+                "foo",
+                trust_set,
+                TrustLevel::Low,
+                policy,
+            )
+            .map(|review| review.from().id.clone())
+            .collect::<BTreeSet<_>>()
+    };
+
+    let flags = trustdb.get_effective_flags(&pkg_id, &trust_set, &any);
+    assert!(flags.any_set());
+    assert_eq!(
+        flags.unmaintained.set_by.into_iter().collect::<Vec<_>>(),
+        vec![a.id.id.clone()]
+    );
+    assert_eq!(flags.unmaintained.not_set_by.len(), 2);
+    assert_eq!(
+        flagged_reviewers(&trustdb, &trust_set, &any),
+        vec![a.id.id.clone()].into_iter().collect()
+    );
+    // outvoted
+    assert!(!trustdb
+        .get_effective_flags(&pkg_id, &trust_set, &majority)
+        .any_set());
+    assert!(flagged_reviewers(&trustdb, &trust_set, &majority).is_empty());
+    // flags alone are not issues
+    assert_eq!(
+        trustdb
+            .get_pkg_reviews_with_issues_for_name(SOURCE, "foo", &trust_set, TrustLevel::Low)
+            .count(),
+        0
+    );
+
+    // `c` changed their mind
+    let trust_set = import(&mut trustdb, vec![build_flagged_review(&c, true, 1)]);
+    let flags = trustdb.get_effective_flags(&pkg_id, &trust_set, &majority);
+    assert!(flags.unmaintained.is_set);
+    assert_eq!(
+        flags
+            .unmaintained
+            .not_set_by
+            .into_iter()
+            .collect::<Vec<_>>(),
+        vec![b.id.id.clone()]
+    );
+    assert_eq!(
+        flagged_reviewers(&trustdb, &trust_set, &majority),
+        vec![a.id.id.clone(), c.id.id.clone()].into_iter().collect()
+    );
+    let none = crev_wot::TrustSet::default();
+    assert!(!trustdb.get_effective_flags(&pkg_id, &none, &any).any_set());
+
+    Ok(())
+}
+
+fn build_rated_review(
+    id: &UnlockedId,
+    name: &str,
+    rating: proof::review::Rating,
+    alternatives: &[&str],
+) -> proof::Proof {
+    let package_info = proof::PackageInfo {
+        id: proof::PackageVersionId::new(
+            SOURCE.into(),
+            name.into(),
+            Version::parse("1.0.0").unwrap(),
+        ),
+        digest: vec![0; 32],
+        digest_type: proof::default_digest_type(),
+        revision: "".into(),
+        revision_type: proof::default_revision_type(),
+    };
+    let mut review = proof::review::Review::new_none();
+    review.rating = rating;
+    proof::review::PackageBuilder::default()
+        .from(id.id.to_owned())
+        .package(package_info)
+        .review(review)
+        .alternatives(
+            alternatives
+                .iter()
+                .map(|name| proof::PackageId {
+                    source: SOURCE.into(),
+                    name: (*name).into(),
+                })
+                .collect(),
+        )
+        .build()
+        .unwrap()
+        .sign_by(id)
+        .unwrap()
+}
+
+#[test]
+fn trusted_alternatives_are_ranked() -> Result<()> {
+    use proof::review::Rating;
+
+    let url = FetchSource::LocalUser;
+    let root = UnlockedId::generate_for_git_url("https://root");
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let evil = UnlockedId::generate_for_git_url("https://evil");
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            root.create_signed_trust_proof(
+                vec![a.as_public_id(), b.as_public_id()],
+                TrustLevel::High,
+            )?,
+            root.create_signed_trust_proof(vec![evil.as_public_id()], TrustLevel::Distrust)?,
+            build_rated_review(&a, "foo", Rating::Neutral, &["bar", "baz"]),
+            build_rated_review(&b, "foo", Rating::Neutral, &["baz"]),
+            build_rated_review(&evil, "foo", Rating::Strong, &["qux", "bar"]),
+            build_rated_review(&a, "bar", Rating::Positive, &[]),
+            build_rated_review(&b, "bar", Rating::Negative, &[]),
+            build_rated_review(&evil, "qux", Rating::Strong, &[]),
+            build_rated_review(&evil, "baz", Rating::Strong, &[]),
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+
+    let pkg_id = proof::PackageId {
+        source: SOURCE.into(),
+        name: "foo".into(),
+    };
+    let trust_set = trustdb.calculate_trust_set(root.as_ref(), &default());
+    let suggestions: Vec<_> = trustdb
+        .get_trusted_pkg_alternatives(&pkg_id, &trust_set, TrustLevel::Low)
+        .into_iter()
+        .map(|s| (s.pkg_id.name, s.reporter_count, s.positive_review_count))
+        .collect();
+    assert_eq!(
+        suggestions,
+        vec![("baz".to_string(), 2, 0), ("bar".to_string(), 1, 1)]
+    );
+
+    // all of them are still reported without the trust filter
+    assert_eq!(trustdb.get_pkg_alternatives(&pkg_id).len(), 5);
+
+    Ok(())
+}
+
+#[test]
+fn alternative_chains_are_followed() -> Result<()> {
+    use proof::review::Rating;
+
+    let url = FetchSource::LocalUser;
+    let root = UnlockedId::generate_for_git_url("https://root");
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let evil = UnlockedId::generate_for_git_url("https://evil");
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            root.create_signed_trust_proof(
+                vec![a.as_public_id(), b.as_public_id()],
+                TrustLevel::High,
+            )?,
+            root.create_signed_trust_proof(vec![evil.as_public_id()], TrustLevel::Distrust)?,
+            build_rated_review(&a, "foo", Rating::Negative, &["bar"]),
+            // `bar` and `baz` are alternatives to each other, and so are `foo` and `bar`
+            build_rated_review(&a, "bar", Rating::Negative, &["baz"]),
+            build_rated_review(&b, "baz", Rating::Negative, &["bar", "qux"]),
+            build_rated_review(&a, "qux", Rating::Positive, &["quux"]),
+            build_rated_review(&evil, "foo", Rating::Strong, &["evil"]),
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+
+    let pkg_id = |name: &str| proof::PackageId {
+        source: SOURCE.into(),
+        name: name.into(),
+    };
+    let trust_set = trustdb.calculate_trust_set(root.as_ref(), &default());
+    let chain = |max_depth| {
+        trustdb
+            .resolve_alternative_chain(&pkg_id("foo"), &trust_set, TrustLevel::Low, max_depth)
+            .into_iter()
+            .map(|hop| {
+                (
+                    hop.from.name,
+                    hop.to.name,
+                    hop.depth,
+                    hop.reporters.into_iter().collect::<Vec<_>>(),
+                    hop.has_positive_reviews,
+                    hop.is_endpoint,
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let mut both = vec![a.id.id.clone(), b.id.id.clone()];
+    both.sort();
+    // `qux` is fine, so its alternatives are not followed
+    assert_eq!(
+        chain(10),
+        vec![
+            (
+                "foo".into(),
+                "bar".into(),
+                1,
+                vec![a.id.id.clone()],
+                false,
+                false
+            ),
+            ("bar".into(), "baz".into(), 2, both, false, false),
+            (
+                "baz".into(),
+                "qux".into(),
+                3,
+                vec![b.id.id.clone()],
+                true,
+                true
+            ),
+        ]
+    );
+    assert_eq!(chain(2), chain(10)[..2].to_vec());
+    assert!(chain(0).is_empty());
+
+    // from the middle of the cycle
+    let names: Vec<_> = trustdb
+        .resolve_alternative_chain(&pkg_id("bar"), &trust_set, TrustLevel::Low, 10)
+        .into_iter()
+        .map(|hop| (hop.from.name, hop.to.name))
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            ("bar".into(), "baz".into()),
+            ("bar".into(), "foo".into()),
+            ("baz".into(), "qux".into()),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn id_stats_of_reviewers_and_trusters() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let truster = UnlockedId::generate_for_git_url("https://truster");
+    let reviewer = UnlockedId::generate_for_git_url("https://reviewer");
+    let other = UnlockedId::generate_for_git_url("https://other");
+    let date = |days| chrono::Utc::now() + chrono::Duration::days(days);
+
+    let trust = |to: Vec<&UnlockedId>, level, days| -> Result<proof::Proof> {
+        let mut trust = truster
+            .as_public_id()
+            .create_trust_proof(to.into_iter().map(|id| id.as_public_id()), level)?;
+        trust.common.date = date(days).into();
+        Ok(trust.sign_by(&truster)?)
+    };
+    let rated = |name: &str, thoroughness, understanding| {
+        let package_info = proof::PackageInfo {
+            id: proof::PackageVersionId::new(
+                "OTHER_SOURCE".into(),
+                name.into(),
+                Version::parse("1.0.0").unwrap(),
+            ),
+            digest: vec![0; 32],
+            digest_type: proof::default_digest_type(),
+            revision: "".into(),
+            revision_type: proof::default_revision_type(),
+        };
+        let mut review = proof::review::Review::new_none();
+        review.thoroughness = thoroughness;
+        review.understanding = understanding;
+        proof::review::PackageBuilder::default()
+            .from(reviewer.id.to_owned())
+            .package(package_info)
+            .review(review)
+            .build()
+            .unwrap()
+            .sign_by(&reviewer)
+            .unwrap()
+    };
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            trust(vec![&reviewer], TrustLevel::Low, -20)?,
+            // supersedes the one above, which still counts for `first_seen`
+            trust(vec![&reviewer, &other], TrustLevel::High, -10)?,
+            trust(vec![&other], TrustLevel::Distrust, -5)?,
+            build_review_dated(&reviewer, "a", "1.0.0", vec![0; 32], -3),
+            build_review_dated(&reviewer, "a", "1.1.0", vec![0; 32], -2),
+            rated("b", crev_data::Level::High, crev_data::Level::Medium),
+            rated("c", crev_data::Level::Low, crev_data::Level::Medium),
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+
+    // only trust proofs
+    let stats = trustdb.get_id_stats(&truster.id.id);
+    assert_eq!(stats.package_reviews, 0);
+    assert!(stats.package_reviews_by_source.is_empty());
+    assert_eq!(stats.average_thoroughness, None);
+    assert_eq!(stats.trust_proofs, 2);
+    assert_eq!(stats.trust_reported_for, 2);
+    assert_eq!(stats.first_seen.unwrap().date(), date(-20).date());
+    assert_eq!(stats.last_seen.unwrap().date(), date(-5).date());
+
+    // only reviews
+    let stats = trustdb.get_id_stats(&reviewer.id.id);
+    assert_eq!(stats.package_reviews, 4);
+    assert_eq!(
+        stats
+            .package_reviews_by_source
+            .into_iter()
+            .collect::<Vec<_>>(),
+        vec![("OTHER_SOURCE".to_owned(), 2), (SOURCE.to_owned(), 2)]
+    );
+    assert_eq!(stats.average_thoroughness, Some(2.0));
+    assert_eq!(stats.average_understanding, Some(2.0));
+    assert_eq!(stats.trust_proofs, 0);
+    assert_eq!(stats.first_seen.unwrap().date(), date(-3).date());
+
+    assert_eq!(trustdb.get_id_stats(&other.id.id), Default::default());
+
+    let all = trustdb.all_id_stats();
+    assert_eq!(all.len(), 2);
+    assert_eq!(all[&truster.id.id], trustdb.get_id_stats(&truster.id.id));
+    assert_eq!(all[&reviewer.id.id], trustdb.get_id_stats(&reviewer.id.id));
+
+    Ok(())
+}
+
+#[test]
+fn sorted_reviews_by_reference() {
+    use crev_wot::{
+        testing::{fabricated_date, fabricated_id, package_review_builder, ProofDBBuilder},
+        ReviewSortOrder,
+    };
+
+    let [root, a, b, c, d] = [0, 1, 2, 3, 4].map(fabricated_id);
+
+    let mut builder = ProofDBBuilder::new();
+    builder
+        .trust(&root, &a, TrustLevel::Low, fabricated_date(0))
+        .trust(&root, &b, TrustLevel::Medium, fabricated_date(0))
+        .trust(&root, &c, TrustLevel::High, fabricated_date(0));
+    for (from, name, version, days) in &[
+        (&a, "foo", "1.0.0", 3),
+        (&b, "foo", "1.1.0", 1),
+        (&c, "foo", "1.0.0", 2),
+        // not trusted
+        (&d, "foo", "2.0.0", 0),
+        (&a, "bar", "1.0.0", 4),
+    ] {
+        builder.package_review(
+            &package_review_builder(from, SOURCE, name, version, fabricated_date(*days))
+                .build()
+                .unwrap(),
+        );
+    }
+    let trustdb = builder.build();
+    let trust_set = trustdb.calculate_trust_set(&root.id, &default());
+
+    let authors = |order, trust_set| {
+        trustdb
+            .get_package_reviews_for_package_sorted_ref(SOURCE, Some("foo"), None, order, trust_set)
+            .into_iter()
+            .map(|review| review.from().id.clone())
+            .collect::<Vec<_>>()
+    };
+    let ids = |ids: &[&crev_data::PublicId]| ids.iter().map(|id| id.id.clone()).collect::<Vec<_>>();
+
+    assert_eq!(
+        authors(ReviewSortOrder::DateAsc, None),
+        ids(&[&d, &b, &c, &a])
+    );
+    assert_eq!(
+        authors(ReviewSortOrder::DateDesc, None),
+        ids(&[&a, &c, &b, &d])
+    );
+    assert_eq!(
+        authors(ReviewSortOrder::VersionThenDate, None),
+        ids(&[&c, &a, &b, &d])
+    );
+    assert_eq!(
+        authors(ReviewSortOrder::TrustLevelDesc, Some(&trust_set)),
+        ids(&[&c, &b, &a, &d])
+    );
+    // without a trust set, everyone is trusted the same
+    assert_eq!(
+        authors(ReviewSortOrder::TrustLevelDesc, None),
+        ids(&[&d, &b, &c, &a])
+    );
+
+    let version = Version::parse("1.0.0").unwrap();
+    assert_eq!(
+        trustdb
+            .get_package_reviews_for_package_sorted_ref(
+                SOURCE,
+                Some("foo"),
+                Some(&version),
+                ReviewSortOrder::DateAsc,
+                None
+            )
+            .len(),
+        2
+    );
+
+    // the cloning variant is the same, and the references point to the very same
+    // reviews it shares, so nothing got cloned
+    let shared = trustdb.get_package_reviews_for_package_sorted(SOURCE, None, None);
+    let refs = trustdb.get_package_reviews_for_package_sorted_ref(
+        SOURCE,
+        None,
+        None,
+        ReviewSortOrder::DateAsc,
+        None,
+    );
+    assert_eq!(refs.len(), 5);
+    assert_eq!(shared.len(), refs.len());
+    for (shared, review) in shared.iter().zip(refs) {
+        assert!(std::ptr::eq(std::sync::Arc::as_ptr(shared), review));
+    }
+}
+
+#[test]
+fn summary_agrees_with_individual_counts() {
+    use crev_wot::{
+        testing::{fabricated_date, fabricated_id, package_review_builder, ProofDBBuilder},
+        DbSummary,
+    };
+
+    let [root, a, b, c, d] = [0, 1, 2, 3, 4].map(fabricated_id);
+    let other_source = "https://other.test";
+
+    let mut builder = ProofDBBuilder::new();
+    builder
+        .trust(&root, &a, TrustLevel::Low, fabricated_date(0))
+        .trust(&root, &b, TrustLevel::High, fabricated_date(0))
+        .trust(&root, &d, TrustLevel::Distrust, fabricated_date(0));
+    for (from, source, name, version, retracted) in &[
+        (&a, SOURCE, "foo", "1.0.0", false),
+        (&b, SOURCE, "foo", "1.0.0", false),
+        (&b, SOURCE, "foo", "1.1.0", false),
+        (&a, SOURCE, "bar", "1.0.0", false),
+        (&c, SOURCE, "baz", "1.0.0", false),
+        (&d, SOURCE, "qux", "1.0.0", false),
+        (&c, SOURCE, "gone", "1.0.0", true),
+        (&c, other_source, "foo", "1.0.0", false),
+    ] {
+        builder.package_review(
+            &package_review_builder(from, source, name, version, fabricated_date(1))
+                .retracted(*retracted)
+                .build()
+                .unwrap(),
+        );
+    }
+    let trustdb = builder.build();
+    let trust_set = trustdb.calculate_trust_set(&root.id, &default());
+
+    let summary = trustdb.summary(SOURCE, Some(&trust_set), TrustLevel::Low);
+    assert_eq!(
+        summary,
+        DbSummary {
+            package_reviews: 6,
+            retracted_package_reviews: 1,
+            packages: 4,
+            versions: 5,
+            reviewers: 4,
+            trusted_package_reviews: 4,
+            trusted_packages: 2,
+            trust_proofs: 3,
+            known_ids: 5,
+        }
+    );
+    let summary = trustdb.summary(SOURCE, Some(&trust_set), TrustLevel::High);
+    assert_eq!(
+        (summary.trusted_package_reviews, summary.trusted_packages),
+        (2, 1)
+    );
+
+    let all = trustdb.summary_all_sources();
+    assert_eq!(
+        all.keys().collect::<Vec<_>>(),
+        [SOURCE, other_source].iter().collect::<Vec<_>>()
+    );
+    for (source, summary) in &all {
+        assert_eq!(
+            summary.package_reviews,
+            trustdb.get_package_review_count(source, None, None)
+        );
+        assert_eq!(summary.trusted_package_reviews, 0);
+        assert_eq!(summary.trust_proofs, trustdb.unique_trust_proof_count());
+        assert_eq!(summary.known_ids, trustdb.all_known_ids().len());
+    }
+    assert_eq!(
+        all.values()
+            .map(|summary| summary.package_reviews + summary.retracted_package_reviews)
+            .sum::<usize>(),
+        trustdb.unique_package_review_proof_count()
+    );
+    assert_eq!(
+        trustdb.summary("https://unknown.test", None, TrustLevel::Low),
+        DbSummary {
+            trust_proofs: 3,
+            known_ids: 5,
+            ..DbSummary::default()
+        }
+    );
+}
+
+#[test]
+fn expired_reviews_are_hidden() {
+    use chrono::{Duration, FixedOffset};
+    use crev_data::proof::CommonOps;
+    use crev_wot::{
+        testing::{fabricated_date, fabricated_id, package_review_builder, ProofDBBuilder},
+        Clock,
+    };
+    use std::collections::BTreeSet;
+
+    let [a, b] = [1, 2].map(fabricated_id);
+    let version = Version::parse("1.0.0").unwrap();
+    let review = |from: &crev_data::PublicId, days, expires_days: Option<i64>| {
+        package_review_builder(from, SOURCE, "foo", "1.0.0", fabricated_date(days))
+            .expires(
+                expires_days.map(|days| fabricated_date(days).with_timezone(&FixedOffset::east(0))),
+            )
+            .build()
+            .unwrap()
+    };
+
+    let mut builder = ProofDBBuilder::new();
+    builder
+        .package_review(&review(&a, 0, Some(10)))
+        // the newest review stands, even once expired
+        .package_review(&review(&b, 0, None))
+        .package_review(&review(&b, 5, Some(7)));
+    let mut db = builder.build();
+    let authors = |db: &ProofDB, including_expired: bool| {
+        let reviews: Vec<_> = if including_expired {
+            db.get_pkg_reviews_for_version_including_expired(SOURCE, "foo", &version)
+                .collect()
+        } else {
+            db.get_pkg_reviews_for_version(SOURCE, "foo", &version)
+                .collect()
+        };
+        reviews
+            .into_iter()
+            .map(|review| (review.common.from.id.clone(), review.date_utc()))
+            .collect::<BTreeSet<_>>()
+    };
+
+    db.set_clock(Clock::Fixed(fabricated_date(7) - Duration::seconds(1)));
+    assert_eq!(
+        authors(&db, false),
+        vec![
+            (a.id.clone(), fabricated_date(0)),
+            (b.id.clone(), fabricated_date(5))
+        ]
+        .into_iter()
+        .collect()
+    );
+
+    db.set_clock(Clock::Fixed(fabricated_date(7)));
+    assert_eq!(
+        authors(&db, false),
+        vec![(a.id.clone(), fabricated_date(0))]
+            .into_iter()
+            .collect()
+    );
+    assert_eq!(
+        authors(&db, true),
+        vec![
+            (a.id.clone(), fabricated_date(0)),
+            (b.id.clone(), fabricated_date(5))
+        ]
+        .into_iter()
+        .collect()
+    );
+
+    db.set_clock(Clock::Fixed(fabricated_date(10)));
+    assert_eq!(db.get_pkg_reviews_for_name(SOURCE, "foo").count(), 0);
+    assert_eq!(
+        db.get_pkg_reviews_for_name_including_expired(SOURCE, "foo")
+            .count(),
+        2
+    );
+}
+
+#[test]
+fn sorted_reviews_skip_expired_ones() {
+    use chrono::FixedOffset;
+    use crev_wot::{
+        testing::{fabricated_date, fabricated_id, package_review_builder, ProofDBBuilder},
+        Clock, ReviewSortOrder,
+    };
+
+    let (a, b) = (fabricated_id(1), fabricated_id(2));
+    let version = Version::parse("1.0.0").unwrap();
+    let mut builder = ProofDBBuilder::new();
+    builder
+        .package_review(
+            &package_review_builder(&a, SOURCE, "foo", "1.0.0", fabricated_date(0))
+                .expires(Some(
+                    fabricated_date(10).with_timezone(&FixedOffset::east(0)),
+                ))
+                .build()
+                .unwrap(),
+        )
+        .package_review(
+            &package_review_builder(&b, SOURCE, "foo", "1.0.0", fabricated_date(1))
+                .build()
+                .unwrap(),
+        );
+    let mut db = builder.build();
+    let authors = |db: &ProofDB| {
+        let sorted: Vec<_> = db
+            .get_package_reviews_for_package_sorted(SOURCE, Some("foo"), None)
+            .iter()
+            .map(|review| review.common.from.id.clone())
+            .collect();
+        let sorted_ref: Vec<_> = db
+            .get_package_reviews_for_package_sorted_ref(
+                SOURCE,
+                Some("foo"),
+                Some(&version),
+                ReviewSortOrder::DateDesc,
+                None,
+            )
+            .iter()
+            .rev()
+            .map(|review| review.common.from.id.clone())
+            .collect();
+        assert_eq!(sorted, sorted_ref);
+        sorted
+    };
+
+    db.set_clock(Clock::Fixed(fabricated_date(9)));
+    assert_eq!(authors(&db), vec![a.id.clone(), b.id.clone()]);
+    db.set_clock(Clock::Fixed(fabricated_date(10)));
+    assert_eq!(authors(&db), vec![b.id.clone()]);
+}
+
+#[test]
+fn review_filter_max_age_uses_the_db_clock() {
+    use chrono::Duration;
+    use crev_wot::{
+        testing::{fabricated_date, fabricated_id, package_review_builder, ProofDBBuilder},
+        Clock, ReviewFilter,
+    };
+    use std::collections::BTreeSet;
+
+    let (a, b) = (fabricated_id(1), fabricated_id(2));
+    let version = Version::parse("1.0.0").unwrap();
+    let review = |from, days| {
+        package_review_builder(from, SOURCE, "foo", "1.0.0", fabricated_date(days))
+            .build()
+            .unwrap()
+    };
+    let mut builder = ProofDBBuilder::new();
+    builder
+        .package_review(&review(&a, 0))
+        .package_review(&review(&b, 5));
+    let mut db = builder.build();
+    let filter = ReviewFilter {
+        max_age: Some(Duration::days(10)),
+        ..ReviewFilter::default()
+    };
+    let authors = |db: &ProofDB| {
+        let by_name: BTreeSet<_> = db
+            .get_pkg_reviews_for_name_filtered(SOURCE, "foo", &filter)
+            .map(|review| review.common.from.id.clone())
+            .collect();
+        let by_version: BTreeSet<_> = db
+            .get_pkg_reviews_for_version_filtered(SOURCE, "foo", &version, &filter)
+            .map(|review| review.common.from.id.clone())
+            .collect();
+        assert_eq!(by_name, by_version);
+        by_name
+    };
+
+    // a review exactly `max_age` old is still accepted
+    db.set_clock(Clock::Fixed(fabricated_date(10)));
+    assert_eq!(
+        authors(&db),
+        vec![a.id.clone(), b.id.clone()].into_iter().collect()
+    );
+    assert!(filter.accepts(&review(&a, 0), db.now()));
+
+    db.set_clock(Clock::Fixed(fabricated_date(10) + Duration::seconds(1)));
+    assert_eq!(authors(&db), vec![b.id.clone()].into_iter().collect());
+    assert!(!filter.accepts(&review(&a, 0), db.now()));
+
+    db.set_clock(Clock::Fixed(fabricated_date(16)));
+    assert!(authors(&db).is_empty());
+}
+
+#[test]
+fn package_score_is_monotonic() {
+    use crev_data::{
+        proof::review::{Flags, Issue, Rating, Review},
+        Level, PublicId,
+    };
+    use crev_wot::{
+        score::{ScoreComponent, ScoreWeights},
+        testing::{fabricated_date, fabricated_id, package_review_builder, ProofDBBuilder},
+        Clock,
+    };
+    use std::collections::BTreeSet;
+
+    let [root, a, b, c, d, e, x] = [0, 1, 2, 3, 4, 5, 6].map(fabricated_id);
+    let pkg = proof::PackageVersionId::new(
+        SOURCE.into(),
+        "foo".into(),
+        Version::parse("1.0.0").unwrap(),
+    );
+    let review = |from, days, rating, issues: Vec<Issue>, unmaintained| {
+        package_review_builder(from, SOURCE, "foo", "1.0.0", fabricated_date(days))
+            .review(Review {
+                rating,
+                ..Review::new_positive()
+            })
+            .issues(issues)
+            .flags(Flags { unmaintained })
+            .build()
+            .unwrap()
+    };
+    let issue = |id: &str, severity| vec![Issue::new_with_severity(id.into(), severity)];
+    // every step may only change the score in one direction
+    let steps = vec![
+        (review(&a, 10, Rating::Positive, vec![], false), true),
+        (review(&b, 50, Rating::Strong, vec![], false), true),
+        (
+            review(&c, 20, Rating::Neutral, issue("I", Level::Low), false),
+            false,
+        ),
+        (
+            review(&d, 30, Rating::Neutral, issue("J", Level::High), false),
+            false,
+        ),
+        (review(&e, 90, Rating::Positive, vec![], false), true),
+        (
+            review(&c, 60, Rating::Negative, issue("I", Level::Low), false),
+            false,
+        ),
+        (
+            review(&d, 70, Rating::Neutral, issue("J", Level::High), true),
+            false,
+        ),
+        (review(&x, 80, Rating::Positive, vec![], false), true),
+    ];
+
+    let score = |reviews: &[&proof::review::Package]| {
+        let mut builder = ProofDBBuilder::new();
+        builder
+            .trust(&root, &a, TrustLevel::Low, fabricated_date(0))
+            .trust(&root, &b, TrustLevel::High, fabricated_date(0))
+            .trust(&root, &c, TrustLevel::Medium, fabricated_date(0))
+            .trust(&root, &d, TrustLevel::Medium, fabricated_date(0))
+            .trust(&root, &e, TrustLevel::Medium, fabricated_date(0))
+            .trust(&root, &x, TrustLevel::Distrust, fabricated_date(0));
+        for review in reviews {
+            builder.package_review(review);
+        }
+        let mut db = builder.build();
+        db.set_clock(Clock::Fixed(fabricated_date(100)));
+        let trust_set = db.calculate_trust_set(&root.id, &default());
+        db.score_package(&pkg, &trust_set, &ScoreWeights::default())
+    };
+
+    let mut reviews = vec![];
+    let mut previous = score(&reviews);
+    assert_eq!(previous.score, 0.);
+    for (step, (review, raises)) in steps.iter().enumerate() {
+        reviews.push(review);
+        let current = score(&reviews);
+        if *raises {
+            assert!(current.score >= previous.score, "step {}", step);
+        } else {
+            assert!(current.score <= previous.score, "step {}", step);
+        }
+        previous = current;
+    }
+
+    let evidence = |component| {
+        previous
+            .contribution(component)
+            .unwrap()
+            .evidence
+            .iter()
+            .cloned()
+            .collect::<BTreeSet<_>>()
+    };
+    let ids = |ids: &[&PublicId]| ids.iter().map(|id| id.id.clone()).collect::<BTreeSet<_>>();
+    assert_eq!(
+        evidence(ScoreComponent::PositiveReviews),
+        ids(&[&a, &b, &e])
+    );
+    assert_eq!(evidence(ScoreComponent::NegativeReviews), ids(&[&c]));
+    assert_eq!(evidence(ScoreComponent::OpenIssues), ids(&[&c, &d]));
+    assert_eq!(evidence(ScoreComponent::Unmaintained), ids(&[&d]));
+    assert_eq!(evidence(ScoreComponent::NewestReview), ids(&[&e]));
+    assert_eq!(
+        previous.score,
+        previous
+            .breakdown
+            .iter()
+            .map(|contribution| contribution.contribution)
+            .sum::<f64>()
+    );
+
+    // the order the proofs were added in doesn't matter
+    reviews.reverse();
+    assert_eq!(score(&reviews), previous);
+}
+
+#[test]
+fn reviews_from_many_sources() {
+    use crev_wot::testing::{
+        fabricated_date, fabricated_id, package_review_builder, ProofDBBuilder,
+    };
+    use std::collections::HashSet;
+
+    let [a, b] = [1, 2].map(fabricated_id);
+    let crates_io = "https://crates.io";
+    let mirror = "https://mirror.test";
+    let elsewhere = "https://elsewhere.test";
+
+    let mut builder = ProofDBBuilder::new();
+    builder
+        .package_review(
+            &package_review_builder(&a, crates_io, "foo", "1.0.0", fabricated_date(1))
+                .build()
+                .unwrap(),
+        )
+        .package_review(
+            &package_review_builder(&b, mirror, "foo", "1.0.0", fabricated_date(2))
+                .alternatives(
+                    vec![proof::PackageId {
+                        source: elsewhere.into(),
+                        name: "bar".into(),
+                    }]
+                    .into_iter()
+                    .collect::<HashSet<_>>(),
+                )
+                .build()
+                .unwrap(),
+        )
+        .package_review(
+            &package_review_builder(&b, mirror, "baz", "1.0.0", fabricated_date(3))
+                .build()
+                .unwrap(),
+        );
+    let trustdb = builder.build();
+
+    assert_eq!(
+        trustdb.known_sources().collect::<Vec<_>>(),
+        vec![crates_io, elsewhere, mirror]
+    );
+    // `summary_all_sources` only has the ones with reviews
+    assert_eq!(
+        trustdb.summary_all_sources().keys().collect::<Vec<_>>(),
+        vec![crates_io, mirror]
+    );
+
+    let foo: Vec<_> = trustdb
+        .get_pkg_reviews_for_name_any_source("foo")
+        .map(|(source, review)| (source.as_str(), review.from().id.clone()))
+        .collect();
+    assert_eq!(foo, vec![(crates_io, a.id.clone()), (mirror, b.id.clone())]);
+    assert_eq!(
+        trustdb.get_pkg_reviews_for_name_any_source("bar").count(),
+        0
+    );
+
+    let mut merged = ProofDB::new();
+    merged.merge(trustdb);
+    assert_eq!(merged.known_sources().count(), 3);
+}
+
+#[test]
+fn review_pages() {
+    use crev_wot::{
+        testing::{fabricated_date, fabricated_id, package_review_builder, ProofDBBuilder},
+        ReviewSortOrder,
+    };
+
+    let [a, b, c, d, e, f, g] = [1, 2, 3, 4, 5, 6, 7].map(fabricated_id);
+    let mut builder = ProofDBBuilder::new();
+    // four of them at the same time
+    for (from, name, version, days, retracted) in &[
+        (&c, "foo", "1.0.0", 5, false),
+        (&e, "foo", "1.0.0", 7, false),
+        (&a, "foo", "1.0.0", 5, false),
+        (&f, "foo", "1.0.0", 1, false),
+        (&d, "foo", "1.1.0", 5, false),
+        (&b, "foo", "1.0.0", 5, false),
+        (&g, "foo", "1.0.0", 9, true),
+        (&a, "bar", "1.0.0", 3, false),
+    ] {
+        builder.package_review(
+            &package_review_builder(from, SOURCE, name, version, fabricated_date(*days))
+                .retracted(*retracted)
+                .build()
+                .unwrap(),
+        );
+    }
+    let trustdb = builder.build();
+
+    let page = |offset, limit| {
+        let page = trustdb.get_pkg_reviews_page(
+            SOURCE,
+            Some("foo"),
+            ReviewSortOrder::DateDesc,
+            offset,
+            limit,
+        );
+        let authors: Vec<_> = page
+            .reviews
+            .iter()
+            .map(|review| review.from().id.clone())
+            .collect();
+        (authors, page.total, page.has_more)
+    };
+    let ids = |ids: &[&crev_data::PublicId]| ids.iter().map(|id| id.id.clone()).collect::<Vec<_>>();
+
+    // same dates are ordered by author
+    assert_eq!(page(0, 2), (ids(&[&e, &a]), 6, true));
+    assert_eq!(page(2, 2), (ids(&[&b, &c]), 6, true));
+    assert_eq!(page(4, 2), (ids(&[&d, &f]), 6, false));
+    // every time the same
+    assert_eq!(page(2, 2), page(2, 2));
+    assert_eq!(page(0, 6), page(0, 100));
+
+    // past the end
+    assert_eq!(page(6, 2), (vec![], 6, false));
+    assert_eq!(page(100, 2), (vec![], 6, false));
+    assert_eq!(page(0, 0), (vec![], 6, true));
+    assert_eq!(
+        trustdb
+            .get_pkg_reviews_page(SOURCE, Some("unknown"), ReviewSortOrder::DateDesc, 0, 10)
+            .total,
+        0
+    );
+
+    // other orders and the whole source are pages of the sorted reviews
+    for order in &[ReviewSortOrder::DateAsc, ReviewSortOrder::VersionThenDate] {
+        let sorted =
+            trustdb.get_package_reviews_for_package_sorted_ref(SOURCE, None, None, *order, None);
+        assert_eq!(sorted.len(), 7);
+        let page = trustdb.get_pkg_reviews_page(SOURCE, None, *order, 3, 3);
+        assert_eq!((page.total, page.has_more), (7, true));
+        assert!(page
+            .reviews
+            .iter()
+            .zip(&sorted[3..6])
+            .all(|(a, b)| std::ptr::eq(*a, *b)));
+    }
+}
+
+#[test]
+fn review_pages_skip_expired_reviews() {
+    use chrono::FixedOffset;
+    use crev_wot::{
+        testing::{fabricated_date, fabricated_id, package_review_builder, ProofDBBuilder},
+        Clock, ReviewSortOrder,
+    };
+
+    let (a, b) = (fabricated_id(1), fabricated_id(2));
+    let mut builder = ProofDBBuilder::new();
+    builder
+        .package_review(
+            &package_review_builder(&a, SOURCE, "foo", "1.0.0", fabricated_date(1))
+                .build()
+                .unwrap(),
+        )
+        .package_review(
+            &package_review_builder(&b, SOURCE, "foo", "1.1.0", fabricated_date(5))
+                .expires(Some(
+                    fabricated_date(8).with_timezone(&FixedOffset::east(0)),
+                ))
+                .build()
+                .unwrap(),
+        );
+    let mut db = builder.build();
+    let page = |db: &ProofDB, order| {
+        let page = db.get_pkg_reviews_page(SOURCE, Some("foo"), order, 0, 10);
+        let authors: Vec<_> = page
+            .reviews
+            .iter()
+            .map(|review| review.from().id.clone())
+            .collect();
+        (authors, page.total)
+    };
+
+    db.set_clock(Clock::Fixed(fabricated_date(7)));
+    assert_eq!(
+        page(&db, ReviewSortOrder::DateDesc),
+        (vec![b.id.clone(), a.id.clone()], 2)
+    );
+    db.set_clock(Clock::Fixed(fabricated_date(8)));
+    for order in &[ReviewSortOrder::DateDesc, ReviewSortOrder::DateAsc] {
+        assert_eq!(page(&db, *order), (vec![a.id.clone()], 1));
+    }
+}
+
+#[test]
+fn search_review_comments() {
+    use crev_wot::testing::{
+        fabricated_date, fabricated_id, package_review_builder, ProofDBBuilder,
+    };
+
+    let [root, a, b, c, x] = [0, 1, 2, 3, 4].map(fabricated_id);
+    let other_source = "https://other.test";
+    let review = |from, source, name, days, comment: &str| {
+        package_review_builder(from, source, name, "1.0.0", fabricated_date(days))
+            .comment(comment.into())
+            .build()
+            .unwrap()
+    };
+
+    let mut builder = ProofDBBuilder::new();
+    builder
+        .trust(&root, &a, TrustLevel::High, fabricated_date(0))
+        .trust(&root, &b, TrustLevel::Low, fabricated_date(0))
+        .trust(&root, &x, TrustLevel::Distrust, fabricated_date(0));
+    for review in &[
+        review(
+            &a,
+            SOURCE,
+            "foo",
+            1,
+            "Executes a build script that downloads binaries",
+        ),
+        review(
+            &a,
+            SOURCE,
+            "foo",
+            2,
+            "Executes a Build Script that downloads binaries, and builds",
+        ),
+        review(&b, SOURCE, "bar", 1, "build script is fine"),
+        review(&x, SOURCE, "baz", 1, "build script downloads stuff"),
+        review(&c, SOURCE, "qux", 1, "downloads binaries in build"),
+        review(&b, other_source, "foo", 1, "build script downloads"),
+    ] {
+        builder.package_review(review);
+    }
+    let trustdb = builder.build();
+    let trust_set = trustdb.calculate_trust_set(&root.id, &default());
+
+    let search = |trustdb: &ProofDB, query, source, trust_set, min_level| {
+        trustdb
+            .search_review_comments(query, source, trust_set, min_level)
+            .iter()
+            .map(|found| {
+                (
+                    found.package.id.source.clone(),
+                    found.package.id.name.clone(),
+                    found.review.from().id.clone(),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+    let found = |matches: &[(&str, &str, &crev_data::PublicId)]| {
+        matches
+            .iter()
+            .map(|(source, name, id)| (source.to_string(), name.to_string(), id.id.clone()))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        search(&trustdb, "build script", None, None, TrustLevel::None),
+        found(&[
+            (SOURCE, "bar", &b),
+            (SOURCE, "baz", &x),
+            (SOURCE, "foo", &a),
+            (other_source, "foo", &b),
+        ])
+    );
+    // distrusted and untrusted Ids, and the ones not trusted enough, don't count
+    assert_eq!(
+        search(
+            &trustdb,
+            "build script",
+            None,
+            Some(&trust_set),
+            TrustLevel::Low
+        ),
+        found(&[
+            (SOURCE, "bar", &b),
+            (SOURCE, "foo", &a),
+            (other_source, "foo", &b)
+        ])
+    );
+    assert_eq!(
+        search(
+            &trustdb,
+            "build script",
+            None,
+            Some(&trust_set),
+            TrustLevel::High
+        ),
+        found(&[(SOURCE, "foo", &a)])
+    );
+    // all the words, in any order and case
+    assert_eq!(
+        search(&trustdb, "DOWNLOADS build", None, None, TrustLevel::None),
+        found(&[
+            (SOURCE, "baz", &x),
+            (SOURCE, "foo", &a),
+            (SOURCE, "qux", &c),
+            (other_source, "foo", &b),
+        ])
+    );
+    assert_eq!(
+        search(
+            &trustdb,
+            "DOWNLOADS build",
+            Some(other_source),
+            None,
+            TrustLevel::None
+        ),
+        found(&[(other_source, "foo", &b)])
+    );
+    assert!(search(
+        &trustdb,
+        "build fine downloads",
+        None,
+        None,
+        TrustLevel::None
+    )
+    .is_empty());
+    assert!(search(&trustdb, "  ", None, None, TrustLevel::None).is_empty());
+    // only the most recent review of `a` counts
+    assert_eq!(
+        search(&trustdb, "builds", None, None, TrustLevel::None).len(),
+        1
+    );
+
+    let matches = trustdb.search_review_comments(
+        "build downloads",
+        Some(SOURCE),
+        Some(&trust_set),
+        TrustLevel::High,
+    );
+    let comment = &matches[0].review.comment;
+    assert_eq!(
+        matches[0]
+            .ranges
+            .iter()
+            .map(|range| &comment[range.clone()])
+            .collect::<Vec<_>>(),
+        vec!["Build", "downloads", "build"]
+    );
+
+    // the index is rebuilt after adding more reviews
+    let mut builder = ProofDBBuilder::from_db(trustdb);
+    builder.package_review(&review(&c, SOURCE, "qux", 2, "no script"));
+    let trustdb = builder.build();
+    assert_eq!(
+        search(&trustdb, "script", Some(SOURCE), None, TrustLevel::None),
+        found(&[
+            (SOURCE, "bar", &b),
+            (SOURCE, "baz", &x),
+            (SOURCE, "foo", &a),
+            (SOURCE, "qux", &c),
+        ])
+    );
+}
@@ -0,0 +1,395 @@
+use super::*;
+
+use chrono::Duration;
+use crev_data::{
+    proof::{self, CommonOps, ContentExt},
+    TrustLevel, UnlockedId,
+};
+use crev_wot::{ChangeEvent, FetchSource, ProofDB};
+use std::sync::Arc;
+
+#[test]
+fn proof_count_by_fetch_source() -> Result<()> {
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let c = UnlockedId::generate_for_git_url("https://c");
+
+    let source_a = FetchSource::Url(Arc::new(crev_data::Url::new_git("https://a")));
+    let source_b = FetchSource::Url(Arc::new(crev_data::Url::new_git("https://b")));
+
+    let a_old = {
+        let mut trust = a
+            .as_public_id()
+            .create_trust_proof(vec![c.as_public_id()], TrustLevel::Low)?;
+        trust.common.date = trust.common.date - Duration::days(10);
+        trust.sign_by(&a)?
+    };
+    let a_new = a.create_signed_trust_proof(vec![b.as_public_id()], TrustLevel::High)?;
+    let b_proof = b.create_signed_trust_proof(vec![a.as_public_id()], TrustLevel::High)?;
+    let c_proof = c.create_signed_trust_proof(vec![a.as_public_id()], TrustLevel::High)?;
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            (a_old.clone(), source_a.clone()),
+            (a_new.clone(), source_a.clone()),
+            (b_proof.clone(), source_b.clone()),
+            // already fetched from `a`, still counted there
+            (a_new.clone(), source_b.clone()),
+            (c_proof.clone(), FetchSource::LocalUser),
+        ]
+        .into_iter(),
+    );
+
+    let stats = trustdb.proof_count_by_fetch_source();
+    assert_eq!(
+        stats.keys().map(String::as_str).collect::<Vec<_>>(),
+        vec!["https://a", "https://b", "local"]
+    );
+    assert_eq!(stats["https://a"].proof_count, 2);
+    assert_eq!(stats["https://a"].newest_proof_date, a_new.date_utc());
+    assert_eq!(stats["https://b"].proof_count, 1);
+    assert_eq!(stats["https://b"].newest_proof_date, b_proof.date_utc());
+    assert_eq!(stats["local"].proof_count, 1);
+
+    assert!(matches!(
+        trustdb.get_fetch_source_of(a_old.signature()),
+        Some(FetchSource::Url(url)) if url.url == "https://a"
+    ));
+    assert!(matches!(
+        trustdb.get_fetch_source_of(c_proof.signature()),
+        Some(FetchSource::LocalUser)
+    ));
+    assert!(trustdb.get_fetch_source_of("nonexistent").is_none());
+
+    Ok(())
+}
+
+fn build_review(id: &UnlockedId, days: i64) -> Result<proof::Proof> {
+    let package_info = proof::PackageInfo {
+        id: proof::PackageVersionId::new(
+            "SOURCE_ID".into(),
+            "foo".into(),
+            semver::Version::parse("1.0.0").unwrap(),
+        ),
+        digest: vec![0; 32],
+        digest_type: proof::default_digest_type(),
+        revision: "".into(),
+        revision_type: proof::default_revision_type(),
+    };
+    let mut review = proof::review::PackageBuilder::default()
+        .from(id.id.to_owned())
+        .package(package_info)
+        .comment(format!("{}", days))
+        .build()
+        .unwrap();
+    review.common.date = review.common.date + Duration::days(days);
+    Ok(review.sign_by(id)?)
+}
+
+#[test]
+fn import_reports_only_actual_changes() -> Result<()> {
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let local = FetchSource::LocalUser;
+
+    let trust = |level, days| -> Result<proof::Proof> {
+        let mut trust = a
+            .as_public_id()
+            .create_trust_proof(vec![b.as_public_id()], level)?;
+        trust.common.date = trust.common.date + Duration::days(days);
+        Ok(trust.sign_by(&a)?)
+    };
+    let review_old = build_review(&a, -1)?;
+    let review_new = build_review(&a, 0)?;
+    let proofs = vec![trust(TrustLevel::Low, -1)?, review_old.clone()];
+
+    let mut trustdb = ProofDB::new();
+    let changes =
+        trustdb.import_from_iter_with_changes(proofs.iter().cloned().map(|x| (x, local.clone())));
+    let review_added = ChangeEvent::PackageReviewAdded {
+        source: "SOURCE_ID".into(),
+        name: "foo".into(),
+        version: semver::Version::parse("1.0.0").unwrap(),
+        author: a.id.id.clone(),
+    };
+    assert_eq!(
+        changes,
+        vec![
+            ChangeEvent::UrlRecorded {
+                id: a.id.id.clone(),
+                verified: true,
+            },
+            ChangeEvent::TrustEdgeChanged {
+                from: a.id.id.clone(),
+                to: b.id.id.clone(),
+                old_level: None,
+                new_level: TrustLevel::Low,
+            },
+            ChangeEvent::UrlRecorded {
+                id: b.id.id.clone(),
+                verified: false,
+            },
+            review_added,
+        ]
+    );
+
+    // re-importing the same proofs changes nothing
+    let changes =
+        trustdb.import_from_iter_with_changes(proofs.iter().cloned().map(|x| (x, local.clone())));
+    assert_eq!(changes, vec![]);
+
+    let changes = trustdb.import_from_iter_with_changes(
+        vec![trust(TrustLevel::High, 0)?, review_new, review_old]
+            .into_iter()
+            .map(|x| (x, local.clone())),
+    );
+    assert_eq!(
+        changes,
+        vec![
+            ChangeEvent::TrustEdgeChanged {
+                from: a.id.id.clone(),
+                to: b.id.id.clone(),
+                old_level: Some(TrustLevel::Low),
+                new_level: TrustLevel::High,
+            },
+            ChangeEvent::PackageReviewSuperseded {
+                source: "SOURCE_ID".into(),
+                name: "foo".into(),
+                version: semver::Version::parse("1.0.0").unwrap(),
+                author: a.id.id.clone(),
+            },
+        ]
+    );
+
+    // plain import doesn't collect anything
+    trustdb.import_from_iter(vec![(trust(TrustLevel::Low, 1)?, local.clone())].into_iter());
+    assert_eq!(
+        trustdb.import_from_iter_with_changes(vec![].into_iter()),
+        vec![]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn remove_proofs_fetched_from_url() -> Result<()> {
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let m = UnlockedId::generate_for_git_url("https://m");
+    let x = UnlockedId::generate_for_git_url("https://x");
+
+    let m_url = crev_data::Url::new_git("https://m");
+    let source_a = FetchSource::Url(Arc::new(crev_data::Url::new_git("https://a")));
+    let source_m = FetchSource::Url(Arc::new(m_url.clone()));
+
+    let a_old = build_review(&a, 0)?;
+    let a_new = build_review(&a, 1)?;
+    let m_review = build_review(&m, 0)?;
+    let m_trust = m.create_signed_trust_proof(vec![x.as_public_id()], TrustLevel::High)?;
+    let b_trust = b.create_signed_trust_proof(vec![a.as_public_id()], TrustLevel::High)?;
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            (a_old, source_a),
+            // a's newer review is known only from m
+            (a_new, source_m.clone()),
+            (m_review, source_m.clone()),
+            (m_trust, source_m.clone()),
+            (b_trust.clone(), source_m.clone()),
+            // seen elsewhere too, so it stays
+            (b_trust.clone(), FetchSource::LocalUser),
+        ]
+        .into_iter(),
+    );
+    let comments = |trustdb: &ProofDB| -> Vec<String> {
+        let mut comments: Vec<_> = trustdb
+            .get_pkg_reviews_for_name("SOURCE_ID", "foo")
+            .map(|review| {
+                format!(
+                    "{} {}",
+                    review.from().url.as_ref().unwrap().url,
+                    review.comment
+                )
+            })
+            .collect();
+        comments.sort();
+        comments
+    };
+    assert_eq!(comments(&trustdb), vec!["https://a 1", "https://m 0"]);
+    assert_eq!(
+        trustdb
+            .ids_claiming_url(&crev_data::Url::new_git("https://x"))
+            .len(),
+        1
+    );
+
+    assert_eq!(trustdb.remove_proofs_fetched_from(&m_url), 3);
+
+    // the superseded review is back
+    assert_eq!(comments(&trustdb), vec!["https://a 0"]);
+    assert_eq!(trustdb.get_direct_trust_for_id(m.as_ref(), None).count(), 0);
+    assert!(trustdb.lookup_url(m.as_ref()).into_owned().is_none());
+    assert!(trustdb.ids_claiming_url(&m_url).is_empty());
+    assert!(trustdb
+        .ids_claiming_url(&crev_data::Url::new_git("https://x"))
+        .is_empty());
+
+    assert_eq!(trustdb.get_direct_trust_for_id(b.as_ref(), None).count(), 1);
+    assert!(matches!(
+        trustdb.get_fetch_source_of(b_trust.signature()),
+        Some(FetchSource::LocalUser)
+    ));
+    assert!(!trustdb
+        .proof_count_by_fetch_source()
+        .contains_key("https://m"));
+
+    assert_eq!(trustdb.remove_proofs_fetched_from(&m_url), 0);
+
+    Ok(())
+}
+
+#[test]
+fn repo_stats_by_fetch_source() -> Result<()> {
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let url_a = crev_data::Url::new_git("https://a");
+    let url_b = crev_data::Url::new_git("https://b");
+    let source_a = FetchSource::Url(Arc::new(url_a.clone()));
+    let source_b = FetchSource::Url(Arc::new(url_b.clone()));
+
+    let trust = a.create_signed_trust_proof(vec![b.as_public_id()], TrustLevel::High)?;
+    let old_review = build_review(&a, -10)?;
+    let b_review = build_review(&b, 5)?;
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            (trust.clone(), source_a.clone()),
+            (old_review.clone(), source_a.clone()),
+            // a mirror of `a`, and a review of `b`
+            (trust.clone(), source_b.clone()),
+            (b_review.clone(), source_b.clone()),
+            // the same proof again doesn't count twice
+            (b_review.clone(), source_b.clone()),
+            (build_review(&a, 0)?, FetchSource::LocalUser),
+        ]
+        .into_iter(),
+    );
+
+    let stats_a = trustdb.get_repo_stats(&url_a).unwrap().clone();
+    assert_eq!(stats_a.trust_proofs, 1);
+    assert_eq!(stats_a.package_reviews, 1);
+    assert_eq!(stats_a.code_reviews, 0);
+    assert_eq!(stats_a.latest_proof_date, trust.date_utc());
+    assert_eq!(stats_a.ids_seen.iter().collect::<Vec<_>>(), vec![&a.id.id]);
+
+    let stats_b = trustdb.get_repo_stats(&url_b).unwrap();
+    assert_eq!(stats_b.proof_count(), 2);
+    assert_eq!(stats_b.latest_proof_date, b_review.date_utc());
+    assert_eq!(stats_b.ids_seen.len(), 2);
+
+    let local = trustdb.get_local_user_stats().unwrap();
+    assert_eq!(local.package_reviews, 1);
+    assert!(trustdb
+        .get_repo_stats(&crev_data::Url::new_git("https://c"))
+        .is_none());
+    assert_eq!(
+        trustdb
+            .all_repo_stats()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>(),
+        vec!["https://a", "https://b", "local"]
+    );
+
+    // stats of other repos stay as they were
+    trustdb.remove_proofs_fetched_from(&url_b);
+    assert!(trustdb.get_repo_stats(&url_b).is_none());
+    assert_eq!(trustdb.get_repo_stats(&url_a), Some(&stats_a));
+
+    let mut other = ProofDB::new();
+    other.import_from_iter(vec![(b_review.clone(), source_b.clone())].into_iter());
+    trustdb.merge(other);
+    assert_eq!(
+        trustdb
+            .get_repo_stats(&url_b)
+            .unwrap()
+            .ids_seen
+            .iter()
+            .collect::<Vec<_>>(),
+        vec![&b.id.id]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn provenance_of_proofs_fetched_from_many_sources() -> Result<()> {
+    use crev_wot::FetchSourceKey;
+
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let c = UnlockedId::generate_for_git_url("https://c");
+
+    let source_a = FetchSource::Url(Arc::new(crev_data::Url::new_git("https://a")));
+    let mirror = FetchSource::Url(Arc::new(crev_data::Url::new_git("https://mirror")));
+
+    let a_review = build_review(&a, 0)?;
+    let b_review = build_review(&b, 0)?;
+    let c_review = build_review(&c, 0)?;
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            (a_review.clone(), mirror.clone()),
+            (a_review.clone(), source_a.clone()),
+            (b_review.clone(), mirror.clone()),
+            (c_review.clone(), FetchSource::LocalUser),
+            (c_review.clone(), mirror.clone()),
+        ]
+        .into_iter(),
+    );
+
+    assert_eq!(
+        trustdb
+            .get_proof_provenance(a_review.signature())
+            .into_iter()
+            .collect::<Vec<_>>(),
+        vec![
+            FetchSourceKey::Url("https://a".into()),
+            FetchSourceKey::Url("https://mirror".into()),
+        ]
+    );
+    assert_eq!(
+        trustdb
+            .get_proof_provenance(c_review.signature())
+            .into_iter()
+            .collect::<Vec<_>>(),
+        vec![
+            FetchSourceKey::LocalUser,
+            FetchSourceKey::Url("https://mirror".into()),
+        ]
+    );
+    assert!(trustdb.get_proof_provenance("nonexistent").is_empty());
+
+    // the URL of `a` is verified, but it's not true for all of its reviews
+    assert!(trustdb
+        .reviews_not_from_verified_source(a.as_ref())
+        .is_empty());
+    let a_newer_review = build_review(&a, 1)?;
+    trustdb.import_from_iter(vec![(a_newer_review.clone(), mirror.clone())].into_iter());
+    assert!(trustdb.lookup_url(a.as_ref()).verified().is_some());
+    let not_verified = trustdb.reviews_not_from_verified_source(a.as_ref());
+    assert_eq!(not_verified.len(), 1);
+    assert_eq!(not_verified[0].0, a_newer_review.signature());
+    assert_eq!(not_verified[0].1.comment, "1");
+
+    let not_verified = trustdb.reviews_not_from_verified_source(b.as_ref());
+    assert_eq!(not_verified.len(), 1);
+    assert_eq!(not_verified[0].0, b_review.signature());
+    assert!(trustdb
+        .reviews_not_from_verified_source(c.as_ref())
+        .is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,2235 @@
+use super::*;
+
+use chrono::{Duration, Utc};
+use crev_data::{
+    proof::{CommonOps, ContentExt},
+    TrustLevel, UnlockedId,
+};
+use crev_wot::{
+    Distance, DistanceMetric, FetchSource, ProofDB, PruneReason, RecordingObserver, TrustSetEvent,
+    TrustSetObserver, TrustSetVisit,
+};
+use std::{collections::BTreeSet, ops::ControlFlow};
+
+fn build_trust_proof(
+    from: &UnlockedId,
+    to: Vec<&UnlockedId>,
+    level: TrustLevel,
+    days: i64,
+) -> Result<proof::Proof> {
+    let mut trust = from
+        .as_public_id()
+        .create_trust_proof(to.into_iter().map(|id| id.as_public_id()), level)?;
+    trust.common.date = trust.common.date + Duration::days(days);
+    Ok(trust.sign_by(from)?)
+}
+
+fn build_review(from: &UnlockedId, name: &str) -> Result<proof::Proof> {
+    build_review_with(from, name, proof::review::Review::new_none())
+}
+
+fn build_review_with(
+    from: &UnlockedId,
+    name: &str,
+    review: proof::review::Review,
+) -> Result<proof::Proof> {
+    let package_info = proof::PackageInfo {
+        id: proof::PackageVersionId::new(
+            "SOURCE_ID".into(),
+            name.into(),
+            semver::Version::parse("1.0.0").unwrap(),
+        ),
+        digest: vec![0; 32],
+        digest_type: proof::default_digest_type(),
+        revision: "".into(),
+        revision_type: proof::default_revision_type(),
+    };
+    let review = proof::review::PackageBuilder::default()
+        .from(from.id.to_owned())
+        .package(package_info)
+        .review(review)
+        .build()
+        .unwrap();
+    Ok(review.sign_by(from)?)
+}
+
+#[test]
+fn direct_trust_reports_most_recent_date() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let c = UnlockedId::generate_for_git_url("https://c");
+
+    let a_to_b_old = build_trust_proof(&a, vec![&b, &c], TrustLevel::Low, -10)?;
+    let a_to_b_new = build_trust_proof(&a, vec![&b], TrustLevel::High, -1)?;
+
+    for order in vec![
+        vec![a_to_b_old.clone(), a_to_b_new.clone()],
+        vec![a_to_b_new.clone(), a_to_b_old.clone()],
+    ] {
+        let mut trustdb = ProofDB::new();
+        trustdb.import_from_iter(order.into_iter().map(|x| (x, url.clone())));
+
+        let direct: Vec<_> = trustdb.get_direct_trust_for_id(a.as_ref(), None).collect();
+        assert_eq!(direct.len(), 2);
+        let to_b = direct.iter().find(|d| d.id == b.as_ref()).unwrap();
+        assert_eq!(to_b.level, TrustLevel::High);
+        assert_eq!(to_b.date, a_to_b_new.date_utc());
+        let to_c = direct.iter().find(|d| d.id == c.as_ref()).unwrap();
+        assert_eq!(to_c.level, TrustLevel::Low);
+        assert_eq!(to_c.date, a_to_b_old.date_utc());
+
+        let recent: Vec<_> = trustdb
+            .get_direct_trust_for_id(a.as_ref(), Some(Utc::now() - Duration::days(5)))
+            .map(|d| d.id.clone())
+            .collect();
+        assert_eq!(recent, vec![b.id.id.clone()]);
+
+        assert_eq!(trustdb.get_direct_trust_for_id(b.as_ref(), None).count(), 0);
+    }
+
+    Ok(())
+}
+
+// Diamond: root trusts `a` and `b`, which vouch for each other and both
+// vouch for `x`. Root also trusts `c` alone, which vouches for `y` alone.
+#[test]
+fn corroboration_mode_caps_uncorroborated_ids() -> Result<()> {
+    use crev_wot::testing::{fabricated_date, fabricated_id, ProofDBBuilder};
+
+    let [root, a, b, c, x, y] = [0, 1, 2, 3, 4, 5].map(fabricated_id);
+
+    let mut builder = ProofDBBuilder::new();
+    for (from, to) in &[
+        (&root, &a),
+        (&root, &b),
+        (&root, &c),
+        (&a, &b),
+        (&a, &x),
+        (&b, &a),
+        (&b, &x),
+        (&c, &y),
+    ] {
+        builder.trust(from, to, TrustLevel::High, fabricated_date(0));
+    }
+    let trustdb = builder.build();
+
+    let default_set = trustdb.calculate_trust_set(&root.id, &default());
+    for id in &[&root, &a, &b, &c, &x, &y] {
+        assert_eq!(
+            default_set.get_effective_trust_level(&id.id),
+            TrustLevel::High
+        );
+    }
+
+    let params = TrustDistanceParams {
+        require_corroboration: Some(crev_wot::CorroborationParams { min_reporters: 2 }),
+        ..default()
+    };
+    let corroborated_set = trustdb.calculate_trust_set(&root.id, &params);
+    for id in &[&root, &a, &b, &x] {
+        assert_eq!(
+            corroborated_set.get_effective_trust_level(&id.id),
+            TrustLevel::High
+        );
+    }
+    assert_eq!(
+        corroborated_set.get_effective_trust_level(&c.id),
+        TrustLevel::Low
+    );
+    // `c` was capped, so it can't vouch for `y` above its own level
+    assert_eq!(
+        corroborated_set.get_effective_trust_level(&y.id),
+        TrustLevel::Low
+    );
+
+    Ok(())
+}
+
+#[test]
+fn preview_trust_change_distrust_cascade() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let root = UnlockedId::generate_for_git_url("https://root");
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let c = UnlockedId::generate_for_git_url("https://c");
+    let d = UnlockedId::generate_for_git_url("https://d");
+    let e = UnlockedId::generate_for_git_url("https://e");
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            build_trust_proof(&root, vec![&a, &d], TrustLevel::High, 0)?,
+            build_trust_proof(&a, vec![&b], TrustLevel::High, 0)?,
+            build_trust_proof(&b, vec![&c], TrustLevel::High, 0)?,
+            build_trust_proof(&e, vec![&b], TrustLevel::Medium, 0)?,
+            build_review(&c, "one")?,
+            build_review(&c, "two")?,
+            build_review(&d, "one")?,
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+    let params = default();
+
+    let delta = trustdb.preview_trust_change(
+        root.as_ref(),
+        &params,
+        &[(a.id.id.clone(), TrustLevel::Distrust)],
+    );
+    assert!(delta.added.is_empty());
+    assert_eq!(delta.removed.keys().collect::<Vec<_>>(), {
+        let mut ids = vec![&a.id.id, &b.id.id, &c.id.id];
+        ids.sort();
+        ids
+    });
+    assert_eq!(
+        delta.newly_distrusted.iter().collect::<Vec<_>>(),
+        vec![&a.id.id]
+    );
+    assert!(delta.level_changed.is_empty());
+    assert_eq!(delta.newly_covered_reviews, 0);
+    assert_eq!(delta.no_longer_covered_reviews, 2);
+
+    // the database itself is untouched
+    let trust_set = trustdb.calculate_trust_set(root.as_ref(), &params);
+    assert!(trust_set.is_trusted(c.as_ref()));
+
+    // trusting `e` brings it in, and lowers nothing; `b` keeps its level
+    let delta = trustdb.preview_trust_change(
+        root.as_ref(),
+        &params,
+        &[(e.id.id.clone(), TrustLevel::Medium)],
+    );
+    assert_eq!(
+        delta.added.into_iter().collect::<Vec<_>>(),
+        vec![(e.id.id.clone(), TrustLevel::Medium)]
+    );
+    assert!(delta.removed.is_empty());
+    assert!(delta.newly_distrusted.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn self_trust_is_ignored_and_counted() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![build_trust_proof(&a, vec![&a, &b], TrustLevel::High, 0)?]
+            .into_iter()
+            .map(|x| (x, url.clone())),
+    );
+
+    assert_eq!(trustdb.unique_trust_proof_count(), 1);
+    assert_eq!(trustdb.self_trusting_id_count(), 1);
+    assert_eq!(trustdb.self_trust_ids().collect::<Vec<_>>(), vec![&a.id.id]);
+    let direct: Vec<_> = trustdb
+        .get_direct_trust_for_id(a.as_ref(), None)
+        .map(|d| d.id.clone())
+        .collect();
+    assert_eq!(direct, vec![b.id.id.clone()]);
+
+    Ok(())
+}
+
+#[test]
+fn trust_cycles_are_reported() -> Result<()> {
+    use crev_wot::testing::{fabricated_date, fabricated_id, ProofDBBuilder};
+
+    let [a, b, c, d, e, x, y] = [1, 2, 3, 4, 5, 6, 7].map(fabricated_id);
+
+    let mut builder = ProofDBBuilder::new();
+    for (from, to, level) in &[
+        (&a, &b, TrustLevel::High),
+        (&b, &c, TrustLevel::Low),
+        (&c, &a, TrustLevel::Medium),
+        (&a, &d, TrustLevel::High),
+        (&d, &a, TrustLevel::High),
+        (&e, &e, TrustLevel::High),
+        // not positive trust, so not a cycle
+        (&x, &y, TrustLevel::High),
+        (&y, &x, TrustLevel::Distrust),
+    ] {
+        builder.trust(from, to, *level, fabricated_date(0));
+    }
+    let trustdb = builder.build();
+
+    assert_eq!(
+        trustdb.find_trust_cycles(3),
+        vec![
+            vec![a.id.clone(), b.id.clone(), c.id.clone()],
+            vec![a.id.clone(), d.id.clone()],
+        ]
+    );
+    assert_eq!(
+        trustdb.find_trust_cycles(2),
+        vec![vec![a.id.clone(), d.id.clone()]]
+    );
+    assert!(trustdb.find_trust_cycles(1).is_empty());
+    assert_eq!(trustdb.self_trust_ids().collect::<Vec<_>>(), vec![&e.id]);
+
+    Ok(())
+}
+
+#[test]
+fn mutual_distrust_is_reported() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let root = UnlockedId::generate_for_git_url("https://root");
+    let x = UnlockedId::generate_for_git_url("https://x");
+    let y = UnlockedId::generate_for_git_url("https://y");
+    let z = UnlockedId::generate_for_git_url("https://z");
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            build_trust_proof(&root, vec![&x, &y, &z], TrustLevel::High, 0)?,
+            build_trust_proof(&x, vec![&y], TrustLevel::Distrust, 0)?,
+            build_trust_proof(&y, vec![&x], TrustLevel::Distrust, 0)?,
+            // one-sided distrust is not a conflict
+            build_trust_proof(&z, vec![&x], TrustLevel::Distrust, 0)?,
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+
+    let trust_set = trustdb.calculate_trust_set(root.as_ref(), &default());
+    let expected = if x.id.id < y.id.id {
+        (x.id.id.clone(), y.id.id.clone())
+    } else {
+        (y.id.id.clone(), x.id.id.clone())
+    };
+    assert_eq!(
+        trust_set.distrust_conflicts().collect::<Vec<_>>(),
+        vec![&expected]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn direct_trust_comment_follows_latest_proof() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+
+    let with_comment = |comment: &str, days: i64| -> Result<proof::Proof> {
+        let mut trust = a
+            .as_public_id()
+            .create_trust_proof(vec![b.as_public_id()], TrustLevel::High)?;
+        trust.comment = comment.into();
+        trust.common.date = trust.common.date + Duration::days(days);
+        Ok(trust.sign_by(&a)?)
+    };
+    let comment_of = |db: &ProofDB| -> String {
+        db.get_direct_trust_for_id(a.as_ref(), None)
+            .next()
+            .unwrap()
+            .comment()
+            .to_owned()
+    };
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![with_comment("met at RustConf", -2)?]
+            .into_iter()
+            .map(|x| (x, url.clone())),
+    );
+    assert_eq!(comment_of(&trustdb), "met at RustConf");
+
+    // older proofs don't override it
+    trustdb.import_from_iter(
+        vec![with_comment("old", -3)?]
+            .into_iter()
+            .map(|x| (x, url.clone())),
+    );
+    assert_eq!(comment_of(&trustdb), "met at RustConf");
+
+    // newer proof without a comment clears it
+    trustdb.import_from_iter(
+        vec![with_comment("", -1)?]
+            .into_iter()
+            .map(|x| (x, url.clone())),
+    );
+    assert_eq!(comment_of(&trustdb), "");
+
+    Ok(())
+}
+
+#[derive(Default)]
+struct CountingObserver {
+    visits: usize,
+    visited: BTreeSet<Id>,
+    distances: Vec<Distance>,
+    distrusts: usize,
+    restarts: usize,
+    cancel_after_visits: Option<usize>,
+}
+
+impl TrustSetObserver for CountingObserver {
+    fn on_visit(&mut self, visit: &TrustSetVisit<'_>) -> ControlFlow<()> {
+        if Some(self.visits) == self.cancel_after_visits {
+            return ControlFlow::Break(());
+        }
+        self.visits += 1;
+        self.visited.insert(visit.id.clone());
+        self.distances.push(visit.distance);
+        ControlFlow::Continue(())
+    }
+
+    fn on_distrust(&mut self, _id: &Id, _by: &Id) -> ControlFlow<()> {
+        self.distrusts += 1;
+        ControlFlow::Continue(())
+    }
+
+    fn on_restart(&mut self, restarts: usize) -> ControlFlow<()> {
+        self.restarts = restarts;
+        ControlFlow::Continue(())
+    }
+}
+
+#[test]
+fn trust_set_observer_counts_and_cancels() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let root = UnlockedId::generate_for_git_url("https://root");
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let c = UnlockedId::generate_for_git_url("https://c");
+    let d = UnlockedId::generate_for_git_url("https://d");
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            build_trust_proof(&root, vec![&a, &b], TrustLevel::High, 0)?,
+            build_trust_proof(&a, vec![&c], TrustLevel::Medium, 0)?,
+            build_trust_proof(&b, vec![&c], TrustLevel::Distrust, 0)?,
+            build_trust_proof(&a, vec![&d], TrustLevel::Low, 1)?,
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+    let params = default();
+
+    // c getting distrusted forces one restart
+    let mut observer = CountingObserver::default();
+    let trust_set = trustdb.calculate_trust_set_with(root.as_ref(), &params, &mut observer);
+    assert!(trust_set.is_complete());
+    assert_eq!(observer.distrusts, 1);
+    assert_eq!(observer.restarts, 1);
+    assert!(observer.visits > trust_set.trusted_ids().count());
+    assert!(trust_set
+        .trusted_ids()
+        .all(|id| observer.visited.contains(id)));
+    assert!(trust_set.is_distrusted(c.as_ref()));
+    assert!(trust_set.is_trusted(d.as_ref()));
+    assert_eq!(
+        trust_set.trusted_ids().count(),
+        trustdb
+            .calculate_trust_set(root.as_ref(), &params)
+            .trusted_ids()
+            .count()
+    );
+
+    let mut observer = CountingObserver {
+        cancel_after_visits: Some(1),
+        ..default()
+    };
+    let trust_set = trustdb.calculate_trust_set_with(root.as_ref(), &params, &mut observer);
+    assert!(!trust_set.is_complete());
+    assert_eq!(observer.visits, 1);
+    // root was visited, and reported its direct trust before cancelling
+    assert!(trust_set.is_trusted(root.as_ref()));
+    assert!(!trust_set.is_trusted(d.as_ref()));
+
+    Ok(())
+}
+
+#[test]
+fn recording_observer_explains_the_trust_set() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let root = UnlockedId::generate_for_git_url("https://root");
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let c = UnlockedId::generate_for_git_url("https://c");
+    let d = UnlockedId::generate_for_git_url("https://d");
+    let e = UnlockedId::generate_for_git_url("https://e");
+    let f = UnlockedId::generate_for_git_url("https://f");
+    let g = UnlockedId::generate_for_git_url("https://g");
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            build_trust_proof(&root, vec![&a, &b], TrustLevel::High, 0)?,
+            build_trust_proof(&a, vec![&c], TrustLevel::Medium, 0)?,
+            build_trust_proof(&b, vec![&c], TrustLevel::Distrust, 0)?,
+            build_trust_proof(&a, vec![&d], TrustLevel::Low, 0)?,
+            build_trust_proof(&d, vec![&e], TrustLevel::Low, 0)?,
+            build_trust_proof(&e, vec![&f], TrustLevel::Low, 0)?,
+            build_trust_proof(&a, vec![&g], TrustLevel::None, 0)?,
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+    let params = default();
+
+    let mut observer = RecordingObserver::new();
+    let trust_set = trustdb.calculate_trust_set_with(root.as_ref(), &params, &mut observer);
+    assert_eq!(
+        trust_set.trusted_ids().count(),
+        trustdb
+            .calculate_trust_set(root.as_ref(), &params)
+            .trusted_ids()
+            .count()
+    );
+
+    assert!(observer.events.contains(&TrustSetEvent::Distrusted {
+        id: c.id.id.clone(),
+        by: b.id.id.clone(),
+    }));
+    assert!(observer
+        .events
+        .contains(&TrustSetEvent::Restarted { restarts: 1 }));
+    assert!(observer.events.contains(&TrustSetEvent::EdgeConsidered {
+        from: a.id.id.clone(),
+        to: d.id.id.clone(),
+        direct_trust_level: TrustLevel::Low,
+        effective_trust_level: TrustLevel::Low,
+    }));
+    // after the restart, trust of a for c is not followed anymore
+    assert!(observer
+        .prune_reasons_for(c.as_ref())
+        .any(|reason| reason == PruneReason::Distrusted));
+    assert_eq!(
+        observer.prune_reasons_for(f.as_ref()).collect::<Vec<_>>(),
+        vec![PruneReason::TooFar]
+    );
+    assert!(observer
+        .prune_reasons_for(g.as_ref())
+        .all(|reason| reason == PruneReason::TrustTooLow));
+    assert_eq!(observer.prune_reasons_for(e.as_ref()).count(), 0);
+
+    // every trusted Id was visited, and every visited one is trusted
+    let visited: BTreeSet<_> = observer
+        .events
+        .iter()
+        .filter_map(|event| match event {
+            TrustSetEvent::Visited { id, .. } => Some(id.clone()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(visited, trust_set.trusted_ids().cloned().collect());
+
+    Ok(())
+}
+
+/// Follows even `None` trust edges, at a higher cost
+struct PermissiveMetric;
+
+impl DistanceMetric for PermissiveMetric {
+    fn distance_by_level(&self, level: TrustLevel) -> Option<Distance> {
+        match level {
+            TrustLevel::None => Some(Distance::from_millis(1500)),
+            TrustLevel::Distrust => None,
+            _ => Some(Distance::from_millis(500)),
+        }
+    }
+
+    fn max_distance(&self) -> Distance {
+        Distance::from_units(2)
+    }
+}
+
+#[test]
+fn custom_distance_metric_traverses_none_edges() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let root = UnlockedId::generate_for_git_url("https://root");
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let c = UnlockedId::generate_for_git_url("https://c");
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            build_trust_proof(&root, vec![&a], TrustLevel::High, 0)?,
+            build_trust_proof(&a, vec![&b], TrustLevel::None, 0)?,
+            build_trust_proof(&b, vec![&c], TrustLevel::High, 0)?,
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+
+    let trust_set = trustdb.calculate_trust_set(root.as_ref(), &default());
+    assert!(trust_set.is_trusted(a.as_ref()));
+    assert!(!trust_set.is_trusted(b.as_ref()));
+
+    // 0.5 + 1.5 is still within the limit, but one more hop is not
+    let trust_set = trustdb.calculate_trust_set_with_metric(root.as_ref(), &PermissiveMetric);
+    assert!(trust_set.is_trusted(a.as_ref()));
+    assert!(trust_set.is_trusted(b.as_ref()));
+    assert_eq!(
+        trust_set.get_effective_trust_level(b.as_ref()),
+        TrustLevel::None
+    );
+    assert!(!trust_set.is_trusted(c.as_ref()));
+
+    let mut observer = CountingObserver::default();
+    trustdb.calculate_trust_set_with(root.as_ref(), &PermissiveMetric, &mut observer);
+    observer.distances.sort();
+    assert_eq!(
+        observer.distances,
+        vec![
+            Distance::ZERO,
+            Distance::from_millis(500),
+            Distance::from_units(2)
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn trust_set_at_point_in_time() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let c = UnlockedId::generate_for_git_url("https://c");
+
+    let proofs = vec![
+        build_trust_proof(&a, vec![&b], TrustLevel::Low, -20)?,
+        build_trust_proof(&b, vec![&c], TrustLevel::High, -20)?,
+        // raised
+        build_trust_proof(&a, vec![&b], TrustLevel::High, -10)?,
+        // revoked
+        build_trust_proof(&a, vec![&b], TrustLevel::None, -1)?,
+    ];
+    let params = default();
+    let days_ago = |days| Utc::now() - Duration::days(days);
+
+    let mut trustdb = ProofDB::new();
+    trustdb.set_keep_trust_history(true);
+    // import order doesn't matter
+    trustdb.import_from_iter(proofs.iter().rev().cloned().map(|x| (x, url.clone())));
+
+    let at = |days| trustdb.calculate_trust_set_at(a.as_ref(), &params, days_ago(days));
+    let before = at(30);
+    assert!(!before.is_trusted(b.as_ref()));
+    let raised = at(15);
+    assert_eq!(
+        raised.get_effective_trust_level(b.as_ref()),
+        TrustLevel::Low
+    );
+    assert_eq!(
+        raised.get_effective_trust_level(c.as_ref()),
+        TrustLevel::Low
+    );
+    let high = at(5);
+    assert_eq!(high.get_effective_trust_level(b.as_ref()), TrustLevel::High);
+    assert_eq!(high.get_effective_trust_level(c.as_ref()), TrustLevel::High);
+    let revoked = at(0);
+    assert!(!revoked.is_trusted(b.as_ref()));
+    assert!(!revoked.is_trusted(c.as_ref()));
+    assert_eq!(
+        revoked.trusted_ids().collect::<Vec<_>>(),
+        trustdb
+            .calculate_trust_set(a.as_ref(), &params)
+            .trusted_ids()
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(trustdb.approximate_memory_usage().trust_history, 4);
+
+    // without history, only the most recent proof of every edge is known
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(proofs.into_iter().map(|x| (x, url.clone())));
+    let trust_set = trustdb.calculate_trust_set_at(a.as_ref(), &params, days_ago(5));
+    assert!(!trust_set.is_trusted(b.as_ref()));
+    assert_eq!(trustdb.approximate_memory_usage().trust_history, 0);
+
+    Ok(())
+}
+
+#[test]
+fn sorted_trust_set_diff_after_import() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let root = UnlockedId::generate_for_git_url("https://root");
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let c = UnlockedId::generate_for_git_url("https://c");
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            build_trust_proof(&root, vec![&a], TrustLevel::High, 0)?,
+            build_trust_proof(&a, vec![&b], TrustLevel::Low, 0)?,
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+    let params = default();
+    let before = trustdb
+        .calculate_trust_set(root.as_ref(), &params)
+        .to_sorted();
+    assert_eq!(
+        before,
+        trustdb
+            .calculate_trust_set(root.as_ref(), &params)
+            .to_sorted()
+    );
+    assert!(before.diff(&before).is_empty());
+
+    trustdb.import_from_iter(
+        vec![build_trust_proof(&root, vec![&b, &c], TrustLevel::High, 0)?]
+            .into_iter()
+            .map(|x| (x, url.clone())),
+    );
+    let after = trustdb
+        .calculate_trust_set(root.as_ref(), &params)
+        .to_sorted();
+    assert_ne!(before, after);
+
+    let diff = before.diff(&after);
+    assert_eq!(
+        diff.added.into_iter().collect::<Vec<_>>(),
+        vec![(c.id.id.clone(), TrustLevel::High)]
+    );
+    assert!(diff.removed.is_empty());
+    assert_eq!(
+        diff.level_changed.into_iter().collect::<Vec<_>>(),
+        vec![(b.id.id.clone(), (TrustLevel::Low, TrustLevel::High))]
+    );
+    assert_eq!(
+        diff.distance_changed.into_iter().collect::<Vec<_>>(),
+        vec![(b.id.id.clone(), (Distance::from_units(5), Distance::ZERO))]
+    );
+    assert!(diff.newly_distrusted.is_empty());
+    assert!(diff.no_longer_distrusted.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn trust_set_diff_names_banned_id_and_lost_coverage() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let root = UnlockedId::generate_for_git_url("https://root");
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let c = UnlockedId::generate_for_git_url("https://c");
+
+    let mut trustdb = ProofDB::new();
+    let import = |trustdb: &mut ProofDB, proofs: Vec<proof::Proof>| {
+        trustdb.import_from_iter(proofs.into_iter().map(|x| (x, url.clone())));
+        trustdb.calculate_trust_set(root.as_ref(), &default())
+    };
+    let before = import(
+        &mut trustdb,
+        vec![
+            build_trust_proof(&root, vec![&a], TrustLevel::High, 0)?,
+            build_trust_proof(&a, vec![&b, &c], TrustLevel::Medium, 0)?,
+            build_review(&b, "x")?,
+            build_review(&b, "y")?,
+            build_review(&c, "y")?,
+            build_review(&c, "z")?,
+        ],
+    );
+    assert!(before.diff(&before).is_empty());
+    let after = import(
+        &mut trustdb,
+        vec![build_trust_proof(&a, vec![&b], TrustLevel::Distrust, 1)?],
+    );
+
+    let diff = before.diff(&after);
+    assert_eq!(
+        diff.newly_distrusted.into_iter().collect::<Vec<_>>(),
+        vec![(b.id.id.clone(), vec![a.id.id.clone()].into_iter().collect())]
+    );
+    assert_eq!(
+        diff.removed.into_iter().collect::<Vec<_>>(),
+        vec![(b.id.id.clone(), TrustLevel::Medium)]
+    );
+    assert!(diff.added.is_empty());
+    assert!(diff.level_changed.is_empty());
+
+    let coverage = trustdb.diff_package_coverage(&before, &after, "SOURCE_ID");
+    let pkg = |name: &str| {
+        proof::PackageVersionId::new(
+            "SOURCE_ID".into(),
+            name.into(),
+            semver::Version::parse("1.0.0").unwrap(),
+        )
+    };
+    assert_eq!(
+        coverage.lost.into_iter().collect::<Vec<_>>(),
+        vec![(pkg("x"), (1, 0)), (pkg("y"), (2, 1))]
+    );
+    assert!(coverage.gained.is_empty());
+    assert!(trustdb
+        .diff_package_coverage(&after, &after, "SOURCE_ID")
+        .is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn trust_paths_explain_trust_and_distrust() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let root = UnlockedId::generate_for_git_url("https://root");
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let c = UnlockedId::generate_for_git_url("https://c");
+    let x = UnlockedId::generate_for_git_url("https://x");
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            build_trust_proof(&root, vec![&a], TrustLevel::High, 0)?,
+            build_trust_proof(&root, vec![&b], TrustLevel::Low, 0)?,
+            build_trust_proof(&a, vec![&c], TrustLevel::Medium, 0)?,
+            build_trust_proof(&b, vec![&c], TrustLevel::High, 0)?,
+            build_trust_proof(&c, vec![&a], TrustLevel::High, 0)?,
+            build_trust_proof(&b, vec![&x], TrustLevel::Distrust, 0)?,
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+    let trust_set = trustdb.calculate_trust_set(root.as_ref(), &default());
+
+    let describe = |id: &UnlockedId| -> Vec<Vec<(Id, TrustLevel, Distance)>> {
+        trust_set
+            .get_trust_paths(id.as_ref())
+            .into_iter()
+            .map(|path| {
+                assert_eq!(&path.root, root.as_ref());
+                assert_eq!(path.target(), id.as_ref());
+                path.hops
+                    .into_iter()
+                    .map(|hop| (hop.id, hop.direct_trust_level, hop.distance))
+                    .collect()
+            })
+            .collect()
+    };
+    let hop =
+        |id: &UnlockedId, level, units| (id.id.id.clone(), level, Distance::from_units(units));
+
+    // shortest first; the `Low` path costs more, even though `b` trusts `c` highly
+    assert_eq!(
+        describe(&c),
+        vec![
+            vec![hop(&a, TrustLevel::High, 0), hop(&c, TrustLevel::Medium, 1)],
+            vec![hop(&b, TrustLevel::Low, 5), hop(&c, TrustLevel::High, 10)],
+        ]
+    );
+    // going through the `a` -> `c` -> `a` cycle is not a path
+    assert_eq!(
+        describe(&a),
+        vec![
+            vec![hop(&a, TrustLevel::High, 0)],
+            vec![
+                hop(&b, TrustLevel::Low, 5),
+                hop(&c, TrustLevel::High, 10),
+                hop(&a, TrustLevel::High, 11)
+            ],
+        ]
+    );
+    assert_eq!(
+        describe(&x),
+        vec![vec![
+            hop(&b, TrustLevel::Low, 5),
+            hop(&x, TrustLevel::Distrust, 5)
+        ]]
+    );
+    assert_eq!(describe(&root), vec![vec![]]);
+    let stranger = UnlockedId::generate_for_git_url("https://stranger");
+    assert!(describe(&stranger).is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn cached_trust_set_matches_recalculation() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let root = UnlockedId::generate_for_git_url("https://root");
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+
+    let mut trustdb = ProofDB::new();
+    let import = |trustdb: &mut ProofDB, proofs: Vec<proof::Proof>| {
+        trustdb.import_from_iter(proofs.into_iter().map(|x| (x, url.clone())));
+    };
+    import(
+        &mut trustdb,
+        vec![
+            build_trust_proof(&root, vec![&a], TrustLevel::High, 0)?,
+            build_trust_proof(&a, vec![&b], TrustLevel::Medium, 0)?,
+        ],
+    );
+    let params = default();
+    let check = |trustdb: &ProofDB| {
+        let cached = trustdb.calculate_trust_set_cached(root.as_ref(), &params);
+        assert_eq!(
+            cached.to_sorted(),
+            trustdb
+                .calculate_trust_set(root.as_ref(), &params)
+                .to_sorted()
+        );
+        cached
+    };
+
+    let first = check(&trustdb);
+    assert!(first.is_trusted(b.as_ref()));
+    assert!(Arc::ptr_eq(&first, &check(&trustdb)));
+
+    // re-importing the same proof changes nothing
+    import(
+        &mut trustdb,
+        vec![build_trust_proof(&a, vec![&b], TrustLevel::Medium, 0)?],
+    );
+    assert!(Arc::ptr_eq(&first, &check(&trustdb)));
+
+    // older proof doesn't override the newer one
+    import(
+        &mut trustdb,
+        vec![build_trust_proof(
+            &root,
+            vec![&a],
+            TrustLevel::Distrust,
+            -1,
+        )?],
+    );
+    assert!(Arc::ptr_eq(&first, &check(&trustdb)));
+
+    // but a newer one does
+    import(
+        &mut trustdb,
+        vec![build_trust_proof(&root, vec![&a], TrustLevel::Distrust, 1)?],
+    );
+    let second = check(&trustdb);
+    assert!(!Arc::ptr_eq(&first, &second));
+    assert!(second.is_distrusted(a.as_ref()));
+    assert!(!second.is_trusted(b.as_ref()));
+
+    // different params are not served from the cache
+    let no_wot = crev_wot::TrustDistanceParams::new_no_wot();
+    assert_eq!(
+        trustdb
+            .calculate_trust_set_cached(root.as_ref(), &no_wot)
+            .to_sorted(),
+        trustdb
+            .calculate_trust_set(root.as_ref(), &no_wot)
+            .to_sorted()
+    );
+    assert_eq!(trustdb.approximate_memory_usage().cached_trust_sets, 1);
+
+    Ok(())
+}
+
+#[test]
+fn review_quality_caps_trust() -> Result<()> {
+    use crev_data::Level;
+    use proof::review::{Rating, Review};
+
+    let url = FetchSource::LocalUser;
+    let root = UnlockedId::generate_for_git_url("https://root");
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let c = UnlockedId::generate_for_git_url("https://c");
+    let d = UnlockedId::generate_for_git_url("https://d");
+
+    let review = |thoroughness| Review {
+        thoroughness,
+        understanding: Level::Medium,
+        rating: Rating::Positive,
+    };
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            build_trust_proof(&root, vec![&a, &b], TrustLevel::High, 0)?,
+            build_trust_proof(&root, vec![&c], TrustLevel::Low, 0)?,
+            build_trust_proof(&a, vec![&d], TrustLevel::High, 0)?,
+            build_review_with(&root, "one", review(Level::None))?,
+            build_review_with(&a, "one", review(Level::None))?,
+            build_review_with(&a, "two", review(Level::None))?,
+            build_review_with(&a, "three", review(Level::High))?,
+            build_review_with(&b, "one", review(Level::Medium))?,
+            build_review_with(&c, "one", review(Level::None))?,
+            // reviews without any review data don't count
+            build_review(&b, "two")?,
+            build_review(&b, "three")?,
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+
+    let levels = |params: &TrustDistanceParams| -> Vec<TrustLevel> {
+        let trust_set = trustdb.calculate_trust_set(root.as_ref(), params);
+        [&root, &a, &b, &c, &d]
+            .iter()
+            .map(|id| trust_set.get_effective_trust_level(id.as_ref()))
+            .collect()
+    };
+
+    // off by default
+    assert_eq!(
+        levels(&default()),
+        vec![
+            TrustLevel::High,
+            TrustLevel::High,
+            TrustLevel::High,
+            TrustLevel::Low,
+            TrustLevel::High
+        ]
+    );
+
+    let params = TrustDistanceParams {
+        review_quality_cap: Some(crev_wot::ReviewQualityParams {
+            min_thoroughness: Level::Low,
+            max_trust_level: TrustLevel::Medium,
+            ..default()
+        }),
+        ..default()
+    };
+    // `a` is capped, and can't vouch for `d` above its own level; `c` was
+    // trusted less than the cap, so stays where it was; root is never capped
+    assert_eq!(
+        levels(&params),
+        vec![
+            TrustLevel::High,
+            TrustLevel::Medium,
+            TrustLevel::High,
+            TrustLevel::Low,
+            TrustLevel::Medium
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn review_quality_caps_invalidate_cached_trust_set() -> Result<()> {
+    use crev_data::Level;
+    use proof::review::{Rating, Review};
+
+    let url = FetchSource::LocalUser;
+    let root = UnlockedId::generate_for_git_url("https://root");
+    let a = UnlockedId::generate_for_git_url("https://a");
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![build_trust_proof(&root, vec![&a], TrustLevel::High, 0)?]
+            .into_iter()
+            .map(|x| (x, url.clone())),
+    );
+    let params = TrustDistanceParams {
+        review_quality_cap: Some(crev_wot::ReviewQualityParams {
+            max_trust_level: TrustLevel::Low,
+            ..default()
+        }),
+        ..default()
+    };
+    let cached_level = |trustdb: &ProofDB| {
+        trustdb
+            .calculate_trust_set_cached(root.as_ref(), &params)
+            .get_effective_trust_level(a.as_ref())
+    };
+    assert_eq!(cached_level(&trustdb), TrustLevel::High);
+
+    // a review doesn't change any trust proofs, but it makes `a` a low quality reviewer
+    let review = Review {
+        thoroughness: Level::None,
+        understanding: Level::None,
+        rating: Rating::Positive,
+    };
+    trustdb.import_from_iter(
+        vec![build_review_with(&a, "one", review)?]
+            .into_iter()
+            .map(|x| (x, url.clone())),
+    );
+    assert_eq!(
+        trustdb
+            .calculate_trust_set(root.as_ref(), &params)
+            .get_effective_trust_level(a.as_ref()),
+        TrustLevel::Low
+    );
+    assert_eq!(cached_level(&trustdb), TrustLevel::Low);
+
+    Ok(())
+}
+
+#[test]
+fn distrust_report_names_distrusting_peer() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let root = UnlockedId::generate_for_git_url("https://root");
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let x = UnlockedId::generate_for_git_url("https://x");
+
+    let b_to_x = build_trust_proof(&b, vec![&x], TrustLevel::Distrust, 0)?;
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            build_trust_proof(&root, vec![&a, &b], TrustLevel::High, 0)?,
+            build_trust_proof(&a, vec![&x], TrustLevel::High, 0)?,
+            b_to_x.clone(),
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+
+    let trust_set = trustdb.calculate_trust_set(root.as_ref(), &default());
+    assert_eq!(
+        trust_set.distrusted_ids().collect::<Vec<_>>(),
+        vec![&x.id.id]
+    );
+    assert!(trust_set.get_distrust_details(a.as_ref()).is_none());
+
+    let report = trust_set.get_distrust_details(x.as_ref()).unwrap();
+    assert_eq!(
+        report.reported_by.keys().collect::<Vec<_>>(),
+        vec![&b.id.id]
+    );
+    let proof = report.reported_by[&b.id.id].as_ref().unwrap();
+    assert_eq!(proof.signature, b_to_x.signature());
+    assert_eq!(proof.date, b_to_x.date_utc());
+
+    Ok(())
+}
+
+#[test]
+fn trust_overrides_propagate_downstream() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let root = UnlockedId::generate_for_git_url("https://root");
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let c = UnlockedId::generate_for_git_url("https://c");
+    let d = UnlockedId::generate_for_git_url("https://d");
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            build_trust_proof(&root, vec![&a, &b], TrustLevel::High, 0)?,
+            build_trust_proof(&a, vec![&c], TrustLevel::High, 0)?,
+            build_trust_proof(&b, vec![&d], TrustLevel::Medium, 0)?,
+            build_trust_proof(&b, vec![&c], TrustLevel::Distrust, 0)?,
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+    let params: TrustDistanceParams = default();
+    let levels = |overrides: Vec<(&UnlockedId, TrustLevel)>| -> Vec<TrustLevel> {
+        let overrides = overrides
+            .into_iter()
+            .map(|(id, level)| (id.id.id.clone(), level))
+            .collect();
+        let trust_set =
+            trustdb.calculate_trust_set_with_overrides(root.as_ref(), &params, &overrides);
+        [&root, &a, &b, &c, &d]
+            .iter()
+            .map(|id| trust_set.get_effective_trust_level_opt(id.as_ref()))
+            .map(|level| level.unwrap_or(TrustLevel::None))
+            .collect()
+    };
+    use TrustLevel::*;
+
+    assert_eq!(levels(vec![]), vec![High, High, High, Distrust, Medium]);
+
+    // overriding root's direct peers affects who they trust
+    assert_eq!(
+        levels(vec![(&b, Low)]),
+        vec![High, High, Low, Distrust, Low]
+    );
+    assert_eq!(levels(vec![(&b, None)]), vec![High, High, None, High, None]);
+    assert_eq!(
+        levels(vec![(&b, Distrust)]),
+        vec![High, High, Distrust, High, None]
+    );
+
+    // a pinned Id can't be distrusted by others, and gets exactly its level
+    assert_eq!(
+        levels(vec![(&c, Medium)]),
+        vec![High, High, High, Medium, Medium]
+    );
+    // the root itself can't be overridden
+    assert_eq!(
+        levels(vec![(&root, None)]),
+        vec![High, High, High, Distrust, Medium]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn trust_overrides_can_raise_levels() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let root = UnlockedId::generate_for_git_url("https://root");
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let c = UnlockedId::generate_for_git_url("https://c");
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            build_trust_proof(&root, vec![&a], TrustLevel::Low, 0)?,
+            build_trust_proof(&a, vec![&b], TrustLevel::High, 0)?,
+            build_trust_proof(&b, vec![&c], TrustLevel::High, 0)?,
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+    let params: TrustDistanceParams = default();
+    let overrides = vec![(b.id.id.clone(), TrustLevel::High)]
+        .into_iter()
+        .collect();
+    let trust_set = trustdb.calculate_trust_set_with_overrides(root.as_ref(), &params, &overrides);
+
+    assert_eq!(
+        [&a, &b, &c]
+            .iter()
+            .map(|id| trust_set.get_effective_trust_level(id.as_ref()))
+            .collect::<Vec<_>>(),
+        vec![TrustLevel::Low, TrustLevel::High, TrustLevel::Low]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn trust_graph_exports_reachable_ids_as_dot() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let root = UnlockedId::generate_for_git_url("https://root");
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let x = UnlockedId::generate_for_git_url("https://x");
+    let unreachable = UnlockedId::generate_for_git_url("https://unreachable");
+
+    let proofs = vec![
+        build_trust_proof(&root, vec![&a, &b], TrustLevel::High, 0)?,
+        build_trust_proof(&a, vec![&x], TrustLevel::Medium, 0)?,
+        build_trust_proof(&b, vec![&x], TrustLevel::Distrust, 0)?,
+        build_trust_proof(&unreachable, vec![&a], TrustLevel::High, 0)?,
+    ];
+    let db = |proofs: Vec<proof::Proof>| {
+        let mut trustdb = ProofDB::new();
+        trustdb.import_from_iter(proofs.into_iter().map(|x| (x, url.clone())));
+        trustdb
+    };
+    let trustdb = db(proofs.clone());
+    let params = default();
+
+    let graph = trustdb.export_trust_graph(Some(root.as_ref()), &params);
+    assert_eq!(
+        graph.nodes.keys().collect::<BTreeSet<_>>(),
+        vec![&root.id.id, &a.id.id, &b.id.id, &x.id.id]
+            .into_iter()
+            .collect()
+    );
+    assert_eq!(graph.edges.len(), 4);
+    assert_eq!(
+        graph.nodes[&x.id.id].effective_trust_level,
+        Some(TrustLevel::Distrust)
+    );
+
+    let dot = graph.to_dot();
+    assert!(dot.starts_with("digraph wot {\n"));
+    assert!(dot.contains(&format!(
+        "\"{}\" [label=\"{}\\nhttps://x\", color=red, fontcolor=red, style=dashed];",
+        x.id.id, x.id.id
+    )));
+    assert!(dot.contains(&format!(
+        "\"{}\" -> \"{}\" [label=\"distrust\", color=red];",
+        b.id.id, x.id.id
+    )));
+    assert!(!dot.contains(&unreachable.id.id.to_string()));
+
+    // the same proofs give the same output, regardless of the import order
+    let mut reversed = proofs;
+    reversed.reverse();
+    assert_eq!(
+        db(reversed)
+            .export_trust_graph(Some(root.as_ref()), &params)
+            .to_dot(),
+        dot
+    );
+
+    // without a root, everything is included
+    let graph = trustdb.export_trust_graph(None, &params);
+    assert_eq!(graph.nodes.len(), 5);
+    assert_eq!(graph.edges.len(), 5);
+    assert!(graph
+        .nodes
+        .values()
+        .all(|node| node.effective_trust_level.is_none()));
+
+    Ok(())
+}
+
+#[test]
+fn higher_trust_is_traversed_first() -> Result<()> {
+    use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+    let url = FetchSource::LocalUser;
+    let root = UnlockedId::generate_for_git_url("https://root");
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let c = UnlockedId::generate_for_git_url("https://c");
+    let d = UnlockedId::generate_for_git_url("https://d");
+
+    // a diamond: c is reachable through both a (high) and b (low); a and b
+    // distrust each other, and the one with higher trust has to win
+    let mut proofs = vec![
+        build_trust_proof(&root, vec![&a], TrustLevel::High, 0)?,
+        build_trust_proof(&root, vec![&b], TrustLevel::Low, 0)?,
+        build_trust_proof(&a, vec![&c], TrustLevel::Medium, 0)?,
+        build_trust_proof(&b, vec![&c], TrustLevel::High, 0)?,
+        build_trust_proof(&c, vec![&d], TrustLevel::High, 0)?,
+        build_trust_proof(&a, vec![&b], TrustLevel::Distrust, 0)?,
+        build_trust_proof(&b, vec![&a], TrustLevel::Distrust, 0)?,
+    ];
+    let params = default();
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(proofs.iter().cloned().map(|x| (x, url.clone())));
+    let expected = trustdb
+        .calculate_trust_set(root.as_ref(), &params)
+        .to_sorted();
+
+    assert!(expected.distrusted.contains_key(&b.id.id));
+    assert!(!expected.distrusted.contains_key(&a.id.id));
+    let trusted = |id: &UnlockedId| {
+        let details = &expected.trusted[&id.id.id];
+        (details.effective_trust_level, details.distance)
+    };
+    assert_eq!(trusted(&a), (TrustLevel::High, Distance::ZERO));
+    // through a, not at the low trust of b
+    assert_eq!(trusted(&c), (TrustLevel::Medium, Distance::from_units(1)));
+    assert_eq!(trusted(&d), (TrustLevel::Medium, Distance::from_units(2)));
+
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..20 {
+        proofs.shuffle(&mut rng);
+        let mut trustdb = ProofDB::new();
+        trustdb.import_from_iter(proofs.iter().cloned().map(|x| (x, url.clone())));
+        assert_eq!(
+            trustdb
+                .calculate_trust_set(root.as_ref(), &params)
+                .to_sorted(),
+            expected
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn trust_set_of_many_roots() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let x = UnlockedId::generate_for_git_url("https://x");
+    let y = UnlockedId::generate_for_git_url("https://y");
+    let z = UnlockedId::generate_for_git_url("https://z");
+    let w = UnlockedId::generate_for_git_url("https://w");
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            build_trust_proof(&a, vec![&x, &y], TrustLevel::High, 0)?,
+            build_trust_proof(&b, vec![&x], TrustLevel::Distrust, 0)?,
+            build_trust_proof(&b, vec![&y], TrustLevel::Low, 0)?,
+            build_trust_proof(&b, vec![&z], TrustLevel::Medium, 0)?,
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+    let roots = vec![a.id.id.clone(), b.id.id.clone()];
+    let params = default();
+
+    // a trusts x, but b distrusts it, which bans it for the whole team
+    let trust_set = trustdb.calculate_trust_set_multi(&roots, &params);
+    assert!(trust_set.is_distrusted(&x.id.id));
+    assert!(!trust_set.is_trusted(&x.id.id));
+    assert_eq!(
+        trust_set.get_effective_trust_level(&a.id.id),
+        TrustLevel::High
+    );
+    assert_eq!(
+        trust_set.get_effective_trust_level(&b.id.id),
+        TrustLevel::High
+    );
+    // the highest level and the shortest distance of all the roots
+    let sorted = trust_set.to_sorted();
+    assert_eq!(
+        sorted.trusted[&y.id.id].effective_trust_level,
+        TrustLevel::High
+    );
+    assert_eq!(sorted.trusted[&y.id.id].distance, Distance::ZERO);
+    assert_eq!(
+        sorted.trusted[&z.id.id].effective_trust_level,
+        TrustLevel::Medium
+    );
+    assert!(sorted.trusted[&y.id.id].reported_by.contains_key(&b.id.id));
+
+    // the same as a single root trust set, with one root
+    assert_eq!(
+        trustdb
+            .calculate_trust_set_multi(&roots[..1], &params)
+            .to_sorted(),
+        trustdb.calculate_trust_set(a.as_ref(), &params).to_sorted()
+    );
+    assert!(trustdb
+        .calculate_trust_set_multi(&[], &params)
+        .trusted_ids()
+        .next()
+        .is_none());
+
+    // distrust of an Id banned by a higher trust Id first doesn't count
+    trustdb.import_from_iter(
+        vec![
+            build_trust_proof(&b, vec![&w], TrustLevel::Low, 1)?,
+            build_trust_proof(&w, vec![&y], TrustLevel::Distrust, 1)?,
+            build_trust_proof(&a, vec![&w], TrustLevel::Distrust, 1)?,
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+    let trust_set = trustdb.calculate_trust_set_multi(&roots, &params);
+    assert!(trust_set.is_distrusted(&w.id.id));
+    assert!(trust_set.is_trusted(&y.id.id));
+
+    Ok(())
+}
+
+#[test]
+fn explain_unreachable_ids() -> Result<()> {
+    use crev_wot::unreachable::UnreachableIdReason;
+
+    let url = FetchSource::LocalUser;
+    let ids: Vec<_> = (0..9)
+        .map(|i| UnlockedId::generate_for_git_url(&format!("https://{}", i)))
+        .collect();
+    let (a, b, c, d, e, f, g, h, i) = (
+        &ids[0], &ids[1], &ids[2], &ids[3], &ids[4], &ids[5], &ids[6], &ids[7], &ids[8],
+    );
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            build_trust_proof(a, vec![b], TrustLevel::Low, 0)?,
+            build_trust_proof(b, vec![c], TrustLevel::Low, 0)?,
+            build_trust_proof(c, vec![d], TrustLevel::Low, 0)?,
+            // effective trust stays `Low`, no matter what `d` says
+            build_trust_proof(d, vec![e], TrustLevel::High, 0)?,
+            build_trust_proof(a, vec![f], TrustLevel::None, 0)?,
+            build_trust_proof(a, vec![g], TrustLevel::Distrust, 0)?,
+            build_trust_proof(g, vec![h], TrustLevel::High, 0)?,
+            build_trust_proof(i, vec![a], TrustLevel::High, 0)?,
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+
+    let params = default();
+    let trust_set = trustdb.calculate_trust_set(a.as_ref(), &params);
+    assert!(trust_set.is_trusted(c.as_ref()));
+
+    let reasons = trustdb.explain_unreachable(a.as_ref(), &params);
+    let reason_of = |id: &UnlockedId| {
+        reasons
+            .iter()
+            .find(|reason| reason.id() == id.as_ref())
+            .cloned()
+    };
+    assert_eq!(reasons.len(), 6);
+    assert!(reasons.iter().all(|r| !trust_set.is_trusted(r.id())));
+    assert_eq!(reason_of(c), None);
+
+    let max_distance = params.max_distance();
+    assert_eq!(
+        reason_of(d),
+        Some(UnreachableIdReason::TooFar {
+            id: d.id.id.clone(),
+            distance: Distance::from_units(15),
+            max_distance,
+        })
+    );
+    assert_eq!(
+        reason_of(e),
+        Some(UnreachableIdReason::TooFar {
+            id: e.id.id.clone(),
+            distance: Distance::from_units(20),
+            max_distance,
+        })
+    );
+    assert_eq!(
+        reason_of(f),
+        Some(UnreachableIdReason::TrustTooLow {
+            id: f.id.id.clone(),
+            reported_by: vec![a.id.id.clone()],
+        })
+    );
+    assert_eq!(
+        reason_of(g),
+        Some(UnreachableIdReason::Distrusted {
+            id: g.id.id.clone(),
+            by: vec![a.id.id.clone()],
+        })
+    );
+    // only trusted through a distrusted Id
+    assert_eq!(
+        reason_of(h),
+        Some(UnreachableIdReason::NoTrustPath {
+            id: h.id.id.clone()
+        })
+    );
+    assert_eq!(
+        reason_of(i),
+        Some(UnreachableIdReason::NoTrustPath {
+            id: i.id.id.clone()
+        })
+    );
+
+    Ok(())
+}
+
+#[test]
+fn trust_limited_to_sources() -> Result<()> {
+    const CRATES_IO: &str = "https://crates.io";
+    const OTHER: &str = "https://example.com";
+
+    let url = FetchSource::LocalUser;
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let c = UnlockedId::generate_for_git_url("https://c");
+
+    let mut scoped_trust = a
+        .as_public_id()
+        .create_trust_proof(vec![b.as_public_id()], TrustLevel::High)?;
+    scoped_trust.sources = vec![CRATES_IO.into()];
+    let scoped_trust = scoped_trust.sign_by(&a)?;
+    assert!(scoped_trust.body().contains(CRATES_IO));
+    let unscoped_trust = build_trust_proof(&a, vec![&c], TrustLevel::Medium, 0)?;
+    assert!(!unscoped_trust.body().contains("sources"));
+
+    let review_with_issue = |from: &UnlockedId, source: &str| -> Result<proof::Proof> {
+        let package_info = proof::PackageInfo {
+            id: proof::PackageVersionId::new(
+                source.into(),
+                "foo".into(),
+                semver::Version::parse("1.0.0").unwrap(),
+            ),
+            digest: vec![0; 32],
+            digest_type: proof::default_digest_type(),
+            revision: "".into(),
+            revision_type: proof::default_revision_type(),
+        };
+        let review = proof::review::PackageBuilder::default()
+            .from(from.id.to_owned())
+            .package(package_info)
+            .issues(vec![proof::review::Issue::new("issue".into())])
+            .build()
+            .unwrap();
+        Ok(review.sign_by(from)?)
+    };
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            scoped_trust,
+            unscoped_trust,
+            review_with_issue(&b, CRATES_IO)?,
+            review_with_issue(&b, OTHER)?,
+            review_with_issue(&c, CRATES_IO)?,
+            review_with_issue(&c, OTHER)?,
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+    let trust_set = trustdb.calculate_trust_set(a.as_ref(), &default());
+
+    assert_eq!(
+        trust_set.get_effective_trust_level(b.as_ref()),
+        TrustLevel::High
+    );
+    assert_eq!(
+        trust_set.get_effective_trust_level_for_source(b.as_ref(), CRATES_IO),
+        TrustLevel::High
+    );
+    assert_eq!(
+        trust_set.get_effective_trust_level_for_source(b.as_ref(), OTHER),
+        TrustLevel::None
+    );
+    // unlimited trust applies everywhere
+    for source in &[CRATES_IO, OTHER] {
+        assert_eq!(
+            trust_set.get_effective_trust_level_for_source(c.as_ref(), source),
+            TrustLevel::Medium
+        );
+    }
+
+    let reporters = |source: &str| {
+        trustdb
+            .get_pkg_reviews_with_issues_for_name(source, "foo", &trust_set, TrustLevel::Low)
+            .map(|review| review.from().id.clone())
+            .collect::<BTreeSet<_>>()
+    };
+    assert_eq!(
+        reporters(CRATES_IO),
+        vec![b.id.id.clone(), c.id.id.clone()].into_iter().collect()
+    );
+    assert_eq!(
+        reporters(OTHER),
+        vec![c.id.id.clone()].into_iter().collect()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn unsigned_trust_overrides() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let root = UnlockedId::generate_for_git_url("https://root");
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            build_trust_proof(&root, vec![&a], TrustLevel::Low, 0)?,
+            build_trust_proof(&a, vec![&b], TrustLevel::High, 0)?,
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+    let params: TrustDistanceParams = default();
+    let levels = |trustdb: &ProofDB| -> Vec<Option<TrustLevel>> {
+        let trust_set = trustdb.calculate_trust_set_cached(root.as_ref(), &params);
+        [&a, &b]
+            .iter()
+            .map(|id| trust_set.get_effective_trust_level_opt(id.as_ref()))
+            .collect()
+    };
+    use TrustLevel::*;
+    let original = levels(&trustdb);
+    assert_eq!(original, vec![Some(Low), Some(Low)]);
+    let proof_count = trustdb.unique_trust_proof_count();
+
+    let date = Utc::now() + Duration::days(1);
+    trustdb.add_unsigned_trust_override(root.as_ref(), a.as_ref(), High, date);
+    assert_eq!(levels(&trustdb), vec![Some(High), Some(High)]);
+    assert_eq!(trustdb.unique_trust_proof_count(), proof_count);
+    assert_eq!(
+        trustdb.list_unsigned_overrides(),
+        vec![crev_wot::UnsignedTrustOverride {
+            from: root.id.id.clone(),
+            to: a.id.id.clone(),
+            level: High,
+            date,
+        }]
+    );
+
+    // overrides of edges from proofs are ignored, unless they win
+    trustdb.set_unsigned_override_precedence(crev_wot::UnsignedOverridePrecedence::ProofWins);
+    assert_eq!(levels(&trustdb), original);
+    trustdb.add_unsigned_trust_override(root.as_ref(), b.as_ref(), Distrust, date);
+    assert_eq!(levels(&trustdb), vec![Some(Low), Some(Distrust)]);
+    trustdb.set_unsigned_override_precedence(crev_wot::UnsignedOverridePrecedence::OverrideWins);
+    assert_eq!(levels(&trustdb), vec![Some(High), Some(Distrust)]);
+    // not in effect before they were added
+    let trust_set =
+        trustdb.calculate_trust_set_at(root.as_ref(), &params, date - Duration::hours(1));
+    assert_eq!(trust_set.get_effective_trust_level(b.as_ref()), Low);
+
+    trustdb.clear_unsigned_overrides();
+    assert!(trustdb.list_unsigned_overrides().is_empty());
+    assert_eq!(levels(&trustdb), original);
+    assert_eq!(trustdb.unique_trust_proof_count(), proof_count);
+
+    Ok(())
+}
+
+#[test]
+fn trust_candidates_by_marginal_coverage() -> Result<()> {
+    use proof::review::Review;
+
+    let url = FetchSource::LocalUser;
+    let root = UnlockedId::generate_for_git_url("https://root");
+    let trusted = UnlockedId::generate_for_git_url("https://trusted");
+    let distrusted = UnlockedId::generate_for_git_url("https://distrusted");
+    let x = UnlockedId::generate_for_git_url("https://x");
+    let y = UnlockedId::generate_for_git_url("https://y");
+    let z = UnlockedId::generate_for_git_url("https://z");
+    let positive = |id, name| build_review_with(id, name, Review::new_positive());
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            build_trust_proof(&root, vec![&trusted], TrustLevel::Medium, 0)?,
+            build_trust_proof(&root, vec![&distrusted], TrustLevel::Distrust, 0)?,
+            // knows `x`, but doesn't trust it
+            build_trust_proof(&trusted, vec![&x], TrustLevel::None, 0)?,
+            positive(&trusted, "p1")?,
+            positive(&x, "p1")?,
+            positive(&x, "p2")?,
+            positive(&x, "p3")?,
+            positive(&y, "p2")?,
+            positive(&y, "p4")?,
+            build_review_with(&y, "p3", Review::new_negative())?,
+            build_review(&y, "p1")?,
+            positive(&z, "p4")?,
+            positive(&z, "unwanted")?,
+            positive(&distrusted, "p3")?,
+            positive(&distrusted, "p4")?,
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+    let wanted: Vec<_> = ["p1", "p2", "p3", "p4", "p5"]
+        .iter()
+        .map(|name| {
+            proof::PackageVersionId::new(
+                "SOURCE_ID".into(),
+                (*name).into(),
+                semver::Version::parse("1.0.0").unwrap(),
+            )
+        })
+        .collect();
+
+    let candidates =
+        trustdb.suggest_trust_candidates(root.as_ref(), &default(), &wanted, usize::MAX);
+    let summary: Vec<_> = candidates
+        .iter()
+        .map(|c| (c.id.clone(), c.reviewed, c.newly_covered))
+        .collect();
+    let mut expected = vec![
+        (x.id.id.clone(), 3, 2),
+        (y.id.id.clone(), 2, 2),
+        (z.id.id.clone(), 1, 1),
+    ];
+    // equal coverage is ordered by Id
+    expected[..2].sort();
+    assert_eq!(summary, expected);
+
+    let x_candidate = candidates.iter().find(|c| c.id == x.id.id).unwrap();
+    assert_eq!(
+        x_candidate.known_by,
+        vec![trusted.id.id.clone()].into_iter().collect()
+    );
+    assert_eq!(x_candidate.url.as_ref().unwrap().url.url, "https://x");
+    assert!(candidates
+        .iter()
+        .filter(|c| c.id != x.id.id)
+        .all(|c| c.known_by.is_empty()));
+
+    let limited = trustdb.suggest_trust_candidates(root.as_ref(), &default(), &wanted, 2);
+    assert_eq!(limited, candidates[..2].to_vec());
+    Ok(())
+}
+
+#[test]
+fn explicit_none_trust_is_queryable() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let root = UnlockedId::generate_for_git_url("https://root");
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let stranger = UnlockedId::generate_for_git_url("https://stranger");
+
+    let mut trustdb = ProofDB::new();
+    let import = |trustdb: &mut ProofDB, proofs: Vec<proof::Proof>| {
+        trustdb.import_from_iter(proofs.into_iter().map(|x| (x, url.clone())));
+        let trust_set = trustdb.calculate_trust_set(root.as_ref(), &default());
+        (
+            trust_set.get_effective_trust_level_opt(a.as_ref()),
+            trustdb
+                .get_direct_trust(root.as_ref(), a.as_ref())
+                .map(|trust| *trust.value()),
+        )
+    };
+
+    assert_eq!(
+        import(
+            &mut trustdb,
+            vec![
+                build_trust_proof(&root, vec![&a], TrustLevel::High, 0)?,
+                build_trust_proof(&b, vec![&a], TrustLevel::Distrust, 0)?,
+            ]
+        ),
+        (Some(TrustLevel::High), Some(TrustLevel::High))
+    );
+    // untrusting stops the traversal, but the edge is kept
+    assert_eq!(
+        import(
+            &mut trustdb,
+            vec![build_trust_proof(&root, vec![&a], TrustLevel::None, 1)?]
+        ),
+        (None, Some(TrustLevel::None))
+    );
+    assert_eq!(
+        trustdb
+            .get_direct_trust(root.as_ref(), a.as_ref())
+            .unwrap()
+            .date,
+        trustdb
+            .get_direct_trust_for_id(root.as_ref(), None)
+            .next()
+            .unwrap()
+            .date
+    );
+    let mut reported_by: Vec<_> = trustdb
+        .get_reverse_trust(a.as_ref())
+        .map(|(from, trust)| (from.clone(), *trust.value()))
+        .collect();
+    reported_by.sort();
+    let mut expected = vec![
+        (root.id.id.clone(), TrustLevel::None),
+        (b.id.id.clone(), TrustLevel::Distrust),
+    ];
+    expected.sort();
+    assert_eq!(reported_by, expected);
+    assert_eq!(
+        trustdb
+            .get_direct_trust_list(b.as_ref())
+            .map(|(to, trust)| (to.clone(), *trust.value()))
+            .collect::<Vec<_>>(),
+        vec![(a.id.id.clone(), TrustLevel::Distrust)]
+    );
+
+    // trusted again
+    assert_eq!(
+        import(
+            &mut trustdb,
+            vec![build_trust_proof(&root, vec![&a], TrustLevel::Low, 2)?]
+        ),
+        (Some(TrustLevel::Low), Some(TrustLevel::Low))
+    );
+    assert_eq!(trustdb.get_reverse_trust(a.as_ref()).count(), 2);
+
+    // never expressed any opinion
+    assert!(trustdb
+        .get_direct_trust(root.as_ref(), stranger.as_ref())
+        .is_none());
+    assert_eq!(trustdb.get_reverse_trust(stranger.as_ref()).count(), 0);
+    Ok(())
+}
+
+#[test]
+fn distrust_policy_limits_who_can_ban() -> Result<()> {
+    let url = FetchSource::LocalUser;
+    let root = UnlockedId::generate_for_git_url("https://root");
+    let high = UnlockedId::generate_for_git_url("https://high");
+    let medium = UnlockedId::generate_for_git_url("https://medium");
+    let x = UnlockedId::generate_for_git_url("https://x");
+    let y = UnlockedId::generate_for_git_url("https://y");
+    let z = UnlockedId::generate_for_git_url("https://z");
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            build_trust_proof(&root, vec![&high], TrustLevel::High, 0)?,
+            build_trust_proof(&root, vec![&medium, &x, &y], TrustLevel::Medium, 1)?,
+            build_trust_proof(&root, vec![&z], TrustLevel::Distrust, 2)?,
+            build_trust_proof(&high, vec![&x], TrustLevel::Distrust, 0)?,
+            build_trust_proof(&medium, vec![&y], TrustLevel::Distrust, 0)?,
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+
+    let distrusted = |policy| {
+        let params = TrustDistanceParams {
+            distrust_policy: policy,
+            ..default()
+        };
+        let trust_set = trustdb.calculate_trust_set(root.as_ref(), &params);
+        assert_eq!(trust_set.distrust_policy(), policy);
+        [&x, &y, &z]
+            .iter()
+            .filter(|id| trust_set.is_distrusted(id.as_ref()))
+            .map(|id| id.id.id.clone())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        distrusted(crev_wot::DistrustPolicy::Anyone),
+        vec![x.id.id.clone(), y.id.id.clone(), z.id.id.clone()]
+    );
+    assert_eq!(
+        distrusted(crev_wot::DistrustPolicy::RequireLevel(TrustLevel::High)),
+        vec![x.id.id.clone(), z.id.id.clone()]
+    );
+    assert_eq!(
+        distrusted(crev_wot::DistrustPolicy::RootOnly),
+        vec![z.id.id.clone()]
+    );
+
+    // ignored distrust doesn't stop the trust
+    let trust_set = trustdb.calculate_trust_set(
+        root.as_ref(),
+        &TrustDistanceParams {
+            distrust_policy: crev_wot::DistrustPolicy::RootOnly,
+            ..default()
+        },
+    );
+    assert_eq!(
+        trust_set.get_effective_trust_level_opt(x.as_ref()),
+        Some(TrustLevel::Medium)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn effective_trust_aggregation_modes() -> Result<()> {
+    use crev_wot::EffectiveTrustAggregation;
+
+    let url = FetchSource::LocalUser;
+    let root = UnlockedId::generate_for_git_url("https://root");
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let c = UnlockedId::generate_for_git_url("https://c");
+    let d = UnlockedId::generate_for_git_url("https://d");
+    let x = UnlockedId::generate_for_git_url("https://x");
+    let y = UnlockedId::generate_for_git_url("https://y");
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            build_trust_proof(&root, vec![&a, &b, &c, &d], TrustLevel::High, 0)?,
+            // peers vouch for each other, so they're well corroborated
+            build_trust_proof(&a, vec![&b, &c, &d], TrustLevel::High, 0)?,
+            build_trust_proof(&b, vec![&a, &c, &d], TrustLevel::High, 0)?,
+            build_trust_proof(&c, vec![&a, &b, &d], TrustLevel::High, 0)?,
+            build_trust_proof(&d, vec![&a, &b, &c], TrustLevel::High, 0)?,
+            // one enthusiast, and a few more reserved opinions
+            build_trust_proof(&a, vec![&x], TrustLevel::High, 0)?,
+            build_trust_proof(&b, vec![&x], TrustLevel::Medium, 0)?,
+            build_trust_proof(&c, vec![&x], TrustLevel::Low, 0)?,
+            build_trust_proof(&d, vec![&x], TrustLevel::Low, 0)?,
+            build_trust_proof(&x, vec![&y], TrustLevel::High, 0)?,
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+
+    let levels = |aggregation| {
+        let params = TrustDistanceParams {
+            trust_aggregation: aggregation,
+            ..default()
+        };
+        let trust_set = trustdb.calculate_trust_set(root.as_ref(), &params);
+        (
+            trust_set.get_effective_trust_level(x.as_ref()),
+            trust_set.get_effective_trust_level(y.as_ref()),
+        )
+    };
+
+    assert_eq!(
+        levels(EffectiveTrustAggregation::Max),
+        (TrustLevel::High, TrustLevel::High)
+    );
+    assert_eq!(
+        trustdb
+            .calculate_trust_set(root.as_ref(), &default())
+            .to_sorted(),
+        trustdb
+            .calculate_trust_set(
+                root.as_ref(),
+                &TrustDistanceParams {
+                    trust_aggregation: EffectiveTrustAggregation::Max,
+                    ..default()
+                }
+            )
+            .to_sorted()
+    );
+    // `x` has only one reporter at `High`, but two at `Medium` or more;
+    // `y` has just one reporter, so it's capped too
+    assert_eq!(
+        levels(EffectiveTrustAggregation::RequireQuorum {
+            level: TrustLevel::Medium,
+            count: 2,
+        }),
+        (TrustLevel::Medium, TrustLevel::Low)
+    );
+    // `Low`, `Low`, `Medium`, `High`
+    assert_eq!(
+        levels(EffectiveTrustAggregation::Median),
+        (TrustLevel::Low, TrustLevel::Low)
+    );
+    // roots are never affected, but `a` has only 4 reporters
+    let trust_set = trustdb.calculate_trust_set(
+        root.as_ref(),
+        &TrustDistanceParams {
+            trust_aggregation: EffectiveTrustAggregation::RequireQuorum {
+                level: TrustLevel::Low,
+                count: 5,
+            },
+            ..default()
+        },
+    );
+    assert_eq!(
+        trust_set.get_effective_trust_level(root.as_ref()),
+        TrustLevel::High
+    );
+    assert_eq!(
+        trust_set.get_effective_trust_level(a.as_ref()),
+        TrustLevel::Low
+    );
+
+    Ok(())
+}
+
+#[test]
+fn id_supersession_redirects_trust() -> Result<()> {
+    use crev_wot::SupersessionParams;
+
+    let url = FetchSource::LocalUser;
+    let root = UnlockedId::generate_for_git_url("https://root");
+    let old = UnlockedId::generate_for_git_url("https://old");
+    let mid = UnlockedId::generate_for_git_url("https://mid");
+    let new = UnlockedId::generate_for_git_url("https://new");
+    let x = UnlockedId::generate_for_git_url("https://x");
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            build_trust_proof(&root, vec![&old], TrustLevel::High, 0)?,
+            build_trust_proof(&new, vec![&x], TrustLevel::High, 0)?,
+            old.create_signed_supersession_proof(mid.as_public_id())?,
+            mid.create_signed_supersession_proof(new.as_public_id())?,
+            build_review(&old, "foo")?,
+        ]
+        .into_iter()
+        .map(|x| (x, url.clone())),
+    );
+    assert_eq!(trustdb.resolve_id(old.as_ref()), new.as_ref());
+    assert_eq!(trustdb.resolve_id(mid.as_ref()), new.as_ref());
+    assert_eq!(trustdb.resolve_id(x.as_ref()), x.as_ref());
+    assert_eq!(
+        trustdb.get_id_superseded_by(old.as_ref()),
+        Some(mid.as_ref())
+    );
+
+    let trust_set_with = |redirect_trust, inherit_reviews| {
+        let params = TrustDistanceParams {
+            supersession: SupersessionParams {
+                redirect_trust,
+                inherit_reviews,
+            },
+            ..default()
+        };
+        trustdb.calculate_trust_set(root.as_ref(), &params)
+    };
+    let trusted = |trust_set: &crev_wot::TrustSet| {
+        [&old, &mid, &new, &x]
+            .iter()
+            .map(|id| trust_set.is_trusted(id.as_ref()))
+            .collect::<Vec<_>>()
+    };
+    let digest = crev_data::Digest::from_vec(vec![0; 32]);
+    let reviews = |trust_set: &crev_wot::TrustSet| {
+        trustdb.trusted_review_count_for_digest(&digest, trust_set, TrustLevel::Low)
+    };
+
+    // supersession proofs are only recorded by default
+    let trust_set = trust_set_with(false, false);
+    assert_eq!(trusted(&trust_set), [true, false, false, false]);
+    assert_eq!(reviews(&trust_set), 1);
+
+    let trust_set = trust_set_with(true, false);
+    assert_eq!(trusted(&trust_set), [false, false, true, true]);
+    assert_eq!(
+        trust_set.get_effective_trust_level(new.as_ref()),
+        TrustLevel::High
+    );
+    assert_eq!(reviews(&trust_set), 0);
+
+    // reviews of `old` count as reviews of `new`, which isn't trusted
+    let trust_set = trust_set_with(false, true);
+    assert_eq!(trusted(&trust_set), [false, false, false, false]);
+    assert_eq!(reviews(&trust_set), 0);
+
+    let trust_set = trust_set_with(true, true);
+    assert_eq!(trusted(&trust_set), [true, true, true, true]);
+    assert_eq!(
+        trust_set.get_effective_trust_level(old.as_ref()),
+        TrustLevel::High
+    );
+    assert_eq!(reviews(&trust_set), 1);
+
+    Ok(())
+}
+
+#[test]
+fn id_supersession_cycles_and_conflicts() {
+    use crev_wot::{
+        testing::{fabricated_date, fabricated_id, ProofDBBuilder},
+        SupersessionParams,
+    };
+
+    let [root, a, b, c, d, e, f] = [0, 1, 2, 3, 4, 5, 6].map(fabricated_id);
+
+    let mut builder = ProofDBBuilder::new();
+    builder
+        // the most recent successor wins, no matter the import order
+        .id_supersession(&a, &c, fabricated_date(2))
+        .id_supersession(&a, &b, fabricated_date(1))
+        // `d` and `e` superseded each other, and `e` did it last
+        .id_supersession(&d, &e, fabricated_date(1))
+        .id_supersession(&e, &d, fabricated_date(3))
+        .id_supersession(&f, &e, fabricated_date(0))
+        // ignored
+        .id_supersession(&b, &b, fabricated_date(0))
+        .trust(&root, &a, TrustLevel::High, fabricated_date(0))
+        .trust(&root, &f, TrustLevel::Medium, fabricated_date(0))
+        // trust for one's own successor doesn't count
+        .trust(&d, &e, TrustLevel::High, fabricated_date(0));
+    let db = builder.build();
+
+    assert_eq!(db.resolve_id(&a.id), &c.id);
+    assert_eq!(db.resolve_id(&b.id), &b.id);
+    assert_eq!(db.resolve_id(&d.id), &d.id);
+    assert_eq!(db.resolve_id(&e.id), &d.id);
+    assert_eq!(db.resolve_id(&f.id), &d.id);
+    assert_eq!(db.approximate_memory_usage().id_supersessions, 4);
+
+    let params = TrustDistanceParams {
+        supersession: SupersessionParams {
+            redirect_trust: true,
+            inherit_reviews: true,
+        },
+        ..default()
+    };
+    let trust_set = db.calculate_trust_set(&root.id, &params);
+    let level = |id: &crev_data::PublicId| trust_set.get_effective_trust_level_opt(&id.id);
+    assert_eq!(level(&a), Some(TrustLevel::High));
+    assert_eq!(level(&b), None);
+    assert_eq!(level(&c), Some(TrustLevel::High));
+    assert_eq!(level(&d), Some(TrustLevel::Medium));
+    assert_eq!(level(&e), Some(TrustLevel::Medium));
+    assert_eq!(level(&f), Some(TrustLevel::Medium));
+
+    // merging gives the same result, in any order
+    let mut first = ProofDBBuilder::new();
+    first.id_supersession(&a, &b, fabricated_date(1));
+    let mut second = ProofDBBuilder::new();
+    second.id_supersession(&a, &c, fabricated_date(2));
+    let (mut first, mut second) = (first.build(), second.build());
+    let (first_copy, second_copy) = (first.clone(), second.clone());
+    first.merge(second_copy);
+    second.merge(first_copy);
+    assert_eq!(first.resolve_id(&a.id), &c.id);
+    assert_eq!(second.resolve_id(&a.id), &c.id);
+}
+
+#[test]
+fn id_supersession_distrust_applies_to_both() {
+    use crev_wot::{
+        testing::{fabricated_date, fabricated_id, ProofDBBuilder},
+        SupersessionParams,
+    };
+
+    let [root, old, new, x, y] = [0, 1, 2, 3, 4].map(fabricated_id);
+
+    let db = |distrusted: &crev_data::PublicId| {
+        let mut builder = ProofDBBuilder::new();
+        builder
+            .id_supersession(&old, &new, fabricated_date(1))
+            .trust(&root, &old, TrustLevel::High, fabricated_date(0))
+            .trust(&root, &new, TrustLevel::High, fabricated_date(0))
+            .trust(&root, &x, TrustLevel::High, fabricated_date(0))
+            .trust(&old, &y, TrustLevel::High, fabricated_date(0))
+            .trust(&x, distrusted, TrustLevel::Distrust, fabricated_date(0));
+        builder.build()
+    };
+    let params = |redirect_trust, inherit_reviews| TrustDistanceParams {
+        supersession: SupersessionParams {
+            redirect_trust,
+            inherit_reviews,
+        },
+        ..default()
+    };
+
+    for distrusted in [&old, &new] {
+        let db = db(distrusted);
+
+        let trust_set = db.calculate_trust_set(&root.id, &params(false, false));
+        for id in [&old, &new] {
+            assert_eq!(trust_set.is_distrusted(&id.id), id == distrusted);
+        }
+
+        for (redirect_trust, inherit_reviews) in [(true, false), (false, true), (true, true)] {
+            let trust_set =
+                db.calculate_trust_set(&root.id, &params(redirect_trust, inherit_reviews));
+            assert!(trust_set.is_distrusted(&old.id));
+            assert!(trust_set.is_distrusted(&new.id));
+            assert!(!trust_set.is_trusted(&y.id));
+            assert!(trust_set.is_trusted(&x.id));
+        }
+    }
+}
+
+#[test]
+fn expired_trust_is_not_followed() {
+    use chrono::FixedOffset;
+    use crev_wot::{
+        testing::{fabricated_date, fabricated_id, ProofDBBuilder},
+        Clock,
+    };
+
+    let [root, a, b, c] = [0, 1, 2, 3].map(fabricated_id);
+    let expiring_trust = |to: &crev_data::PublicId, days, expires_days| proof::Trust {
+        common: proof::Common {
+            kind: Some(proof::Trust::KIND.into()),
+            version: -1,
+            date: fabricated_date(days).with_timezone(&FixedOffset::east(0)),
+            from: root.clone(),
+        },
+        ids: vec![to.clone()],
+        trust: TrustLevel::High,
+        comment: String::new(),
+        sources: vec![],
+        expires: Some(fabricated_date(expires_days).with_timezone(&FixedOffset::east(0))),
+    };
+
+    let mut builder = ProofDBBuilder::new();
+    builder
+        .trust_proof(&expiring_trust(&a, 0, 10))
+        .trust(&a, &b, TrustLevel::High, fabricated_date(0))
+        // the newest proof stands, even once expired
+        .trust(&root, &c, TrustLevel::High, fabricated_date(0))
+        .trust_proof(&expiring_trust(&c, 5, 7));
+    let mut db = builder.build();
+    let params = TrustDistanceParams::default();
+
+    db.set_clock(Clock::Fixed(fabricated_date(10) - Duration::seconds(1)));
+    let trust_set = db.calculate_trust_set_cached(&root.id, &params);
+    assert!(trust_set.is_trusted(&a.id));
+    assert!(trust_set.is_trusted(&b.id));
+    assert!(!trust_set.is_trusted(&c.id));
+
+    // expired at the very instant, even if the trust set was cached before
+    db.set_clock(Clock::Fixed(fabricated_date(10)));
+    let trust_set = db.calculate_trust_set_cached(&root.id, &params);
+    assert!(!trust_set.is_trusted(&a.id));
+    assert!(!trust_set.is_trusted(&b.id));
+    assert_eq!(
+        db.get_direct_trust(&root.id, &a.id)
+            .map(|trust| *trust.value()),
+        Some(TrustLevel::High)
+    );
+
+    // back before the expiration of `c`'s trust
+    db.set_clock(Clock::Fixed(fabricated_date(6)));
+    let trust_set = db.calculate_trust_set_cached(&root.id, &params);
+    assert!(trust_set.is_trusted(&a.id));
+    assert!(trust_set.is_trusted(&c.id));
+
+    // re-affirmed without an expiration
+    let mut builder = ProofDBBuilder::from_db(db);
+    builder.trust(&root, &a, TrustLevel::High, fabricated_date(12));
+    let mut db = builder.build();
+    db.set_clock(Clock::Fixed(fabricated_date(1000)));
+    let trust_set = db.calculate_trust_set(&root.id, &params);
+    assert!(trust_set.is_trusted(&a.id));
+    assert!(trust_set.is_trusted(&b.id));
+    assert!(!trust_set.is_trusted(&c.id));
+}
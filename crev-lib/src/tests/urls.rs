@@ -0,0 +1,530 @@
+use super::*;
+
+use crev_data::{TrustLevel, UnlockedId};
+use crev_wot::{canonicalize_url, FetchSource, ProofDB, UrlClaim, UrlOfId, Verification};
+
+#[test]
+fn canonicalize_url_cases() {
+    let canonical = "https://github.com/foo/crev-proofs";
+    for url in &[
+        "https://github.com/foo/crev-proofs",
+        "https://github.com/foo/crev-proofs/",
+        "https://github.com/foo/crev-proofs.git",
+        "https://github.com/foo/crev-proofs.git/",
+        "HTTPS://GitHub.com/foo/crev-proofs",
+        " https://github.com/foo/crev-proofs ",
+    ] {
+        assert_eq!(canonicalize_url(url, false), canonical, "{}", url);
+    }
+
+    // path is case sensitive
+    assert_ne!(
+        canonicalize_url("https://github.com/Foo/crev-proofs", false),
+        canonical
+    );
+    assert_ne!(
+        canonicalize_url("http://github.com/foo/crev-proofs", false),
+        canonical
+    );
+    assert_eq!(
+        canonicalize_url("http://github.com/foo/crev-proofs", true),
+        canonical
+    );
+}
+
+#[test]
+fn url_is_verified_when_fetched_from_equivalent_url() -> Result<()> {
+    let a = UnlockedId::generate_for_git_url("https://github.com/a/crev-proofs");
+    let b = UnlockedId::generate_for_git_url("https://github.com/b/crev-proofs");
+    let c = UnlockedId::generate_for_git_url("http://github.com/c/crev-proofs");
+
+    let fetched_from =
+        |url: &str| FetchSource::Url(std::sync::Arc::new(crev_data::Url::new_git(url)));
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            (
+                a.create_signed_trust_proof(vec![b.as_public_id()], TrustLevel::Low)?,
+                fetched_from("https://github.com/a/crev-proofs.git"),
+            ),
+            (
+                b.create_signed_trust_proof(vec![a.as_public_id()], TrustLevel::Low)?,
+                fetched_from("https://github.com/b/crev-proofs/"),
+            ),
+            (
+                c.create_signed_trust_proof(vec![a.as_public_id()], TrustLevel::Low)?,
+                fetched_from("https://github.com/c/crev-proofs"),
+            ),
+        ]
+        .into_iter(),
+    );
+
+    // the URL is displayed as the Id reported it
+    match trustdb.lookup_url(a.as_ref()) {
+        UrlOfId::FromSelfVerified(url) => assert_eq!(url.url, "https://github.com/a/crev-proofs"),
+        other => panic!("unexpected: {:?}", other),
+    }
+    assert!(matches!(
+        trustdb.lookup_url(b.as_ref()),
+        UrlOfId::FromSelfVerified(_)
+    ));
+    assert!(matches!(
+        trustdb.lookup_url(c.as_ref()),
+        UrlOfId::FromSelf(_)
+    ));
+
+    let mut trustdb = ProofDB::new();
+    trustdb.set_ignore_url_scheme(true);
+    trustdb.import_from_iter(
+        vec![(
+            c.create_signed_trust_proof(vec![a.as_public_id()], TrustLevel::Low)?,
+            fetched_from("https://github.com/c/crev-proofs"),
+        )]
+        .into_iter(),
+    );
+    assert!(matches!(
+        trustdb.lookup_url(c.as_ref()),
+        UrlOfId::FromSelfVerified(_)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn ids_claiming_the_same_url() -> Result<()> {
+    let old = UnlockedId::generate_for_git_url("https://github.com/foo/crev-proofs");
+    let new = UnlockedId::generate_for_git_url("https://github.com/foo/crev-proofs.git");
+    let single = UnlockedId::generate_for_git_url("https://github.com/single/crev-proofs");
+    let other = UnlockedId::generate_for_git_url("https://github.com/other/crev-proofs");
+
+    let fetched_from =
+        |url: &str| FetchSource::Url(std::sync::Arc::new(crev_data::Url::new_git(url)));
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            (
+                old.create_signed_trust_proof(vec![new.as_public_id()], TrustLevel::High)?,
+                fetched_from("https://github.com/foo/crev-proofs"),
+            ),
+            (
+                new.create_signed_trust_proof(vec![old.as_public_id()], TrustLevel::High)?,
+                fetched_from("https://example.com/mirror"),
+            ),
+            (
+                single.create_signed_trust_proof(vec![other.as_public_id()], TrustLevel::Low)?,
+                FetchSource::LocalUser,
+            ),
+        ]
+        .into_iter(),
+    );
+
+    let conflicts = trustdb.url_conflicts();
+    assert_eq!(conflicts.len(), 1);
+    let mut ids: Vec<_> = conflicts[0].claims.iter().map(|(id, _)| id).collect();
+    ids.sort();
+    let mut expected = vec![&old.id.id, &new.id.id];
+    expected.sort();
+    assert_eq!(ids, expected);
+
+    let claims = trustdb.ids_claiming_url(&crev_data::Url::new_git(
+        "https://github.com/foo/crev-proofs/",
+    ));
+    assert_eq!(claims.len(), 2);
+    for (id, claim) in claims {
+        if id == old.id.id {
+            assert!(matches!(claim, UrlClaim::FromSelfVerified(_)));
+        } else {
+            assert_eq!(id, new.id.id);
+            assert!(matches!(claim, UrlClaim::FromSelf(_)));
+        }
+    }
+
+    // only reported by `single`, so not a conflict
+    assert!(matches!(
+        trustdb
+            .ids_claiming_url(other.url())
+            .as_slice(),
+        [(id, UrlClaim::FromOthers(_))] if *id == other.id.id
+    ));
+    assert_eq!(trustdb.ids_claiming_url(single.url()).len(), 1);
+    assert!(trustdb
+        .ids_claiming_url(&crev_data::Url::new_git("https://nobody"))
+        .is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn bulk_url_lookup_matches_single_lookup() -> Result<()> {
+    let a = UnlockedId::generate_for_git_url("https://github.com/a/crev-proofs");
+    let b = UnlockedId::generate_for_git_url("https://github.com/b/crev-proofs");
+    let c = UnlockedId::generate_for_git_url("https://github.com/c/crev-proofs");
+    let unknown = UnlockedId::generate_for_git_url("https://github.com/unknown/crev-proofs");
+
+    let fetched_from =
+        |url: &str| FetchSource::Url(std::sync::Arc::new(crev_data::Url::new_git(url)));
+
+    // a: self-verified, b: self-reported (and reported by a), c: reported by a only
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            (
+                a.create_signed_trust_proof(
+                    vec![b.as_public_id(), c.as_public_id()],
+                    TrustLevel::Low,
+                )?,
+                fetched_from("https://github.com/a/crev-proofs"),
+            ),
+            (
+                b.create_signed_trust_proof(vec![a.as_public_id()], TrustLevel::Low)?,
+                fetched_from("https://example.com/mirror"),
+            ),
+        ]
+        .into_iter(),
+    );
+
+    let ids: Vec<_> = [&a, &b, &c, &unknown]
+        .iter()
+        .map(|id| id.id.id.clone())
+        .collect();
+    let bulk = trustdb.lookup_urls_bulk(&ids);
+    assert_eq!(bulk.len(), 3);
+    for id in &ids {
+        assert_eq!(bulk.get(id).cloned(), trustdb.lookup_url(id).into_owned());
+    }
+    assert_eq!(bulk[a.as_ref()].verification, Verification::SelfVerified);
+    assert_eq!(bulk[b.as_ref()].verification, Verification::SelfReported);
+    assert_eq!(
+        bulk[c.as_ref()].verification,
+        Verification::ReportedByOthers
+    );
+    assert!(matches!(
+        bulk[b.as_ref()].as_url_of_id(),
+        UrlOfId::FromSelf(url) if url.url == "https://github.com/b/crev-proofs"
+    ));
+
+    let all: Vec<_> = trustdb.all_ids_with_urls().collect();
+    assert_eq!(all.len(), trustdb.all_known_ids().len());
+    assert_eq!(all.len(), 3);
+    for (id, url) in all {
+        assert_eq!(url.into_owned(), trustdb.lookup_url(id).into_owned());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn ids_by_url_substring() -> Result<()> {
+    let a = UnlockedId::generate_for_git_url("https://github.com/a/crev-proofs");
+    let b = UnlockedId::generate_for_git_url("https://github.com/B/crev-proofs");
+
+    let fetched_from =
+        |url: &str| FetchSource::Url(std::sync::Arc::new(crev_data::Url::new_git(url)));
+    // `a` reports a different URL for `b` than `b` itself does
+    let b_elsewhere = crev_data::PublicId::new(
+        b.id.id.clone(),
+        crev_data::Url::new_git("https://gitlab.com/b-mirror/proofs"),
+    );
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            (
+                a.create_signed_trust_proof(vec![&b_elsewhere], TrustLevel::Low)?,
+                fetched_from("https://github.com/a/crev-proofs"),
+            ),
+            (
+                b.create_signed_trust_proof(vec![a.as_public_id()], TrustLevel::Low)?,
+                fetched_from("https://example.com/mirror"),
+            ),
+        ]
+        .into_iter(),
+    );
+
+    let find = |needle| {
+        trustdb
+            .find_ids_by_url_substring(needle)
+            .into_iter()
+            .map(|(id, url)| {
+                let (verification, url) = match url {
+                    UrlOfId::FromSelfVerified(url) => (Verification::SelfVerified, url),
+                    UrlOfId::FromSelf(url) => (Verification::SelfReported, url),
+                    UrlOfId::FromOthers(url) => (Verification::ReportedByOthers, url),
+                    UrlOfId::None => unreachable!(),
+                };
+                (id, verification, url.url.clone())
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        find("GITLAB"),
+        vec![(
+            b.id.id.clone(),
+            Verification::ReportedByOthers,
+            "https://gitlab.com/b-mirror/proofs".to_owned()
+        )]
+    );
+    // both URLs match, the self-reported one wins
+    let b_self = (
+        b.id.id.clone(),
+        Verification::SelfReported,
+        "https://github.com/B/crev-proofs".to_owned(),
+    );
+    assert_eq!(find("/b"), vec![b_self.clone()]);
+
+    let mut expected = vec![
+        (
+            a.id.id.clone(),
+            Verification::SelfVerified,
+            "https://github.com/a/crev-proofs".to_owned(),
+        ),
+        b_self,
+    ];
+    expected.sort_by(|x, y| x.0.cmp(&y.0));
+    assert_eq!(find("github.com/"), expected);
+    assert!(find("bitbucket").is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn url_history_and_conflicts() -> Result<()> {
+    use crev_data::proof::ContentExt;
+
+    let victim_url = "https://github.com/victim/crev-proofs";
+    let mover = UnlockedId::generate_for_git_url("https://github.com/mover/old-proofs");
+    let victim = UnlockedId::generate_for_git_url(victim_url);
+    let attacker = UnlockedId::generate_for_git_url(victim_url);
+    let friend = UnlockedId::generate_for_git_url("https://github.com/friend/crev-proofs");
+
+    let fetched_from =
+        |url: &str| FetchSource::Url(std::sync::Arc::new(crev_data::Url::new_git(url)));
+    let base = chrono::Utc::now() - chrono::Duration::days(30);
+    // a trust proof by `mover`, signed when it was using the `url` repo
+    let moved = |url: &str, days: i64| -> Result<_> {
+        let mut trust = mover
+            .as_public_id()
+            .create_trust_proof(vec![friend.as_public_id()], TrustLevel::Low)?;
+        trust.common.from.url = Some(crev_data::Url::new_git(url));
+        trust.common.date = (base + chrono::Duration::days(days)).into();
+        Ok((trust.sign_by(&mover)?, fetched_from(url)))
+    };
+
+    let mut trustdb = ProofDB::new();
+    trustdb.import_from_iter(
+        vec![
+            moved("https://github.com/mover/new-proofs", 10)?,
+            moved("https://github.com/mover/old-proofs", 0)?,
+            (
+                victim.create_signed_trust_proof(vec![friend.as_public_id()], TrustLevel::High)?,
+                fetched_from(victim_url),
+            ),
+            (
+                attacker
+                    .create_signed_trust_proof(vec![victim.as_public_id()], TrustLevel::High)?,
+                fetched_from("https://evil.example.com/proofs"),
+            ),
+        ]
+        .into_iter(),
+    );
+
+    // `mover` moved repos: the newest URL is still the one looked up
+    assert_eq!(
+        trustdb.lookup_url(&mover.id.id).from_self().unwrap().url,
+        "https://github.com/mover/new-proofs"
+    );
+    let history: Vec<_> = trustdb
+        .get_url_history(&mover.id.id)
+        .into_iter()
+        .map(|url| (url.value().url.clone(), url.date))
+        .collect();
+    assert_eq!(
+        history,
+        vec![
+            ("https://github.com/mover/old-proofs".to_owned(), base),
+            (
+                "https://github.com/mover/new-proofs".to_owned(),
+                base + chrono::Duration::days(10)
+            ),
+        ]
+    );
+    assert!(trustdb.get_url_history(&friend.id.id).is_empty());
+
+    // only the repo of `victim` is a conflict, not the old repo of `mover`
+    let conflicts = trustdb.find_url_conflicts();
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0].url.url, victim_url);
+    let claims = &conflicts[0].claims;
+    assert_eq!(claims.len(), 2);
+    for (id, claim) in claims {
+        if *id == victim.id.id {
+            assert!(matches!(claim, UrlClaim::FromSelfVerified(_)));
+        } else {
+            assert_eq!(*id, attacker.id.id);
+            assert!(matches!(claim, UrlClaim::FromSelf(_)));
+        }
+    }
+    assert_eq!(
+        trustdb.lookup_url(&victim.id.id).verified().unwrap().url,
+        victim_url
+    );
+    assert!(trustdb.lookup_url(&attacker.id.id).verified().is_none());
+
+    Ok(())
+}
+
+#[test]
+fn lookup_id_by_url_orders_claims() {
+    use crev_data::{PublicId, Url};
+    use crev_wot::{
+        testing::{fabricated_date, fabricated_id, ProofDBBuilder},
+        IdOfUrl,
+    };
+
+    let url = "https://github.com/foo/crev-proofs";
+    let claiming = |n| PublicId::new(fabricated_id(n).id, Url::new_git(url));
+    let (verified, older, newer, reported) = (claiming(1), claiming(2), claiming(3), claiming(4));
+    // reported to own the URL, but claims another one itself
+    let moved = claiming(5);
+    let reporter = fabricated_id(6);
+    let fetched_from = |url: &str| FetchSource::Url(std::sync::Arc::new(Url::new_git(url)));
+
+    let mut builder = ProofDBBuilder::new();
+    builder
+        .fetched_from(fetched_from("https://github.com/foo/crev-proofs.git"))
+        .url(&verified, fabricated_date(0))
+        .fetched_from(fetched_from("https://example.com/mirror"))
+        .url(&older, fabricated_date(1))
+        .url(&newer, fabricated_date(5))
+        .url(&fabricated_id(5), fabricated_date(5))
+        .trust(&reporter, &reported, TrustLevel::Low, fabricated_date(9))
+        .trust(&reporter, &moved, TrustLevel::Low, fabricated_date(9));
+    let trustdb = builder.build();
+
+    for pasted in &[
+        url,
+        "https://github.com/foo/crev-proofs/",
+        "https://github.com/foo/crev-proofs.git",
+        "HTTPS://GitHub.com/foo/crev-proofs.git/",
+    ] {
+        assert_eq!(
+            trustdb.lookup_id_by_url(&Url::new_git(*pasted)),
+            vec![
+                IdOfUrl::FromSelfVerified(&verified.id),
+                IdOfUrl::FromSelf(&newer.id),
+                IdOfUrl::FromSelf(&older.id),
+                IdOfUrl::FromOthers(&reported.id),
+            ],
+            "{}",
+            pasted
+        );
+    }
+    assert_eq!(
+        trustdb.lookup_id_by_url(&Url::new_git(url))[0].verification(),
+        Verification::SelfVerified
+    );
+    assert_eq!(
+        trustdb.lookup_id_by_url(&Url::new_git("https://id-5.test")),
+        vec![IdOfUrl::FromSelf(&moved.id)]
+    );
+    assert!(trustdb
+        .lookup_id_by_url(&Url::new_git("https://github.com/foo/other"))
+        .is_empty());
+
+    // claims reported by others are conflicts only for `find_url_conflicts`
+    let self_reported = trustdb.url_conflicts();
+    assert_eq!(self_reported.len(), 1);
+    assert_eq!(self_reported[0].claims.len(), 3);
+    let all = trustdb.find_url_conflicts();
+    assert_eq!(all.len(), 1);
+    assert_eq!(all[0].claims.len(), 4);
+    assert_eq!(all[0].claims[..3], self_reported[0].claims[..]);
+}
+
+#[test]
+fn id_aliases() {
+    use crev_data::Url;
+    use crev_wot::{
+        aliases::{AliasLookupError, IdAliases},
+        testing::{fabricated_date, fabricated_id, ProofDBBuilder},
+    };
+
+    let (first, second, third) = (fabricated_id(1), fabricated_id(2), fabricated_id(3));
+    let fetched_from = |url: &str| FetchSource::Url(std::sync::Arc::new(Url::new_git(url)));
+
+    let mut builder = ProofDBBuilder::new();
+    builder
+        .fetched_from(fetched_from("https://id-1.test/"))
+        .url(&first, fabricated_date(0))
+        .fetched_from(fetched_from("https://example.com/mirror"))
+        .trust(&third, &first, TrustLevel::Low, fabricated_date(1))
+        .trust(&third, &second, TrustLevel::Low, fabricated_date(1));
+    let trustdb = builder.build();
+
+    // fabricated Ids only differ in a few bytes, so they share a long prefix
+    let (first_str, second_str) = (first.id.to_string(), second.id.to_string());
+    let common_len = first_str
+        .chars()
+        .zip(second_str.chars())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut aliases = IdAliases::new();
+    assert_eq!(
+        aliases.resolve(&trustdb, &first_str[..common_len + 1]),
+        Ok(first.id.clone())
+    );
+    assert_eq!(aliases.resolve(&trustdb, &first_str), Ok(first.id.clone()));
+    match aliases.resolve(&trustdb, &first_str[..common_len]) {
+        Err(AliasLookupError::AmbiguousIdPrefix(candidates)) => {
+            assert!(candidates.contains(&first.id));
+            assert!(candidates.contains(&second.id));
+        }
+        other => panic!("not ambiguous: {:?}", other),
+    }
+    assert_eq!(
+        aliases.resolve(&trustdb, "alice-work"),
+        Err(AliasLookupError::Unknown("alice-work".into()))
+    );
+    assert!(aliases.resolve(&trustdb, "").is_err());
+
+    // many aliases for one Id are fine, but not one alias for many Ids
+    aliases
+        .set_alias("alice-work".into(), first.id.clone())
+        .unwrap();
+    aliases.set_alias("alice".into(), first.id.clone()).unwrap();
+    aliases.set_alias("alice".into(), first.id.clone()).unwrap();
+    assert_eq!(
+        aliases.set_alias("alice".into(), second.id.clone()),
+        Err(AliasLookupError::AliasTaken {
+            alias: "alice".into(),
+            id: first.id.clone(),
+        })
+    );
+    assert_eq!(aliases.len(), 2);
+    assert_eq!(
+        aliases.aliases_of(&first.id).collect::<Vec<_>>(),
+        vec!["alice", "alice-work"]
+    );
+    assert_eq!(
+        aliases.resolve(&trustdb, "alice-work"),
+        Ok(first.id.clone())
+    );
+    assert_eq!(aliases.resolve(&trustdb, "alice"), Ok(first.id.clone()));
+
+    // aliases first, then verified URLs, then the beginning of the Id
+    assert_eq!(aliases.display_name(&trustdb, &first.id), "alice");
+    aliases.remove_alias("alice");
+    aliases.remove_alias("alice-work");
+    assert_eq!(aliases.display_name(&trustdb, &first.id), "id-1.test");
+    assert_eq!(
+        aliases.display_name(&trustdb, &second.id),
+        format!("{}...", &second_str[..8])
+    );
+
+    aliases.set_alias("bob".into(), second.id.clone()).unwrap();
+    let stored = serde_yaml::to_string(&aliases).unwrap();
+    assert_eq!(serde_yaml::from_str::<IdAliases>(&stored).unwrap(), aliases);
+}
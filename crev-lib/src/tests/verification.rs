@@ -0,0 +1,943 @@
+use super::*;
+
+use crev_data::{
+    proof::{self, review::Review, ContentExt},
+    Digest, Level, TrustLevel, UnlockedId,
+};
+use crev_wot::{
+    verification::{CoveragePosition, Verdict, VerificationRequirements, VerificationStatus},
+    FetchSource, ProofDB, ReviewIgnoreList,
+};
+use semver::Version;
+
+const SOURCE: &str = "SOURCE_ID";
+const NAME: &str = "name";
+
+fn build_review(
+    id: &UnlockedId,
+    version: &str,
+    digest: u8,
+    review: Review,
+    issues: Vec<proof::review::package::Issue>,
+) -> proof::Proof {
+    let package_info = proof::PackageInfo {
+        id: proof::PackageVersionId::new(
+            SOURCE.into(),
+            NAME.into(),
+            Version::parse(version).unwrap(),
+        ),
+        digest: vec![digest; 32],
+        digest_type: proof::default_digest_type(),
+        revision: "".into(),
+        revision_type: proof::default_revision_type(),
+    };
+    proof::review::PackageBuilder::default()
+        .from(id.id.to_owned())
+        .package(package_info)
+        .review(review)
+        .issues(issues)
+        .build()
+        .unwrap()
+        .sign_by(&id)
+        .unwrap()
+}
+
+fn positive(id: &UnlockedId, version: &str, digest: u8) -> proof::Proof {
+    build_review(id, version, digest, Review::new_positive(), vec![])
+}
+
+struct Fixture {
+    db: ProofDB,
+    root: UnlockedId,
+    high: UnlockedId,
+    low: UnlockedId,
+    stranger: UnlockedId,
+}
+
+impl Fixture {
+    fn new() -> Result<Self> {
+        let root = UnlockedId::generate_for_git_url("https://root");
+        let high = UnlockedId::generate_for_git_url("https://high");
+        let low = UnlockedId::generate_for_git_url("https://low");
+        let stranger = UnlockedId::generate_for_git_url("https://stranger");
+        let mut db = ProofDB::new();
+        db.import_from_iter(
+            vec![
+                root.create_signed_trust_proof(vec![high.as_public_id()], TrustLevel::High)?,
+                root.create_signed_trust_proof(vec![low.as_public_id()], TrustLevel::Low)?,
+            ]
+            .into_iter()
+            .map(|x| (x, FetchSource::LocalUser)),
+        );
+        Ok(Fixture {
+            db,
+            root,
+            high,
+            low,
+            stranger,
+        })
+    }
+
+    fn import(&mut self, proofs: Vec<proof::Proof>) {
+        self.db
+            .import_from_iter(proofs.into_iter().map(|x| (x, FetchSource::LocalUser)));
+    }
+
+    fn verify(
+        &self,
+        version: &str,
+        digest: Option<u8>,
+        reqs: &VerificationRequirements,
+    ) -> VerificationStatus {
+        let trust_set = self.db.calculate_trust_set(self.root.as_ref(), &default());
+        self.db.verify_package(
+            SOURCE,
+            NAME,
+            &Version::parse(version).unwrap(),
+            digest.map(|d| Digest::from_vec(vec![d; 32])).as_ref(),
+            &trust_set,
+            reqs,
+        )
+    }
+}
+
+fn reqs() -> VerificationRequirements {
+    VerificationRequirements {
+        thoroughness: Level::Low,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn verify_package_insufficient_reviews() -> Result<()> {
+    let mut f = Fixture::new()?;
+    assert_eq!(
+        f.verify("1.0.0", None, &reqs()),
+        VerificationStatus::NoReviews
+    );
+
+    // untrusted reviewers don't count
+    let stranger_review = positive(&f.stranger, "1.0.0", 1);
+    f.import(vec![stranger_review]);
+    assert_eq!(
+        f.verify("1.0.0", None, &reqs()),
+        VerificationStatus::NoReviews
+    );
+
+    // neither do too shallow ones
+    let shallow = build_review(&f.high, "1.0.0", 1, Review::new_none(), vec![]);
+    f.import(vec![shallow]);
+    assert_eq!(
+        f.verify("1.0.0", None, &reqs()),
+        VerificationStatus::InsufficientReviews { have: 0, need: 1 }
+    );
+    Ok(())
+}
+
+#[test]
+fn verify_package_verified() -> Result<()> {
+    let mut f = Fixture::new()?;
+    let proofs = vec![positive(&f.high, "1.0.0", 1), positive(&f.low, "1.0.0", 1)];
+    f.import(proofs);
+
+    assert!(f.verify("1.0.0", Some(1), &reqs()).is_verified());
+    assert!(f
+        .verify(
+            "1.0.0",
+            None,
+            &VerificationRequirements {
+                redundancy: 2,
+                ..reqs()
+            }
+        )
+        .is_verified());
+
+    // `low` doesn't count for higher trust requirement
+    assert_eq!(
+        f.verify(
+            "1.0.0",
+            None,
+            &VerificationRequirements {
+                redundancy: 2,
+                trust_level: TrustLevel::Medium,
+                ..reqs()
+            }
+        ),
+        VerificationStatus::InsufficientReviews { have: 1, need: 2 }
+    );
+    // default thoroughness requirement is higher than a default positive review
+    assert_eq!(
+        f.verify("1.0.0", None, &VerificationRequirements::default()),
+        VerificationStatus::InsufficientReviews { have: 0, need: 1 }
+    );
+    Ok(())
+}
+
+#[test]
+fn verify_package_digest_mismatch() -> Result<()> {
+    let mut f = Fixture::new()?;
+    let proofs = vec![positive(&f.high, "1.0.0", 1)];
+    f.import(proofs);
+
+    assert_eq!(
+        f.verify("1.0.0", Some(2), &reqs()),
+        VerificationStatus::DigestMismatch
+    );
+    assert!(f.verify("1.0.0", Some(1), &reqs()).is_verified());
+    Ok(())
+}
+
+#[test]
+fn verify_package_digest_only() -> Result<()> {
+    use proof::review::package::Issue;
+
+    let mut f = Fixture::new()?;
+    f.import(vec![
+        positive(&f.high, "1.0.0", 1),
+        build_review(&f.low, "1.0.0", 1, Review::new_negative(), vec![]),
+        positive(&f.high, "1.0.0", 2),
+        build_review(
+            &f.high,
+            "2.0.0",
+            3,
+            Review::new_positive(),
+            vec![Issue::new_with_severity("BUG".into(), Level::High)],
+        ),
+    ]);
+    let trust_set = f.db.calculate_trust_set(f.root.as_ref(), &default());
+    let verify = |digest: u8, reqs: &VerificationRequirements| {
+        let digest = Digest::from_vec(vec![digest; 32]);
+        let status = f.db.verify_package_digest(&digest, &trust_set, reqs);
+        assert_eq!(
+            crate::verify_package_digest(&digest, &trust_set, reqs, &f.db),
+            status
+        );
+        status
+    };
+
+    // like `verify_package`, negative reviews count only if trusted enough
+    assert_eq!(
+        verify(1, &reqs()),
+        VerificationStatus::Distrusted {
+            by: vec![f.low.id.id.clone()].into_iter().collect()
+        }
+    );
+    let trusting_high_only = VerificationRequirements {
+        trust_level: TrustLevel::High,
+        ..reqs()
+    };
+    assert!(verify(1, &trusting_high_only).is_verified());
+    // the negative review is of another digest
+    assert!(verify(2, &reqs()).is_verified());
+    assert_eq!(
+        verify(3, &reqs()),
+        VerificationStatus::OpenIssues {
+            ids: vec!["BUG".to_owned()].into_iter().collect()
+        }
+    );
+    assert_eq!(verify(4, &reqs()), VerificationStatus::NoReviews);
+    Ok(())
+}
+
+#[test]
+fn verify_package_distrusted() -> Result<()> {
+    let mut f = Fixture::new()?;
+    let proofs = vec![
+        positive(&f.high, "1.0.0", 1),
+        build_review(&f.low, "1.0.0", 1, Review::new_negative(), vec![]),
+        build_review(&f.stranger, "1.0.0", 1, Review::new_negative(), vec![]),
+    ];
+    f.import(proofs);
+
+    assert_eq!(
+        f.verify("1.0.0", Some(1), &reqs()),
+        VerificationStatus::Distrusted {
+            by: vec![f.low.id.id.clone()].into_iter().collect()
+        }
+    );
+    // ... unless the negative reviewer is not trusted enough
+    assert!(f
+        .verify(
+            "1.0.0",
+            Some(1),
+            &VerificationRequirements {
+                trust_level: TrustLevel::High,
+                ..reqs()
+            }
+        )
+        .is_verified());
+    Ok(())
+}
+
+#[test]
+fn review_verdict_veto() -> Result<()> {
+    let mut f = Fixture::new()?;
+    let peer = UnlockedId::generate_for_git_url("https://peer");
+    let trust_peer = f
+        .root
+        .create_signed_trust_proof(vec![peer.as_public_id()], TrustLevel::Low)?;
+    let negative = |id, version| build_review(id, version, 1, Review::new_negative(), vec![]);
+    f.import(vec![
+        trust_peer,
+        // vetoed by a more trusted reviewer
+        positive(&f.low, "1.0.0", 1),
+        positive(&f.stranger, "1.0.0", 1),
+        build_review(&peer, "1.0.0", 1, Review::new_none(), vec![]),
+        negative(&f.high, "1.0.0"),
+        // positive and negative reviews by equally trusted reviewers
+        positive(&f.low, "2.0.0", 1),
+        negative(&peer, "2.0.0"),
+        // a more trusted positive review
+        positive(&f.high, "3.0.0", 1),
+        negative(&f.low, "3.0.0"),
+        // only a negative review
+        negative(&f.low, "4.0.0"),
+    ]);
+    let trust_set = f.db.calculate_trust_set(f.root.as_ref(), &default());
+    let verdict = |version: &str, veto_level| {
+        f.db.get_pkg_review_verdict(
+            SOURCE,
+            NAME,
+            &Version::parse(version).unwrap(),
+            &trust_set,
+            TrustLevel::Low,
+            veto_level,
+        )
+    };
+
+    let version = Version::parse("1.0.0").unwrap();
+    assert_eq!(
+        f.db.get_pkg_endorsements_for_version(SOURCE, NAME, &version)
+            .count(),
+        2
+    );
+    assert_eq!(
+        f.db.get_pkg_negative_reviews_for_version(SOURCE, NAME, &version)
+            .count(),
+        1
+    );
+
+    // the stranger's review doesn't count
+    let vetoed = verdict("1.0.0", TrustLevel::Medium);
+    assert_eq!(
+        (vetoed.positive, vetoed.neutral, vetoed.negative),
+        (1, 1, 1)
+    );
+    assert!(!vetoed.is_endorsed());
+    let veto = vetoed.veto.unwrap();
+    assert_eq!(veto.from(), &f.high.id.id);
+    assert_eq!(veto.package_version_id().version, version);
+
+    // when in doubt, the package is vetoed ...
+    let tie = verdict("2.0.0", TrustLevel::Low);
+    assert_eq!((tie.positive, tie.negative), (1, 1));
+    assert_eq!(tie.veto.unwrap().from(), &peer.id.id);
+    // ... if the reviewer is trusted enough
+    let tie = verdict("2.0.0", TrustLevel::Medium);
+    assert!(tie.is_endorsed());
+
+    let outweighed = verdict("3.0.0", TrustLevel::Low);
+    assert_eq!((outweighed.positive, outweighed.negative), (1, 1));
+    assert!(outweighed.is_endorsed());
+
+    assert!(verdict("4.0.0", TrustLevel::Low).is_vetoed());
+    assert!(!verdict("4.0.0", TrustLevel::Medium).is_vetoed());
+    assert_eq!(verdict("5.0.0", TrustLevel::Low), Verdict::default());
+    Ok(())
+}
+
+#[test]
+fn verify_digests_at_once() -> Result<()> {
+    let mut f = Fixture::new()?;
+    let distrust = f
+        .root
+        .create_signed_trust_proof(vec![f.stranger.as_public_id()], TrustLevel::Distrust)?;
+    let proofs = vec![
+        distrust,
+        positive(&f.low, "1.0.0", 1),
+        positive(&f.high, "1.0.0", 1),
+        build_review(&f.low, "1.1.0", 2, Review::new_negative(), vec![]),
+        // only reviewed by someone distrusted
+        positive(&f.stranger, "1.2.0", 3),
+    ];
+    f.import(proofs);
+
+    let trust_set = f.db.calculate_trust_set(f.root.as_ref(), &default());
+    let digests: Vec<_> = (1..=4u8).map(|d| Digest::from_vec(vec![d; 32])).collect();
+    let verified = f.db.verify_digests(&digests, &trust_set, TrustLevel::Low);
+    assert_eq!(verified.len(), 4);
+
+    let both = &verified[&digests[0]];
+    assert_eq!(both.trusted_count(), 2);
+    assert_eq!(both.max_trust_level, Some(TrustLevel::High));
+    assert_eq!(both.best_review().unwrap().from().id, f.high.id.id);
+    assert!(both.negative.is_empty());
+
+    let negative = &verified[&digests[1]];
+    assert_eq!(negative.trusted_count(), 1);
+    assert_eq!(negative.negative.len(), 1);
+    assert_eq!(negative.max_trust_level, Some(TrustLevel::Low));
+
+    for unreviewed in &digests[2..] {
+        let verification = &verified[unreviewed];
+        assert!(!verification.is_reviewed());
+        assert!(verification.negative.is_empty());
+        assert_eq!(verification.max_trust_level, None);
+    }
+
+    // the same as querying one by one
+    for digest in &digests {
+        assert_eq!(
+            verified[digest].best_review().map(|r| r as *const _),
+            f.db.best_review_for_digest(digest, &trust_set, TrustLevel::Low)
+                .map(|r| r as *const _)
+        );
+    }
+
+    let high_only = f.db.verify_digests(&digests, &trust_set, TrustLevel::High);
+    assert_eq!(high_only[&digests[0]].trusted_count(), 1);
+    assert!(!high_only[&digests[1]].is_reviewed());
+    Ok(())
+}
+
+#[test]
+fn review_coverage_of_version_range() -> Result<()> {
+    let mut f = Fixture::new()?;
+    let diff_review = |id: &UnlockedId, version: &str, base: &str| {
+        let package_info = |version: &str, digest| proof::PackageInfo {
+            id: proof::PackageVersionId::new(
+                SOURCE.into(),
+                NAME.into(),
+                Version::parse(version).unwrap(),
+            ),
+            digest: vec![digest; 32],
+            digest_type: proof::default_digest_type(),
+            revision: "".into(),
+            revision_type: proof::default_revision_type(),
+        };
+        proof::review::PackageBuilder::default()
+            .from(id.id.to_owned())
+            .package(package_info(version, 3))
+            .diff_base(Some(package_info(base, 1)))
+            .review(Review::new_positive())
+            .build()
+            .unwrap()
+            .sign_by(id)
+            .unwrap()
+    };
+    let proofs = vec![
+        positive(&f.high, "1.0.0", 1),
+        diff_review(&f.low, "1.3.0", "1.0.0"),
+        // not trusted
+        positive(&f.stranger, "1.4.0", 4),
+        positive(&f.high, "2.0.0", 5),
+        positive(&f.high, "3.0.0-alpha.1", 6),
+        positive(&f.low, "3.0.0-beta.1", 7),
+    ];
+    f.import(proofs);
+    let trust_set = f.db.calculate_trust_set(f.root.as_ref(), &default());
+    let coverage = |from: &str, to: &str| {
+        f.db.get_review_coverage_range(
+            SOURCE,
+            NAME,
+            &Version::parse(from).unwrap(),
+            &Version::parse(to).unwrap(),
+            &trust_set,
+            TrustLevel::Low,
+        )
+        .into_iter()
+        .map(|c| {
+            (
+                c.version.to_string(),
+                c.position,
+                c.known,
+                c.trusted_reviewers,
+                c.diff_bases.iter().map(ToString::to_string).collect(),
+            )
+        })
+        .collect::<Vec<(String, CoveragePosition, bool, usize, Vec<String>)>>()
+    };
+    let entry = |version: &str, position, known, trusted_reviewers, diff_bases: &[&str]| {
+        (
+            version.to_owned(),
+            position,
+            known,
+            trusted_reviewers,
+            diff_bases.iter().map(|v| v.to_string()).collect(),
+        )
+    };
+    use CoveragePosition::*;
+
+    assert_eq!(
+        coverage("1.2.0", "1.5.1"),
+        vec![
+            entry("1.0.0", Before, true, 1, &[]),
+            entry("1.2.0", InRange, false, 0, &[]),
+            entry("1.3.0", InRange, true, 1, &["1.0.0"]),
+            entry("1.4.0", InRange, true, 0, &[]),
+            entry("1.5.1", InRange, false, 0, &[]),
+            entry("2.0.0", After, true, 1, &[]),
+        ]
+    );
+    // nothing reviewed within the range
+    assert_eq!(
+        coverage("1.6.0", "1.9.0"),
+        vec![
+            entry("1.3.0", Before, true, 1, &["1.0.0"]),
+            entry("1.6.0", InRange, false, 0, &[]),
+            entry("1.9.0", InRange, false, 0, &[]),
+            entry("2.0.0", After, true, 1, &[]),
+        ]
+    );
+    assert_eq!(coverage("1.5.1", "1.2.0"), vec![]);
+    // only pre-releases of the upper end were reviewed
+    assert_eq!(
+        coverage("2.1.0", "3.0.0"),
+        vec![
+            entry("2.0.0", Before, true, 1, &[]),
+            entry("2.1.0", InRange, false, 0, &[]),
+            entry("3.0.0-alpha.1", InRange, true, 1, &[]),
+            entry("3.0.0-beta.1", InRange, true, 1, &[]),
+            entry("3.0.0", InRange, false, 0, &[]),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn verify_package_open_issues() -> Result<()> {
+    use proof::review::package::Issue;
+
+    let mut f = Fixture::new()?;
+    let proofs = vec![
+        positive(&f.high, "1.0.0", 1),
+        build_review(
+            &f.low,
+            "0.9.0",
+            0,
+            Review::new_none(),
+            vec![Issue::new_with_severity("BAD".into(), Level::Medium)],
+        ),
+    ];
+    f.import(proofs);
+
+    assert_eq!(
+        f.verify("1.0.0", Some(1), &reqs()),
+        VerificationStatus::OpenIssues {
+            ids: vec!["BAD".to_owned()].into_iter().collect()
+        }
+    );
+    assert!(f
+        .verify(
+            "1.0.0",
+            Some(1),
+            &VerificationRequirements {
+                max_issue_severity: Level::Medium,
+                ..reqs()
+            }
+        )
+        .is_verified());
+    Ok(())
+}
+
+#[test]
+fn verify_packages_at_once() -> Result<()> {
+    use proof::review::package::Issue;
+
+    let mut f = Fixture::new()?;
+    let proofs = vec![
+        positive(&f.high, "1.0.0", 1),
+        positive(&f.high, "2.0.0", 2),
+        build_review(
+            &f.low,
+            "1.5.0",
+            0,
+            Review::new_none(),
+            vec![Issue::new_with_severity("BAD".into(), Level::Medium)],
+        ),
+        positive(&f.low, "3.0.0", 3),
+    ];
+    f.import(proofs);
+
+    let pkgs: Vec<_> = ["1.0.0", "2.0.0", "3.0.0", "4.0.0"]
+        .iter()
+        .map(|version| {
+            proof::PackageVersionId::new(
+                SOURCE.into(),
+                NAME.into(),
+                Version::parse(version).unwrap(),
+            )
+        })
+        .collect();
+    let trust_set = f.db.calculate_trust_set(f.root.as_ref(), &default());
+    let reqs = VerificationRequirements {
+        trust_level: TrustLevel::Medium,
+        ..reqs()
+    };
+
+    // the issue reported by `low` is not fixed in `2.0.0`, but it doesn't
+    // count, unless `low` is trusted enough; neither does its review of `3.0.0`
+    let issue = VerificationStatus::OpenIssues {
+        ids: vec!["BAD".to_owned()].into_iter().collect(),
+    };
+    assert_eq!(
+        f.db.verify_packages(&pkgs, &trust_set, &reqs),
+        vec![
+            VerificationStatus::Verified,
+            VerificationStatus::Verified,
+            VerificationStatus::NoReviews,
+            VerificationStatus::NoReviews,
+        ]
+    );
+    let reqs = VerificationRequirements {
+        trust_level: TrustLevel::Low,
+        ..reqs
+    };
+    assert_eq!(
+        f.db.verify_packages(&pkgs, &trust_set, &reqs),
+        vec![
+            VerificationStatus::Verified,
+            issue.clone(),
+            issue.clone(),
+            issue,
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn version_oracle_excludes_unavailable_versions() -> Result<()> {
+    use crev_wot::{VersionOracle, VersionStatus};
+    use std::collections::HashMap;
+
+    struct MockOracle(HashMap<Version, VersionStatus>);
+
+    impl VersionOracle for MockOracle {
+        fn status(&self, source: &str, name: &str, version: &Version) -> VersionStatus {
+            if source != SOURCE || name != NAME {
+                return VersionStatus::Unknown;
+            }
+            self.0
+                .get(version)
+                .copied()
+                .unwrap_or(VersionStatus::NotFound)
+        }
+    }
+
+    let mut f = Fixture::new()?;
+    let proofs = vec![
+        positive(&f.high, "1.0.0", 1),
+        positive(&f.high, "1.0.1", 2),
+        positive(&f.high, "1.0.2", 3),
+    ];
+    f.import(proofs);
+    let pkgs: Vec<_> = ["1.0.0", "1.0.1", "1.0.2"]
+        .iter()
+        .map(|version| {
+            proof::PackageVersionId::new(
+                SOURCE.into(),
+                NAME.into(),
+                Version::parse(version).unwrap(),
+            )
+        })
+        .collect();
+    let trust_set = f.db.calculate_trust_set(f.root.as_ref(), &default());
+    let existing_versions = |db: &ProofDB| -> Vec<String> {
+        let mut versions: Vec<_> = db
+            .get_pkg_reviews_for_name_existing(SOURCE, NAME)
+            .map(|review| review.package.id.version.to_string())
+            .collect();
+        versions.sort();
+        versions
+    };
+
+    // nothing is known by default, so nothing is excluded
+    assert_eq!(existing_versions(&f.db), vec!["1.0.0", "1.0.1", "1.0.2"]);
+    let annotated = f.db.verify_packages_annotated(&pkgs, &trust_set, &reqs());
+    assert!(annotated
+        .iter()
+        .all(
+            |verification| verification.version_status == VersionStatus::Unknown
+                && verification.is_verified()
+        ));
+
+    // `1.0.2` is not in the map, so it was never published
+    f.db.set_version_oracle(Box::new(MockOracle(
+        vec![
+            (Version::parse("1.0.0").unwrap(), VersionStatus::Exists),
+            (Version::parse("1.0.1").unwrap(), VersionStatus::Yanked),
+        ]
+        .into_iter()
+        .collect(),
+    )));
+    assert_eq!(existing_versions(&f.db), vec!["1.0.0"]);
+    assert_eq!(f.db.get_pkg_reviews_for_name(SOURCE, NAME).count(), 3);
+
+    let annotated = f.db.verify_packages_annotated(&pkgs, &trust_set, &reqs());
+    assert_eq!(
+        annotated
+            .iter()
+            .map(|verification| verification.version_status)
+            .collect::<Vec<_>>(),
+        vec![
+            VersionStatus::Exists,
+            VersionStatus::Yanked,
+            VersionStatus::NotFound
+        ]
+    );
+    // the reviews themselves still verify the versions
+    assert!(annotated
+        .iter()
+        .all(|verification| verification.status == VerificationStatus::Verified));
+    assert_eq!(
+        annotated
+            .iter()
+            .map(|verification| verification.is_verified())
+            .collect::<Vec<_>>(),
+        vec![true, false, false]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn verification_context_computes_each_package_once() -> Result<()> {
+    use crev_wot::context::VerificationContext;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let mut f = Fixture::new()?;
+    let proofs = vec![
+        positive(&f.high, "1.0.0", 1),
+        positive(&f.low, "1.0.0", 1),
+        positive(&f.stranger, "1.0.0", 1),
+        positive(&f.low, "2.0.0", 2),
+    ];
+    f.import(proofs);
+    let pkg = |version: &str| {
+        proof::PackageVersionId::new(SOURCE.into(), NAME.into(), Version::parse(version).unwrap())
+    };
+    let reqs = VerificationRequirements {
+        trust_level: TrustLevel::Medium,
+        ..reqs()
+    };
+
+    let computed = AtomicUsize::new(0);
+    let context = VerificationContext::new(&f.db, f.root.as_ref(), default(), reqs.clone(), 2)
+        .on_compute(|_| {
+            computed.fetch_add(1, Ordering::SeqCst);
+        });
+    for _ in 0..3 {
+        let status = context.status_for(&pkg("1.0.0"));
+        assert_eq!(status.verification, VerificationStatus::Verified);
+        assert_eq!((status.trusted_reviews, status.total_reviews), (1, 3));
+        assert!(status.issues.is_empty());
+    }
+    assert_eq!(computed.load(Ordering::SeqCst), 1);
+
+    let status = context.status_for(&pkg("2.0.0"));
+    assert_eq!(status.verification, VerificationStatus::NoReviews);
+    assert_eq!((status.trusted_reviews, status.total_reviews), (0, 1));
+    assert_eq!(
+        status.verification,
+        f.db.verify_package(
+            SOURCE,
+            NAME,
+            &pkg("2.0.0").version,
+            None,
+            context.trust_set(),
+            &reqs
+        )
+    );
+    assert_eq!(computed.load(Ordering::SeqCst), 2);
+
+    // the least recently used one is evicted
+    context.status_for(&pkg("1.0.0"));
+    context.status_for(&pkg("3.0.0"));
+    assert_eq!(computed.load(Ordering::SeqCst), 3);
+    context.status_for(&pkg("1.0.0"));
+    assert_eq!(computed.load(Ordering::SeqCst), 3);
+    context.status_for(&pkg("2.0.0"));
+    assert_eq!(computed.load(Ordering::SeqCst), 4);
+
+    Ok(())
+}
+
+#[test]
+fn verify_package_ignore_list() -> Result<()> {
+    use proof::review::package::Issue;
+
+    let mut f = Fixture::new()?;
+    let proofs = vec![
+        positive(&f.high, "1.0.0", 1),
+        build_review(&f.low, "1.0.0", 1, Review::new_negative(), vec![]),
+        build_review(
+            &f.low,
+            "0.9.0",
+            0,
+            Review::new_none(),
+            vec![Issue::new_with_severity("STYLE".into(), Level::High)],
+        ),
+        positive(&f.low, "2.0.0", 2),
+    ];
+    f.import(proofs);
+    assert!(matches!(
+        f.verify("1.0.0", Some(1), &reqs()),
+        VerificationStatus::Distrusted { .. }
+    ));
+
+    let ignore_list: ReviewIgnoreList = vec![(f.low.id.id.clone(), SOURCE.into(), NAME.into())]
+        .into_iter()
+        .collect();
+    let reqs_ignoring = VerificationRequirements {
+        ignore_list: ignore_list.clone(),
+        ..reqs()
+    };
+    // neither the negative review, nor the reported issue count anymore
+    assert!(f.verify("1.0.0", Some(1), &reqs_ignoring).is_verified());
+    assert!(f
+        .db
+        .get_open_issues_for_version_ignoring(
+            SOURCE,
+            NAME,
+            &Version::parse("1.0.0").unwrap(),
+            &f.db.calculate_trust_set(f.root.as_ref(), &default()),
+            TrustLevel::Low,
+            &ignore_list,
+        )
+        .is_empty());
+    let trust_set = f.db.calculate_trust_set(f.root.as_ref(), &default());
+    let with_issues = |ignore_list: &ReviewIgnoreList| {
+        f.db.get_pkg_reviews_with_issues_for_ignoring(
+            SOURCE,
+            Some(NAME),
+            None,
+            &trust_set,
+            TrustLevel::Low,
+            ignore_list,
+        )
+        .count()
+    };
+    assert_eq!(with_issues(&ReviewIgnoreList::new()), 1);
+    assert_eq!(with_issues(&ignore_list), 0);
+    assert_eq!(
+        f.db.get_pkg_reviews_with_issues_or_flags_for_source_ignoring(
+            SOURCE,
+            &trust_set,
+            TrustLevel::Low,
+            &default(),
+            &ignore_list,
+        )
+        .count(),
+        0
+    );
+
+    // the same author's reviews of other packages still count
+    let other = positive(&f.low, "1.0.0", 3);
+    let other = {
+        let mut content: proof::review::Package = other.parse_content()?;
+        content.package.id.id.name = "other".into();
+        content.sign_by(&f.low)?
+    };
+    f.import(vec![other]);
+    let trust_set = f.db.calculate_trust_set(f.root.as_ref(), &default());
+    assert!(f
+        .db
+        .verify_package(
+            SOURCE,
+            "other",
+            &Version::parse("1.0.0").unwrap(),
+            None,
+            &trust_set,
+            &reqs_ignoring,
+        )
+        .is_verified());
+
+    Ok(())
+}
+
+#[test]
+fn selected_proofs_reproduce_verification() -> Result<()> {
+    use proof::review::package::Issue;
+
+    let root = UnlockedId::generate_for_git_url("https://root");
+    let a = UnlockedId::generate_for_git_url("https://a");
+    let b = UnlockedId::generate_for_git_url("https://b");
+    let c = UnlockedId::generate_for_git_url("https://c");
+    let d = UnlockedId::generate_for_git_url("https://d");
+    let stranger = UnlockedId::generate_for_git_url("https://stranger");
+
+    let needed = vec![
+        root.create_signed_trust_proof(vec![a.as_public_id()], TrustLevel::High)?,
+        a.create_signed_trust_proof(vec![b.as_public_id()], TrustLevel::Medium)?,
+        root.create_signed_trust_proof(vec![c.as_public_id()], TrustLevel::Low)?,
+        positive(&b, "1.1.0", 1),
+        build_review(
+            &c,
+            "1.0.0",
+            0,
+            Review::new_none(),
+            vec![Issue::new_with_severity("BAD".into(), Level::Medium)],
+        ),
+    ];
+    let not_needed = vec![
+        root.create_signed_trust_proof(vec![d.as_public_id()], TrustLevel::High)?,
+        c.create_signed_trust_proof(vec![stranger.as_public_id()], TrustLevel::None)?,
+        positive(&d, "2.0.0", 2),
+        positive(&d, "1.0.0", 0),
+        positive(&stranger, "1.1.0", 1),
+    ];
+    let mut db = ProofDB::new();
+    db.import_from_iter(
+        needed
+            .iter()
+            .chain(not_needed.iter())
+            .cloned()
+            .map(|x| (x, FetchSource::LocalUser)),
+    );
+
+    let version = Version::parse("1.1.0").unwrap();
+    let selected = db.select_proofs_for(
+        root.as_ref(),
+        &default(),
+        &[(SOURCE.into(), NAME.into(), version.clone())],
+    );
+    let mut expected: Vec<_> = needed.iter().map(|p| p.signature().to_owned()).collect();
+    expected.sort();
+    assert_eq!(selected, expected);
+
+    let mut subset_db = ProofDB::new();
+    subset_db.import_from_iter(
+        needed
+            .iter()
+            .chain(not_needed.iter())
+            .filter(|p| selected.iter().any(|s| s == p.signature()))
+            .cloned()
+            .map(|x| (x, FetchSource::LocalUser)),
+    );
+
+    let verify = |db: &ProofDB, reqs: &VerificationRequirements| {
+        let trust_set = db.calculate_trust_set(root.as_ref(), &default());
+        db.verify_package(
+            SOURCE,
+            NAME,
+            &version,
+            Some(&Digest::from_vec(vec![1; 32])),
+            &trust_set,
+            reqs,
+        )
+    };
+    let tolerant = VerificationRequirements {
+        max_issue_severity: Level::Medium,
+        ..reqs()
+    };
+    assert!(matches!(
+        verify(&db, &reqs()),
+        VerificationStatus::OpenIssues { .. }
+    ));
+    assert_eq!(verify(&subset_db, &reqs()), verify(&db, &reqs()));
+    assert!(verify(&db, &tolerant).is_verified());
+    assert_eq!(verify(&subset_db, &tolerant), verify(&db, &tolerant));
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+//! `ProofDB::verify_digests` against querying digests one by one
+//!
+//! Run with `cargo bench -p crev-wot --bench digests`
+use crev_data::{proof, proof::ContentExt, Digest, TrustLevel, UnlockedId};
+use crev_wot::{FetchSource, ProofDB, TrustDistanceParams};
+use criterion::{criterion_group, criterion_main, Criterion};
+use semver::Version;
+
+const AUTHORS: usize = 20;
+const PACKAGES: usize = 500;
+
+fn verify_digests(c: &mut Criterion) {
+    let root = UnlockedId::generate_for_git_url("https://example.com/root");
+    let ids: Vec<_> = (0..AUTHORS)
+        .map(|i| UnlockedId::generate_for_git_url(&format!("https://example.com/{}", i)))
+        .collect();
+    let mut proofs = vec![root
+        .create_signed_trust_proof(ids.iter().map(|id| id.as_public_id()), TrustLevel::Medium)
+        .unwrap()];
+    for (i, id) in ids.iter().enumerate() {
+        for package in (i % 2..PACKAGES).step_by(2) {
+            let package_info = proof::PackageInfo {
+                id: proof::PackageVersionId::new(
+                    "https://crates.io".into(),
+                    format!("package-{}", package),
+                    Version::new(1, 0, 0),
+                ),
+                digest: digest(package).into_vec(),
+                digest_type: proof::default_digest_type(),
+                revision: "".into(),
+                revision_type: proof::default_revision_type(),
+            };
+            let review = proof::review::PackageBuilder::default()
+                .from(id.id.to_owned())
+                .package(package_info)
+                .build()
+                .unwrap();
+            proofs.push(review.sign_by(id).unwrap());
+        }
+    }
+    let mut db = ProofDB::new();
+    db.import_from_iter(proofs.into_iter().map(|x| (x, FetchSource::LocalUser)));
+    let trust_set = db.calculate_trust_set(&root.id.id, &TrustDistanceParams::default());
+    let digests: Vec<_> = (0..PACKAGES).map(digest).collect();
+
+    let verified = db.verify_digests(&digests, &trust_set, TrustLevel::Low);
+    assert!(verified.values().all(|v| v.trusted_count() == AUTHORS / 2));
+
+    let mut group = c.benchmark_group("verify 500 digests");
+    group.bench_function("one by one", |b| {
+        b.iter(|| {
+            digests
+                .iter()
+                .filter(|digest| {
+                    db.best_review_for_digest(digest, &trust_set, TrustLevel::Low)
+                        .is_some()
+                        && db.trusted_review_count_for_digest(digest, &trust_set, TrustLevel::Low)
+                            > 0
+                })
+                .count()
+        })
+    });
+    group.bench_function("verify_digests", |b| {
+        b.iter(|| db.verify_digests(&digests, &trust_set, TrustLevel::Low))
+    });
+    group.finish();
+}
+
+fn digest(package: usize) -> Digest {
+    let mut digest = vec![0; 32];
+    digest[..8].copy_from_slice(&(package as u64).to_le_bytes());
+    Digest::from_vec(digest)
+}
+
+criterion_group!(benches, verify_digests);
+criterion_main!(benches);
@@ -0,0 +1,99 @@
+//! The phases of a staged import, against `ProofDB::import_from_iter`
+//!
+//! Run with `cargo bench -p crev-wot --bench import`
+use crev_data::{proof, proof::ContentExt, UnlockedId};
+use crev_wot::{FetchSource, ProofDB};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use semver::Version;
+
+const AUTHORS: usize = 100;
+const PACKAGES: usize = 100;
+
+fn proofs() -> Vec<(proof::Proof, FetchSource)> {
+    let ids: Vec<_> = (0..AUTHORS)
+        .map(|i| UnlockedId::generate_for_git_url(&format!("https://example.com/{}", i)))
+        .collect();
+    let mut proofs = vec![];
+    for (i, id) in ids.iter().enumerate() {
+        let trusted = ids.iter().skip(i + 1).take(3).map(|id| id.as_public_id());
+        proofs.push(
+            id.create_signed_trust_proof(trusted, crev_data::TrustLevel::Medium)
+                .unwrap(),
+        );
+        for package in 0..PACKAGES - 1 {
+            let package_info = proof::PackageInfo {
+                id: proof::PackageVersionId::new(
+                    "https://crates.io".into(),
+                    format!("package-{}", package),
+                    Version::new(1, (i % 4) as u64, 0),
+                ),
+                digest: vec![(package % 256) as u8; 32],
+                digest_type: proof::default_digest_type(),
+                revision: "".into(),
+                revision_type: proof::default_revision_type(),
+            };
+            let review = proof::review::PackageBuilder::default()
+                .from(id.id.to_owned())
+                .package(package_info)
+                .build()
+                .unwrap();
+            proofs.push(review.sign_by(id).unwrap());
+        }
+    }
+    proofs
+        .into_iter()
+        .map(|proof| (proof, FetchSource::LocalUser))
+        .collect()
+}
+
+fn staged_import(c: &mut Criterion) {
+    let proofs = proofs();
+    assert_eq!(proofs.len(), AUTHORS * PACKAGES);
+
+    let mut imported = ProofDB::new();
+    imported.import_from_iter(proofs.clone().into_iter());
+    let mut staged = ProofDB::new();
+    staged.apply(staged.stage_import(proofs.clone().into_iter()));
+    assert_eq!(
+        staged.unique_package_review_proof_count(),
+        imported.unique_package_review_proof_count()
+    );
+
+    let mut group = c.benchmark_group("import 10k proofs");
+    group.sample_size(10);
+    group.bench_function("import_from_iter", |b| {
+        b.iter_batched(
+            || proofs.clone(),
+            |proofs| ProofDB::new().import_from_iter(proofs.into_iter()),
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("stage_import", |b| {
+        let db = ProofDB::new();
+        b.iter_batched(
+            || proofs.clone(),
+            |proofs| db.stage_import(proofs.into_iter()),
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("apply", |b| {
+        let batch_db = ProofDB::new();
+        b.iter_batched(
+            || {
+                (
+                    ProofDB::new(),
+                    batch_db.stage_import(proofs.clone().into_iter()),
+                )
+            },
+            |(mut db, batch)| {
+                db.apply(batch);
+                db
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, staged_import);
+criterion_main!(benches);
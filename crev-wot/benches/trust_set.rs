@@ -0,0 +1,52 @@
+//! The WoT calculation, on synthetic WoTs of growing size
+//!
+//! Run with `cargo bench -p crev-wot --features testing --bench trust_set`
+use crev_wot::{
+    testing::{synthetic_wot, SyntheticWotParams},
+    RecordingObserver, TrustDistanceParams,
+};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+fn trust_set(c: &mut Criterion) {
+    let params = TrustDistanceParams::default();
+    let mut group = c.benchmark_group("calculate_trust_set");
+    group.sample_size(10);
+    for id_count in &[100, 1_000, 10_000] {
+        let wot = synthetic_wot(&SyntheticWotParams {
+            id_count: *id_count,
+            ..Default::default()
+        });
+        assert!(wot
+            .db
+            .calculate_trust_set(&wot.root, &params)
+            .trusted_ids()
+            .next()
+            .is_some());
+
+        group.bench_with_input(BenchmarkId::new("plain", id_count), &wot, |b, wot| {
+            b.iter(|| wot.db.calculate_trust_set(&wot.root, &params))
+        });
+        // the no-op observer must not cost anything over the plain calculation
+        group.bench_with_input(
+            BenchmarkId::new("no-op observer", id_count),
+            &wot,
+            |b, wot| b.iter(|| wot.db.calculate_trust_set_with(&wot.root, &params, &mut ())),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("recording observer", id_count),
+            &wot,
+            |b, wot| {
+                b.iter(|| {
+                    let mut observer = RecordingObserver::new();
+                    wot.db
+                        .calculate_trust_set_with(&wot.root, &params, &mut observer);
+                    observer
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, trust_set);
+criterion_main!(benches);
@@ -0,0 +1,143 @@
+//! Local nicknames of Ids, for reading and typing them more easily
+//!
+//! Ids are long hashes, and URLs of them are not always known (or verified),
+//! so users can give them names of their own. `IdAliases` only holds the names,
+//! and is meant to be stored by the callers; resolving them, and Id prefixes,
+//! uses the Ids known to a `ProofDB`.
+use crate::ProofDB;
+use crev_data::Id;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Number of characters of an Id `IdAliases::display_name` shows, when there's nothing better
+const DISPLAY_ID_PREFIX_LEN: usize = 8;
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum AliasLookupError {
+    #[error("No alias or Id matching '{}'", _0)]
+    Unknown(Box<str>),
+
+    #[error("Ambiguous Id prefix: {} matching Ids", _0.len())]
+    AmbiguousIdPrefix(Vec<Id>),
+
+    #[error("Alias '{}' is already used for Id {}", alias, id)]
+    AliasTaken { alias: Box<str>, id: Id },
+}
+
+/// Nicknames of Ids, see `ProofDB::all_known_ids`
+///
+/// An Id can have many aliases, but every alias is for exactly one Id.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct IdAliases {
+    ids_by_alias: BTreeMap<String, Id>,
+}
+
+impl IdAliases {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Use `alias` for `id`
+    ///
+    /// Setting the same alias for the same Id again does nothing, but
+    /// an alias already used for another Id has to be removed first.
+    pub fn set_alias(&mut self, alias: String, id: Id) -> Result<(), AliasLookupError> {
+        match self.ids_by_alias.get(&alias) {
+            Some(existing) if *existing != id => Err(AliasLookupError::AliasTaken {
+                alias: alias.into(),
+                id: existing.clone(),
+            }),
+            Some(_) => Ok(()),
+            None => {
+                self.ids_by_alias.insert(alias, id);
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns the Id the alias was for
+    pub fn remove_alias(&mut self, alias: &str) -> Option<Id> {
+        self.ids_by_alias.remove(alias)
+    }
+
+    pub fn get(&self, alias: &str) -> Option<&Id> {
+        self.ids_by_alias.get(alias)
+    }
+
+    /// All the aliases of `id`, sorted
+    pub fn aliases_of<'a>(&'a self, id: &'a Id) -> impl Iterator<Item = &'a str> + 'a {
+        self.ids_by_alias
+            .iter()
+            .filter(move |(_, alias_id)| *alias_id == id)
+            .map(|(alias, _)| alias.as_str())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Id)> {
+        self.ids_by_alias
+            .iter()
+            .map(|(alias, id)| (alias.as_str(), id))
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids_by_alias.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids_by_alias.is_empty()
+    }
+
+    /// Id with the alias `name_or_id_prefix`, or the only Id starting with it
+    ///
+    /// Aliases take precedence over prefixes. Prefixes are matched against
+    /// the Ids as crev displays them (base64 encoded), both the ones `db`
+    /// knows about and the ones with aliases. If the prefix matches more than
+    /// one Id, `AliasLookupError::AmbiguousIdPrefix` listing all the candidates
+    /// is returned.
+    pub fn resolve(&self, db: &ProofDB, name_or_id_prefix: &str) -> Result<Id, AliasLookupError> {
+        if let Some(id) = self.ids_by_alias.get(name_or_id_prefix) {
+            return Ok(id.clone());
+        }
+        if name_or_id_prefix.is_empty() {
+            return Err(AliasLookupError::Unknown(name_or_id_prefix.into()));
+        }
+
+        let candidates: BTreeSet<Id> = db
+            .all_known_ids()
+            .into_iter()
+            .chain(self.ids_by_alias.values().cloned())
+            .filter(|id| id.to_string().starts_with(name_or_id_prefix))
+            .collect();
+
+        let mut candidates = candidates.into_iter();
+        match (candidates.next(), candidates.next()) {
+            (Some(id), None) => Ok(id),
+            (None, _) => Err(AliasLookupError::Unknown(name_or_id_prefix.into())),
+            (Some(first), Some(second)) => Err(AliasLookupError::AmbiguousIdPrefix(
+                vec![first, second].into_iter().chain(candidates).collect(),
+            )),
+        }
+    }
+
+    /// What to show instead of the whole `id`
+    ///
+    /// The first alias of the Id, or the host and path of its verified URL,
+    /// or just the beginning of the Id.
+    pub fn display_name(&self, db: &ProofDB, id: &Id) -> String {
+        if let Some(alias) = self.aliases_of(id).next() {
+            return alias.to_owned();
+        }
+        if let Some(url) = db.lookup_url(id).verified() {
+            let url = url.url.trim().trim_end_matches('/');
+            return match url.find("://") {
+                Some(pos) => url[pos + 3..].to_owned(),
+                None => url.to_owned(),
+            };
+        }
+        let id = id.to_string();
+        match id.char_indices().nth(DISPLAY_ID_PREFIX_LEN) {
+            Some((end, _)) => format!("{}...", &id[..end]),
+            None => id,
+        }
+    }
+}
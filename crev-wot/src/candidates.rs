@@ -0,0 +1,95 @@
+//! Suggesting Ids to trust, to get more of the needed packages reviewed
+//!
+//! When the trust set covers only a small part of the dependencies, Ids
+//! that already reviewed many of the others are the best ones to look at.
+use crate::{OwnedUrlOfId, ProofDB, TrustDistanceParams};
+use crev_data::{
+    proof::{self, trust::TrustLevel, CommonOps},
+    Id,
+};
+use std::{
+    cmp,
+    collections::{BTreeMap, BTreeSet},
+};
+
+/// An Id worth trusting, see `ProofDB::suggest_trust_candidates`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrustCandidate {
+    pub id: Id,
+    pub url: Option<OwnedUrlOfId>,
+    /// Number of the wanted package versions the Id reviewed positively
+    pub reviewed: usize,
+    /// Number of those not reviewed positively by anyone trusted yet
+    pub newly_covered: usize,
+    /// Trusted Ids already reporting trust (other than distrust) for the Id
+    pub known_by: BTreeSet<Id>,
+}
+
+impl ProofDB {
+    /// Ids not trusted by `root`, that reviewed the most of the `wanted` package versions
+    ///
+    /// A package version is covered, if someone trusted at least at `Low`
+    /// reviewed it positively. Candidates are Ids trusted less than that, who
+    /// reviewed any of the `wanted` versions positively, and are not distrusted.
+    /// They are ordered by the number of versions they would newly cover if
+    /// they were trusted at `Low`, then by Id; at most `limit` are returned.
+    pub fn suggest_trust_candidates(
+        &self,
+        root: &Id,
+        params: &TrustDistanceParams,
+        wanted: &[proof::PackageVersionId],
+        limit: usize,
+    ) -> Vec<TrustCandidate> {
+        let trust_set = self.calculate_trust_set(root, params);
+
+        // (reviewed, newly covered) by every candidate
+        let mut counts: BTreeMap<&Id, (usize, usize)> = BTreeMap::new();
+        let wanted: BTreeSet<_> = wanted.iter().collect();
+        for pkg_version_id in wanted {
+            let trust_set = trust_set.for_source(&pkg_version_id.id.source);
+            let authors: BTreeSet<&Id> = self
+                .get_pkg_endorsements_for_version(
+                    &pkg_version_id.id.source,
+                    &pkg_version_id.id.name,
+                    &pkg_version_id.version,
+                )
+                .map(|review| &review.from().id)
+                .filter(|id| !trust_set.is_distrusted(id))
+                .collect();
+            let (trusted, untrusted): (Vec<&Id>, Vec<&Id>) = authors
+                .into_iter()
+                .partition(|id| trust_set.get_effective_trust_level(id) >= TrustLevel::Low);
+            for id in untrusted {
+                let (reviewed, newly_covered) = counts.entry(id).or_default();
+                *reviewed += 1;
+                if trusted.is_empty() {
+                    *newly_covered += 1;
+                }
+            }
+        }
+
+        let mut known_by: BTreeMap<&Id, BTreeSet<Id>> = BTreeMap::new();
+        for from in trust_set.trusted_ids() {
+            for (to, edge) in self.trust_id_to_id.get(from).into_iter().flatten() {
+                if edge.value.level != TrustLevel::Distrust && counts.contains_key(to) {
+                    known_by.entry(to).or_default().insert(from.clone());
+                }
+            }
+        }
+
+        let mut candidates: Vec<_> = counts
+            .into_iter()
+            .map(|(id, (reviewed, newly_covered))| TrustCandidate {
+                url: self.lookup_url(id).into_owned(),
+                reviewed,
+                newly_covered,
+                known_by: known_by.remove(id).unwrap_or_default(),
+                id: id.clone(),
+            })
+            .collect();
+        // already ordered by Id
+        candidates.sort_by_key(|candidate| cmp::Reverse(candidate.newly_covered));
+        candidates.truncate(limit);
+        candidates
+    }
+}
@@ -0,0 +1,205 @@
+//! Verifying many packages against the same trust set
+//!
+//! Verifying e.g. all the dependencies of a project queries the same
+//! `ProofDB` with the same `TrustSet` over and over. `VerificationContext`
+//! calculates the trust set once, and remembers the results for recently
+//! verified packages, so that every package is aggregated only once.
+use crate::{
+    canonicalize_package_name,
+    verification::{VerificationRequirements, VerificationStatus},
+    FlagsSummary, IssueDetails, ProofDB, TrustDistanceParams, TrustSet, VersionMatchPolicy,
+};
+use crev_data::{
+    proof::{self, CommonOps},
+    Id,
+};
+use semver::Version;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync,
+};
+
+/// Everything known about a package version, see `VerificationContext::status_for`
+#[derive(Debug, Clone)]
+pub struct PackageStatus {
+    pub verification: VerificationStatus,
+    /// Open issues, see `ProofDB::get_open_issues_for_version`
+    pub issues: BTreeMap<String, IssueDetails>,
+    pub flags: FlagsSummary,
+    /// Number of reviews of the version by authors trusted enough
+    pub trusted_reviews: usize,
+    /// Number of reviews of the version by anyone
+    pub total_reviews: usize,
+}
+
+/// Source, canonical name and version of a package
+type PackageKey = (String, String, Version);
+
+/// See `VerificationContext::on_compute`
+type ComputeHook<'a> = Box<dyn Fn(&proof::PackageVersionId) + Send + Sync + 'a>;
+
+/// Least recently used entries are evicted once `capacity` is reached
+struct StatusCache {
+    capacity: usize,
+    /// Incremented on every use of an entry
+    tick: u64,
+    entries: HashMap<PackageKey, (u64, PackageStatus)>,
+    /// Keys of `entries` by their last use
+    by_use: BTreeMap<u64, PackageKey>,
+}
+
+impl StatusCache {
+    fn get(&mut self, key: &PackageKey) -> Option<PackageStatus> {
+        let (used, status) = self.entries.get_mut(key)?;
+        self.tick += 1;
+        let key = self.by_use.remove(used).expect("in sync with entries");
+        *used = self.tick;
+        self.by_use.insert(self.tick, key);
+        Some(status.clone())
+    }
+
+    fn insert(&mut self, key: PackageKey, status: PackageStatus) {
+        self.tick += 1;
+        if let Some((used, _)) = self.entries.remove(&key) {
+            self.by_use.remove(&used);
+        }
+        while self.capacity <= self.entries.len() {
+            let used = *self.by_use.keys().next().expect("in sync with entries");
+            let oldest = self.by_use.remove(&used).expect("exists");
+            self.entries.remove(&oldest);
+        }
+        self.by_use.insert(self.tick, key.clone());
+        self.entries.insert(key, (self.tick, status));
+    }
+}
+
+/// `ProofDB` with a trust set, to verify many packages with
+///
+/// Results of at most `capacity` (passed to `new`) most recently
+/// verified packages are kept. New proofs are not taken into account:
+/// create a new context after importing any.
+pub struct VerificationContext<'a> {
+    db: &'a ProofDB,
+    trust_set: sync::Arc<TrustSet>,
+    params: TrustDistanceParams,
+    reqs: VerificationRequirements,
+    cache: sync::Mutex<StatusCache>,
+    on_compute: Option<ComputeHook<'a>>,
+}
+
+impl<'a> VerificationContext<'a> {
+    /// Context for verifying packages with the trust set of `root`
+    pub fn new(
+        db: &'a ProofDB,
+        root: &Id,
+        params: TrustDistanceParams,
+        reqs: VerificationRequirements,
+        capacity: usize,
+    ) -> Self {
+        let trust_set = db.calculate_trust_set_cached(root, &params);
+        Self {
+            db,
+            trust_set,
+            params,
+            reqs,
+            cache: sync::Mutex::new(StatusCache {
+                capacity: capacity.max(1),
+                tick: 0,
+                entries: HashMap::new(),
+                by_use: BTreeMap::new(),
+            }),
+            on_compute: None,
+        }
+    }
+
+    /// Call `f` for every package status that is calculated, and not taken from the cache
+    pub fn on_compute(mut self, f: impl Fn(&proof::PackageVersionId) + Send + Sync + 'a) -> Self {
+        self.on_compute = Some(Box::new(f));
+        self
+    }
+
+    pub fn db(&self) -> &'a ProofDB {
+        self.db
+    }
+
+    pub fn trust_set(&self) -> &TrustSet {
+        &self.trust_set
+    }
+
+    pub fn params(&self) -> &TrustDistanceParams {
+        &self.params
+    }
+
+    pub fn requirements(&self) -> &VerificationRequirements {
+        &self.reqs
+    }
+
+    /// Status of a package version, calculated only if not cached
+    ///
+    /// The digest of the package is not checked, just like in `ProofDB::verify_packages`.
+    pub fn status_for(&self, pkg: &proof::PackageVersionId) -> PackageStatus {
+        let key = (
+            pkg.id.source.clone(),
+            canonicalize_package_name(&pkg.id.source, &pkg.id.name),
+            pkg.version.clone(),
+        );
+        if let Some(status) = self.lock_cache().get(&key) {
+            return status;
+        }
+
+        if let Some(on_compute) = &self.on_compute {
+            on_compute(pkg);
+        }
+        let status = self.compute(pkg);
+        self.lock_cache().insert(key, status.clone());
+        status
+    }
+
+    fn lock_cache(&self) -> sync::MutexGuard<'_, StatusCache> {
+        // the cache is always consistent between the calls, even if one panicked
+        self.cache
+            .lock()
+            .unwrap_or_else(sync::PoisonError::into_inner)
+    }
+
+    fn compute(&self, pkg: &proof::PackageVersionId) -> PackageStatus {
+        let (source, name, version) = (&pkg.id.source, &pkg.id.name, &pkg.version);
+        let trust_set = self.trust_set.for_source(source);
+        let mut trusted_reviews = 0;
+        let mut total_reviews = 0;
+        for review in self.db.get_pkg_reviews_for_version(source, name, version) {
+            total_reviews += 1;
+            let from = &review.from().id;
+            if !trust_set.is_distrusted(from)
+                && self.reqs.trust_level <= trust_set.get_effective_trust_level(from)
+            {
+                trusted_reviews += 1;
+            }
+        }
+        PackageStatus {
+            verification: self.db.verify_package(
+                source,
+                name,
+                version,
+                None,
+                &self.trust_set,
+                &self.reqs,
+            ),
+            issues: self.db.get_open_issues_for_version_with(
+                source,
+                name,
+                version,
+                &self.trust_set,
+                self.reqs.trust_level,
+                &self.reqs.ignore_list,
+                &self.reqs.review_filter,
+                VersionMatchPolicy::default(),
+            ),
+            flags: self
+                .db
+                .get_pkg_flags_summary(&pkg.id, &self.trust_set, self.reqs.trust_level),
+            trusted_reviews,
+            total_reviews,
+        }
+    }
+}
@@ -0,0 +1,99 @@
+//! Writing a selection of the proofs, e.g. to be imported by another machine
+//!
+//! Proofs can't be re-created from the indexed data, since their signatures
+//! cover the exact text of them, so only the proofs kept with
+//! `ProofDB::set_keep_signed_proofs` can be exported.
+use crate::{canonical_package_id, ProofDB, ProofKind, Result};
+use chrono::{offset::Utc, DateTime};
+use crev_data::{
+    proof::{self, review, CommonOps},
+    Id,
+};
+use std::{collections::BTreeSet, io};
+
+/// Which proofs `ProofDB::export_proofs` writes
+///
+/// Empty sets don't restrict anything, so the default filter accepts all the proofs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExportFilter {
+    /// Only proofs by these Ids
+    pub authors: BTreeSet<Id>,
+    /// Only reviews of these packages; proofs of other kinds are not affected
+    pub packages: BTreeSet<proof::PackageId>,
+    pub kinds: BTreeSet<ProofKind>,
+    /// Only proofs created at this date, or later
+    pub since: Option<DateTime<Utc>>,
+    /// Only proofs created before this date
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl ProofDB {
+    /// Package reviewed in a kept proof, if it's a review
+    fn reviewed_package_of(&self, proof: &proof::Proof) -> Option<proof::PackageId> {
+        match ProofKind::of(proof.kind()) {
+            ProofKind::PackageReview => self
+                .package_review_by_signature
+                .get(proof.signature())
+                .map(|review| review.package.id.id.clone()),
+            ProofKind::CodeReview => proof
+                .parse_content::<review::Code>()
+                .ok()
+                .map(|review| review.package.id.id),
+            ProofKind::Trust | ProofKind::Other => None,
+        }
+    }
+
+    /// Write the kept proofs the `filter` accepts, returning how many
+    ///
+    /// The output is in the usual format of proof files, so it can be parsed with
+    /// `proof::Proof::parse_from` and imported into another `ProofDB`. Proofs are
+    /// ordered by date, then by signature, so importing them one by one gives the same
+    /// result as `import_par_from_vec` of them would. Only proofs imported after
+    /// `set_keep_signed_proofs` can be exported; superseded ones (e.g. older reviews
+    /// of a package version by the same author) are included too.
+    pub fn export_proofs(&self, filter: &ExportFilter, mut w: impl io::Write) -> Result<usize> {
+        let packages: BTreeSet<_> = filter
+            .packages
+            .iter()
+            .map(|pkg_id| canonical_package_id(&pkg_id.source, &pkg_id.name))
+            .collect();
+
+        let mut proofs: Vec<_> = self
+            .signed_proofs
+            .values()
+            .filter(|proof| filter.authors.is_empty() || filter.authors.contains(proof.author_id()))
+            .filter(|proof| {
+                filter.kinds.is_empty() || filter.kinds.contains(&ProofKind::of(proof.kind()))
+            })
+            .filter(|proof| match filter.since {
+                Some(since) => since <= proof.date_utc(),
+                None => true,
+            })
+            .filter(|proof| match filter.until {
+                Some(until) => proof.date_utc() < until,
+                None => true,
+            })
+            .filter(|proof| {
+                if packages.is_empty() {
+                    return true;
+                }
+                match self.reviewed_package_of(proof) {
+                    Some(pkg_id) => {
+                        packages.contains(&canonical_package_id(&pkg_id.source, &pkg_id.name))
+                    }
+                    None => true,
+                }
+            })
+            .collect();
+        proofs.sort_by(|a, b| {
+            a.date_utc()
+                .cmp(&b.date_utc())
+                .then_with(|| a.signature().cmp(b.signature()))
+        });
+
+        for proof in &proofs {
+            write!(w, "{}", proof)?;
+        }
+        Ok(proofs.len())
+    }
+}
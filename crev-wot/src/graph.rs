@@ -0,0 +1,193 @@
+//! The Web of Trust as a graph, for visualization and auditing
+//!
+//! Nodes are Ids, and edges are the most recent trust proofs between them.
+use crate::{OwnedUrlOfId, ProofDB, TrustDistanceParams};
+use chrono::{offset::Utc, DateTime};
+use crev_data::{proof::trust::TrustLevel, Id};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+};
+
+/// Ids and trust edges between them, see `ProofDB::export_trust_graph`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrustGraph {
+    pub nodes: BTreeMap<Id, TrustGraphNode>,
+    /// Ordered by `from`, then `to`
+    pub edges: Vec<TrustGraphEdge>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrustGraphNode {
+    pub url: Option<OwnedUrlOfId>,
+    /// Effective trust level from the root (`Distrust` for distrusted Ids),
+    /// if the graph was exported for a root
+    pub effective_trust_level: Option<TrustLevel>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrustGraphEdge {
+    pub from: Id,
+    pub to: Id,
+    /// Trust level reported by `from` for `to`
+    pub level: TrustLevel,
+    /// Date of the trust proof
+    pub date: DateTime<Utc>,
+}
+
+impl TrustGraph {
+    /// Render in the Graphviz DOT format
+    ///
+    /// Distrusted Ids and distrust edges are drawn in red, and Ids are
+    /// labeled with their URLs. The output is the same for the same graph.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::new();
+        out.push_str("digraph wot {\n");
+        for (id, node) in &self.nodes {
+            let mut label = id.to_string();
+            if let Some(url) = &node.url {
+                label.push('\n');
+                label.push_str(&url.url.url);
+            }
+            write!(out, "  \"{}\" [label=\"{}\"", id, escape(&label)).expect("writing to String");
+            match node.effective_trust_level {
+                Some(TrustLevel::Distrust) => {
+                    out.push_str(", color=red, fontcolor=red, style=dashed")
+                }
+                Some(level) => write!(out, ", trust={}", level).expect("writing to String"),
+                None => {}
+            }
+            out.push_str("];\n");
+        }
+        for edge in &self.edges {
+            write!(
+                out,
+                "  \"{}\" -> \"{}\" [label=\"{}\"",
+                edge.from, edge.to, edge.level
+            )
+            .expect("writing to String");
+            if edge.level == TrustLevel::Distrust {
+                out.push_str(", color=red");
+            }
+            out.push_str("];\n");
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// Escape a string for a DOT quoted string
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+impl ProofDB {
+    /// All the Ids and trust edges between them
+    ///
+    /// With a `root`, only Ids in its trust set (trusted or distrusted) are
+    /// included, with their effective trust levels, and only edges from trusted Ids.
+    pub fn export_trust_graph(
+        &self,
+        root: Option<&Id>,
+        params: &TrustDistanceParams,
+    ) -> TrustGraph {
+        let trust_set = root.map(|root| self.calculate_trust_set(root, params));
+        let effective_trust_level = |id: &Id| {
+            trust_set
+                .as_ref()
+                .and_then(|trust_set| trust_set.get_effective_trust_level_opt(id))
+        };
+        let included = |id: &Id| trust_set.is_none() || effective_trust_level(id).is_some();
+
+        let mut graph = TrustGraph::default();
+        let add_node = |graph: &mut TrustGraph, id: &Id| {
+            if !graph.nodes.contains_key(id) {
+                graph.nodes.insert(
+                    id.clone(),
+                    TrustGraphNode {
+                        url: self.lookup_url(id).into_owned(),
+                        effective_trust_level: effective_trust_level(id),
+                    },
+                );
+            }
+        };
+        if let Some(root) = root {
+            add_node(&mut graph, root);
+        }
+
+        let mut froms: Vec<_> = self.trust_id_to_id.iter().collect();
+        froms.sort_by_key(|(from, _)| *from);
+        for (from, edges) in froms {
+            let from_trusted = match &trust_set {
+                Some(trust_set) => trust_set.is_trusted(from),
+                None => true,
+            };
+            if !from_trusted {
+                continue;
+            }
+            for (to, edge) in edges {
+                if !included(to) {
+                    continue;
+                }
+                add_node(&mut graph, from);
+                add_node(&mut graph, to);
+                graph.edges.push(TrustGraphEdge {
+                    from: from.clone(),
+                    to: to.clone(),
+                    level: edge.value.level,
+                    date: edge.date,
+                });
+            }
+        }
+        graph
+    }
+
+    /// Cycles of positive trust (`Low` or higher), of at most `max_len` Ids
+    ///
+    /// Rings of Ids trusting each other are not a problem for the WoT calculation,
+    /// but they can shorten the distances artificially, so UIs may warn about them.
+    /// Every cycle is reported once, starting with its smallest Id, and cycles
+    /// are ordered. Self-trust is not a cycle, see `self_trust_ids`.
+    pub fn find_trust_cycles(&self, max_len: usize) -> Vec<Vec<Id>> {
+        let positive = |from: &Id| {
+            self.trust_id_to_id
+                .get(from)
+                .into_iter()
+                .flatten()
+                .filter(|(_, edge)| edge.value.level >= TrustLevel::Low)
+                .map(|(to, _)| to)
+        };
+
+        let starts: BTreeSet<&Id> = self.trust_id_to_id.keys().collect();
+        let mut cycles = vec![];
+        for start in starts {
+            // depth-first, visiting only Ids greater than `start`, so that
+            // every cycle is found only from its smallest Id
+            let mut path = vec![start];
+            let mut stack = vec![positive(start)];
+            while let Some(edges) = stack.last_mut() {
+                match edges.next() {
+                    Some(to) if to == start => {
+                        if path.len() > 1 {
+                            cycles.push(path.iter().map(|id| (*id).clone()).collect());
+                        }
+                    }
+                    Some(to) => {
+                        if to > start && path.len() < max_len && !path.contains(&to) {
+                            path.push(to);
+                            stack.push(positive(to));
+                        }
+                    }
+                    None => {
+                        stack.pop();
+                        path.pop();
+                    }
+                }
+            }
+        }
+        cycles.sort();
+        cycles
+    }
+}
@@ -15,17 +15,42 @@
 use chrono::{self, offset::Utc, DateTime};
 use crev_data::{
     self,
-    proof::{self, review, trust::TrustLevel, CommonOps, Content},
+    proof::{self, review, review::Rating, trust::TrustLevel, CommonOps, Content},
     Digest, Id, Level, Url,
 };
 use default::default;
 use log::debug;
-use semver::Version;
+use semver::{Version, VersionReq};
 use std::{
-    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
-    sync,
+    borrow::Cow,
+    cmp,
+    collections::{btree_map, hash_map, BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    fmt,
+    ops::{self, Bound, ControlFlow},
+    path::{Path, PathBuf},
+    str, sync,
 };
 
+pub mod aliases;
+pub mod candidates;
+pub mod context;
+pub mod export;
+pub mod graph;
+pub mod score;
+pub mod search;
+#[cfg(feature = "serde")]
+mod snapshot;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+#[cfg(test)]
+mod tests;
+pub mod timeline;
+pub mod unreachable;
+pub mod verification;
+
+#[cfg(feature = "serde")]
+pub use snapshot::{proof_set_digest, SnapshotHeader, SNAPSHOT_VERSION};
+
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
     #[error("Unknown proof type '{}'", _0)]
@@ -33,12 +58,107 @@ pub enum Error {
 
     #[error("{}", _0)]
     Data(#[from] crev_data::Error),
+
+    #[error("Digest prefix too short, at least {} characters required", _0)]
+    DigestPrefixTooShort(usize),
+
+    #[error("Ambiguous digest prefix: {} matching digests", _0.len())]
+    AmbiguousDigestPrefix(Vec<Vec<u8>>),
+
+    #[error("Invalid review id: '{}'", _0)]
+    InvalidPkgReviewId(Box<str>),
+
+    #[error("I/O: {}", _0)]
+    Io(#[from] std::io::Error),
+
+    #[cfg(feature = "serde")]
+    #[error("Snapshot: {}", _0)]
+    Snapshot(#[from] serde_cbor::Error),
+
+    #[cfg(feature = "serde")]
+    #[error("Unsupported snapshot version {} (expected {})", _0, SNAPSHOT_VERSION)]
+    UnsupportedSnapshotVersion(u32),
 }
 
 type Result<T, E=Error> = std::result::Result<T, E>;
 
+/// Minimum length of a digest prefix accepted by `ProofDB::find_reviews_by_digest_prefix`
+pub const MIN_DIGEST_PREFIX_LEN: usize = 8;
+
+/// Canonical form of a proof repo URL, for comparing URLs with each other
+///
+/// Scheme and host are lowercased, and trailing slashes and `.git` suffix
+/// are removed, so e.g. `https://github.com/foo/crev-proofs.git` and
+/// `https://GitHub.com/foo/crev-proofs/` are the same. With `ignore_scheme`
+/// `http://` is treated as `https://`.
+///
+/// Only for comparisons - the original URL is what should be displayed.
+pub fn canonicalize_url(url: &str, ignore_scheme: bool) -> String {
+    let url = url.trim();
+    let (scheme, rest) = match url.find("://") {
+        Some(i) => (url[..i].to_ascii_lowercase(), &url[i + 3..]),
+        None => (String::new(), url),
+    };
+    let scheme = if ignore_scheme && scheme == "http" {
+        "https".into()
+    } else {
+        scheme
+    };
+    let (host, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+    let mut path = path.trim_end_matches('/');
+    if let Some(stripped) = path.strip_suffix(".git") {
+        path = stripped.trim_end_matches('/');
+    }
+
+    if scheme.is_empty() {
+        format!("{}{}", host.to_ascii_lowercase(), path)
+    } else {
+        format!("{}://{}{}", scheme, host.to_ascii_lowercase(), path)
+    }
+}
+
+/// Source of crates.io packages
+pub const CRATES_IO_SOURCE: &str = "https://crates.io";
+
+/// Canonical form of a package name, under which reviews of it are indexed
+///
+/// crates.io package names are compared case-insensitively, and with `-`
+/// and `_` being the same. For other sources names are compared exactly.
+pub fn canonicalize_package_name(source: &str, name: &str) -> String {
+    if source == CRATES_IO_SOURCE {
+        name.to_lowercase().replace('_', "-")
+    } else {
+        name.to_owned()
+    }
+}
+
+fn canonical_package_id(source: &str, name: &str) -> proof::PackageId {
+    proof::PackageId {
+        source: source.to_owned(),
+        name: canonicalize_package_name(source, name),
+    }
+}
+
+/// `id`, but with the canonical package name
+fn canonical_package_version_id(id: &proof::PackageVersionId) -> proof::PackageVersionId {
+    proof::PackageVersionId {
+        id: canonical_package_id(&id.id.source, &id.id.name),
+        version: id.version.clone(),
+    }
+}
+
+/// `PkgReviewId` of the `review`, with the canonical package id
+fn canonical_pkg_review_id(review: &review::Package) -> PkgReviewId {
+    let pkg_id = &review.package.id.id;
+    PkgReviewId::new(
+        review.from().id.clone(),
+        canonical_package_id(&pkg_id.source, &pkg_id.name),
+    )
+}
+
 /// Where a proof has been fetched from
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FetchSource {
     /// Remote repository (other people's proof repos)
     Url(sync::Arc<Url>),
@@ -46,20 +166,412 @@ pub enum FetchSource {
     LocalUser,
 }
 
+impl FetchSource {
+    /// Order of preference when the same proof was fetched from many places,
+    /// and it's not known which one was first: user's own repos, then by URL
+    fn preference_key(&self) -> (bool, &str) {
+        match self {
+            FetchSource::LocalUser => (false, ""),
+            FetchSource::Url(url) => (true, &url.url),
+        }
+    }
+
+    /// Name of the bucket in `ProofDB::proof_count_by_fetch_source`
+    ///
+    /// The URL for remote repositories, `local` for user's own repos.
+    pub fn name(&self) -> &str {
+        match self {
+            FetchSource::Url(url) => &url.url,
+            FetchSource::LocalUser => "local",
+        }
+    }
+
+    pub fn key(&self) -> FetchSourceKey {
+        match self {
+            FetchSource::Url(url) => FetchSourceKey::Url(url.url.clone()),
+            FetchSource::LocalUser => FetchSourceKey::LocalUser,
+        }
+    }
+}
+
+/// `FetchSource` that can be ordered, see `ProofDB::get_proof_provenance`
+///
+/// User's own repos come first, then remote repositories by URL.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FetchSourceKey {
+    LocalUser,
+    Url(String),
+}
+
+/// Kind of a proof, as far as `RepoStats` and `export::ExportFilter` are concerned
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ProofKind {
+    Trust,
+    PackageReview,
+    CodeReview,
+    /// Id supersessions, and proofs of unknown kinds
+    Other,
+}
+
+impl ProofKind {
+    fn of(kind: &str) -> Self {
+        match kind {
+            proof::Trust::KIND => ProofKind::Trust,
+            proof::PackageReview::KIND => ProofKind::PackageReview,
+            proof::CodeReview::KIND => ProofKind::CodeReview,
+            _ => ProofKind::Other,
+        }
+    }
+}
+
+/// Origin of a single proof
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct FetchSourceInfo {
+    source: FetchSource,
+    /// Date of the proof
+    date: DateTime<Utc>,
+    kind: ProofKind,
+    author: Id,
+    /// Other places the same proof has been fetched from, by `FetchSource::preference_key`
+    other_sources: Vec<FetchSource>,
+}
+
+impl FetchSourceInfo {
+    fn new(source: FetchSource, date: DateTime<Utc>, kind: ProofKind, author: Id) -> Self {
+        FetchSourceInfo {
+            source,
+            date,
+            kind,
+            author,
+            other_sources: vec![],
+        }
+    }
+
+    fn sources(&self) -> impl Iterator<Item = &FetchSource> {
+        std::iter::once(&self.source).chain(&self.other_sources)
+    }
+
+    /// Record that the proof has also been fetched from `source`
+    ///
+    /// Returns `false` if it was already known.
+    fn add_source(&mut self, source: FetchSource) -> bool {
+        if self.sources().any(|known| known.name() == source.name()) {
+            return false;
+        }
+        let pos = self
+            .other_sources
+            .binary_search_by(|known| known.preference_key().cmp(&source.preference_key()))
+            .unwrap_or_else(|pos| pos);
+        self.other_sources.insert(pos, source);
+        true
+    }
+}
+
+/// Trusted Ids that set a given flag, see `FlagsSummary`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FlagSummary {
+    /// Number of distinct Ids that set the flag
+    pub count: usize,
+    /// The highest effective trust level among them
+    pub max_trust_level: Option<TrustLevel>,
+    /// Date of the most recent review that set the flag
+    pub latest: Option<DateTime<Utc>>,
+}
+
+impl FlagSummary {
+    fn record(&mut self, level: TrustLevel, date: DateTime<Utc>) {
+        self.count += 1;
+        self.max_trust_level = cmp::max(self.max_trust_level, Some(level));
+        self.latest = cmp::max(self.latest, Some(date));
+    }
+}
+
+/// Flags of a package set by trusted Ids, see `ProofDB::get_pkg_flags_summary`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FlagsSummary {
+    pub unmaintained: FlagSummary,
+}
+
+/// When a flag of a package is considered set, see `ProofDB::get_effective_flags`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlagPolicy {
+    /// Only Ids with at least this effective trust level count
+    pub min_level: TrustLevel,
+    pub rule: FlagRule,
+}
+
+impl Default for FlagPolicy {
+    fn default() -> Self {
+        Self {
+            min_level: TrustLevel::Low,
+            rule: FlagRule::default(),
+        }
+    }
+}
+
+/// How many of the trusted reviewers of a package have to set a flag, see `FlagPolicy`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FlagRule {
+    /// At least one of them
+    #[default]
+    Any,
+    /// More than half of them, counting only their most recent reviews
+    Majority,
+}
+
+impl FlagRule {
+    fn is_set(self, flag: &EffectiveFlag) -> bool {
+        match self {
+            FlagRule::Any => !flag.set_by.is_empty(),
+            FlagRule::Majority => flag.not_set_by.len() < flag.set_by.len(),
+        }
+    }
+}
+
+/// A flag of a package, see `EffectiveFlags`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EffectiveFlag {
+    /// The flag should be considered set, according to the `FlagPolicy`
+    pub is_set: bool,
+    /// Trusted Ids that set the flag
+    pub set_by: BTreeSet<Id>,
+    /// Trusted Ids that reviewed the package without setting the flag
+    pub not_set_by: BTreeSet<Id>,
+}
+
+/// Flags of a package according to a `FlagPolicy`, see `ProofDB::get_effective_flags`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EffectiveFlags {
+    pub unmaintained: EffectiveFlag,
+}
+
+impl EffectiveFlags {
+    /// Any of the flags is set
+    pub fn any_set(&self) -> bool {
+        self.unmaintained.is_set
+    }
+}
+
+/// An alternative to a package, see `ProofDB::get_trusted_pkg_alternatives`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlternativeSuggestion {
+    pub pkg_id: proof::PackageId,
+    /// Number of trusted Ids that reported it as an alternative
+    pub reporter_count: usize,
+    /// Number of positive (or better) reviews of it by trusted Ids, for any version
+    pub positive_review_count: usize,
+}
+
+/// A step of `ProofDB::resolve_alternative_chain`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlternativeChainHop {
+    /// Package `to` is an alternative to
+    pub from: proof::PackageId,
+    pub to: proof::PackageId,
+    /// Number of hops from the queried package, 1 for its direct alternatives
+    pub depth: usize,
+    /// Trusted Ids that reported `to` as an alternative to `from`
+    pub reporters: BTreeSet<Id>,
+    /// `to` has positive (or better) reviews by trusted Ids, for any version
+    pub has_positive_reviews: bool,
+    /// `to` has no negative reviews, issues or `unmaintained` flags from trusted Ids,
+    /// so it's a plausible final recommendation, and its alternatives are not followed
+    pub is_endpoint: bool,
+}
+
+/// Proofs fetched from a given `FetchSource`
+#[derive(Debug, Clone)]
+pub struct FetchStats {
+    pub proof_count: usize,
+    /// Date of the most recently created proof
+    pub newest_proof_date: DateTime<Utc>,
+}
+
+/// Proofs that entered the `ProofDB` from a given proof repo, see `ProofDB::get_repo_stats`
+///
+/// A proof fetched from many repos counts for every one of them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RepoStats {
+    pub trust_proofs: usize,
+    pub package_reviews: usize,
+    pub code_reviews: usize,
+    /// Proofs of kinds this version doesn't understand (see `ProofDB::store_unknown_proofs`)
+    pub other_proofs: usize,
+    /// Date of the most recently created proof
+    pub latest_proof_date: DateTime<Utc>,
+    /// Authors of the proofs
+    pub ids_seen: BTreeSet<Id>,
+}
+
+impl RepoStats {
+    fn new(date: DateTime<Utc>) -> Self {
+        RepoStats {
+            trust_proofs: 0,
+            package_reviews: 0,
+            code_reviews: 0,
+            other_proofs: 0,
+            latest_proof_date: date,
+            ids_seen: BTreeSet::new(),
+        }
+    }
+
+    fn record(&mut self, info: &FetchSourceInfo) {
+        match info.kind {
+            ProofKind::Trust => self.trust_proofs += 1,
+            ProofKind::PackageReview => self.package_reviews += 1,
+            ProofKind::CodeReview => self.code_reviews += 1,
+            ProofKind::Other => self.other_proofs += 1,
+        }
+        self.latest_proof_date = cmp::max(self.latest_proof_date, info.date);
+        if !self.ids_seen.contains(&info.author) {
+            self.ids_seen.insert(info.author.clone());
+        }
+    }
+
+    /// Total number of proofs
+    pub fn proof_count(&self) -> usize {
+        self.trust_proofs + self.package_reviews + self.code_reviews + self.other_proofs
+    }
+}
+
+/// Numbers of proofs, Ids and packages, see `ProofDB::summary`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DbSummary {
+    /// Package reviews of the source (most recent reviews, without retractions),
+    /// same as `ProofDB::get_package_review_count`
+    pub package_reviews: usize,
+    /// Most recent reviews that are retractions, not counted in `package_reviews`
+    pub retracted_package_reviews: usize,
+    /// Distinct packages with any of the `package_reviews`
+    pub packages: usize,
+    /// Distinct package versions with any of the `package_reviews`
+    pub versions: usize,
+    /// Distinct authors of the `package_reviews`
+    pub reviewers: usize,
+    /// `package_reviews` by Ids trusted at least at the required level
+    pub trusted_package_reviews: usize,
+    /// Distinct packages with any of the `trusted_package_reviews`
+    pub trusted_packages: usize,
+    /// Trust proofs of all sources, same as `ProofDB::unique_trust_proof_count`
+    pub trust_proofs: usize,
+    /// Ids of all sources, same as the size of `ProofDB::all_known_ids`
+    pub known_ids: usize,
+}
+
+/// Activity of an Id, see `ProofDB::get_id_stats`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IdStats {
+    /// Package versions reviewed (most recent reviews, without retractions)
+    pub package_reviews: usize,
+    /// Number of `package_reviews` by package source
+    pub package_reviews_by_source: BTreeMap<Source, usize>,
+    /// Average `Level` (0 for `None` to 3 for `High`) of the `package_reviews`
+    /// that have a review (and not just flags, advisories etc.), if there are any
+    pub average_thoroughness: Option<f64>,
+    pub average_understanding: Option<f64>,
+    /// Trust proofs still in use, for any level (`Distrust` included)
+    pub trust_proofs: usize,
+    /// Ids it reports any trust level for
+    pub trust_reported_for: usize,
+    /// Date of the oldest proof, superseded ones included
+    pub first_seen: Option<DateTime<Utc>>,
+    /// Date of the most recent proof
+    pub last_seen: Option<DateTime<Utc>>,
+}
+
+/// `IdStats` in the making
+#[derive(Default)]
+struct IdStatsBuilder {
+    stats: IdStats,
+    rated_reviews: u32,
+    thoroughness_sum: u32,
+    understanding_sum: u32,
+}
+
+impl IdStatsBuilder {
+    fn add_package_review(&mut self, review: &review::Package) {
+        self.stats.package_reviews += 1;
+        *self
+            .stats
+            .package_reviews_by_source
+            .entry(review.package.id.id.source.clone())
+            .or_default() += 1;
+        if let Some(review) = review.review() {
+            self.rated_reviews += 1;
+            self.thoroughness_sum += review.thoroughness as u32;
+            self.understanding_sum += review.understanding as u32;
+        }
+    }
+
+    fn add_trust_edges(&mut self, edges: &BTreeMap<Id, TimestampedTrustEdge>) {
+        let signatures: HashSet<_> = edges.values().map(|edge| &edge.value.signature).collect();
+        self.stats.trust_proofs += signatures.len();
+        self.stats.trust_reported_for += edges.len();
+    }
+
+    fn add_proof_date(&mut self, date: DateTime<Utc>) {
+        self.stats.first_seen = Some(self.stats.first_seen.map_or(date, |d| cmp::min(d, date)));
+        self.stats.last_seen = cmp::max(self.stats.last_seen, Some(date));
+    }
+
+    fn build(self) -> IdStats {
+        let average = |sum: u32| {
+            if self.rated_reviews == 0 {
+                None
+            } else {
+                Some(f64::from(sum) / f64::from(self.rated_reviews))
+            }
+        };
+        IdStats {
+            average_thoroughness: average(self.thoroughness_sum),
+            average_understanding: average(self.understanding_sum),
+            ..self.stats
+        }
+    }
+}
+
 /// A `T` with a timestamp
 ///
 /// This allows easily keeping track of a most recent version
 /// of `T`. Typically `T` is some information from a timestamped
 /// *proof* of some kind.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Timestamped<T> {
     pub date: chrono::DateTime<Utc>,
     value: T,
 }
 
 impl<T> Timestamped<T> {
-    // Return `true` if value was updated
-    fn update_to_more_recent(&mut self, other: &Self)
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    pub fn into_value(self) -> T {
+        self.value
+    }
+
+    pub fn as_ref(&self) -> Timestamped<&T> {
+        Timestamped {
+            date: self.date,
+            value: &self.value,
+        }
+    }
+
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Timestamped<U> {
+        Timestamped {
+            date: self.date,
+            value: f(self.value),
+        }
+    }
+
+    /// Replace with `other` if it's not older
+    ///
+    /// Return `true` if value was updated
+    pub fn update_to_more_recent(&mut self, other: &Self) -> bool
     where
         T: Clone,
     {
@@ -69,6 +581,19 @@ impl<T> Timestamped<T> {
         if self.date <= other.date {
             self.date = other.date;
             self.value = other.value.clone();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Keep the more recent of `self` and `other`
+    ///
+    /// Unlike `update_to_more_recent`, the result doesn't depend on which
+    /// one is `self`: if the dates are equal, the one with the greater `key` wins.
+    fn merge_more_recent<K: Ord>(&mut self, other: Self, key: impl Fn(&T) -> K) {
+        if (self.date, key(&self.value)) < (other.date, key(&other.value)) {
+            *self = other;
         }
     }
 }
@@ -86,11 +611,290 @@ where
 }
 
 pub type Signature = String;
+/// Signature of a review, shared by all the indices of the review
+type SharedSignature = sync::Arc<str>;
 type TimestampedUrl = Timestamped<Url>;
 type TimestampedTrustLevel = Timestamped<TrustLevel>;
+type TimestampedTrustEdge = Timestamped<TrustEdge>;
 type TimestampedReview = Timestamped<review::Review>;
-type TimestampedSignature = Timestamped<Signature>;
+type TimestampedSignature = Timestamped<SharedSignature>;
 type TimestampedFlags = Timestamped<proof::Flags>;
+type TimestampedFileReview = Timestamped<FileReview>;
+type PkgReviewIdsByDate = BTreeSet<(cmp::Reverse<DateTime<Utc>>, PkgVersionReviewId)>;
+type TimestampedPkgVersionReviewId = Timestamped<PkgVersionReviewId>;
+/// Every trust level reported for a given edge, oldest first
+type TrustLevelHistory = Vec<TimestampedTrustLevel>;
+
+/// Is anything expiring at `expires` expired at `now` (expiration takes effect at the very instant)
+fn is_expired_at(expires: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+    match expires {
+        Some(expires) => expires <= now,
+        None => false,
+    }
+}
+
+/// Trust level in effect at `as_of`, if the edge existed back then
+fn trust_level_at(history: &[TimestampedTrustLevel], as_of: DateTime<Utc>) -> Option<TrustLevel> {
+    history
+        .iter()
+        .rev()
+        .find(|entry| entry.date <= as_of)
+        .map(|entry| entry.value)
+}
+
+/// Trust edges as seen by the trust set calculation
+#[derive(Default, Clone)]
+struct TrustEdgeView {
+    /// Only the edges of trust applying to these package sources
+    scope: EdgeScope,
+    /// Hypothetical edges, overriding the ones from proofs
+    extra_edges: BTreeMap<Id, BTreeMap<Id, TrustLevel>>,
+    /// Use trust levels in effect at this point in time, instead of the current ones
+    as_of: Option<DateTime<Utc>>,
+    /// Effective trust levels set by the root, no matter what the edges say
+    overrides: HashMap<Id, TrustLevel>,
+}
+
+/// Whether an unsigned trust override or a trust proof decides the trust level of an edge
+///
+/// See `ProofDB::add_unsigned_trust_override`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnsignedOverridePrecedence {
+    /// Overrides replace the trust reported in proofs
+    #[default]
+    OverrideWins,
+    /// Overrides only add edges nobody reported trust for in proofs
+    ProofWins,
+}
+
+/// Source of the current time of a `ProofDB`, see `ProofDB::set_clock`
+///
+/// Trust edges and reviews past their expiration date (see `proof::Trust::expires`)
+/// are ignored by most queries, so the answers depend on what time it is.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Clock {
+    /// The system clock
+    #[default]
+    System,
+    /// Always the same point in time, e.g. for reproducible tests
+    Fixed(DateTime<Utc>),
+}
+
+impl Clock {
+    pub fn now(&self) -> DateTime<Utc> {
+        match self {
+            Clock::System => Utc::now(),
+            Clock::Fixed(now) => *now,
+        }
+    }
+}
+
+/// What the registry of a package knows about a version of it, see `VersionOracle`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VersionStatus {
+    /// The version is published
+    Exists,
+    /// The version was published, but then yanked
+    Yanked,
+    /// Nothing is known about the version
+    Unknown,
+    /// The version was never published (e.g. there's a typo in a proof written by hand)
+    NotFound,
+}
+
+impl VersionStatus {
+    /// Reviews of the version don't cover anything that should be used
+    pub fn is_unavailable(self) -> bool {
+        matches!(self, VersionStatus::Yanked | VersionStatus::NotFound)
+    }
+}
+
+/// Source of `VersionStatus`es of package versions, see `ProofDB::set_version_oracle`
+///
+/// `crev-wot` never accesses the network, so it's up to the users to look
+/// the versions up, e.g. in a local copy of the registry index.
+pub trait VersionOracle: Send + Sync {
+    fn status(&self, source: &str, name: &str, version: &Version) -> VersionStatus;
+}
+
+/// `VersionOracle` that doesn't know anything, used by default
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoVersionOracle;
+
+impl VersionOracle for NoVersionOracle {
+    fn status(&self, _source: &str, _name: &str, _version: &Version) -> VersionStatus {
+        VersionStatus::Unknown
+    }
+}
+
+fn default_version_oracle() -> sync::Arc<dyn VersionOracle> {
+    sync::Arc::new(NoVersionOracle)
+}
+
+/// Trust edge added with `ProofDB::add_unsigned_trust_override`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsignedTrustOverride {
+    pub from: Id,
+    pub to: Id,
+    pub level: TrustLevel,
+    pub date: DateTime<Utc>,
+}
+
+/// Which trust edges a `TrustEdgeView` includes, by the sources the trust is limited to
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+enum EdgeScope {
+    #[default]
+    All,
+    /// Edges of trust applying to packages from the source
+    Source(String),
+    /// Only edges of trust that is not limited to any sources
+    Unlimited,
+}
+
+/// Most relevant reviews of a package by a single author
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct LatestPkgReviews {
+    /// The most recent one; for equal dates, the one of the newest version
+    by_date: TimestampedPkgVersionReviewId,
+    /// The one of the newest version; for equal versions, the most recent one
+    by_version: TimestampedPkgVersionReviewId,
+}
+
+impl LatestPkgReviews {
+    fn new(review: TimestampedPkgVersionReviewId) -> Self {
+        LatestPkgReviews {
+            by_date: review.clone(),
+            by_version: review,
+        }
+    }
+
+    fn update(&mut self, review: &TimestampedPkgVersionReviewId) {
+        fn date_key(
+            review: &TimestampedPkgVersionReviewId,
+        ) -> (DateTime<Utc>, &Version, &PkgVersionReviewId) {
+            (
+                review.date,
+                &review.value.package_version_id.version,
+                &review.value,
+            )
+        }
+        fn version_key(
+            review: &TimestampedPkgVersionReviewId,
+        ) -> (&Version, DateTime<Utc>, &PkgVersionReviewId) {
+            (
+                &review.value.package_version_id.version,
+                review.date,
+                &review.value,
+            )
+        }
+
+        if date_key(review) >= date_key(&self.by_date) {
+            self.by_date = review.clone();
+        }
+        if version_key(review) >= version_key(&self.by_version) {
+            self.by_version = review.clone();
+        }
+    }
+
+    fn get(&self, by: LatestBy) -> &PkgVersionReviewId {
+        match by {
+            LatestBy::Date => &self.by_date.value,
+            LatestBy::Version => &self.by_version.value,
+        }
+    }
+}
+
+fn record_latest_review(
+    latest: &mut HashMap<proof::PackageId, BTreeMap<Id, LatestPkgReviews>>,
+    pkg_review_id: &PkgVersionReviewId,
+    date: DateTime<Utc>,
+) {
+    let pkg_id = &pkg_review_id.package_version_id.id;
+    let review = TimestampedPkgVersionReviewId {
+        date,
+        value: pkg_review_id.clone(),
+    };
+    match latest
+        .entry(canonical_package_id(&pkg_id.source, &pkg_id.name))
+        .or_default()
+        .entry((*pkg_review_id.from).clone())
+    {
+        btree_map::Entry::Occupied(mut e) => e.get_mut().update(&review),
+        btree_map::Entry::Vacant(e) => {
+            e.insert(LatestPkgReviews::new(review));
+        }
+    }
+}
+
+/// A result of `ProofDB::calculate_trust_set_cached`
+#[derive(Clone)]
+struct CachedTrustSet {
+    params: TrustDistanceParams,
+    /// Value of `ProofDB::trust_insertion_counter` at the time of the calculation
+    trust_insertion_counter: usize,
+    /// Value of `ProofDB::insertion_counter` at the time of the calculation, only
+    /// relevant if the trust set depends on the reviews (see `review_quality_cap`)
+    insertion_counter: usize,
+    /// `ProofDB::now` at the time of the calculation
+    calculated_at: DateTime<Utc>,
+    /// The first trust edge expiration after `calculated_at`, if any
+    next_expiration: Option<DateTime<Utc>>,
+    trust_set: sync::Arc<TrustSet>,
+}
+
+/// Do two package reviews with the same signature actually say the same thing
+fn is_same_package_review(a: &review::Package, b: &review::Package) -> bool {
+    a.package.id == b.package.id
+        && a.package.digest == b.package.digest
+        && a.from().id == b.from().id
+        && a.date() == b.date()
+}
+
+fn url_key(url: &Url) -> (String, String) {
+    (url.url.clone(), url.url_type.clone())
+}
+
+/// See `Timestamped::merge_more_recent`
+fn merge_entry<K: Ord, T, O: Ord>(
+    entry: btree_map::Entry<'_, K, Timestamped<T>>,
+    value: Timestamped<T>,
+    key: impl Fn(&T) -> O,
+) {
+    match entry {
+        btree_map::Entry::Occupied(mut e) => e.get_mut().merge_more_recent(value, key),
+        btree_map::Entry::Vacant(e) => {
+            e.insert(value);
+        }
+    }
+}
+
+/// Trust of one Id for another, from the most recent trust proof
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct TrustEdge {
+    level: TrustLevel,
+    comment: String,
+    /// Of the proof the edge comes from
+    signature: Signature,
+    /// Package sources the trust is limited to, see `proof::Trust::sources`
+    sources: Vec<String>,
+    /// See `proof::Trust::expires`
+    expires: Option<DateTime<Utc>>,
+}
+
+impl TrustEdge {
+    /// Does the trust apply to packages from `scope`
+    fn applies_to(&self, scope: &EdgeScope) -> bool {
+        match scope {
+            EdgeScope::All => true,
+            EdgeScope::Source(source) => {
+                self.sources.is_empty() || self.sources.iter().any(|s| s == source)
+            }
+            EdgeScope::Unlimited => self.sources.is_empty(),
+        }
+    }
+}
 
 impl From<proof::Trust> for TimestampedTrustLevel {
     fn from(trust: proof::Trust) -> Self {
@@ -119,46 +923,221 @@ impl<'a, T: proof::WithReview + Content + CommonOps> From<&'a T> for Timestamped
 /// * pkg source
 /// * pkg name
 /// * pkg version
-#[derive(Hash, Debug, Clone, PartialEq, Eq)]
+///
+/// Copies of it are stored in many indices of `ProofDB`, so the Id and
+/// the package version are shared by all of them, and cheap to clone.
+/// The package name is canonical (see `canonicalize_package_name`), so
+/// reviews of the same package spelled differently have the same id.
+#[derive(Hash, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PkgVersionReviewId {
-    from: Id,
-    package_version_id: proof::PackageVersionId,
+    from: sync::Arc<Id>,
+    package_version_id: sync::Arc<proof::PackageVersionId>,
 }
 
-impl From<review::Package> for PkgVersionReviewId {
-    fn from(review: review::Package) -> Self {
+impl PkgVersionReviewId {
+    pub fn new(from: Id, package_version_id: proof::PackageVersionId) -> Self {
         PkgVersionReviewId {
-            from: review.from().id.clone(),
-            package_version_id: review.package.id,
+            from: sync::Arc::new(from),
+            package_version_id: sync::Arc::new(canonical_package_version_id(&package_version_id)),
         }
     }
-}
 
-impl From<&review::Package> for PkgVersionReviewId {
-    fn from(review: &review::Package) -> Self {
-        PkgVersionReviewId {
-            from: review.from().id.to_owned(),
-            package_version_id: review.package.id.clone(),
-        }
+    pub fn from(&self) -> &Id {
+        &self.from
     }
-}
 
-/// An unique id for a review by a given author of a given package.
-///
-/// Similar to `PackageVersionReviewId`, but where
-/// exact version is not important.
-#[derive(Hash, Debug, Clone, PartialEq, Eq)]
-pub struct PkgReviewId {
-    from: Id,
-    package_id: proof::PackageId,
+    pub fn package_version_id(&self) -> &proof::PackageVersionId {
+        &self.package_version_id
+    }
+
+    pub fn to_pkg_review_id(&self) -> PkgReviewId {
+        PkgReviewId::new((*self.from).clone(), self.package_version_id.id.clone())
+    }
 }
 
-impl From<review::Package> for PkgReviewId {
-    fn from(review: review::Package) -> Self {
-        PkgReviewId {
-            from: review.from().id.clone(),
-            package_id: review.package.id.id,
-        }
+/// Formatted as `<author id>/<source>/<name>/<version>`
+impl fmt::Display for PkgVersionReviewId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{}",
+            self.to_pkg_review_id(),
+            self.package_version_id.version
+        )
+    }
+}
+
+impl str::FromStr for PkgVersionReviewId {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::InvalidPkgReviewId(s.into());
+        // source can contain slashes (it's usually an URL), version and name can't
+        let slash = s.rfind('/').ok_or_else(invalid)?;
+        let (rest, version) = (&s[..slash], &s[slash + 1..]);
+        let pkg_review_id: PkgReviewId = rest.parse().map_err(|_| invalid())?;
+        let version = Version::parse(version).map_err(|_| invalid())?;
+        Ok(PkgVersionReviewId::new(
+            pkg_review_id.from,
+            proof::PackageVersionId {
+                id: pkg_review_id.package_id,
+                version,
+            },
+        ))
+    }
+}
+
+impl From<review::Package> for PkgVersionReviewId {
+    fn from(review: review::Package) -> Self {
+        PkgVersionReviewId::new(review.from().id.clone(), review.package.id)
+    }
+}
+
+impl From<&review::Package> for PkgVersionReviewId {
+    fn from(review: &review::Package) -> Self {
+        PkgVersionReviewId::new(review.from().id.to_owned(), review.package.id.clone())
+    }
+}
+
+/// Single shared copy of values stored in many indices
+///
+/// Values are only held on to while they are used; see `Interner::release_unused`.
+#[derive(Default, Clone)]
+struct Interner {
+    ids: HashSet<sync::Arc<Id>>,
+    package_version_ids: HashSet<sync::Arc<proof::PackageVersionId>>,
+}
+
+impl Interner {
+    fn intern<T: Eq + std::hash::Hash + Clone>(
+        set: &mut HashSet<sync::Arc<T>>,
+        value: &T,
+    ) -> sync::Arc<T> {
+        if let Some(shared) = set.get(value) {
+            return shared.clone();
+        }
+        let shared = sync::Arc::new(value.clone());
+        set.insert(shared.clone());
+        shared
+    }
+
+    fn pkg_version_review_id(&mut self, review: &review::Package) -> PkgVersionReviewId {
+        PkgVersionReviewId {
+            from: Self::intern(&mut self.ids, &review.from().id),
+            package_version_id: Self::intern(
+                &mut self.package_version_ids,
+                &canonical_package_version_id(&review.package.id),
+            ),
+        }
+    }
+
+    /// Like `intern`, but for a value already in an `Arc`, used if there's no shared copy yet
+    #[cfg(feature = "serde")]
+    fn share<T: Eq + std::hash::Hash>(
+        set: &mut HashSet<sync::Arc<T>>,
+        value: sync::Arc<T>,
+    ) -> sync::Arc<T> {
+        if let Some(shared) = set.get(&value) {
+            return shared.clone();
+        }
+        set.insert(value.clone());
+        value
+    }
+
+    /// The same `id`, but with the parts shared with all the others
+    #[cfg(feature = "serde")]
+    fn share_pkg_version_review_id(&mut self, id: PkgVersionReviewId) -> PkgVersionReviewId {
+        PkgVersionReviewId {
+            from: Self::share(&mut self.ids, id.from),
+            package_version_id: Self::share(&mut self.package_version_ids, id.package_version_id),
+        }
+    }
+
+    /// Forget the values not used anywhere else anymore
+    fn release_unused(&mut self) {
+        self.ids.retain(|id| sync::Arc::strong_count(id) > 1);
+        self.package_version_ids
+            .retain(|id| sync::Arc::strong_count(id) > 1);
+        self.ids.shrink_to_fit();
+        self.package_version_ids.shrink_to_fit();
+    }
+
+    fn merge(&mut self, other: Interner) {
+        self.ids.extend(other.ids);
+        self.package_version_ids.extend(other.package_version_ids);
+    }
+}
+
+/// An unique id for a review by a given author of a given package.
+///
+/// Similar to `PackageVersionReviewId`, but where
+/// exact version is not important.
+#[derive(Hash, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PkgReviewId {
+    from: Id,
+    package_id: proof::PackageId,
+}
+
+impl PkgReviewId {
+    pub fn new(from: Id, package_id: proof::PackageId) -> Self {
+        PkgReviewId { from, package_id }
+    }
+
+    pub fn from(&self) -> &Id {
+        &self.from
+    }
+
+    pub fn package_id(&self) -> &proof::PackageId {
+        &self.package_id
+    }
+}
+
+/// Formatted as `<author id>/<source>/<name>`
+impl fmt::Display for PkgReviewId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{}/{}",
+            self.from, self.package_id.source, self.package_id.name
+        )
+    }
+}
+
+impl str::FromStr for PkgReviewId {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::InvalidPkgReviewId(s.into());
+        // source can contain slashes (it's usually an URL), Id and name can't
+        let first_slash = s.find('/').ok_or_else(invalid)?;
+        let last_slash = s.rfind('/').expect("found");
+        if first_slash == last_slash {
+            return Err(invalid());
+        }
+        let from = &s[..first_slash];
+        let (source, name) = (&s[first_slash + 1..last_slash], &s[last_slash + 1..]);
+        if source.is_empty() || name.is_empty() {
+            return Err(invalid());
+        }
+        let from = Id::crevid_from_str(from).map_err(crev_data::Error::from)?;
+        Ok(PkgReviewId::new(
+            from,
+            proof::PackageId {
+                source: source.to_owned(),
+                name: name.to_owned(),
+            },
+        ))
+    }
+}
+
+impl From<review::Package> for PkgReviewId {
+    fn from(review: review::Package) -> Self {
+        PkgReviewId {
+            from: review.from().id.clone(),
+            package_id: review.package.id.id,
+        }
     }
 }
 
@@ -171,6 +1150,108 @@ impl From<&review::Package> for PkgReviewId {
     }
 }
 
+/// Reviews to disregard, even though their authors are trusted
+///
+/// Every entry is a (reviewer, package) pair, and covers all the reviews
+/// of any version of the package by that reviewer.
+///
+/// Honored by `VerificationRequirements::ignore_list`, and the `*_ignoring`
+/// variants of the issue queries (`get_open_issues_for_version_ignoring`,
+/// `get_pkg_reviews_with_issues_for_ignoring` and the like). Flags, alternatives
+/// and reviews by digest are still reported as their authors made them.
+#[derive(Debug, Clone, Default)]
+pub struct ReviewIgnoreList {
+    ignored: BTreeSet<PkgReviewId>,
+}
+
+impl ReviewIgnoreList {
+    pub fn new() -> Self {
+        default()
+    }
+
+    pub fn insert(&mut self, id: Id, source: &str, name: &str) {
+        self.ignored.insert(PkgReviewId {
+            from: id,
+            package_id: proof::PackageId {
+                source: source.to_owned(),
+                name: name.to_owned(),
+            },
+        });
+    }
+
+    pub fn is_ignored(&self, review: &review::Package) -> bool {
+        !self.ignored.is_empty() && self.ignored.contains(&review.into())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ignored.is_empty()
+    }
+}
+
+impl std::iter::FromIterator<(Id, String, String)> for ReviewIgnoreList {
+    fn from_iter<I: IntoIterator<Item = (Id, String, String)>>(iter: I) -> Self {
+        let mut list = Self::new();
+        for (id, source, name) in iter {
+            list.insert(id, &source, &name);
+        }
+        list
+    }
+}
+
+/// Reviews to take into account, by their age and quality
+///
+/// The default filter accepts all the reviews. Reviews that only report
+/// issues or advisories, without rating the package, are not subject
+/// to `min_thoroughness` and `min_understanding`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReviewFilter {
+    /// Disregard reviews older than this, at the time of the query (see `ProofDB::now`)
+    pub max_age: Option<chrono::Duration>,
+    /// Disregard reviews created before this date
+    pub min_date: Option<DateTime<Utc>>,
+    pub min_thoroughness: Option<Level>,
+    pub min_understanding: Option<Level>,
+}
+
+impl ReviewFilter {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Does the filter accept the `review` at `now` (see `ProofDB::now`)
+    pub fn accepts(&self, review: &review::Package, now: DateTime<Utc>) -> bool {
+        self.accepts_since(review, self.cutoff(now))
+    }
+
+    /// Date of the oldest reviews accepted at `now`
+    fn cutoff(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let by_age = self.max_age.map(|max_age| now - max_age);
+        match (self.min_date, by_age) {
+            (Some(a), Some(b)) => Some(cmp::max(a, b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    fn accepts_since(&self, review: &review::Package, cutoff: Option<DateTime<Utc>>) -> bool {
+        if matches!(cutoff, Some(cutoff) if review.date_utc() < cutoff) {
+            return false;
+        }
+        match review.review() {
+            Some(details) => {
+                !matches!(self.min_thoroughness, Some(min) if details.thoroughness < min)
+                    && !matches!(self.min_understanding, Some(min) if details.understanding < min)
+            }
+            None => true,
+        }
+    }
+
+    /// Predicate for filtering reviews, with the cutoff date of `now`
+    fn predicate(&self, now: DateTime<Utc>) -> impl Fn(&&review::Package) -> bool + '_ {
+        let cutoff = self.cutoff(now);
+        move |review| self.accepts_since(review, cutoff)
+    }
+}
+
 pub type Source = String;
 pub type Name = String;
 
@@ -180,8 +1261,9 @@ pub type Name = String;
 #[derive(Default)]
 struct AlternativesData {
     derived_recalculation_counter: usize,
-    for_pkg: HashMap<proof::PackageId, HashMap<Id, HashSet<proof::PackageId>>>,
-    reported_by: HashMap<(proof::PackageId, proof::PackageId), HashMap<Id, Signature>>,
+    // all the `PackageId`s here are canonical
+    for_pkg: HashMap<proof::PackageId, BTreeMap<Id, BTreeSet<proof::PackageId>>>,
+    reported_by: HashMap<(proof::PackageId, proof::PackageId), BTreeMap<Id, Signature>>,
 }
 
 impl AlternativesData {
@@ -193,10 +1275,10 @@ impl AlternativesData {
         *self = Self::new();
     }
 
-    fn record_from_proof(&mut self, review: &review::Package, signature: &Signature) {
+    fn record_from_proof(&mut self, review: &review::Package, signature: &str) {
+        let a = &canonical_package_id(&review.package.id.id.source, &review.package.id.id.name);
         for alternative in &review.alternatives {
-            let a = &review.package.id.id;
-            let b = alternative;
+            let b = &canonical_package_id(&alternative.source, &alternative.name);
             let id = &review.from().id;
             self.for_pkg
                 .entry(a.clone())
@@ -215,12 +1297,12 @@ impl AlternativesData {
             self.reported_by
                 .entry((a.clone(), b.clone()))
                 .or_default()
-                .insert(id.clone(), signature.clone());
+                .insert(id.clone(), signature.to_owned());
 
             self.reported_by
                 .entry((b.clone(), a.clone()))
                 .or_default()
-                .insert(id.clone(), signature.clone());
+                .insert(id.clone(), signature.to_owned());
         }
     }
 }
@@ -232,10 +1314,29 @@ impl AlternativesData {
 /// Right now, for every invocation of crev, we just load it up with
 /// all known proofs, and then query. If it ever becomes too slow,
 /// all the logic here will have to be moved to a real embedded db
-/// of some kind.
+/// of some kind. Until then, with the `serde` feature, the whole
+/// database can be stored as a snapshot, see `ProofDB::serialize_snapshot`.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ProofDB {
     /// who -(trusts)-> whom
-    trust_id_to_id: HashMap<Id, HashMap<Id, TimestampedTrustLevel>>,
+    trust_id_to_id: HashMap<Id, BTreeMap<Id, TimestampedTrustEdge>>,
+
+    /// whom <-(reported any trust level for)- who; the reverse of `trust_id_to_id`
+    trust_reported_by: HashMap<Id, BTreeSet<Id>>,
+
+    /// who -(trusts)-> whom, not backed by any proofs, see `add_unsigned_trust_override`;
+    /// just like settings, neither merged nor stored in snapshots
+    #[cfg_attr(feature = "serde", serde(skip))]
+    unsigned_trust_overrides: BTreeMap<Id, BTreeMap<Id, TimestampedTrustLevel>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    unsigned_override_precedence: UnsignedOverridePrecedence,
+
+    /// Ids that reported trust for themselves, kept out of `trust_id_to_id`
+    self_trust: BTreeMap<Id, TimestampedTrustLevel>,
+
+    /// old Id -> the Id it declared to be superseded by, in its most recent
+    /// supersession proof; see `resolve_id`
+    id_superseded_by: HashMap<Id, Timestamped<Id>>,
 
     /// Id->URL mapping verified by Id's signature
     /// boolean is whether it's been fetched from the same URL, or local trusted repo,
@@ -245,25 +1346,70 @@ pub struct ProofDB {
     /// Id->URL relationship reported by someone else that this Id
     url_by_id_reported_by_others: HashMap<Id, TimestampedUrl>,
 
+    /// Canonical URL -> all Ids that were ever claimed to own it
+    ids_by_url: BTreeMap<String, BTreeSet<Id>>,
+
+    /// Id -> canonical URL -> the URL as first self-reported by Id, with the date
+    url_history: HashMap<Id, BTreeMap<String, TimestampedUrl>>,
+
     // all reviews are here
-    package_review_by_signature: HashMap<Signature, review::Package>,
+    package_review_by_signature: HashMap<SharedSignature, sync::Arc<review::Package>>,
 
     // we can get the to the review through the signature from these two
     package_review_signatures_by_package_digest:
-        HashMap<Vec<u8>, HashMap<PkgVersionReviewId, TimestampedSignature>>,
+        BTreeMap<Vec<u8>, BTreeMap<PkgVersionReviewId, TimestampedSignature>>,
     package_review_signatures_by_pkg_review_id: HashMap<PkgVersionReviewId, TimestampedSignature>,
 
-    // pkg_review_id by package information, nicely grouped
+    // all the reviews ever seen for every pkg_review_id (not only the most
+    // recent ones), by date; for queries about the past, like `get_pkg_reviews_for_version_at`
+    package_review_history: HashMap<PkgVersionReviewId, BTreeSet<(DateTime<Utc>, SharedSignature)>>,
+
+    // pkg_review_id by package information, nicely grouped;
+    // names (here, and in all the other `PackageId`-keyed indices of reviews,
+    // including flags and alternatives) are canonical, see `canonicalize_package_name`
     package_reviews:
-        BTreeMap<Source, BTreeMap<Name, BTreeMap<Version, HashSet<PkgVersionReviewId>>>>,
+        BTreeMap<Source, BTreeMap<Name, BTreeMap<Version, BTreeSet<PkgVersionReviewId>>>>,
+
+    // most recent pkg_review_ids by package, newest first
+    package_reviews_by_date: HashMap<proof::PackageId, PkgReviewIdsByDate>,
+
+    // reviews that were (at some point) reviewing a range of versions
+    // (had a `diff_base`); only the most recent review for every
+    // pkg_review_id counts, so it has to be checked when querying
+    package_range_reviews: HashMap<proof::PackageId, BTreeSet<PkgVersionReviewId>>,
+
+    // pkg_review_ids of every author
+    package_reviews_by_author: HashMap<Id, BTreeSet<PkgVersionReviewId>>,
+
+    // the most relevant pkg_review_id of every author, by package
+    // (so effectively by `PkgReviewId`)
+    latest_package_reviews: HashMap<proof::PackageId, BTreeMap<Id, LatestPkgReviews>>,
 
-    package_flags: HashMap<proof::PackageId, HashMap<Id, TimestampedFlags>>,
+    package_flags: HashMap<proof::PackageId, BTreeMap<Id, TimestampedFlags>>,
+
+    // most recent review of every file, by every author, from code reviews
+    code_review_files:
+        HashMap<proof::PackageId, BTreeMap<PathBuf, BTreeMap<Id, TimestampedFileReview>>>,
+
+    // the same, by file digest: (author, package version, path) of every file
+    // with the digest, to find reviews of files shared by many packages
+    file_reviews_by_digest: HashMap<Vec<u8>, HashSet<(Id, proof::PackageVersionId, PathBuf)>>,
 
     // original data about pkg alternatives
     // for every package_id, we store a map of ids that had alternatives for it,
     // and a timestamped signature of the proof, so we keep track of only
     // the newest alternatives list for a `(PackageId, reporting Id)` pair
-    package_alternatives: HashMap<proof::PackageId, HashMap<Id, TimestampedSignature>>,
+    package_alternatives: HashMap<proof::PackageId, BTreeMap<Id, TimestampedSignature>>,
+
+    // sources of all the packages mentioned as alternatives, which don't have
+    // to have any reviews themselves; see `known_sources`
+    alternative_sources: BTreeSet<Source>,
+
+    // parts of `PkgVersionReviewId`s of the reviews, shared by all the indices;
+    // snapshots store a copy for every use, so it's filled again by
+    // `share_pkg_version_review_ids` once one is restored
+    #[cfg_attr(feature = "serde", serde(skip))]
+    interner: Interner,
 
     // derived data about pkg alternatives
     // it is hard to keep track of some data when proofs are being added
@@ -271,35 +1417,225 @@ pub struct ProofDB {
     // we don't keep track of it, until needed, and only then we just lazily
     // recalculate it
     insertion_counter: usize,
+    #[cfg_attr(feature = "serde", serde(skip))]
     derived_alternatives: sync::RwLock<AlternativesData>,
+    // the same goes for the index of review comments, see `search_review_comments`
+    #[cfg_attr(feature = "serde", serde(skip))]
+    derived_comment_index: sync::RwLock<search::CommentIndex>,
+
+    // trust sets calculated so far, by root Id; just like with the alternatives
+    // they are only valid until trust edges change, which is tracked by a counter
+    trust_insertion_counter: usize,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    cached_trust_sets: sync::RwLock<HashMap<Id, CachedTrustSet>>,
+
+    // proofs of kinds this version doesn't understand, kept only
+    // if `store_unknown_proofs` is set
+    store_unknown_proofs: bool,
+    #[cfg_attr(feature = "serde", serde(with = "snapshot::unknown_proofs"))]
+    unknown_proofs: Vec<(proof::Proof, FetchSource)>,
+
+    // the accepted proofs exactly as they were signed, by signature,
+    // if `keep_signed_proofs` is set; see `export_proofs`
+    keep_signed_proofs: bool,
+    #[cfg_attr(feature = "serde", serde(with = "snapshot::signed_proofs"))]
+    signed_proofs: HashMap<Signature, proof::Proof>,
+
+    // treat `http://` and `https://` URLs as the same when comparing them
+    ignore_url_scheme: bool,
+
+    // what time it is, for the expiration of proofs; a setting, not stored in snapshots
+    #[cfg_attr(feature = "serde", serde(skip))]
+    clock: Clock,
+
+    // what's known about the versions of packages, like the clock a setting
+    #[cfg_attr(feature = "serde", serde(skip, default = "default_version_oracle"))]
+    version_oracle: sync::Arc<dyn VersionOracle>,
+
+    // where every proof came from
+    fetched_from_by_signature: HashMap<Signature, FetchSourceInfo>,
+
+    // proofs by every place they came from, by `FetchSource::name`; recalculated
+    // from `fetched_from_by_signature` whenever anything is removed from it
+    repo_stats: BTreeMap<String, RepoStats>,
+
+    // who -(trusted, at these points in time)-> whom; only if `keep_trust_history` is set
+    keep_trust_history: bool,
+    trust_history: HashMap<Id, BTreeMap<Id, TrustLevelHistory>>,
+
+    // changes made by the proofs being imported, if anyone is interested
+    #[cfg_attr(feature = "serde", serde(skip))]
+    change_events: Option<Vec<ChangeEvent>>,
+
+    integrity_warnings: Vec<IntegrityWarning>,
 }
 
 impl Default for ProofDB {
     fn default() -> Self {
         ProofDB {
             trust_id_to_id: default(),
+            trust_reported_by: default(),
+            unsigned_trust_overrides: default(),
+            unsigned_override_precedence: default(),
+            self_trust: default(),
+            id_superseded_by: default(),
             url_by_id_self_reported: default(),
             url_by_id_reported_by_others: default(),
+            ids_by_url: default(),
+            url_history: default(),
             package_review_signatures_by_package_digest: default(),
             package_review_signatures_by_pkg_review_id: default(),
             package_review_by_signature: default(),
+            package_review_history: default(),
             package_reviews: default(),
+            package_reviews_by_date: default(),
+            package_range_reviews: default(),
+            package_reviews_by_author: default(),
+            latest_package_reviews: default(),
             package_alternatives: default(),
+            alternative_sources: default(),
+            interner: default(),
             package_flags: default(),
+            code_review_files: default(),
+            file_reviews_by_digest: default(),
 
             insertion_counter: 0,
             derived_alternatives: sync::RwLock::new(AlternativesData::new()),
+            derived_comment_index: sync::RwLock::new(search::CommentIndex::new()),
+
+            trust_insertion_counter: 0,
+            cached_trust_sets: default(),
+
+            store_unknown_proofs: false,
+            unknown_proofs: default(),
+            keep_signed_proofs: false,
+            signed_proofs: default(),
+
+            ignore_url_scheme: false,
+            clock: default(),
+            version_oracle: default_version_oracle(),
+
+            fetched_from_by_signature: default(),
+            repo_stats: default(),
+
+            keep_trust_history: false,
+            trust_history: default(),
+
+            change_events: None,
+
+            integrity_warnings: default(),
+        }
+    }
+}
+
+/// Copy of all the data and settings, e.g. to keep answering queries while the original
+/// is being modified
+///
+/// Reviews, and Ids and package versions referred to by the review indices
+/// are shared with the original, not copied. Lazily calculated data is shared
+/// too, while it's valid for both.
+impl Clone for ProofDB {
+    fn clone(&self) -> Self {
+        ProofDB {
+            trust_id_to_id: self.trust_id_to_id.clone(),
+            trust_reported_by: self.trust_reported_by.clone(),
+            unsigned_trust_overrides: self.unsigned_trust_overrides.clone(),
+            unsigned_override_precedence: self.unsigned_override_precedence,
+            self_trust: self.self_trust.clone(),
+            id_superseded_by: self.id_superseded_by.clone(),
+            url_by_id_self_reported: self.url_by_id_self_reported.clone(),
+            url_by_id_reported_by_others: self.url_by_id_reported_by_others.clone(),
+            ids_by_url: self.ids_by_url.clone(),
+            url_history: self.url_history.clone(),
+            package_review_signatures_by_package_digest: self
+                .package_review_signatures_by_package_digest
+                .clone(),
+            package_review_signatures_by_pkg_review_id: self
+                .package_review_signatures_by_pkg_review_id
+                .clone(),
+            package_review_by_signature: self.package_review_by_signature.clone(),
+            package_review_history: self.package_review_history.clone(),
+            package_reviews: self.package_reviews.clone(),
+            package_reviews_by_date: self.package_reviews_by_date.clone(),
+            package_range_reviews: self.package_range_reviews.clone(),
+            package_reviews_by_author: self.package_reviews_by_author.clone(),
+            latest_package_reviews: self.latest_package_reviews.clone(),
+            package_alternatives: self.package_alternatives.clone(),
+            alternative_sources: self.alternative_sources.clone(),
+            interner: self.interner.clone(),
+            package_flags: self.package_flags.clone(),
+            code_review_files: self.code_review_files.clone(),
+            file_reviews_by_digest: self.file_reviews_by_digest.clone(),
+
+            insertion_counter: self.insertion_counter,
+            // cheaper to recalculate than to copy
+            derived_alternatives: sync::RwLock::new(AlternativesData::new()),
+            derived_comment_index: sync::RwLock::new(search::CommentIndex::new()),
+
+            trust_insertion_counter: self.trust_insertion_counter,
+            cached_trust_sets: sync::RwLock::new(
+                self.cached_trust_sets.read().expect("lock to work").clone(),
+            ),
+
+            store_unknown_proofs: self.store_unknown_proofs,
+            unknown_proofs: self.unknown_proofs.clone(),
+            keep_signed_proofs: self.keep_signed_proofs,
+            signed_proofs: self.signed_proofs.clone(),
+
+            ignore_url_scheme: self.ignore_url_scheme,
+            clock: self.clock,
+            version_oracle: self.version_oracle.clone(),
+
+            fetched_from_by_signature: self.fetched_from_by_signature.clone(),
+            repo_stats: self.repo_stats.clone(),
+
+            keep_trust_history: self.keep_trust_history,
+            trust_history: self.trust_history.clone(),
+
+            change_events: None,
+
+            integrity_warnings: self.integrity_warnings.clone(),
+        }
+    }
+}
+
+impl Extend<ProofDB> for ProofDB {
+    fn extend<I: IntoIterator<Item = ProofDB>>(&mut self, iter: I) {
+        for other in iter {
+            self.merge(other);
         }
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
+#[non_exhaustive]
 pub struct IssueDetails {
+    /// Highest severity any of the reviews in `issues` and `advisories` reported the issue with
     pub severity: Level,
     /// Reviews that reported a given issue by `issues` field
-    pub issues: HashSet<PkgVersionReviewId>,
+    pub issues: BTreeSet<PkgVersionReviewId>,
     /// Reviews that reported a given issue by `advisories` field
-    pub advisories: HashSet<PkgVersionReviewId>,
+    pub advisories: BTreeSet<PkgVersionReviewId>,
+    /// The oldest version an advisory reported the issue as fixed in, if any
+    pub fixed_in: Option<Version>,
+    /// Authors of the reviews in `issues` and `advisories`
+    pub reporting_ids: HashSet<Id>,
+}
+
+/// Issues affecting a package with a given digest, see `ProofDB::get_open_issues_for_digest`
+#[derive(Default, Debug, Clone)]
+pub struct DigestIssues {
+    /// Issues of all the `versions`, by issue id
+    pub issues: BTreeMap<String, IssueDetails>,
+    /// Package versions reviews say the digest is
+    pub versions: Vec<proof::PackageVersionId>,
+}
+
+impl DigestIssues {
+    /// Whether reviews disagree about which package version the digest is
+    pub fn version_mismatch(&self) -> bool {
+        self.versions.len() > 1
+    }
 }
 
 impl ProofDB {
@@ -307,6 +1643,324 @@ impl ProofDB {
         default()
     }
 
+    /// Like `new`, but proofs of unknown kinds are kept instead of rejected
+    ///
+    /// They are not interpreted in any way, but can be listed with
+    /// `unknown_proofs`, e.g. to let the user know they should upgrade.
+    pub fn new_storing_unknown_proofs() -> Self {
+        ProofDB {
+            store_unknown_proofs: true,
+            ..default()
+        }
+    }
+
+    /// Keep proofs of unknown kinds, like `new_storing_unknown_proofs` does
+    ///
+    /// Affects only proofs imported afterwards.
+    pub fn set_store_unknown_proofs(&mut self, store: bool) {
+        self.store_unknown_proofs = store;
+    }
+
+    /// Treat `http://` and `https://` URLs as equal when comparing them
+    ///
+    /// Affects only proofs imported afterwards.
+    pub fn set_ignore_url_scheme(&mut self, ignore: bool) {
+        self.ignore_url_scheme = ignore;
+    }
+
+    /// Where the current time comes from (the system clock by default)
+    ///
+    /// Trust edges and package reviews expire at the time set in their proofs
+    /// (`expires`): from that instant on they are ignored by the trust set
+    /// calculation and the package review queries, unless re-affirmed by a newer proof.
+    pub fn set_clock(&mut self, clock: Clock) {
+        self.clock = clock;
+    }
+
+    pub fn clock(&self) -> Clock {
+        self.clock
+    }
+
+    /// Current time according to the `clock`
+    pub fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+
+    /// Where the `VersionStatus` of package versions comes from
+    ///
+    /// The default `NoVersionOracle` doesn't know anything about any version.
+    /// Used by queries like `get_pkg_reviews_for_name_existing`, to tell apart
+    /// reviews of versions that were yanked, or never existed.
+    pub fn set_version_oracle(&mut self, oracle: Box<dyn VersionOracle>) {
+        self.version_oracle = oracle.into();
+    }
+
+    /// Status of a package version according to the version oracle
+    pub fn get_version_status(&self, source: &str, name: &str, version: &Version) -> VersionStatus {
+        self.version_oracle.status(source, name, version)
+    }
+
+    /// Is the review neither retracted nor expired
+    fn is_in_effect(&self, review: &review::Package) -> bool {
+        !review.retracted
+            && !is_expired_at(
+                review.expires.map(|expires| expires.with_timezone(&Utc)),
+                self.now(),
+            )
+    }
+
+    /// Keep all the trust levels ever reported, not only the most recent ones
+    ///
+    /// Required for accurate `calculate_trust_set_at`. Affects only proofs imported afterwards.
+    pub fn set_keep_trust_history(&mut self, keep: bool) {
+        self.keep_trust_history = keep;
+    }
+
+    /// Keep every accepted proof exactly as it was signed, so it can be exported
+    ///
+    /// Required for `export_proofs`. Affects only proofs imported afterwards.
+    pub fn set_keep_signed_proofs(&mut self, keep: bool) {
+        self.keep_signed_proofs = keep;
+    }
+
+    /// The accepted proof with the `signature`, if signed proofs are kept
+    /// (see `set_keep_signed_proofs`)
+    pub fn get_signed_proof(&self, signature: &str) -> Option<&proof::Proof> {
+        self.signed_proofs.get(signature)
+    }
+
+    /// Proofs of kinds this version of `crev-wot` can't interpret
+    ///
+    /// Always empty, unless created with `new_storing_unknown_proofs`
+    /// (or `set_store_unknown_proofs` was used).
+    pub fn unknown_proofs(&self) -> &[(proof::Proof, FetchSource)] {
+        &self.unknown_proofs
+    }
+
+    /// Inconsistencies found in the proofs imported so far
+    pub fn integrity_warnings(&self) -> &[IntegrityWarning] {
+        &self.integrity_warnings
+    }
+
+    /// Number of `unknown_proofs` of every kind
+    pub fn unknown_proof_count_by_kind(&self) -> BTreeMap<&str, usize> {
+        let mut res = BTreeMap::new();
+        for (proof, _) in &self.unknown_proofs {
+            *res.entry(proof.kind()).or_default() += 1;
+        }
+        res
+    }
+
+    /// Remove all the `unknown_proofs` of `kind`, e.g. to be handled by a newer component
+    ///
+    /// They are forgotten completely, so importing them again adds them anew
+    /// (e.g. after upgrading to a version that understands them).
+    /// URLs of Ids recorded from them are kept.
+    pub fn take_unknown_proofs(&mut self, kind: &str) -> Vec<(proof::Proof, FetchSource)> {
+        let (taken, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut self.unknown_proofs)
+            .into_iter()
+            .partition(|(proof, _)| proof.kind() == kind);
+        self.unknown_proofs = kept;
+        if !taken.is_empty() {
+            for (proof, _) in &taken {
+                self.fetched_from_by_signature.remove(proof.signature());
+            }
+            self.recalculate_repo_stats();
+        }
+        taken
+    }
+
+    /// Release memory of derived data, to be recalculated when needed again
+    pub fn clear_derived_caches(&mut self) {
+        let derived = self.derived_alternatives.get_mut().expect("lock to work");
+        derived.wipe();
+        derived.for_pkg.shrink_to_fit();
+        derived.reported_by.shrink_to_fit();
+        let comments = self.derived_comment_index.get_mut().expect("lock to work");
+        comments.wipe();
+        comments.shrink_to_fit();
+    }
+
+    /// Release spare capacity of all internal indices
+    ///
+    /// Useful after importing all the proofs, if the `ProofDB` is going to be kept around.
+    pub fn shrink_to_fit(&mut self) {
+        self.trust_id_to_id.shrink_to_fit();
+        self.trust_reported_by.shrink_to_fit();
+        self.id_superseded_by.shrink_to_fit();
+        self.trust_history.shrink_to_fit();
+        for history in self
+            .trust_history
+            .values_mut()
+            .flat_map(BTreeMap::values_mut)
+        {
+            history.shrink_to_fit();
+        }
+        self.url_by_id_self_reported.shrink_to_fit();
+        self.url_history.shrink_to_fit();
+        self.url_by_id_reported_by_others.shrink_to_fit();
+        self.package_review_by_signature.shrink_to_fit();
+        for review in self.package_review_by_signature.values_mut() {
+            // reviews shared with the caller are left alone
+            if let Some(review) = sync::Arc::get_mut(review) {
+                review.issues.shrink_to_fit();
+                review.advisories.shrink_to_fit();
+                review.alternatives.shrink_to_fit();
+            }
+        }
+        self.package_review_signatures_by_pkg_review_id
+            .shrink_to_fit();
+        self.package_review_history.shrink_to_fit();
+        self.package_reviews_by_date.shrink_to_fit();
+        self.package_range_reviews.shrink_to_fit();
+        self.package_reviews_by_author.shrink_to_fit();
+        self.latest_package_reviews.shrink_to_fit();
+        self.package_flags.shrink_to_fit();
+        self.code_review_files.shrink_to_fit();
+        self.file_reviews_by_digest.shrink_to_fit();
+        self.package_alternatives.shrink_to_fit();
+        self.unknown_proofs.shrink_to_fit();
+        self.signed_proofs.shrink_to_fit();
+        self.fetched_from_by_signature.shrink_to_fit();
+        self.interner.release_unused();
+
+        let derived = self.derived_alternatives.get_mut().expect("lock to work");
+        derived.for_pkg.shrink_to_fit();
+        derived.reported_by.shrink_to_fit();
+        self.derived_comment_index
+            .get_mut()
+            .expect("lock to work")
+            .shrink_to_fit();
+    }
+
+    /// Make all the indices use the same copies of the `PkgVersionReviewId` parts again
+    ///
+    /// Every copy is a separate allocation after restoring a snapshot.
+    #[cfg(feature = "serde")]
+    fn share_pkg_version_review_ids(&mut self) {
+        let interner = &mut self.interner;
+        let mut share = |id| interner.share_pkg_version_review_id(id);
+
+        for reviews in self
+            .package_review_signatures_by_package_digest
+            .values_mut()
+        {
+            *reviews = std::mem::take(reviews)
+                .into_iter()
+                .map(|(id, signature)| (share(id), signature))
+                .collect();
+        }
+        self.package_review_signatures_by_pkg_review_id =
+            std::mem::take(&mut self.package_review_signatures_by_pkg_review_id)
+                .into_iter()
+                .map(|(id, signature)| (share(id), signature))
+                .collect();
+        self.package_review_history = std::mem::take(&mut self.package_review_history)
+            .into_iter()
+            .map(|(id, history)| (share(id), history))
+            .collect();
+        for ids in self
+            .package_reviews
+            .values_mut()
+            .flat_map(|by_name| by_name.values_mut())
+            .flat_map(|by_version| by_version.values_mut())
+            .chain(self.package_range_reviews.values_mut())
+            .chain(self.package_reviews_by_author.values_mut())
+        {
+            *ids = std::mem::take(ids).into_iter().map(&mut share).collect();
+        }
+        for ids in self.package_reviews_by_date.values_mut() {
+            *ids = std::mem::take(ids)
+                .into_iter()
+                .map(|(date, id)| (date, share(id)))
+                .collect();
+        }
+        for latest in self
+            .latest_package_reviews
+            .values_mut()
+            .flat_map(|by_author| by_author.values_mut())
+        {
+            latest.by_date.value = share(latest.by_date.value.clone());
+            latest.by_version.value = share(latest.by_version.value.clone());
+        }
+    }
+
+    /// Number of entries in every internal index
+    pub fn approximate_memory_usage(&self) -> MemoryStats {
+        let derived = self.derived_alternatives.read().expect("lock to work");
+        MemoryStats {
+            trust_edges: self.trust_id_to_id.values().map(BTreeMap::len).sum(),
+            self_trust: self.self_trust.len(),
+            id_supersessions: self.id_superseded_by.len(),
+            trust_history: self
+                .trust_history
+                .values()
+                .flat_map(BTreeMap::values)
+                .map(Vec::len)
+                .sum(),
+            urls_self_reported: self.url_by_id_self_reported.len(),
+            url_history: self.url_history.values().map(BTreeMap::len).sum(),
+            urls_reported_by_others: self.url_by_id_reported_by_others.len(),
+            url_claims: self.ids_by_url.values().map(BTreeSet::len).sum(),
+            package_reviews: self.package_review_by_signature.len(),
+            package_reviews_by_digest: self
+                .package_review_signatures_by_package_digest
+                .values()
+                .map(BTreeMap::len)
+                .sum(),
+            package_reviews_by_pkg_review_id: self.package_review_signatures_by_pkg_review_id.len(),
+            package_review_history: self
+                .package_review_history
+                .values()
+                .map(BTreeSet::len)
+                .sum(),
+            package_reviews_by_date: self
+                .package_reviews_by_date
+                .values()
+                .map(BTreeSet::len)
+                .sum(),
+            package_reviews_by_version: self
+                .package_reviews
+                .values()
+                .flat_map(BTreeMap::values)
+                .flat_map(BTreeMap::values)
+                .map(BTreeSet::len)
+                .sum(),
+            package_range_reviews: self.package_range_reviews.values().map(BTreeSet::len).sum(),
+            package_reviews_by_author: self
+                .package_reviews_by_author
+                .values()
+                .map(BTreeSet::len)
+                .sum(),
+            latest_package_reviews: self
+                .latest_package_reviews
+                .values()
+                .map(BTreeMap::len)
+                .sum(),
+            package_flags: self.package_flags.values().map(BTreeMap::len).sum(),
+            code_review_files: self
+                .code_review_files
+                .values()
+                .flat_map(BTreeMap::values)
+                .map(BTreeMap::len)
+                .sum(),
+            file_reviews_by_digest: self.file_reviews_by_digest.values().map(HashSet::len).sum(),
+            package_alternatives: self.package_alternatives.values().map(BTreeMap::len).sum(),
+            alternative_sources: self.alternative_sources.len(),
+            derived_alternatives: derived.reported_by.len(),
+            derived_comment_index: self
+                .derived_comment_index
+                .read()
+                .expect("lock to work")
+                .len(),
+            cached_trust_sets: self.cached_trust_sets.read().expect("lock to work").len(),
+            unknown_proofs: self.unknown_proofs.len(),
+            signed_proofs: self.signed_proofs.len(),
+            fetch_sources: self.fetched_from_by_signature.len(),
+            repo_stats: self.repo_stats.len(),
+        }
+    }
+
     fn get_derived_alternatives<'s>(&'s self) -> sync::RwLockReadGuard<'s, AlternativesData> {
         {
             let read = self.derived_alternatives.read().expect("lock to work");
@@ -340,13 +1994,13 @@ impl ProofDB {
         &'s self,
         from: &'a Id,
         pkg_id: &'a proof::PackageId,
-    ) -> HashSet<proof::PackageId> {
+    ) -> BTreeSet<proof::PackageId> {
         let from = from.to_owned();
 
         let alternatives = self.get_derived_alternatives();
         alternatives
             .for_pkg
-            .get(pkg_id)
+            .get(&canonical_package_id(&pkg_id.source, &pkg_id.name))
             .into_iter()
             .flat_map(move |i| i.get(&from))
             .flatten()
@@ -357,12 +2011,12 @@ impl ProofDB {
     pub fn get_pkg_alternatives<'s, 'a>(
         &'s self,
         pkg_id: &'a proof::PackageId,
-    ) -> HashSet<(Id, proof::PackageId)> {
+    ) -> BTreeSet<(Id, proof::PackageId)> {
         let alternatives = self.get_derived_alternatives();
 
         alternatives
             .for_pkg
-            .get(pkg_id)
+            .get(&canonical_package_id(&pkg_id.source, &pkg_id.name))
             .into_iter()
             .flat_map(move |i| i.iter())
             .flat_map(move |(id, pkg_ids)| {
@@ -371,29 +2025,216 @@ impl ProofDB {
             .collect()
     }
 
-    pub fn get_pkg_flags_by_author<'s, 'a>(
-        &'s self,
-        from: &'a Id,
-        pkg_id: &'a proof::PackageId,
-    ) -> Option<&'s proof::Flags> {
-        let from = from.to_owned();
-        self.package_flags
-            .get(pkg_id)
-            .and_then(move |i| i.get(&from))
-            .map(move |timestampted| &timestampted.value)
-    }
+    /// Alternatives to a package reported by Ids with at least `min_level` of effective trust
+    ///
+    /// Best suggestions come first: the ones reported by most Ids, then
+    /// the ones with most positive reviews. Distrusted Ids never count.
+    pub fn get_trusted_pkg_alternatives(
+        &self,
+        pkg_id: &proof::PackageId,
+        trust_set: &TrustSet,
+        min_level: TrustLevel,
+    ) -> Vec<AlternativeSuggestion> {
+        let trust_set = trust_set.for_source(&pkg_id.source);
+        let is_trusted = |id: &Id| {
+            !trust_set.is_distrusted(id) && min_level <= trust_set.get_effective_trust_level(id)
+        };
+
+        let mut reporter_counts: BTreeMap<proof::PackageId, usize> = BTreeMap::new();
+        for (id, alternatives) in self
+            .get_derived_alternatives()
+            .for_pkg
+            .get(&canonical_package_id(&pkg_id.source, &pkg_id.name))
+            .into_iter()
+            .flatten()
+        {
+            if is_trusted(id) {
+                for alternative in alternatives {
+                    *reporter_counts.entry(alternative.clone()).or_default() += 1;
+                }
+            }
+        }
 
-    pub fn get_pkg_flags<'s, 'a>(
-        &'s self,
-        pkg_id: &'a proof::PackageId,
-    ) -> impl Iterator<Item = (&Id, &'s proof::Flags)> {
-        self.package_flags
-            .get(pkg_id)
+        let mut suggestions: Vec<_> = reporter_counts
+            .into_iter()
+            .map(|(pkg_id, reporter_count)| {
+                let positive_review_count = self
+                    .get_pkg_reviews_for_name(&pkg_id.source, &pkg_id.name)
+                    .filter(|review| is_trusted(&review.from().id))
+                    .filter(
+                        |review| matches!(review.review(), Some(r) if Rating::Positive <= r.rating),
+                    )
+                    .count();
+                AlternativeSuggestion {
+                    pkg_id,
+                    reporter_count,
+                    positive_review_count,
+                }
+            })
+            .collect();
+        // stable sort, so equally good ones stay ordered by `PackageId`
+        suggestions.sort_by(|a, b| {
+            (b.reporter_count, b.positive_review_count)
+                .cmp(&(a.reporter_count, a.positive_review_count))
+        });
+        suggestions
+    }
+
+    /// Alternatives to a package, and alternatives to these, etc., as reported
+    /// by Ids with at least `min_level` of effective trust
+    ///
+    /// The alternatives are followed breadth-first, up to `max_depth` hops away,
+    /// but not past packages that trusted Ids have no concerns about (see
+    /// `AlternativeChainHop::is_endpoint`). Every package is reached at most once,
+    /// so cycles of alternatives (like a package being an alternative to its own
+    /// alternative) are not followed. Hops are ordered by depth, then by `PackageId`s.
+    pub fn resolve_alternative_chain(
+        &self,
+        pkg_id: &proof::PackageId,
+        trust_set: &TrustSet,
+        min_level: TrustLevel,
+        max_depth: usize,
+    ) -> Vec<AlternativeChainHop> {
+        let trust_set = trust_set.for_source(&pkg_id.source);
+        let is_trusted = |id: &Id| {
+            !trust_set.is_distrusted(id) && min_level <= trust_set.get_effective_trust_level(id)
+        };
+        let alternatives = self.get_derived_alternatives();
+        let pkg_id = canonical_package_id(&pkg_id.source, &pkg_id.name);
+
+        let mut hops = vec![];
+        let mut reached = BTreeSet::new();
+        reached.insert(pkg_id.clone());
+        let mut queue = VecDeque::new();
+        queue.push_back((pkg_id, 0));
+        while let Some((from, depth)) = queue.pop_front() {
+            if depth >= max_depth {
+                continue;
+            }
+            let mut reporters_by_alternative: BTreeMap<&proof::PackageId, BTreeSet<Id>> =
+                BTreeMap::new();
+            for (id, pkg_ids) in alternatives.for_pkg.get(&from).into_iter().flatten() {
+                if is_trusted(id) {
+                    for to in pkg_ids {
+                        reporters_by_alternative
+                            .entry(to)
+                            .or_default()
+                            .insert(id.clone());
+                    }
+                }
+            }
+            for (to, reporters) in reporters_by_alternative {
+                if !reached.insert(to.clone()) {
+                    continue;
+                }
+                let mut has_positive_reviews = false;
+                let mut has_concerns = false;
+                for review in self
+                    .get_pkg_reviews_for_name(&to.source, &to.name)
+                    .filter(|review| is_trusted(&review.from().id))
+                {
+                    let rating = review.review().map(|review| review.rating);
+                    has_positive_reviews |= matches!(rating, Some(r) if Rating::Positive <= r);
+                    has_concerns |= rating == Some(Rating::Negative)
+                        || !review.issues.is_empty()
+                        || review.flags.unmaintained;
+                }
+                if has_concerns {
+                    queue.push_back((to.clone(), depth + 1));
+                }
+                hops.push(AlternativeChainHop {
+                    from: from.clone(),
+                    to: to.clone(),
+                    depth: depth + 1,
+                    reporters,
+                    has_positive_reviews,
+                    is_endpoint: !has_concerns,
+                });
+            }
+        }
+        hops
+    }
+
+    pub fn get_pkg_flags_by_author<'s, 'a>(
+        &'s self,
+        from: &'a Id,
+        pkg_id: &'a proof::PackageId,
+    ) -> Option<&'s proof::Flags> {
+        let from = from.to_owned();
+        self.package_flags
+            .get(&canonical_package_id(&pkg_id.source, &pkg_id.name))
+            .and_then(move |i| i.get(&from))
+            .map(move |timestampted| &timestampted.value)
+    }
+
+    pub fn get_pkg_flags<'s, 'a>(
+        &'s self,
+        pkg_id: &'a proof::PackageId,
+    ) -> impl Iterator<Item = (&Id, &'s proof::Flags)> {
+        self.package_flags
+            .get(&canonical_package_id(&pkg_id.source, &pkg_id.name))
             .into_iter()
             .flat_map(move |i| i.iter())
             .map(|(id, flags)| (id, &flags.value))
     }
 
+    /// Flags of a package, counted only for Ids with at least `min_level` of effective trust
+    ///
+    /// Only the most recent review of every Id counts, and distrusted Ids never do.
+    pub fn get_pkg_flags_summary(
+        &self,
+        pkg_id: &proof::PackageId,
+        trust_set: &TrustSet,
+        min_level: TrustLevel,
+    ) -> FlagsSummary {
+        let trust_set = trust_set.for_source(&pkg_id.source);
+        let mut summary = FlagsSummary::default();
+        let pkg_id = canonical_package_id(&pkg_id.source, &pkg_id.name);
+        for (id, flags) in self.package_flags.get(&pkg_id).into_iter().flatten() {
+            if trust_set.is_distrusted(id) {
+                continue;
+            }
+            let level = trust_set.get_effective_trust_level(id);
+            if level < min_level {
+                continue;
+            }
+            if flags.value.unmaintained {
+                summary.unmaintained.record(level, flags.date);
+            }
+        }
+        summary
+    }
+
+    /// Flags of a package that are set according to the `policy`
+    ///
+    /// Only the most recent review of every Id trusted enough counts,
+    /// and distrusted Ids never do.
+    pub fn get_effective_flags(
+        &self,
+        pkg_id: &proof::PackageId,
+        trust_set: &TrustSet,
+        policy: &FlagPolicy,
+    ) -> EffectiveFlags {
+        let trust_set = trust_set.for_source(&pkg_id.source);
+        let mut flags = EffectiveFlags::default();
+        let pkg_id = canonical_package_id(&pkg_id.source, &pkg_id.name);
+        for (id, reported) in self.package_flags.get(&pkg_id).into_iter().flatten() {
+            if trust_set.is_distrusted(id)
+                || trust_set.get_effective_trust_level(id) < policy.min_level
+            {
+                continue;
+            }
+            let unmaintained = &mut flags.unmaintained;
+            if reported.value.unmaintained {
+                unmaintained.set_by.insert(id.clone());
+            } else {
+                unmaintained.not_set_by.insert(id.clone());
+            }
+        }
+        flags.unmaintained.is_set = policy.rule.is_set(&flags.unmaintained);
+        flags
+    }
+
     pub fn get_pkg_reviews_for_source<'a, 'b>(
         &'a self,
         source: &'b str,
@@ -408,797 +2249,5762 @@ impl ProofDB {
                 self.get_pkg_review_by_pkg_review_id(pkg_review_id)
                     .expect("exists")
             })
+            .filter(move |review| self.is_in_effect(review))
     }
 
-    pub fn get_pkg_reviews_for_name<'a, 'b, 'c: 'a>(
+    pub fn get_pkg_reviews_for_name<'a>(
         &'a self,
-        source: &'b str,
-        name: &'c str,
+        source: &str,
+        name: &str,
     ) -> impl Iterator<Item = &'a proof::review::Package> {
+        let name = canonicalize_package_name(source, name);
         self.package_reviews
             .get(source)
             .into_iter()
-            .flat_map(move |map| map.get(name))
+            .flat_map(move |map| map.get(&name))
             .flat_map(move |map| map.iter())
             .flat_map(|(_, v)| v)
             .map(move |pkg_review_id| {
                 self.get_pkg_review_by_pkg_review_id(pkg_review_id)
                     .expect("exists")
             })
+            .filter(move |review| self.is_in_effect(review))
     }
 
-    pub fn get_pkg_reviews_for_version<'a, 'b, 'c: 'a, 'd: 'a>(
+    /// Like `get_pkg_reviews_for_name`, but only the reviews the `filter` accepts
+    pub fn get_pkg_reviews_for_name_filtered<'a>(
         &'a self,
-        source: &'b str,
-        name: &'c str,
-        version: &'d Version,
+        source: &str,
+        name: &str,
+        filter: &'a ReviewFilter,
     ) -> impl Iterator<Item = &'a proof::review::Package> {
+        self.get_pkg_reviews_for_name(source, name)
+            .filter(filter.predicate(self.now()))
+    }
+
+    /// Names of the reviewed packages of `source` starting with `prefix`, in order
+    ///
+    /// The `prefix` is canonicalized just like package names are
+    /// (see `canonicalize_package_name`), but otherwise it's case sensitive.
+    pub fn search_package_names<'a>(
+        &'a self,
+        source: &str,
+        prefix: &str,
+    ) -> impl Iterator<Item = &'a Name> {
+        let prefix = canonicalize_package_name(source, prefix);
         self.package_reviews
             .get(source)
             .into_iter()
-            .flat_map(move |map| map.get(name))
-            .flat_map(move |map| map.get(version))
+            .flat_map(move |by_name| {
+                let prefix = prefix.clone();
+                by_name
+                    .range(prefix.clone()..)
+                    .map(|(name, _)| name)
+                    .take_while(move |name| name.starts_with(&prefix))
+            })
+    }
+
+    /// One review of a package by every author who reviewed it, ordered by author Id
+    ///
+    /// Of all the versions reviewed by an author, the one picked is either
+    /// the most recently reviewed (ties broken by the newer version), or
+    /// the newest version (ties broken by the more recent review). Retracted
+    /// and expired reviews are never picked, so if an author retracted the review
+    /// of their latest version, the latest of their other reviews is returned.
+    pub fn get_latest_review_per_author<'a>(
+        &'a self,
+        source: &str,
+        name: &str,
+        by: LatestBy,
+    ) -> impl Iterator<Item = &'a proof::review::Package> {
+        let pkg_id = canonical_package_id(source, name);
+        self.latest_package_reviews
+            .get(&pkg_id)
+            .into_iter()
             .flatten()
-            .map(move |pkg_review_id| {
-                self.get_pkg_review_by_pkg_review_id(pkg_review_id)
-                    .expect("exists")
+            .filter_map(move |(author, latest)| {
+                let review = self
+                    .get_pkg_review_by_pkg_review_id(latest.get(by))
+                    .expect("exists");
+                if self.is_in_effect(review) {
+                    Some(review)
+                } else {
+                    self.get_latest_review_in_effect(author, &pkg_id, by)
+                }
             })
     }
 
-    pub fn get_pkg_reviews_gte_version<'a, 'b, 'c: 'a, 'd: 'a>(
+    /// Like the `LatestPkgReviews` of `author`, but picked only among the reviews in effect
+    ///
+    /// Slow, but only needed when the indexed review is retracted or expired.
+    fn get_latest_review_in_effect(
+        &self,
+        author: &Id,
+        canonical_pkg_id: &proof::PackageId,
+        by: LatestBy,
+    ) -> Option<&proof::review::Package> {
+        let mut latest: Option<LatestPkgReviews> = None;
+        for pkg_review_id in self
+            .package_reviews_by_author
+            .get(author)
+            .into_iter()
+            .flatten()
+        {
+            let pkg_id = &pkg_review_id.package_version_id.id;
+            if canonical_package_id(&pkg_id.source, &pkg_id.name) != *canonical_pkg_id {
+                continue;
+            }
+            let signature = &self.package_review_signatures_by_pkg_review_id[pkg_review_id];
+            if !self.is_in_effect(&self.package_review_by_signature[&signature.value]) {
+                continue;
+            }
+            let review = TimestampedPkgVersionReviewId {
+                date: signature.date,
+                value: pkg_review_id.clone(),
+            };
+            match &mut latest {
+                Some(latest) => latest.update(&review),
+                None => latest = Some(LatestPkgReviews::new(review)),
+            }
+        }
+        latest.map(|latest| {
+            self.get_pkg_review_by_pkg_review_id(latest.get(by))
+                .expect("exists")
+        })
+    }
+
+    /// All package versions reviewed by `id`, ordered by package
+    ///
+    /// Only the most recent review of every package version is returned.
+    pub fn get_pkg_reviews_by_author<'a>(
         &'a self,
-        source: &'b str,
-        name: &'c str,
-        version: &'d Version,
+        id: &Id,
     ) -> impl Iterator<Item = &'a proof::review::Package> {
-        self.package_reviews
-            .get(source)
+        self.package_reviews_by_author
+            .get(id)
             .into_iter()
-            .flat_map(move |map| map.get(name))
-            .flat_map(move |map| map.range(version..))
-            .flat_map(move |(_, v)| v)
+            .flatten()
             .map(move |pkg_review_id| {
                 self.get_pkg_review_by_pkg_review_id(pkg_review_id)
                     .expect("exists")
             })
+            .filter(move |review| self.is_in_effect(review))
     }
 
-    pub fn get_pkg_reviews_lte_version<'a, 'b, 'c: 'a, 'd: 'a>(
+    /// Most recent reviews of files of a package from code reviews, ordered by path and author
+    pub fn get_code_reviews_for_package<'a>(
         &'a self,
-        source: &'b str,
-        name: &'c str,
-        version: &'d Version,
-    ) -> impl Iterator<Item = &'a proof::review::Package> {
+        pkg_id: &proof::PackageId,
+    ) -> impl Iterator<Item = (&'a Path, &'a Id, &'a Timestamped<FileReview>)> {
+        self.code_review_files
+            .get(&canonical_package_id(&pkg_id.source, &pkg_id.name))
+            .into_iter()
+            .flatten()
+            .flat_map(|(path, reviews)| {
+                reviews
+                    .iter()
+                    .map(move |(id, file_review)| (path.as_path(), id, file_review))
+            })
+    }
+
+    /// Files of a package reviewed by Ids with at least `min_level` of effective trust
+    ///
+    /// Reviews of different authors can be of different versions of the package,
+    /// so digests of the same file can differ.
+    pub fn get_reviewed_files<'a>(
+        &'a self,
+        pkg_id: &proof::PackageId,
+        trust_set: &TrustSet,
+        min_level: TrustLevel,
+    ) -> BTreeMap<&'a Path, BTreeMap<&'a Id, &'a Timestamped<FileReview>>> {
+        let trust_set = trust_set.for_source(&pkg_id.source);
+        let mut files: BTreeMap<_, BTreeMap<_, _>> = BTreeMap::new();
+        for (path, id, file_review) in self.get_code_reviews_for_package(pkg_id) {
+            if !trust_set.is_distrusted(id) && trust_set.get_effective_trust_level(id) >= min_level
+            {
+                files.entry(path).or_default().insert(id, file_review);
+            }
+        }
+        files
+    }
+
+    /// Who reviewed a file with the `digest`, in which package version, and at which path
+    ///
+    /// Just like in `get_code_reviews_for_package`, only the most recent review of
+    /// every file of a package by every author counts, but the same file can be part
+    /// of many packages. With a `trust_set`, only Ids with at least `min_level` of
+    /// effective trust (for the package's source) are returned. Ordered by Id.
+    pub fn get_file_reviews_by_digest<'a>(
+        &'a self,
+        digest: &[u8],
+        trust_set: Option<&TrustSet>,
+        min_level: TrustLevel,
+    ) -> Vec<(&'a Id, &'a proof::PackageVersionId, &'a Path)> {
+        let mut reviews: Vec<_> = self
+            .iter_file_reviews_by_digest(digest, trust_set, min_level)
+            .map(|(id, pkg_version_id, path)| (id, pkg_version_id, path.as_path()))
+            .collect();
+        reviews.sort();
+        reviews
+    }
+
+    fn iter_file_reviews_by_digest<'a: 't, 't>(
+        &'a self,
+        digest: &[u8],
+        trust_set: Option<&'t TrustSet>,
+        min_level: TrustLevel,
+    ) -> impl Iterator<Item = &'a (Id, proof::PackageVersionId, PathBuf)> + 't {
+        self.file_reviews_by_digest
+            .get(digest)
+            .into_iter()
+            .flatten()
+            .filter(move |(id, pkg_version_id, _)| match trust_set {
+                Some(trust_set) => {
+                    let trust_set = trust_set.for_source(&pkg_version_id.id.source);
+                    !trust_set.is_distrusted(id)
+                        && trust_set.get_effective_trust_level(id) >= min_level
+                }
+                None => true,
+            })
+    }
+
+    /// How many of the `digests` (e.g. of all the files of a package) were reviewed anywhere
+    ///
+    /// See `get_file_reviews_by_digest`.
+    pub fn count_reviewed_files_matching(
+        &self,
+        digests: &[Vec<u8>],
+        trust_set: Option<&TrustSet>,
+        min_level: TrustLevel,
+    ) -> usize {
+        digests
+            .iter()
+            .filter(|digest| {
+                self.iter_file_reviews_by_digest(digest, trust_set, min_level)
+                    .next()
+                    .is_some()
+            })
+            .count()
+    }
+
+    /// Canonical names of reviewed packages from `source`, starting with `query`
+    pub fn find_package_names(&self, source: &str, query: &str) -> Vec<&str> {
+        let prefix = canonicalize_package_name(source, query);
         self.package_reviews
             .get(source)
             .into_iter()
-            .flat_map(move |map| map.get(name))
-            .flat_map(move |map| map.range(..=version))
-            .flat_map(|(_, v)| v)
-            .map(move |pkg_review_id| {
+            .flat_map(|map| {
+                map.range::<str, _>((Bound::Included(prefix.as_str()), Bound::Unbounded))
+            })
+            .map(|(name, _)| name.as_str())
+            .take_while(|name| name.starts_with(prefix.as_str()))
+            .collect()
+    }
+
+    /// Get one page of reviews of a given package, and the total number of them
+    ///
+    /// For a given `order`, the order of reviews is always the same, so
+    /// consecutive pages don't overlap. Unlike other queries, this one includes
+    /// retractions (see `review::Package::retracted`), so the history can be displayed.
+    pub fn get_pkg_reviews_for_name_page(
+        &self,
+        source: &str,
+        name: &str,
+        offset: usize,
+        limit: usize,
+        order: ReviewOrder,
+    ) -> (Vec<&review::Package>, usize) {
+        let by_version = self
+            .package_reviews
+            .get(source)
+            .and_then(|map| map.get(&canonicalize_package_name(source, name)));
+        let total = by_version.map_or(0, |map| map.values().map(BTreeSet::len).sum());
+
+        let pkg_review_ids: Vec<&PkgVersionReviewId> = match order {
+            ReviewOrder::ByVersionDesc => by_version
+                .into_iter()
+                .flat_map(|map| map.values().rev())
+                .flatten()
+                .skip(offset)
+                .take(limit)
+                .collect(),
+            ReviewOrder::ByDateDesc => self
+                .package_reviews_by_date
+                .get(&canonical_package_id(source, name))
+                .into_iter()
+                .flatten()
+                .map(|(_, pkg_review_id)| pkg_review_id)
+                .skip(offset)
+                .take(limit)
+                .collect(),
+            ReviewOrder::ByAuthor => {
+                let mut all: Vec<_> = by_version
+                    .into_iter()
+                    .flat_map(|map| map.values().rev())
+                    .flatten()
+                    .collect();
+                // stable sort, so versions stay in descending order
+                all.sort_by(|a, b| a.from.cmp(&b.from));
+                all.into_iter().skip(offset).take(limit).collect()
+            }
+        };
+
+        let reviews = pkg_review_ids
+            .into_iter()
+            .map(|pkg_review_id| {
                 self.get_pkg_review_by_pkg_review_id(pkg_review_id)
                     .expect("exists")
             })
+            .collect();
+
+        (reviews, total)
     }
 
-    pub fn get_pkg_review_by_pkg_review_id(
+    /// One page of the reviews `get_package_reviews_for_package_sorted_ref` would return
+    ///
+    /// Newest first reviews of a single package come straight from an index
+    /// kept in date order, so they aren't all collected and sorted on every
+    /// call; reviews with the same date are then ordered by author, then version.
+    /// Other orders sort all the reviews, like `get_package_reviews_for_package_sorted_ref`
+    /// without a trust set.
+    pub fn get_pkg_reviews_page(
         &self,
-        uniq: &PkgVersionReviewId,
-    ) -> Option<&proof::review::Package> {
-        let signature = &self
-            .package_review_signatures_by_pkg_review_id
-            .get(uniq)?
-            .value;
-        self.package_review_by_signature.get(signature)
+        source: &str,
+        name: Option<&str>,
+        order: ReviewSortOrder,
+        offset: usize,
+        limit: usize,
+    ) -> ReviewPage<'_> {
+        match (name, order) {
+            (Some(name), ReviewSortOrder::DateDesc) => ReviewPage::of(
+                self.package_reviews_by_date
+                    .get(&canonical_package_id(source, name))
+                    .into_iter()
+                    .flatten()
+                    .map(|(_, pkg_review_id)| {
+                        self.get_pkg_review_by_pkg_review_id(pkg_review_id)
+                            .expect("exists")
+                    })
+                    .filter(move |review| self.is_in_effect(review)),
+                offset,
+                limit,
+            ),
+            _ => ReviewPage::of(
+                self.get_package_review_arcs_sorted(source, name, None, order, None)
+                    .into_iter()
+                    .map(|review| &**review),
+                offset,
+                limit,
+            ),
+        }
     }
 
-    pub fn get_pkg_review<'a, 'b, 'c: 'a, 'd: 'a>(
+    /// Most recent reviews of `version` by every author, unless retracted or expired
+    pub fn get_pkg_reviews_for_version<'a, 'b, 'c: 'a, 'd: 'a>(
         &'a self,
         source: &'b str,
         name: &'c str,
         version: &'d Version,
-        id: &Id,
-    ) -> Option<&proof::review::Package> {
-        self.get_pkg_reviews_for_version(source, name, version)
-            .find(|pkg_review| pkg_review.from().id == *id)
+    ) -> impl Iterator<Item = &'a proof::review::Package> {
+        self.get_pkg_reviews_for_version_including_retracted(source, name, version)
+            .filter(move |review| self.is_in_effect(review))
     }
 
-    pub fn get_advisories<'a, 'b, 'c: 'a, 'd: 'a>(
+    /// Like `get_pkg_reviews_for_version`, but only the ones rating the package
+    /// positively (or better)
+    pub fn get_pkg_endorsements_for_version<'a, 'b, 'c: 'a, 'd: 'a>(
         &'a self,
         source: &'b str,
-        name: Option<&'c str>,
-        version: Option<&'d Version>,
-    ) -> impl Iterator<Item = &'a proof::review::Package> + 'a {
-        match (name, version) {
-            (Some(ref name), Some(ref version)) => {
-                Box::new(self.get_advisories_for_version(source, name, version))
-                    as Box<dyn Iterator<Item = _>>
-            }
-
-            (Some(ref name), None) => Box::new(self.get_advisories_for_package(source, name)),
-            (None, None) => Box::new(self.get_advisories_for_source(source)),
-            (None, Some(_)) => panic!("Wrong usage"),
-        }
+        name: &'c str,
+        version: &'d Version,
+    ) -> impl Iterator<Item = &'a proof::review::Package> {
+        self.get_pkg_reviews_for_version(source, name, version)
+            .filter(|review| review.review_possibly_none().rating >= Rating::Positive)
     }
 
-    pub fn get_pkg_reviews_with_issues_for<'a, 'b, 'c: 'a, 'd: 'a>(
+    /// Like `get_pkg_reviews_for_version`, but only the ones rating the package negatively
+    pub fn get_pkg_negative_reviews_for_version<'a, 'b, 'c: 'a, 'd: 'a>(
         &'a self,
         source: &'b str,
-        name: Option<&'c str>,
-        version: Option<&'c Version>,
-        trust_set: &'d TrustSet,
-        trust_level_required: TrustLevel,
-    ) -> impl Iterator<Item = &proof::review::Package> {
-        match (name, version) {
-            (Some(name), Some(version)) => Box::new(self.get_pkg_reviews_with_issues_for_version(
-                source,
-                name,
-                version,
-                trust_set,
-                trust_level_required,
-            )) as Box<dyn Iterator<Item = _>>,
-            (Some(name), None) => Box::new(self.get_pkg_reviews_with_issues_for_name(
-                source,
-                name,
-                trust_set,
-                trust_level_required,
-            )),
-            (None, None) => Box::new(self.get_pkg_reviews_with_issues_for_source(
-                source,
-                trust_set,
-                trust_level_required,
-            )),
-            (None, Some(_)) => panic!("Wrong usage"),
-        }
+        name: &'c str,
+        version: &'d Version,
+    ) -> impl Iterator<Item = &'a proof::review::Package> {
+        self.get_pkg_reviews_for_version(source, name, version)
+            .filter(|review| review.review_possibly_none().rating <= Rating::Negative)
     }
 
-    pub fn get_advisories_for_version<'a, 'b, 'c: 'a, 'd: 'a>(
+    /// Like `get_pkg_reviews_for_version`, but only the reviews the `filter` accepts
+    pub fn get_pkg_reviews_for_version_filtered<'a, 'b, 'c: 'a, 'd: 'a>(
         &'a self,
         source: &'b str,
         name: &'c str,
         version: &'d Version,
-    ) -> impl Iterator<Item = &proof::review::Package> {
-        self.get_pkg_reviews_gte_version(source, name, version)
-            .filter(move |review| review.is_advisory_for(&version))
+        filter: &'a ReviewFilter,
+    ) -> impl Iterator<Item = &'a proof::review::Package> {
+        self.get_pkg_reviews_for_version(source, name, version)
+            .filter(filter.predicate(self.now()))
     }
 
-    pub fn get_advisories_for_package<'a, 'b, 'c: 'a>(
+    /// Like `get_pkg_reviews_for_version`, but including expired reviews
+    ///
+    /// An expired review (see `review::Package::expires`) still replaces any older
+    /// review of the version by its author, so it's returned instead of them.
+    pub fn get_pkg_reviews_for_version_including_expired<'a, 'b, 'c: 'a, 'd: 'a>(
         &'a self,
         source: &'b str,
         name: &'c str,
-    ) -> impl Iterator<Item = &proof::review::Package> {
-        self.package_reviews
-            .get(source)
-            .into_iter()
-            .flat_map(move |map| map.get(name))
-            .flat_map(move |map| map.iter())
-            .flat_map(|(_, v)| v)
-            .flat_map(move |pkg_review_id| {
-                let review = &self.package_review_by_signature
-                    [&self.package_review_signatures_by_pkg_review_id[pkg_review_id].value];
+        version: &'d Version,
+    ) -> impl Iterator<Item = &'a proof::review::Package> {
+        self.get_pkg_reviews_for_version_including_retracted(source, name, version)
+            .filter(|review| !review.retracted)
+    }
 
-                if !review.advisories.is_empty() {
-                    Some(review)
-                } else {
-                    None
-                }
+    /// Like `get_pkg_reviews_for_name`, but without the reviews of versions
+    /// that were yanked, or not found (see `set_version_oracle`)
+    pub fn get_pkg_reviews_for_name_existing<'a>(
+        &'a self,
+        source: &str,
+        name: &str,
+    ) -> impl Iterator<Item = &'a proof::review::Package> {
+        self.get_pkg_reviews_for_name(source, name)
+            .filter(move |review| {
+                let pkg = &review.package.id;
+                !self
+                    .get_version_status(&pkg.id.source, &pkg.id.name, &pkg.version)
+                    .is_unavailable()
             })
     }
 
-    pub fn get_advisories_for_source(
-        &self,
+    /// Like `get_pkg_reviews_for_name`, but including expired reviews
+    pub fn get_pkg_reviews_for_name_including_expired<'a>(
+        &'a self,
         source: &str,
-    ) -> impl Iterator<Item = &proof::review::Package> {
-        self.get_pkg_reviews_for_source(source)
-            .filter(|review| !review.advisories.is_empty())
+        name: &str,
+    ) -> impl Iterator<Item = &'a proof::review::Package> {
+        let name = canonicalize_package_name(source, name);
+        self.package_reviews
+            .get(source)
+            .into_iter()
+            .flat_map(move |map| map.get(&name))
+            .flat_map(BTreeMap::values)
+            .flatten()
+            .map(move |pkg_review_id| {
+                self.get_pkg_review_by_pkg_review_id(pkg_review_id)
+                    .expect("exists")
+            })
+            .filter(|review| !review.retracted)
     }
 
-    /// Get all issues affecting a given package version
+    /// Like `get_pkg_reviews_for_version`, but including retractions (and expired reviews)
     ///
-    /// Collect a map of Issue ID -> `IssueReports`, listing
-    /// all issues known to affect a given package version.
+    /// A retraction (see `review::Package::retracted`) is returned instead of the review
+    /// it replaced, if it's the most recent review of the version by its author.
+    pub fn get_pkg_reviews_for_version_including_retracted<'a, 'b, 'c: 'a, 'd: 'a>(
+        &'a self,
+        source: &'b str,
+        name: &'c str,
+        version: &'d Version,
+    ) -> impl Iterator<Item = &'a proof::review::Package> {
+        let name = canonicalize_package_name(source, name);
+        self.package_reviews
+            .get(source)
+            .into_iter()
+            .flat_map(move |map| map.get(&name))
+            .flat_map(move |map| map.get(version))
+            .flatten()
+            .map(move |pkg_review_id| {
+                self.get_pkg_review_by_pkg_review_id(pkg_review_id)
+                    .expect("exists")
+            })
+    }
+
+    /// Reviews of `version`, as they were at `as_of`
     ///
-    /// These are calculated from `advisories` and `issues` fields
-    /// of the package reviews of reviewers intside a given `trust_set`
-    /// of at least given `trust_level_required`.
-    pub fn get_open_issues_for_version(
+    /// Every author's most recent review issued no later than `as_of` counts, so
+    /// reviews that were superseded or retracted afterwards are returned instead
+    /// of what replaced them, and reviews issued afterwards are absent.
+    pub fn get_pkg_reviews_for_version_at<'a, 'b, 'c: 'a, 'd: 'a>(
+        &'a self,
+        source: &'b str,
+        name: &'c str,
+        version: &'d Version,
+        as_of: DateTime<Utc>,
+    ) -> impl Iterator<Item = &'a proof::review::Package> {
+        let name = canonicalize_package_name(source, name);
+        self.package_reviews
+            .get(source)
+            .into_iter()
+            .flat_map(move |map| map.get(&name))
+            .flat_map(move |map| map.get(version))
+            .flatten()
+            .filter_map(move |pkg_review_id| self.get_pkg_review_at(pkg_review_id, as_of))
+    }
+
+    /// Reviews of all versions of a package, as they were at `as_of`
+    ///
+    /// See `get_pkg_reviews_for_version_at`.
+    pub fn get_pkg_reviews_for_name_at<'a, 'b, 'c: 'a>(
+        &'a self,
+        source: &'b str,
+        name: &'c str,
+        as_of: DateTime<Utc>,
+    ) -> impl Iterator<Item = &'a proof::review::Package> {
+        let name = canonicalize_package_name(source, name);
+        self.package_reviews
+            .get(source)
+            .into_iter()
+            .flat_map(move |map| map.get(&name))
+            .flat_map(BTreeMap::values)
+            .flatten()
+            .filter_map(move |pkg_review_id| self.get_pkg_review_at(pkg_review_id, as_of))
+    }
+
+    /// The review `pkg_review_id` was pointing to at `as_of`, unless it was a retraction
+    fn get_pkg_review_at(
+        &self,
+        pkg_review_id: &PkgVersionReviewId,
+        as_of: DateTime<Utc>,
+    ) -> Option<&proof::review::Package> {
+        let (_, signature) = self
+            .package_review_history
+            .get(pkg_review_id)?
+            .iter()
+            .rev()
+            .find(|(date, _)| *date <= as_of)?;
+        let review = self.package_review_by_signature.get(signature)?;
+        if review.retracted {
+            None
+        } else {
+            Some(review)
+        }
+    }
+
+    /// Get reviews of `version`, and reviews of a range of versions including it
+    ///
+    /// A review with a `diff_base` is considered to cover all the versions
+    /// from the base version up to the reviewed version. Reviews of exactly
+    /// `version` are returned first.
+    pub fn get_pkg_reviews_covering_version<'a, 'b, 'c: 'a, 'd: 'a>(
+        &'a self,
+        source: &'b str,
+        name: &'c str,
+        version: &'d Version,
+    ) -> impl Iterator<Item = &'a proof::review::Package> {
+        let range_reviews = self
+            .package_range_reviews
+            .get(&canonical_package_id(source, name))
+            .into_iter()
+            .flatten()
+            .map(move |pkg_review_id| {
+                self.get_pkg_review_by_pkg_review_id(pkg_review_id)
+                    .expect("exists")
+            })
+            .filter(move |review| match &review.diff_base {
+                Some(base) => {
+                    self.is_in_effect(review)
+                        && base.id.version <= *version
+                        && *version < review.package.id.version
+                }
+                None => false,
+            });
+
+        self.get_pkg_reviews_for_version(source, name, version)
+            .chain(range_reviews)
+    }
+
+    pub fn get_pkg_reviews_gte_version<'a, 'b, 'c: 'a, 'd: 'a>(
+        &'a self,
+        source: &'b str,
+        name: &'c str,
+        version: &'d Version,
+    ) -> impl Iterator<Item = &'a proof::review::Package> {
+        self.get_pkg_reviews_gte_version_matching(source, name, version, default())
+    }
+
+    /// Like `get_pkg_reviews_gte_version`, but comparing versions according to the `policy`
+    pub fn get_pkg_reviews_gte_version_matching<'a, 'b, 'c: 'a, 'd: 'a>(
+        &'a self,
+        source: &'b str,
+        name: &'c str,
+        version: &'d Version,
+        policy: VersionMatchPolicy,
+    ) -> impl Iterator<Item = &'a proof::review::Package> {
+        let name = canonicalize_package_name(source, name);
+        let lowest = policy.lowest_equal(version).into_owned();
+        let normalized = policy.normalize(version);
+        self.package_reviews
+            .get(source)
+            .into_iter()
+            .flat_map(move |map| map.get(&name))
+            .flat_map(move |map| map.range(lowest.clone()..))
+            .flat_map(move |(_, v)| v)
+            .map(move |pkg_review_id| {
+                self.get_pkg_review_by_pkg_review_id(pkg_review_id)
+                    .expect("exists")
+            })
+            .filter(move |review| {
+                let reviewed = &review.package.id.version;
+                self.is_in_effect(review)
+                    && policy.relates(reviewed, version)
+                    && *policy.normalize(reviewed) >= *normalized
+            })
+    }
+
+    pub fn get_pkg_reviews_lte_version<'a, 'b, 'c: 'a, 'd: 'a>(
+        &'a self,
+        source: &'b str,
+        name: &'c str,
+        version: &'d Version,
+    ) -> impl Iterator<Item = &'a proof::review::Package> {
+        self.get_pkg_reviews_lte_version_matching(source, name, version, default())
+    }
+
+    /// Like `get_pkg_reviews_lte_version`, but comparing versions according to the `policy`
+    pub fn get_pkg_reviews_lte_version_matching<'a, 'b, 'c: 'a, 'd: 'a>(
+        &'a self,
+        source: &'b str,
+        name: &'c str,
+        version: &'d Version,
+        policy: VersionMatchPolicy,
+    ) -> impl Iterator<Item = &'a proof::review::Package> {
+        let name = canonicalize_package_name(source, name);
+        // normalized versions are the highest of the ones equal to them
+        let normalized = policy.normalize(version);
+        let highest = normalized.clone().into_owned();
+        self.package_reviews
+            .get(source)
+            .into_iter()
+            .flat_map(move |map| map.get(&name))
+            .flat_map(move |map| map.range(..=highest.clone()))
+            .flat_map(|(_, v)| v)
+            .map(move |pkg_review_id| {
+                self.get_pkg_review_by_pkg_review_id(pkg_review_id)
+                    .expect("exists")
+            })
+            .filter(move |review| {
+                let reviewed = &review.package.id.version;
+                self.is_in_effect(review)
+                    && policy.relates(reviewed, version)
+                    && *policy.normalize(reviewed) <= *normalized
+            })
+    }
+
+    /// Reviews of versions matching `req`
+    ///
+    /// Like in Cargo, pre-release versions match only if `req` mentions
+    /// a pre-release of the same version (e.g. `^1.2.0-beta`).
+    pub fn get_pkg_reviews_for_req<'a, 'b, 'c: 'a, 'd: 'a>(
+        &'a self,
+        source: &'b str,
+        name: &'c str,
+        req: &'d VersionReq,
+    ) -> impl Iterator<Item = &'a proof::review::Package> {
+        let name = canonicalize_package_name(source, name);
+        // `VersionReq` doesn't expose its bounds, so all versions are checked
+        self.package_reviews
+            .get(source)
+            .into_iter()
+            .flat_map(move |map| map.get(&name))
+            .flatten()
+            .filter(move |(version, _)| req.matches(version))
+            .flat_map(|(_, v)| v)
+            .map(move |pkg_review_id| {
+                self.get_pkg_review_by_pkg_review_id(pkg_review_id)
+                    .expect("exists")
+            })
+            .filter(move |review| self.is_in_effect(review))
+    }
+
+    /// Reviewed version closest to `version`, to suggest as an alternative
+    ///
+    /// Only reviews by Ids with at least `min_level` of effective trust count.
+    /// Versions with the same major and minor version are preferred, then
+    /// the ones with the same major version. Returns `version` itself, if it's reviewed.
+    pub fn get_nearest_reviewed_version(
         &self,
         source: &str,
         name: &str,
-        queried_version: &Version,
+        version: &Version,
         trust_set: &TrustSet,
-        trust_level_required: TrustLevel,
-    ) -> HashMap<String, IssueDetails> {
-        // This is one of the most complicated calculations in whole crev. I hate this code
-        // already, and I have barely put it together.
-
-        // Here we track all the reported isue by issue id
-        let mut issue_reports_by_id: HashMap<String, IssueDetails> = HashMap::new();
-
-        // First we go through all the reports in previous versions with `issues` fields and collect these.
-        // Easy.
-        for (review, issue) in self
-            .get_pkg_reviews_lte_version(source, name, queried_version)
-            .filter(|review| {
-                let effective = trust_set.get_effective_trust_level(&review.from().id);
-                effective >= trust_level_required
+        min_level: TrustLevel,
+    ) -> Option<Version> {
+        let trust_set = trust_set.for_source(source);
+        let distance = |a: u64, b: u64| cmp::max(a, b) - cmp::min(a, b);
+        self.package_reviews
+            .get(source)?
+            .get(&canonicalize_package_name(source, name))?
+            .iter()
+            .filter(|(_, pkg_review_ids)| {
+                pkg_review_ids.iter().any(|pkg_review_id| {
+                    !trust_set.is_distrusted(&pkg_review_id.from)
+                        && trust_set.get_effective_trust_level(&pkg_review_id.from) >= min_level
+                        && matches!(
+                            self.get_pkg_review_by_pkg_review_id(pkg_review_id),
+                            Some(review) if !review.retracted
+                        )
+                })
             })
-            .flat_map(move |review| review.issues.iter().map(move |issue| (review, issue)))
-            .filter(|(review, issue)| {
-                issue.is_for_version_when_reported_in_version(
-                    queried_version,
-                    &review.package.id.version,
+            .map(|(reviewed, _)| reviewed)
+            .min_by_key(|reviewed| {
+                (
+                    distance(reviewed.major, version.major),
+                    distance(reviewed.minor, version.minor),
+                    distance(reviewed.patch, version.patch),
+                    reviewed.is_prerelease(),
+                    // on a tie, prefer the newer version
+                    cmp::Reverse(*reviewed),
                 )
             })
-        {
-            issue_reports_by_id
-                .entry(issue.id.clone())
-                .or_default()
-                .issues
-                .insert(PkgVersionReviewId::from(review));
-        }
+            .cloned()
+    }
 
-        // Now the complicated part. We go through all the advisories for all the versions
-        // of given package.
-        //
-        // Advisories itself have two functions: first, they might have report an issue
-        // by advertising that a given version should be upgraded to a newer version.
-        //
-        // Second - they might cancel `issues` inside `issue_reports_by_id` because they
-        // advertise a fix that happened somewhere between the `issue` report and
-        // the current `queried_version`.
-        for (review, advisory) in self
-            .get_pkg_reviews_for_name(source, name)
-            .filter(|review| {
-                let effective = trust_set.get_effective_trust_level(&review.from().id);
-                effective >= trust_level_required
+    pub fn get_pkg_review_by_pkg_review_id(
+        &self,
+        uniq: &PkgVersionReviewId,
+    ) -> Option<&proof::review::Package> {
+        let signature = &self
+            .package_review_signatures_by_pkg_review_id
+            .get(uniq)?
+            .value;
+        self.package_review_by_signature
+            .get(signature)
+            .map(|review| &**review)
+    }
+
+    fn get_pkg_review_arc_by_pkg_review_id(
+        &self,
+        uniq: &PkgVersionReviewId,
+    ) -> Option<&sync::Arc<review::Package>> {
+        let signature = &self
+            .package_review_signatures_by_pkg_review_id
+            .get(uniq)?
+            .value;
+        self.package_review_by_signature.get(signature)
+    }
+
+    /// Like `get_pkg_reviews_for_source`, but the result doesn't borrow the `ProofDB`
+    pub fn get_pkg_reviews_for_source_owned(
+        &self,
+        source: &str,
+    ) -> Vec<sync::Arc<review::Package>> {
+        self.package_reviews
+            .get(source)
+            .into_iter()
+            .flat_map(|map| map.values())
+            .flat_map(|map| map.values())
+            .flatten()
+            .map(|pkg_review_id| {
+                self.get_pkg_review_arc_by_pkg_review_id(pkg_review_id)
+                    .expect("exists")
+                    .clone()
             })
-            .flat_map(move |review| {
-                review
-                    .advisories
-                    .iter()
-                    .map(move |advisory| (review, advisory))
+            .filter(move |review| self.is_in_effect(review))
+            .collect()
+    }
+
+    /// Like `get_pkg_reviews_for_name`, but the result doesn't borrow the `ProofDB`
+    pub fn get_pkg_reviews_for_name_owned(
+        &self,
+        source: &str,
+        name: &str,
+    ) -> Vec<sync::Arc<review::Package>> {
+        self.package_reviews
+            .get(source)
+            .and_then(|map| map.get(&canonicalize_package_name(source, name)))
+            .into_iter()
+            .flat_map(|map| map.values())
+            .flatten()
+            .map(|pkg_review_id| {
+                self.get_pkg_review_arc_by_pkg_review_id(pkg_review_id)
+                    .expect("exists")
+                    .clone()
             })
-        {
-            // Add new issue reports created by the advisory
-            if advisory.is_for_version_when_reported_in_version(
-                &queried_version,
-                &review.package.id.version,
-            ) {
-                for id in &advisory.ids {
-                    issue_reports_by_id
-                        .entry(id.clone())
-                        .or_default()
-                        .issues
-                        .insert(PkgVersionReviewId::from(review));
+            .filter(move |review| self.is_in_effect(review))
+            .collect()
+    }
+
+    /// Like `get_pkg_reviews_for_version`, but the result doesn't borrow the `ProofDB`
+    pub fn get_pkg_reviews_for_version_owned(
+        &self,
+        source: &str,
+        name: &str,
+        version: &Version,
+    ) -> Vec<sync::Arc<review::Package>> {
+        self.package_reviews
+            .get(source)
+            .and_then(|map| map.get(&canonicalize_package_name(source, name)))
+            .and_then(|map| map.get(version))
+            .into_iter()
+            .flatten()
+            .map(|pkg_review_id| {
+                self.get_pkg_review_arc_by_pkg_review_id(pkg_review_id)
+                    .expect("exists")
+                    .clone()
+            })
+            .filter(move |review| self.is_in_effect(review))
+            .collect()
+    }
+
+    pub fn get_pkg_review<'a, 'b, 'c: 'a, 'd: 'a>(
+        &'a self,
+        source: &'b str,
+        name: &'c str,
+        version: &'d Version,
+        id: &Id,
+    ) -> Option<&proof::review::Package> {
+        self.get_pkg_reviews_for_version(source, name, version)
+            .find(|pkg_review| pkg_review.from().id == *id)
+    }
+
+    /// Every review `id` ever issued for `version`, including superseded ones
+    ///
+    /// Ordered by date, newest last, and by signature between reviews with the
+    /// same date. Retractions are included too, so the last one is not necessarily
+    /// what `get_pkg_review` returns.
+    pub fn get_pkg_review_history(
+        &self,
+        source: &str,
+        name: &str,
+        version: &Version,
+        id: &Id,
+    ) -> Vec<&proof::review::Package> {
+        let name = canonicalize_package_name(source, name);
+        let mut history: Vec<_> = self
+            .package_reviews
+            .get(source)
+            .and_then(|names| names.get(&name))
+            .and_then(|versions| versions.get(version))
+            .into_iter()
+            .flatten()
+            .filter(|pkg_review_id| *pkg_review_id.from == *id)
+            .filter_map(|pkg_review_id| self.package_review_history.get(pkg_review_id))
+            .flatten()
+            .collect();
+        // there can be more than one pkg_review_id if the name was spelled differently
+        history.sort();
+        history
+            .into_iter()
+            .map(|(_, signature)| &*self.package_review_by_signature[signature])
+            .collect()
+    }
+
+    pub fn get_advisories<'a, 'b, 'c: 'a, 'd: 'a>(
+        &'a self,
+        source: &'b str,
+        name: Option<&'c str>,
+        version: Option<&'d Version>,
+    ) -> impl Iterator<Item = &'a proof::review::Package> + 'a {
+        match (name, version) {
+            (Some(ref name), Some(ref version)) => {
+                Box::new(self.get_advisories_for_version(source, name, version))
+                    as Box<dyn Iterator<Item = _>>
+            }
+
+            (Some(ref name), None) => Box::new(self.get_advisories_for_package(source, name)),
+            (None, None) => Box::new(self.get_advisories_for_source(source)),
+            (None, Some(_)) => panic!("Wrong usage"),
+        }
+    }
+
+    pub fn get_pkg_reviews_with_issues_for<'a, 'b, 'c: 'a, 'd: 'a>(
+        &'a self,
+        source: &'b str,
+        name: Option<&'c str>,
+        version: Option<&'c Version>,
+        trust_set: &'d TrustSet,
+        trust_level_required: TrustLevel,
+    ) -> impl Iterator<Item = &proof::review::Package> {
+        self.get_pkg_reviews_with_issues_for_ignoring(
+            source,
+            name,
+            version,
+            trust_set,
+            trust_level_required,
+            &ReviewIgnoreList::new(),
+        )
+    }
+
+    /// Like `get_pkg_reviews_with_issues_for`, but disregarding reviews on the `ignore_list`
+    pub fn get_pkg_reviews_with_issues_for_ignoring<'a, 'b, 'c: 'a, 'd: 'a>(
+        &'a self,
+        source: &'b str,
+        name: Option<&'c str>,
+        version: Option<&'c Version>,
+        trust_set: &'d TrustSet,
+        trust_level_required: TrustLevel,
+        ignore_list: &ReviewIgnoreList,
+    ) -> impl Iterator<Item = &'a proof::review::Package> {
+        match (name, version) {
+            (Some(name), Some(version)) => {
+                Box::new(self.get_pkg_reviews_with_issues_for_version_ignoring(
+                    source,
+                    name,
+                    version,
+                    trust_set,
+                    trust_level_required,
+                    ignore_list,
+                )) as Box<dyn Iterator<Item = _>>
+            }
+            (Some(name), None) => Box::new(self.get_pkg_reviews_with_issues_for_name_ignoring(
+                source,
+                name,
+                trust_set,
+                trust_level_required,
+                ignore_list,
+            )),
+            (None, None) => Box::new(self.get_pkg_reviews_with_issues_for_source_ignoring(
+                source,
+                trust_set,
+                trust_level_required,
+                ignore_list,
+            )),
+            (None, Some(_)) => panic!("Wrong usage"),
+        }
+    }
+
+    pub fn get_advisories_for_version<'a, 'b, 'c: 'a, 'd: 'a>(
+        &'a self,
+        source: &'b str,
+        name: &'c str,
+        version: &'d Version,
+    ) -> impl Iterator<Item = &proof::review::Package> {
+        self.get_advisories_for_version_matching(source, name, version, default())
+    }
+
+    /// Like `get_advisories_for_version`, but comparing versions according to the `policy`
+    pub fn get_advisories_for_version_matching<'a, 'b, 'c: 'a, 'd: 'a>(
+        &'a self,
+        source: &'b str,
+        name: &'c str,
+        version: &'d Version,
+        policy: VersionMatchPolicy,
+    ) -> impl Iterator<Item = &'a proof::review::Package> {
+        let normalized = policy.normalize(version);
+        self.get_pkg_reviews_gte_version_matching(source, name, version, policy)
+            .filter(move |review| {
+                let reported_in = policy.normalize(&review.package.id.version);
+                review.advisories.iter().any(|advisory| {
+                    advisory.is_for_version_when_reported_in_version(&normalized, &reported_in)
+                })
+            })
+    }
+
+    pub fn get_advisories_for_package<'a, 'b, 'c: 'a>(
+        &'a self,
+        source: &'b str,
+        name: &'c str,
+    ) -> impl Iterator<Item = &proof::review::Package> {
+        let name = canonicalize_package_name(source, name);
+        self.package_reviews
+            .get(source)
+            .into_iter()
+            .flat_map(move |map| map.get(&name))
+            .flat_map(move |map| map.iter())
+            .flat_map(|(_, v)| v)
+            .flat_map(move |pkg_review_id| {
+                let review = &*self.package_review_by_signature
+                    [&self.package_review_signatures_by_pkg_review_id[pkg_review_id].value];
+
+                if !review.advisories.is_empty() {
+                    Some(review)
+                } else {
+                    None
+                }
+            })
+    }
+
+    pub fn get_advisories_for_source(
+        &self,
+        source: &str,
+    ) -> impl Iterator<Item = &proof::review::Package> {
+        self.get_pkg_reviews_for_source(source)
+            .filter(|review| !review.advisories.is_empty())
+    }
+
+    /// Get all issues affecting a given package version
+    ///
+    /// Collect a map of Issue ID -> `IssueReports`, listing
+    /// all issues known to affect a given package version.
+    ///
+    /// These are calculated from `advisories` and `issues` fields
+    /// of the package reviews of reviewers intside a given `trust_set`
+    /// of at least given `trust_level_required`.
+    pub fn get_open_issues_for_version(
+        &self,
+        source: &str,
+        name: &str,
+        queried_version: &Version,
+        trust_set: &TrustSet,
+        trust_level_required: TrustLevel,
+    ) -> BTreeMap<String, IssueDetails> {
+        self.get_open_issues_for_version_ignoring(
+            source,
+            name,
+            queried_version,
+            trust_set,
+            trust_level_required,
+            &ReviewIgnoreList::new(),
+        )
+    }
+
+    /// Like `get_open_issues_for_version`, but disregarding reviews on the `ignore_list`
+    pub fn get_open_issues_for_version_ignoring(
+        &self,
+        source: &str,
+        name: &str,
+        queried_version: &Version,
+        trust_set: &TrustSet,
+        trust_level_required: TrustLevel,
+        ignore_list: &ReviewIgnoreList,
+    ) -> BTreeMap<String, IssueDetails> {
+        self.get_open_issues_for_version_with(
+            source,
+            name,
+            queried_version,
+            trust_set,
+            trust_level_required,
+            ignore_list,
+            &ReviewFilter::default(),
+            default(),
+        )
+    }
+
+    /// Like `get_open_issues_for_version`, but disregarding reviews
+    /// the `filter` doesn't accept (e.g. stale issue reports)
+    ///
+    /// Advisories in the disregarded reviews don't mark issues as fixed either.
+    pub fn get_open_issues_for_version_filtered(
+        &self,
+        source: &str,
+        name: &str,
+        queried_version: &Version,
+        trust_set: &TrustSet,
+        trust_level_required: TrustLevel,
+        filter: &ReviewFilter,
+    ) -> BTreeMap<String, IssueDetails> {
+        self.get_open_issues_for_version_with(
+            source,
+            name,
+            queried_version,
+            trust_set,
+            trust_level_required,
+            &ReviewIgnoreList::new(),
+            filter,
+            default(),
+        )
+    }
+
+    /// Like `get_open_issues_for_version`, but comparing versions according to the `policy`
+    ///
+    /// E.g. with `VersionMatchPolicy::PreReleaseAsRelease`, advisories reported
+    /// in `1.2.3` don't affect `1.2.3-rc.1`, and issues reported in `1.2.3-rc.1`
+    /// are open in `1.2.3` too.
+    pub fn get_open_issues_for_version_matching(
+        &self,
+        source: &str,
+        name: &str,
+        queried_version: &Version,
+        trust_set: &TrustSet,
+        trust_level_required: TrustLevel,
+        policy: VersionMatchPolicy,
+    ) -> BTreeMap<String, IssueDetails> {
+        self.get_open_issues_for_version_with(
+            source,
+            name,
+            queried_version,
+            trust_set,
+            trust_level_required,
+            &ReviewIgnoreList::new(),
+            &ReviewFilter::default(),
+            policy,
+        )
+    }
+
+    /// Highest severity any of the reviews reported the issue `id` with
+    fn issue_severity(&self, id: &str, details: &IssueDetails) -> Level {
+        details
+            .issues
+            .iter()
+            .chain(details.advisories.iter())
+            .filter_map(|pkg_review_id| self.get_pkg_review_by_pkg_review_id(pkg_review_id))
+            .flat_map(|review| {
+                let issues = review
+                    .issues
+                    .iter()
+                    .filter(move |issue| issue.id == id)
+                    .map(|issue| issue.severity);
+                let advisories = review
+                    .advisories
+                    .iter()
+                    .filter(move |advisory| advisory.ids.iter().any(|i| i == id))
+                    .map(|advisory| advisory.severity);
+                issues.chain(advisories)
+            })
+            .max()
+            .unwrap_or(Level::None)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn get_open_issues_for_version_with(
+        &self,
+        source: &str,
+        name: &str,
+        queried_version: &Version,
+        trust_set: &TrustSet,
+        trust_level_required: TrustLevel,
+        ignore_list: &ReviewIgnoreList,
+        filter: &ReviewFilter,
+        policy: VersionMatchPolicy,
+    ) -> BTreeMap<String, IssueDetails> {
+        let trust_set = trust_set.for_source(source);
+        let accepted = filter.predicate(self.now());
+        let normalized_version = policy.normalize(queried_version);
+        // This is one of the most complicated calculations in whole crev. I hate this code
+        // already, and I have barely put it together.
+
+        // Here we track all the reported isue by issue id
+        let mut issue_reports_by_id: BTreeMap<String, IssueDetails> = BTreeMap::new();
+
+        // First we go through all the reports in previous versions with `issues` fields and collect these.
+        // Easy.
+        for (review, issue) in self
+            .get_pkg_reviews_lte_version_matching(source, name, queried_version, policy)
+            .filter(|review| {
+                let effective = trust_set.get_effective_trust_level(&review.from().id);
+                effective >= trust_level_required
+                    && !ignore_list.is_ignored(review)
+                    && accepted(review)
+            })
+            .flat_map(move |review| review.issues.iter().map(move |issue| (review, issue)))
+            .filter(|(review, issue)| {
+                issue.is_for_version_when_reported_in_version(
+                    &normalized_version,
+                    &policy.normalize(&review.package.id.version),
+                )
+            })
+        {
+            issue_reports_by_id
+                .entry(issue.id.clone())
+                .or_default()
+                .issues
+                .insert(review.into());
+        }
+
+        // Now the complicated part. We go through all the advisories for all the versions
+        // of given package.
+        //
+        // Advisories itself have two functions: first, they might have report an issue
+        // by advertising that a given version should be upgraded to a newer version.
+        //
+        // Second - they might cancel `issues` inside `issue_reports_by_id` because they
+        // advertise a fix that happened somewhere between the `issue` report and
+        // the current `queried_version`.
+        for (review, advisory) in self
+            .get_pkg_reviews_for_name(source, name)
+            .filter(|review| {
+                let effective = trust_set.get_effective_trust_level(&review.from().id);
+                effective >= trust_level_required
+                    && !ignore_list.is_ignored(review)
+                    && accepted(review)
+                    && policy.relates(&review.package.id.version, queried_version)
+            })
+            .flat_map(move |review| {
+                review
+                    .advisories
+                    .iter()
+                    .map(move |advisory| (review, advisory))
+            })
+        {
+            // Add new issue reports created by the advisory
+            let reported_in = policy.normalize(&review.package.id.version);
+            if advisory.is_for_version_when_reported_in_version(&normalized_version, &reported_in) {
+                let fixed_in = &review.package.id.version;
+                for id in &advisory.ids {
+                    let details = issue_reports_by_id.entry(id.clone()).or_default();
+                    details.issues.insert(review.into());
+                    if !matches!(&details.fixed_in, Some(version) if version <= fixed_in) {
+                        details.fixed_in = Some(fixed_in.clone());
+                    }
+                }
+            }
+
+            // Remove the reports that are already fixed
+            for id in &advisory.ids {
+                if let Some(mut issue_marker) = issue_reports_by_id.get_mut(id) {
+                    let issues = std::mem::take(&mut issue_marker.issues);
+                    issue_marker.issues = issues
+                        .into_iter()
+                        .filter(|pkg_review_id| {
+                            let signature = &self
+                                .package_review_signatures_by_pkg_review_id
+                                .get(pkg_review_id)
+                                .expect("review for this signature")
+                                .value;
+                            let issue_review = self
+                                .package_review_by_signature
+                                .get(signature)
+                                .expect("review for this pkg_review_id");
+                            !advisory.is_for_version_when_reported_in_version(
+                                &policy.normalize(&issue_review.package.id.version),
+                                &reported_in,
+                            )
+                        })
+                        .collect();
+                }
+            }
+        }
+
+        issue_reports_by_id
+            .into_iter()
+            .filter(|(_id, markers)| !markers.issues.is_empty() || !markers.advisories.is_empty())
+            .map(|(id, mut details)| {
+                details.severity = self.issue_severity(&id, &details);
+                details.reporting_ids = details
+                    .issues
+                    .iter()
+                    .chain(&details.advisories)
+                    .map(|pkg_review_id| pkg_review_id.from().clone())
+                    .collect();
+                (id, details)
+            })
+            .collect()
+    }
+
+    /// Advisories (from reviews by authors with at least `min_level` of trust)
+    /// that affect the given package `version`
+    pub fn advisories_affecting(
+        &self,
+        source: &str,
+        name: &str,
+        version: &Version,
+        trust_set: &TrustSet,
+        min_level: TrustLevel,
+    ) -> Vec<AdvisorySummary<'_>> {
+        let trust_set = trust_set.for_source(source);
+        let open_in_latest = self.open_issues_in_latest_version(source, name, trust_set, min_level);
+        self.get_pkg_reviews_for_name(source, name)
+            .filter(|review| trust_set.get_effective_trust_level(&review.from().id) >= min_level)
+            .flat_map(|review| {
+                (0..review.advisories.len())
+                    .filter(move |&i| self.is_version_affected_by_advisory(review, i, version))
+                    .map(move |i| (review, i))
+            })
+            .map(|(review, i)| AdvisorySummary::new(review, i, trust_set, open_in_latest.as_ref()))
+            .collect()
+    }
+
+    /// Advisories (from reviews by authors with at least `min_level` of trust)
+    /// of all the packages of the `source`, by package name
+    ///
+    /// Advisories reported by more than one Id (with the same ids) are listed once,
+    /// as reported by the most trusted of them (and then in the newest version).
+    /// Distrusted Ids are never taken into account. See `AdvisorySummary::fixed`
+    /// for telling apart the ones that are still not fixed in the newest version.
+    pub fn get_open_advisories(
+        &self,
+        source: &str,
+        trust_set: &TrustSet,
+        min_level: TrustLevel,
+    ) -> BTreeMap<Name, Vec<AdvisorySummary<'_>>> {
+        let trust_set = trust_set.for_source(source);
+        let min_level = cmp::max(min_level, TrustLevel::None);
+        let mut by_name: BTreeMap<&str, Vec<&review::Package>> = BTreeMap::new();
+        for review in self.get_advisories_for_source(source) {
+            if trust_set.get_effective_trust_level(&review.from().id) >= min_level {
+                by_name
+                    .entry(review.package.id.id.name.as_str())
+                    .or_default()
+                    .push(review);
+            }
+        }
+
+        by_name
+            .into_iter()
+            .map(|(name, reviews)| {
+                let open_in_latest =
+                    self.open_issues_in_latest_version(source, name, trust_set, min_level);
+                fn sort_key<'a>(
+                    summary: &AdvisorySummary<'a>,
+                ) -> (TrustLevel, &'a Version, cmp::Reverse<&'a Id>) {
+                    (
+                        summary.effective_trust_level,
+                        summary.fixed_in,
+                        cmp::Reverse(summary.reporter),
+                    )
+                }
+                let mut by_ids: BTreeMap<&[String], AdvisorySummary<'_>> = BTreeMap::new();
+                for review in reviews {
+                    for i in 0..review.advisories.len() {
+                        let summary =
+                            AdvisorySummary::new(review, i, trust_set, open_in_latest.as_ref());
+                        match by_ids.entry(summary.ids) {
+                            btree_map::Entry::Vacant(entry) => {
+                                entry.insert(summary);
+                            }
+                            btree_map::Entry::Occupied(mut entry) => {
+                                if sort_key(entry.get()) < sort_key(&summary) {
+                                    entry.insert(summary);
+                                }
+                            }
+                        }
+                    }
+                }
+                (name.to_owned(), by_ids.into_values().collect())
+            })
+            .collect()
+    }
+
+    /// Issues open in the newest version of a package reviewed by Ids
+    /// with at least `min_level` of trust, if there's any such version
+    fn open_issues_in_latest_version(
+        &self,
+        source: &str,
+        name: &str,
+        trust_set: &TrustSet,
+        min_level: TrustLevel,
+    ) -> Option<BTreeMap<String, IssueDetails>> {
+        let trust_set = trust_set.for_source(source);
+        let latest = self
+            .get_pkg_reviews_for_name(source, name)
+            .filter(|review| trust_set.get_effective_trust_level(&review.from().id) >= min_level)
+            .map(|review| &review.package.id.version)
+            .max()?;
+        Some(self.get_open_issues_for_version(source, name, latest, trust_set, min_level))
+    }
+
+    /// Is `version` affected by the `advisory_index`-th advisory of `advisory_review`
+    ///
+    /// Advisories are reported in the version fixing the problem, and affect
+    /// (some of, depending on the advisory range) older versions.
+    pub fn is_version_affected_by_advisory(
+        &self,
+        advisory_review: &review::Package,
+        advisory_index: usize,
+        version: &Version,
+    ) -> bool {
+        match advisory_review.advisories.get(advisory_index) {
+            Some(advisory) => advisory.is_for_version_when_reported_in_version(
+                version,
+                &advisory_review.package.id.version,
+            ),
+            None => false,
+        }
+    }
+
+    /// Package versions that reviews of the given `digest` claim it to be
+    ///
+    /// Normally there's exactly one, but nothing prevents reviewers
+    /// from disagreeing (or making a mistake).
+    pub fn lookup_package_version_by_digest(
+        &self,
+        digest: &Digest,
+    ) -> Vec<proof::PackageVersionId> {
+        self.package_review_signatures_by_package_digest
+            .get(digest.as_slice())
+            .into_iter()
+            .flat_map(|reviews| reviews.values())
+            .map(|signature| {
+                self.package_review_by_signature[&signature.value]
+                    .package
+                    .id
+                    .clone()
+            })
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    /// Get all issues affecting a package with a given `digest`
+    ///
+    /// See `get_open_issues_for_version`. If reviews disagree about which package
+    /// version the `digest` is, issues of all of them are returned, and
+    /// `DigestIssues::version_mismatch` tells about it.
+    pub fn get_open_issues_for_digest(
+        &self,
+        digest: &Digest,
+        trust_set: &TrustSet,
+        trust_level_required: TrustLevel,
+    ) -> DigestIssues {
+        let versions = self.lookup_package_version_by_digest(digest);
+
+        let mut issues: BTreeMap<String, IssueDetails> = BTreeMap::new();
+        for version in &versions {
+            for (id, details) in self.get_open_issues_for_version(
+                &version.id.source,
+                &version.id.name,
+                &version.version,
+                trust_set,
+                trust_level_required,
+            ) {
+                let entry = issues.entry(id).or_default();
+                entry.severity = cmp::max(entry.severity, details.severity);
+                entry.issues.extend(details.issues);
+                entry.advisories.extend(details.advisories);
+                entry.fixed_in = match (entry.fixed_in.take(), details.fixed_in) {
+                    (Some(a), Some(b)) => Some(cmp::min(a, b)),
+                    (a, b) => a.or(b),
+                };
+                entry.reporting_ids.extend(details.reporting_ids);
+            }
+        }
+        DigestIssues { issues, versions }
+    }
+
+    pub fn get_pkg_reviews_with_issues_for_version<'a, 'b, 'c: 'a>(
+        &'a self,
+        source: &'b str,
+        name: &'c str,
+        queried_version: &'c Version,
+        trust_set: &'c TrustSet,
+        trust_level_required: TrustLevel,
+    ) -> impl Iterator<Item = &proof::review::Package> {
+        self.get_pkg_reviews_with_issues_for_version_ignoring(
+            source,
+            name,
+            queried_version,
+            trust_set,
+            trust_level_required,
+            &ReviewIgnoreList::new(),
+        )
+    }
+
+    /// Like `get_pkg_reviews_with_issues_for_version`, but disregarding reviews on the `ignore_list`
+    pub fn get_pkg_reviews_with_issues_for_version_ignoring<'a, 'b, 'c: 'a>(
+        &'a self,
+        source: &'b str,
+        name: &'c str,
+        queried_version: &'c Version,
+        trust_set: &'c TrustSet,
+        trust_level_required: TrustLevel,
+        ignore_list: &ReviewIgnoreList,
+    ) -> impl Iterator<Item = &'a proof::review::Package> {
+        self.get_pkg_reviews_with_issues_for_name_ignoring(
+            source,
+            name,
+            trust_set,
+            trust_level_required,
+            ignore_list,
+        )
+        .filter(move |review| {
+            !review.issues.is_empty()
+                || review.advisories.iter().any(|advi| {
+                    advi.is_for_version_when_reported_in_version(
+                        &queried_version,
+                        &review.package.id.version,
+                    )
+                })
+        })
+    }
+
+    pub fn get_pkg_reviews_with_issues_for_name<'a, 'b, 'c: 'a>(
+        &'a self,
+        source: &'b str,
+        name: &'c str,
+        trust_set: &'c TrustSet,
+        trust_level_required: TrustLevel,
+    ) -> impl Iterator<Item = &proof::review::Package> {
+        self.get_pkg_reviews_with_issues_for_name_ignoring(
+            source,
+            name,
+            trust_set,
+            trust_level_required,
+            &ReviewIgnoreList::new(),
+        )
+    }
+
+    /// Like `get_pkg_reviews_with_issues_for_name`, but disregarding reviews on the `ignore_list`
+    pub fn get_pkg_reviews_with_issues_for_name_ignoring<'a, 'b, 'c: 'a>(
+        &'a self,
+        source: &'b str,
+        name: &'c str,
+        trust_set: &'c TrustSet,
+        trust_level_required: TrustLevel,
+        ignore_list: &ReviewIgnoreList,
+    ) -> impl Iterator<Item = &'a proof::review::Package> {
+        self.get_pkg_reviews_with_issues_or_flags(
+            self.get_pkg_reviews_for_name(source, name),
+            trust_set.for_source(source),
+            trust_level_required,
+            ignore_list,
+            HashSet::new(),
+        )
+    }
+
+    pub fn get_pkg_reviews_with_issues_for_source<'a, 'b, 'c: 'a>(
+        &'a self,
+        source: &'b str,
+        trust_set: &'c TrustSet,
+        trust_level_required: TrustLevel,
+    ) -> impl Iterator<Item = &proof::review::Package> {
+        self.get_pkg_reviews_with_issues_for_source_ignoring(
+            source,
+            trust_set,
+            trust_level_required,
+            &ReviewIgnoreList::new(),
+        )
+    }
+
+    /// Like `get_pkg_reviews_with_issues_for_source`, but disregarding reviews on the `ignore_list`
+    pub fn get_pkg_reviews_with_issues_for_source_ignoring<'a, 'b, 'c: 'a>(
+        &'a self,
+        source: &'b str,
+        trust_set: &'c TrustSet,
+        trust_level_required: TrustLevel,
+        ignore_list: &ReviewIgnoreList,
+    ) -> impl Iterator<Item = &'a proof::review::Package> {
+        self.get_pkg_reviews_with_issues_or_flags(
+            self.get_pkg_reviews_for_source(source),
+            trust_set.for_source(source),
+            trust_level_required,
+            ignore_list,
+            HashSet::new(),
+        )
+    }
+
+    /// Like `get_pkg_reviews_with_issues_for_name`, but including the reviews
+    /// of the package setting flags that are effectively set (see `get_effective_flags`)
+    pub fn get_pkg_reviews_with_issues_or_flags_for_name<'a, 'b, 'c: 'a>(
+        &'a self,
+        source: &'b str,
+        name: &'c str,
+        trust_set: &'c TrustSet,
+        trust_level_required: TrustLevel,
+        policy: &FlagPolicy,
+    ) -> impl Iterator<Item = &'a proof::review::Package> {
+        self.get_pkg_reviews_with_issues_or_flags_for_name_ignoring(
+            source,
+            name,
+            trust_set,
+            trust_level_required,
+            policy,
+            &ReviewIgnoreList::new(),
+        )
+    }
+
+    /// Like `get_pkg_reviews_with_issues_or_flags_for_name`, but disregarding
+    /// reviews on the `ignore_list`
+    pub fn get_pkg_reviews_with_issues_or_flags_for_name_ignoring<'a, 'b, 'c: 'a>(
+        &'a self,
+        source: &'b str,
+        name: &'c str,
+        trust_set: &'c TrustSet,
+        trust_level_required: TrustLevel,
+        policy: &FlagPolicy,
+        ignore_list: &ReviewIgnoreList,
+    ) -> impl Iterator<Item = &'a proof::review::Package> {
+        let canonical_name = canonicalize_package_name(source, name);
+        let flagged = self.flagged_packages(source, trust_set, policy, |pkg_id| {
+            canonicalize_package_name(source, &pkg_id.name) == canonical_name
+        });
+        self.get_pkg_reviews_with_issues_or_flags(
+            self.get_pkg_reviews_for_name(source, name),
+            trust_set.for_source(source),
+            trust_level_required,
+            ignore_list,
+            flagged,
+        )
+    }
+
+    /// Like `get_pkg_reviews_with_issues_for_source`, but including the reviews
+    /// of packages setting flags that are effectively set (see `get_effective_flags`)
+    pub fn get_pkg_reviews_with_issues_or_flags_for_source<'a, 'b, 'c: 'a>(
+        &'a self,
+        source: &'b str,
+        trust_set: &'c TrustSet,
+        trust_level_required: TrustLevel,
+        policy: &FlagPolicy,
+    ) -> impl Iterator<Item = &'a proof::review::Package> {
+        self.get_pkg_reviews_with_issues_or_flags_for_source_ignoring(
+            source,
+            trust_set,
+            trust_level_required,
+            policy,
+            &ReviewIgnoreList::new(),
+        )
+    }
+
+    /// Like `get_pkg_reviews_with_issues_or_flags_for_source`, but disregarding
+    /// reviews on the `ignore_list`
+    pub fn get_pkg_reviews_with_issues_or_flags_for_source_ignoring<'a, 'b, 'c: 'a>(
+        &'a self,
+        source: &'b str,
+        trust_set: &'c TrustSet,
+        trust_level_required: TrustLevel,
+        policy: &FlagPolicy,
+        ignore_list: &ReviewIgnoreList,
+    ) -> impl Iterator<Item = &'a proof::review::Package> {
+        let flagged = self.flagged_packages(source, trust_set, policy, |_| true);
+        self.get_pkg_reviews_with_issues_or_flags(
+            self.get_pkg_reviews_for_source(source),
+            trust_set.for_source(source),
+            trust_level_required,
+            ignore_list,
+            flagged,
+        )
+    }
+
+    /// Packages of `source` the `filter` accepts, with any of the flags effectively set
+    ///
+    /// The returned `PackageId`s are canonical.
+    fn flagged_packages(
+        &self,
+        source: &str,
+        trust_set: &TrustSet,
+        policy: &FlagPolicy,
+        filter: impl Fn(&proof::PackageId) -> bool,
+    ) -> HashSet<proof::PackageId> {
+        self.package_flags
+            .keys()
+            .filter(|pkg_id| pkg_id.source == source && filter(pkg_id))
+            .filter(|pkg_id| {
+                self.get_effective_flags(pkg_id, trust_set, policy)
+                    .any_set()
+            })
+            .cloned()
+            .collect()
+    }
+
+    fn get_pkg_reviews_with_issues_or_flags<'a, 'c: 'a>(
+        &'a self,
+        reviews: impl Iterator<Item = &'a proof::review::Package> + 'a,
+        trust_set: &'c TrustSet,
+        trust_level_required: TrustLevel,
+        ignore_list: &ReviewIgnoreList,
+        flagged: HashSet<proof::PackageId>,
+    ) -> impl Iterator<Item = &'a proof::review::Package> {
+        // usually empty, and never long, so not worth borrowing
+        let ignore_list = ignore_list.clone();
+        reviews
+            .filter(move |review| {
+                let effective = trust_set.get_effective_trust_level(&review.from().id);
+                effective >= trust_level_required && !ignore_list.is_ignored(review)
+            })
+            .filter(move |review| {
+                !review.issues.is_empty()
+                    || !review.advisories.is_empty()
+                    || (review.flags != proof::Flags::default()
+                        && flagged.contains(&canonical_package_id(
+                            &review.package.id.id.source,
+                            &review.package.id.id.name,
+                        )))
+            })
+    }
+
+    pub fn unique_package_review_proof_count(&self) -> usize {
+        self.package_review_signatures_by_pkg_review_id.len()
+    }
+
+    /// Numbers of package reviews of `source`, and of Ids and trust proofs
+    ///
+    /// Calculated in one pass over the reviews of the `source`. With a `trust_set`,
+    /// reviews by Ids trusted (for the `source`) at `min_level` or more are counted
+    /// as trusted; without it, none are.
+    pub fn summary(
+        &self,
+        source: &str,
+        trust_set: Option<&TrustSet>,
+        min_level: TrustLevel,
+    ) -> DbSummary {
+        let trust_set = trust_set.map(|trust_set| trust_set.for_source(source));
+        let is_trusted = |id: &Id| match trust_set {
+            Some(trust_set) => {
+                !trust_set.is_distrusted(id) && trust_set.get_effective_trust_level(id) >= min_level
+            }
+            None => false,
+        };
+
+        let mut summary = DbSummary {
+            trust_proofs: self.unique_trust_proof_count(),
+            known_ids: self.known_id_count(),
+            ..default()
+        };
+        let mut reviewers = HashSet::new();
+        for versions in self
+            .package_reviews
+            .get(source)
+            .into_iter()
+            .flat_map(BTreeMap::values)
+        {
+            let mut package_reviewed = false;
+            let mut package_trusted = false;
+            for pkg_review_ids in versions.values() {
+                let mut version_reviewed = false;
+                for pkg_review_id in pkg_review_ids {
+                    let review = self
+                        .get_pkg_review_by_pkg_review_id(pkg_review_id)
+                        .expect("exists");
+                    if review.retracted {
+                        summary.retracted_package_reviews += 1;
+                        continue;
+                    }
+                    summary.package_reviews += 1;
+                    version_reviewed = true;
+                    reviewers.insert(&pkg_review_id.from);
+                    if is_trusted(&pkg_review_id.from) {
+                        summary.trusted_package_reviews += 1;
+                        package_trusted = true;
+                    }
+                }
+                if version_reviewed {
+                    summary.versions += 1;
+                    package_reviewed = true;
+                }
+            }
+            if package_reviewed {
+                summary.packages += 1;
+            }
+            if package_trusted {
+                summary.trusted_packages += 1;
+            }
+        }
+        summary.reviewers = reviewers.len();
+        summary
+    }
+
+    /// `summary` of every package source, without any trust set
+    pub fn summary_all_sources(&self) -> BTreeMap<Source, DbSummary> {
+        self.package_reviews
+            .keys()
+            .map(|source| (source.clone(), self.summary(source, None, TrustLevel::None)))
+            .collect()
+    }
+
+    /// All the package sources anything is known about, sorted
+    ///
+    /// These are the sources of the reviewed packages, and of the packages
+    /// mentioned only as alternatives of them.
+    pub fn known_sources(&self) -> impl Iterator<Item = &Source> {
+        self.package_reviews
+            .keys()
+            .chain(&self.alternative_sources)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+    }
+
+    /// Reviews of the packages named `name` from every source, with their source
+    ///
+    /// Useful with registries mirroring others, or for different packages
+    /// of the same name from different sources.
+    pub fn get_pkg_reviews_for_name_any_source<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> impl Iterator<Item = (&'a Source, &'a proof::review::Package)> {
+        self.package_reviews.keys().flat_map(move |source| {
+            self.get_pkg_reviews_for_name(source, name)
+                .map(move |review| (source, review))
+        })
+    }
+
+    /// Size of `all_known_ids`, without collecting them
+    fn known_id_count(&self) -> usize {
+        self.url_by_id_self_reported.len()
+            + self
+                .url_by_id_reported_by_others
+                .keys()
+                .filter(|id| !self.url_by_id_self_reported.contains_key(*id))
+                .count()
+    }
+
+    pub fn unique_trust_proof_count(&self) -> usize {
+        self.trust_id_to_id
+            .iter()
+            .fold(0, |count, (_id, set)| count + set.len())
+    }
+
+    /// Number of Ids that reported trust for themselves
+    ///
+    /// Such trust is ignored, and not included in `unique_trust_proof_count`.
+    pub fn self_trusting_id_count(&self) -> usize {
+        self.self_trust.len()
+    }
+
+    /// Ids that reported trust for themselves, see `self_trusting_id_count`
+    pub fn self_trust_ids(&self) -> impl Iterator<Item = &Id> {
+        self.self_trust.keys()
+    }
+
+    fn add_code_review(
+        &mut self,
+        review: &review::Code,
+        signature: &str,
+        fetched_from: FetchSource,
+    ) {
+        let from = &review.from();
+        self.record_url_from_from_field(&review.date_utc(), &from, &fetched_from);
+
+        let pkg_id = canonical_package_id(&review.package.id.id.source, &review.package.id.id.name);
+        let files = self.code_review_files.entry(pkg_id.clone()).or_default();
+        for file in &review.files {
+            let file_review = TimestampedFileReview {
+                date: review.date_utc(),
+                value: FileReview {
+                    version: review.package.id.version.clone(),
+                    digest: file.digest.clone(),
+                    digest_type: file.digest_type.clone(),
+                    review: review.review.clone(),
+                    signature: signature.to_owned(),
+                },
+            };
+            // `Some` with the review replaced (if any), unless there is a more recent one
+            let mut replaced = None;
+            files
+                .entry(file.path.clone())
+                .or_default()
+                .entry(from.id.clone())
+                .and_modify(|f| {
+                    let old = f.value.clone();
+                    if f.update_to_more_recent(&file_review) {
+                        replaced = Some(Some(old));
+                    }
+                })
+                .or_insert_with(|| {
+                    replaced = Some(None);
+                    file_review.clone()
+                });
+            let replaced = match replaced {
+                Some(replaced) => replaced,
+                None => continue,
+            };
+            let key_of = |version: &Version| {
+                (
+                    from.id.clone(),
+                    proof::PackageVersionId::new(
+                        pkg_id.source.clone(),
+                        pkg_id.name.clone(),
+                        version.clone(),
+                    ),
+                    file.path.clone(),
+                )
+            };
+            if let Some(old) = replaced {
+                if let hash_map::Entry::Occupied(mut e) =
+                    self.file_reviews_by_digest.entry(old.digest.clone())
+                {
+                    e.get_mut().remove(&key_of(&old.version));
+                    if e.get().is_empty() {
+                        e.remove();
+                    }
+                }
+            }
+            self.file_reviews_by_digest
+                .entry(file.digest.clone())
+                .or_default()
+                .insert(key_of(&review.package.id.version));
+        }
+    }
+
+    /// Rebuild `file_reviews_by_digest` from `code_review_files`
+    fn reindex_file_reviews_by_digest(&mut self) {
+        let mut by_digest: HashMap<_, HashSet<_>> = HashMap::new();
+        for (pkg_id, files) in &self.code_review_files {
+            for (path, by_author) in files {
+                for (id, file_review) in by_author {
+                    by_digest
+                        .entry(file_review.value.digest.clone())
+                        .or_default()
+                        .insert((
+                            id.clone(),
+                            proof::PackageVersionId::new(
+                                pkg_id.source.clone(),
+                                pkg_id.name.clone(),
+                                file_review.value.version.clone(),
+                            ),
+                            path.clone(),
+                        ));
+                }
+            }
+        }
+        self.file_reviews_by_digest = by_digest;
+    }
+
+    /// Rebuild `alternative_sources` from the reviews in `package_alternatives`
+    fn reindex_alternative_sources(&mut self) {
+        let mut sources = BTreeSet::new();
+        for signature in self
+            .package_alternatives
+            .values()
+            .flat_map(BTreeMap::values)
+        {
+            if let Some(review) = self.package_review_by_signature.get(&signature.value) {
+                sources.extend(
+                    review
+                        .alternatives
+                        .iter()
+                        .map(|alternative| alternative.source.clone()),
+                );
+            }
+        }
+        self.alternative_sources = sources;
+    }
+
+    fn add_package_review(
+        &mut self,
+        review: &review::Package,
+        signature: &str,
+        fetched_from: FetchSource,
+    ) {
+        if let Some(stored) = self.package_review_by_signature.get(signature).cloned() {
+            if !is_same_package_review(&stored, review) {
+                if !self.resolve_signature_conflict(&stored, review, signature, &fetched_from) {
+                    return;
+                }
+                self.unindex_package_review(&stored, signature);
+                self.package_review_by_signature.remove(signature);
+                self.restore_package_reviews(
+                    &std::iter::once(canonical_pkg_review_id(&stored)).collect(),
+                );
+                self.fetched_from_by_signature.insert(
+                    signature.to_owned(),
+                    FetchSourceInfo::new(
+                        fetched_from.clone(),
+                        review.date_utc(),
+                        ProofKind::PackageReview,
+                        review.from().id.clone(),
+                    ),
+                );
+                self.recalculate_repo_stats();
+            }
+        }
+
+        self.insertion_counter += 1;
+
+        let from = &review.from();
+        self.record_url_from_from_field(&review.date_utc(), &from, &fetched_from);
+
+        let signature = match self.package_review_by_signature.get_key_value(signature) {
+            Some((signature, _)) => signature.clone(),
+            None => {
+                let signature = SharedSignature::from(signature);
+                self.package_review_by_signature
+                    .insert(signature.clone(), sync::Arc::new(review.to_owned()));
+                signature
+            }
+        };
+
+        let pkg_review_id = self.interner.pkg_version_review_id(review);
+        let timestamp_signature = TimestampedSignature::from((review.date(), signature.clone()));
+        let timestamp_flags = TimestampedFlags::from((review.date(), review.flags.clone()));
+
+        self.package_review_history
+            .entry(pkg_review_id.clone())
+            .or_default()
+            .insert((timestamp_signature.date, signature));
+
+        self.package_review_signatures_by_package_digest
+            .entry(review.package.digest.to_owned())
+            .or_default()
+            .entry(pkg_review_id.clone())
+            .and_modify(|s| {
+                s.update_to_more_recent(&timestamp_signature);
+            })
+            .or_insert_with(|| timestamp_signature.clone());
+
+        // date of the review this one replaces, if any
+        let mut replaced_date = None;
+        let mut is_most_recent = true;
+        let mut change = Some(ChangeKind::Added);
+        self.package_review_signatures_by_pkg_review_id
+            .entry(pkg_review_id.clone())
+            .and_modify(|s| {
+                let prev_date = s.date;
+                let prev_signature = s.value.clone();
+                if s.update_to_more_recent(&timestamp_signature) {
+                    replaced_date = Some(prev_date);
+                    change = if prev_signature == s.value {
+                        None
+                    } else {
+                        Some(ChangeKind::Superseded)
+                    };
+                } else {
+                    is_most_recent = false;
+                    change = None;
+                }
+            })
+            .or_insert_with(|| timestamp_signature.clone());
+        if let Some(change) = change {
+            let source = review.package.id.id.source.clone();
+            let name = review.package.id.id.name.clone();
+            let version = review.package.id.version.clone();
+            let author = review.from().id.clone();
+            self.record_change(match change {
+                ChangeKind::Added => ChangeEvent::PackageReviewAdded {
+                    source,
+                    name,
+                    version,
+                    author,
+                },
+                ChangeKind::Superseded => ChangeEvent::PackageReviewSuperseded {
+                    source,
+                    name,
+                    version,
+                    author,
+                },
+            });
+        }
+
+        let pkg_id = &review.package.id.id;
+        let canonical_pkg_id = canonical_package_id(&pkg_id.source, &pkg_id.name);
+        if is_most_recent {
+            let by_date = self
+                .package_reviews_by_date
+                .entry(canonical_pkg_id.clone())
+                .or_default();
+            if let Some(replaced_date) = replaced_date {
+                by_date.remove(&(cmp::Reverse(replaced_date), pkg_review_id.clone()));
+            }
+            by_date.insert((
+                cmp::Reverse(timestamp_signature.date),
+                pkg_review_id.clone(),
+            ));
+            record_latest_review(
+                &mut self.latest_package_reviews,
+                &pkg_review_id,
+                timestamp_signature.date,
+            );
+        }
+
+        self.package_reviews
+            .entry(canonical_pkg_id.source.clone())
+            .or_default()
+            .entry(canonical_pkg_id.name.clone())
+            .or_default()
+            .entry(review.package.id.version.clone())
+            .or_default()
+            .insert(pkg_review_id.clone());
+
+        self.package_reviews_by_author
+            .entry(review.from().id.clone())
+            .or_default()
+            .insert(pkg_review_id.clone());
+
+        if review.diff_base.is_some() {
+            self.package_range_reviews
+                .entry(canonical_pkg_id.clone())
+                .or_default()
+                .insert(pkg_review_id);
+        }
+
+        self.package_alternatives
+            .entry(canonical_pkg_id.clone())
+            .or_default()
+            .entry(review.from().id.clone())
+            .and_modify(|a| {
+                a.update_to_more_recent(&timestamp_signature);
+            })
+            .or_insert_with(|| timestamp_signature);
+        for alternative in &review.alternatives {
+            if !self.alternative_sources.contains(&alternative.source) {
+                self.alternative_sources.insert(alternative.source.clone());
+            }
+        }
+
+        self.package_flags
+            .entry(canonical_pkg_id)
+            .or_default()
+            .entry(review.from().id.clone())
+            .and_modify(|f| {
+                f.update_to_more_recent(&timestamp_flags);
+            })
+            .or_insert_with(|| timestamp_flags);
+    }
+
+    /// Record a conflict between the `stored` review and the `incoming` one
+    /// having the same `signature`
+    ///
+    /// Returns `true` if the `incoming` one should replace the `stored` one.
+    fn resolve_signature_conflict(
+        &mut self,
+        stored: &review::Package,
+        incoming: &review::Package,
+        signature: &str,
+        fetched_from: &FetchSource,
+    ) -> bool {
+        // every stored review should have a fetch source, but don't rely on it
+        let stored_from = match self.fetched_from_by_signature.get(signature) {
+            Some(info) => info.source.clone(),
+            None => fetched_from.clone(),
+        };
+        let replace = self.is_authors_own_source(&incoming.from().id, fetched_from)
+            && !self.is_authors_own_source(&stored.from().id, &stored_from);
+
+        let (kept_from, discarded_from) = if replace {
+            (fetched_from.clone(), stored_from)
+        } else {
+            (stored_from, fetched_from.clone())
+        };
+        debug!(
+            "Conflicting proofs with signature {}: keeping one from {}, discarding one from {}",
+            signature,
+            kept_from.name(),
+            discarded_from.name()
+        );
+        self.integrity_warnings
+            .push(IntegrityWarning::ConflictingSignature {
+                signature: signature.to_owned(),
+                kept_from,
+                discarded_from,
+            });
+
+        replace
+    }
+
+    /// Is `fetched_from` user's own repo, or the one `author` reports as their own
+    fn is_authors_own_source(&self, author: &Id, fetched_from: &FetchSource) -> bool {
+        match fetched_from {
+            FetchSource::LocalUser => true,
+            FetchSource::Url(fetched_url) => match self.url_by_id_self_reported.get(author) {
+                Some((url, _)) => self.canonical_url(fetched_url) == self.canonical_url(&url.value),
+                None => false,
+            },
+        }
+    }
+
+    /// Recalculate `latest_package_reviews` of the `author` from scratch
+    fn reindex_latest_reviews(&mut self, canonical_pkg_id: &proof::PackageId, author: &Id) {
+        if let Some(by_author) = self.latest_package_reviews.get_mut(canonical_pkg_id) {
+            by_author.remove(author);
+        }
+        let pkg_review_ids = self
+            .package_reviews
+            .get(&canonical_pkg_id.source)
+            .and_then(|names| names.get(&canonical_pkg_id.name))
+            .into_iter()
+            .flat_map(BTreeMap::values)
+            .flatten()
+            .filter(|pkg_review_id| *pkg_review_id.from == *author);
+        for pkg_review_id in pkg_review_ids {
+            if let Some(signature) = self
+                .package_review_signatures_by_pkg_review_id
+                .get(pkg_review_id)
+            {
+                record_latest_review(
+                    &mut self.latest_package_reviews,
+                    pkg_review_id,
+                    signature.date,
+                );
+            }
+        }
+    }
+
+    /// Remove the `review` stored under `signature` from all the indices
+    fn unindex_package_review(&mut self, review: &review::Package, signature: &str) {
+        let pkg_review_id: PkgVersionReviewId = review.into();
+        let points_to_review = |s: &TimestampedSignature| &*s.value == signature;
+
+        if let hash_map::Entry::Occupied(mut e) =
+            self.package_review_history.entry(pkg_review_id.clone())
+        {
+            e.get_mut()
+                .remove(&(review.date_utc(), SharedSignature::from(signature)));
+            if e.get().is_empty() {
+                e.remove();
+            }
+        }
+
+        if let Some(by_id) = self
+            .package_review_signatures_by_package_digest
+            .get_mut(&review.package.digest)
+        {
+            if matches!(by_id.get(&pkg_review_id), Some(s) if points_to_review(s)) {
+                by_id.remove(&pkg_review_id);
+            }
+        }
+
+        let date = match self
+            .package_review_signatures_by_pkg_review_id
+            .get(&pkg_review_id)
+        {
+            Some(s) if points_to_review(s) => s.date,
+            _ => return,
+        };
+        self.package_review_signatures_by_pkg_review_id
+            .remove(&pkg_review_id);
+
+        let pkg_id = &review.package.id.id;
+        let canonical_pkg_id = canonical_package_id(&pkg_id.source, &pkg_id.name);
+        if let Some(by_date) = self.package_reviews_by_date.get_mut(&canonical_pkg_id) {
+            by_date.remove(&(cmp::Reverse(date), pkg_review_id.clone()));
+        }
+        if let Some(pkg_review_ids) = self
+            .package_reviews
+            .get_mut(&canonical_pkg_id.source)
+            .and_then(|names| names.get_mut(&canonical_pkg_id.name))
+            .and_then(|versions| versions.get_mut(&review.package.id.version))
+        {
+            pkg_review_ids.remove(&pkg_review_id);
+        }
+        if let Some(pkg_review_ids) = self.package_reviews_by_author.get_mut(&review.from().id) {
+            pkg_review_ids.remove(&pkg_review_id);
+        }
+        if let Some(range_reviews) = self.package_range_reviews.get_mut(&canonical_pkg_id) {
+            range_reviews.remove(&pkg_review_id);
+        }
+        self.reindex_latest_reviews(&canonical_pkg_id, &review.from().id);
+
+        let author = &review.from().id;
+        if let Some(alternatives) = self.package_alternatives.get_mut(&canonical_pkg_id) {
+            if matches!(alternatives.get(author), Some(s) if points_to_review(s)) {
+                alternatives.remove(author);
+                if let Some(flags) = self.package_flags.get_mut(&canonical_pkg_id) {
+                    flags.remove(author);
+                }
+            }
+        }
+    }
+
+    /// Index again all the stored reviews of the authors of the packages in `superseded`
+    ///
+    /// To be called after some of their reviews were unindexed, so that the
+    /// ones these superseded take their place again.
+    fn restore_package_reviews(&mut self, superseded: &HashSet<PkgReviewId>) {
+        if superseded.is_empty() {
+            return;
+        }
+        let mut restored: Vec<_> = self
+            .package_review_by_signature
+            .iter()
+            .filter(|(_, review)| superseded.contains(&canonical_pkg_review_id(review)))
+            .map(|(signature, review)| (signature.clone(), review.clone()))
+            .collect();
+        restored.sort_by(|a, b| a.1.date_utc().cmp(&b.1.date_utc()).then(a.0.cmp(&b.0)));
+        for (signature, review) in restored {
+            let fetched_from = match self.fetched_from_by_signature.get(&*signature) {
+                Some(info) => info.source.clone(),
+                None => FetchSource::LocalUser,
+            };
+            self.add_package_review(&review, &signature, fetched_from);
+        }
+    }
+
+    pub fn get_package_review_count(
+        &self,
+        source: &str,
+        name: Option<&str>,
+        version: Option<&Version>,
+    ) -> usize {
+        self.get_package_reviews_for_package(source, name, version)
+            .count()
+    }
+
+    pub fn get_package_reviews_for_package<'a, 'b, 'c: 'a, 'd: 'a>(
+        &'a self,
+        source: &'b str,
+        name: Option<&'c str>,
+        version: Option<&'d Version>,
+    ) -> impl Iterator<Item = &'a proof::review::Package> + 'a {
+        match (name, version) {
+            (Some(ref name), Some(ref version)) => {
+                Box::new(self.get_pkg_reviews_for_version(source, name, version))
+                    as Box<dyn Iterator<Item = _>>
+            }
+            (Some(ref name), None) => Box::new(self.get_pkg_reviews_for_name(source, name)),
+            (None, None) => Box::new(self.get_pkg_reviews_for_source(source)),
+            (None, Some(_)) => panic!("Wrong usage"),
+        }
+    }
+
+    /// Like `get_package_reviews_for_package_sorted_ref` in `ReviewSortOrder::DateAsc`,
+    /// but the result doesn't borrow the `ProofDB`
+    pub fn get_package_reviews_for_package_sorted<'a, 'b, 'c: 'a, 'd: 'a>(
+        &'a self,
+        source: &'b str,
+        name: Option<&'c str>,
+        version: Option<&'d Version>,
+    ) -> Vec<sync::Arc<proof::review::Package>> {
+        self.get_package_review_arcs_sorted(source, name, version, ReviewSortOrder::DateAsc, None)
+            .into_iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Reviews of a package (or all the packages of a `source`, or of all versions
+    /// of a package, like in `get_package_reviews_for_package`), in the given `order`
+    ///
+    /// The `trust_set` is needed only for `ReviewSortOrder::TrustLevelDesc`.
+    pub fn get_package_reviews_for_package_sorted_ref<'a>(
+        &'a self,
+        source: &str,
+        name: Option<&str>,
+        version: Option<&Version>,
+        order: ReviewSortOrder,
+        trust_set: Option<&TrustSet>,
+    ) -> Vec<&'a review::Package> {
+        self.get_package_review_arcs_sorted(source, name, version, order, trust_set)
+            .into_iter()
+            .map(|review| &**review)
+            .collect()
+    }
+
+    fn get_package_review_arcs_sorted<'a>(
+        &'a self,
+        source: &str,
+        name: Option<&str>,
+        version: Option<&Version>,
+        order: ReviewSortOrder,
+        trust_set: Option<&TrustSet>,
+    ) -> Vec<&'a sync::Arc<review::Package>> {
+        let by_name = self.package_reviews.get(source);
+        let pkg_review_ids: Box<dyn Iterator<Item = &PkgVersionReviewId>> = match (name, version)
+        {
+            (Some(name), Some(version)) => Box::new(
+                by_name
+                    .and_then(|map| map.get(&canonicalize_package_name(source, name)))
+                    .and_then(|map| map.get(version))
+                    .into_iter()
+                    .flatten(),
+            ),
+            (Some(name), None) => Box::new(
+                by_name
+                    .and_then(|map| map.get(&canonicalize_package_name(source, name)))
+                    .into_iter()
+                    .flat_map(BTreeMap::values)
+                    .flatten(),
+            ),
+            (None, None) => Box::new(
+                by_name
+                    .into_iter()
+                    .flat_map(BTreeMap::values)
+                    .flat_map(BTreeMap::values)
+                    .flatten(),
+            ),
+            (None, Some(_)) => panic!("Wrong usage"),
+        };
+        let mut reviews: Vec<_> = pkg_review_ids
+            .map(|pkg_review_id| {
+                self.get_pkg_review_arc_by_pkg_review_id(pkg_review_id)
+                    .expect("exists")
+            })
+            .filter(|review| self.is_in_effect(review))
+            .collect();
+
+        // stable sorts, so equal reviews stay in the order of the index
+        match order {
+            ReviewSortOrder::DateAsc => reviews.sort_by_key(|review| review.date_utc()),
+            ReviewSortOrder::DateDesc => {
+                reviews.sort_by_key(|review| cmp::Reverse(review.date_utc()))
+            }
+            ReviewSortOrder::VersionThenDate => reviews.sort_by(|a, b| {
+                a.package
+                    .id
+                    .version
+                    .cmp(&b.package.id.version)
+                    .then_with(|| a.date_utc().cmp(&b.date_utc()))
+            }),
+            ReviewSortOrder::TrustLevelDesc => {
+                let trust_set = trust_set.map(|trust_set| trust_set.for_source(source));
+                let level = |review: &review::Package| {
+                    trust_set.map_or(TrustLevel::None, |trust_set| {
+                        trust_set.get_effective_trust_level(&review.from().id)
+                    })
+                };
+                reviews.sort_by(|a, b| {
+                    level(b)
+                        .cmp(&level(a))
+                        .then_with(|| a.date_utc().cmp(&b.date_utc()))
+                })
+            }
+        }
+        reviews
+    }
+
+    fn add_trust_raw(&mut self, from: &Id, to: &Id, proof: &proof::Trust, signature: &str) {
+        let date = proof.date_utc();
+        let trust = proof.trust;
+        if self.keep_trust_history {
+            let history = self
+                .trust_history
+                .entry(from.to_owned())
+                .or_default()
+                .entry(to.to_owned())
+                .or_default();
+            let entry = TimestampedTrustLevel { value: trust, date };
+            match history.binary_search_by_key(&date, |e| e.date) {
+                Ok(i) => history[i] = entry,
+                Err(i) => history.insert(i, entry),
+            }
+        }
+
+        let tl = TimestampedTrustEdge {
+            value: TrustEdge {
+                level: trust,
+                comment: proof.comment.clone(),
+                signature: signature.to_owned(),
+                sources: proof.sources.clone(),
+                expires: proof.expires.map(|expires| expires.with_timezone(&Utc)),
+            },
+            date,
+        };
+        let mut old_level = None;
+        let mut scope_changed = false;
+        let new_level = self
+            .trust_id_to_id
+            .entry(from.to_owned())
+            .or_default()
+            .entry(to.to_owned())
+            .and_modify(|e| {
+                old_level = Some(e.value.level);
+                let old_sources = e.value.sources.clone();
+                let old_expires = e.value.expires;
+                e.update_to_more_recent(&tl);
+                scope_changed = old_sources != e.value.sources || old_expires != e.value.expires;
+            })
+            .or_insert_with(|| tl)
+            .value
+            .level;
+        if old_level.is_none() {
+            self.trust_reported_by
+                .entry(to.to_owned())
+                .or_default()
+                .insert(from.to_owned());
+        }
+        if old_level != Some(new_level) || scope_changed {
+            self.trust_insertion_counter += 1;
+        }
+        if old_level != Some(new_level) {
+            self.record_change(ChangeEvent::TrustEdgeChanged {
+                from: from.clone(),
+                to: to.clone(),
+                old_level,
+                new_level,
+            });
+        }
+    }
+
+    fn add_trust(&mut self, trust: &proof::Trust, signature: &str, fetched_from: FetchSource) {
+        let from = &trust.from();
+        self.record_url_from_from_field(&trust.date_utc(), &from, &fetched_from);
+        for to in &trust.ids {
+            if to.id == from.id {
+                // Trusting oneself is meaningless; keep it aside
+                // so it can be reported, but not traversed
+                debug!("{} reports trust for itself", from.id);
+                let tl = TimestampedTrustLevel {
+                    value: trust.trust,
+                    date: trust.date_utc(),
+                };
+                self.self_trust
+                    .entry(from.id.clone())
+                    .and_modify(|e| {
+                        e.update_to_more_recent(&tl);
+                    })
+                    .or_insert(tl);
+                continue;
+            }
+            self.add_trust_raw(&from.id, &to.id, trust, signature);
+        }
+        for to in &trust.ids {
+            // Others should not be making verified claims about this URL,
+            // regardless of where these proofs were fetched from, because only
+            // owner of the Id is authoritative.
+            self.record_url_from_to_field(&trust.date_utc(), &to)
+        }
+    }
+
+    fn add_id_supersession(
+        &mut self,
+        supersession: &proof::IdSupersession,
+        fetched_from: FetchSource,
+    ) {
+        let from = supersession.from();
+        let date = supersession.date_utc();
+        self.record_url_from_from_field(&date, from, &fetched_from);
+        self.record_url_from_to_field(&date, &supersession.superseded_by);
+        if supersession.superseded_by.id == from.id {
+            debug!("{} declares it's superseded by itself", from.id);
+            return;
+        }
+        let superseded_by = Timestamped {
+            date,
+            value: supersession.superseded_by.id.clone(),
+        };
+        let changed = match self.id_superseded_by.entry(from.id.clone()) {
+            hash_map::Entry::Occupied(mut e) => e.get_mut().update_to_more_recent(&superseded_by),
+            hash_map::Entry::Vacant(e) => {
+                e.insert(superseded_by);
+                true
+            }
+        };
+        if changed {
+            self.trust_insertion_counter += 1;
+        }
+    }
+
+    /// The Id `id` declared to be superseded by, in its most recent supersession proof
+    pub fn get_id_superseded_by(&self, id: &Id) -> Option<&Id> {
+        self.id_superseded_by.get(id).map(Timestamped::value)
+    }
+
+    /// The current Id of whoever used `id`, following the chain of supersession proofs
+    ///
+    /// `id` itself, if it's not superseded. If the chain loops back (e.g. two Ids
+    /// declared to be superseded by each other), the Id named by the most recent
+    /// supersession proof in the loop is the current one.
+    pub fn resolve_id<'s>(&'s self, id: &'s Id) -> &'s Id {
+        let mut chain = vec![];
+        let mut current = id;
+        while let Some(next) = self.id_superseded_by.get(current) {
+            if let Some(pos) = chain.iter().position(|id| *id == current) {
+                return chain[pos..]
+                    .iter()
+                    .map(|id| &self.id_superseded_by[*id])
+                    .max_by(|a, b| a.date.cmp(&b.date).then(b.value.cmp(&a.value)))
+                    .map(Timestamped::value)
+                    .expect("not empty");
+            }
+            chain.push(current);
+            current = &next.value;
+        }
+        current
+    }
+
+    /// All the Ids resolving to the same current Id, by the current Id (included)
+    ///
+    /// Only the superseded Ids and their successors are there.
+    fn supersession_groups(&self) -> BTreeMap<&Id, Vec<&Id>> {
+        let mut groups: BTreeMap<&Id, Vec<&Id>> = BTreeMap::new();
+        for old in self.id_superseded_by.keys() {
+            let current = self.resolve_id(old);
+            if current != old {
+                groups
+                    .entry(current)
+                    .or_insert_with(|| vec![current])
+                    .push(old);
+            }
+        }
+        groups
+    }
+
+    pub fn all_known_ids(&self) -> BTreeSet<Id> {
+        self.url_by_id_self_reported
+            .keys()
+            .chain(self.url_by_id_reported_by_others.keys())
+            .cloned()
+            .collect()
+    }
+
+    /// Where the proof with a given signature has been fetched from
+    pub fn get_fetch_source_of(&self, signature: &str) -> Option<&FetchSource> {
+        self.fetched_from_by_signature
+            .get(signature)
+            .map(|info| &info.source)
+    }
+
+    /// All the places the proof with a given signature has been fetched from
+    ///
+    /// Empty, if the proof is not known.
+    pub fn get_proof_provenance(&self, signature: &str) -> BTreeSet<FetchSourceKey> {
+        self.fetched_from_by_signature
+            .get(signature)
+            .into_iter()
+            .flat_map(FetchSourceInfo::sources)
+            .map(FetchSource::key)
+            .collect()
+    }
+
+    /// Package reviews by `id` (and their signatures) that have never been fetched
+    /// from the URL of `id`, or user's own repos
+    ///
+    /// Unlike the verification of the URL of `id`, which is done once for all
+    /// of its proofs, these are the reviews that could have been forged by
+    /// third-party repos, e.g. if the signing key has been compromised.
+    pub fn reviews_not_from_verified_source(&self, id: &Id) -> Vec<(&str, &review::Package)> {
+        self.package_reviews_by_author
+            .get(id)
+            .into_iter()
+            .flatten()
+            .map(|pkg_review_id| {
+                let signature = &self.package_review_signatures_by_pkg_review_id[pkg_review_id];
+                let review = &self.package_review_by_signature[&signature.value];
+                (&*signature.value, &**review)
+            })
+            .filter(|(_, review)| !review.retracted)
+            .filter(|(signature, review)| {
+                let url = match &review.from().url {
+                    Some(url) => url,
+                    None => return true,
+                };
+                !self
+                    .fetched_from_by_signature
+                    .get(*signature)
+                    .into_iter()
+                    .flat_map(FetchSourceInfo::sources)
+                    .any(|source| self.is_verified_fetch_source(source, url))
+            })
+            .collect()
+    }
+
+    /// Number of proofs and the newest proof date, by `FetchSource::name`
+    pub fn proof_count_by_fetch_source(&self) -> BTreeMap<String, FetchStats> {
+        let mut res: BTreeMap<String, FetchStats> = BTreeMap::new();
+        for info in self.fetched_from_by_signature.values() {
+            res.entry(info.source.name().to_owned())
+                .and_modify(|stats| {
+                    stats.proof_count += 1;
+                    stats.newest_proof_date = std::cmp::max(stats.newest_proof_date, info.date);
+                })
+                .or_insert(FetchStats {
+                    proof_count: 1,
+                    newest_proof_date: info.date,
+                });
+        }
+        res
+    }
+
+    /// Activity of `id`: its package reviews, trust proofs, and when it was active
+    ///
+    /// Calculated on demand; to get these of many Ids, `all_id_stats` is faster.
+    pub fn get_id_stats(&self, id: &Id) -> IdStats {
+        let mut stats = IdStatsBuilder::default();
+        for review in self.get_pkg_reviews_by_author(id) {
+            stats.add_package_review(review);
+        }
+        if let Some(edges) = self.trust_id_to_id.get(id) {
+            stats.add_trust_edges(edges);
+        }
+        for info in self.fetched_from_by_signature.values() {
+            if &info.author == id {
+                stats.add_proof_date(info.date);
+            }
+        }
+        stats.build()
+    }
+
+    /// `get_id_stats` of every Id that authored a proof, in one pass over all of them
+    pub fn all_id_stats(&self) -> BTreeMap<Id, IdStats> {
+        let mut stats: HashMap<&Id, IdStatsBuilder> = HashMap::new();
+        for (pkg_review_id, signature) in &self.package_review_signatures_by_pkg_review_id {
+            let review = &self.package_review_by_signature[&signature.value];
+            if !review.retracted {
+                stats
+                    .entry(&pkg_review_id.from)
+                    .or_default()
+                    .add_package_review(review);
+            }
+        }
+        for (id, edges) in &self.trust_id_to_id {
+            stats.entry(id).or_default().add_trust_edges(edges);
+        }
+        for info in self.fetched_from_by_signature.values() {
+            stats
+                .entry(&info.author)
+                .or_default()
+                .add_proof_date(info.date);
+        }
+        stats
+            .into_iter()
+            .map(|(id, stats)| (id.clone(), stats.build()))
+            .collect()
+    }
+
+    /// Get all Ids that authored a proof (with total count)
+    pub fn all_author_ids(&self) -> BTreeMap<Id, usize> {
+        let mut res = BTreeMap::new();
+        for (id, set) in &self.trust_id_to_id {
+            *res.entry(id.to_owned()).or_default() += set.len();
+        }
+
+        for uniq_rev in self.package_review_signatures_by_pkg_review_id.keys() {
+            *res.entry((*uniq_rev.from).clone()).or_default() += 1;
+        }
+
+        res
+    }
+
+    pub fn get_package_review_by_signature<'a>(
+        &'a self,
+        signature: &str,
+    ) -> Option<&'a review::Package> {
+        self.package_review_by_signature
+            .get(signature)
+            .map(|review| &**review)
+    }
+
+    pub fn get_package_reviews_by_digest<'a>(
+        &'a self,
+        digest: &Digest,
+    ) -> impl Iterator<Item = &'a review::Package> + 'a {
+        self.get_package_reviews_by_digest_including_retracted(digest)
+            .filter(move |review| self.is_in_effect(review))
+    }
+
+    /// Like `get_package_reviews_by_digest`, but including retractions
+    pub fn get_package_reviews_by_digest_including_retracted<'a>(
+        &'a self,
+        digest: &Digest,
+    ) -> impl Iterator<Item = &'a review::Package> + 'a {
+        self.package_review_signatures_by_package_digest
+            .get(digest.as_slice())
+            .into_iter()
+            .flat_map(move |unique_reviews| {
+                unique_reviews
+                    .iter()
+                    .map(move |(_unique_review, signature)| {
+                        &*self.package_review_by_signature[&signature.value]
+                    })
+            })
+    }
+
+    /// All the package versions reviewed with a given digest
+    ///
+    /// More than one means the same code has been published under different
+    /// names or sources (e.g. a renamed crate, or a vendored fork).
+    /// Retracted reviews count too, as they still say what the digest is.
+    pub fn get_package_ids_by_digest(&self, digest: &Digest) -> Vec<proof::PackageVersionId> {
+        let ids: BTreeSet<_> = self
+            .package_review_signatures_by_package_digest
+            .get(digest.as_slice())
+            .into_iter()
+            .flat_map(BTreeMap::values)
+            .map(|signature| {
+                &self.package_review_by_signature[&signature.value]
+                    .package
+                    .id
+            })
+            .collect();
+        ids.into_iter().cloned().collect()
+    }
+
+    /// Digests of a package version, as reported by its reviews
+    pub fn get_digests_for_package(
+        &self,
+        source: &str,
+        name: &str,
+        version: &Version,
+    ) -> BTreeSet<Digest> {
+        self.get_pkg_reviews_for_version(source, name, version)
+            .map(|review| Digest::from_vec(review.package.digest.clone()))
+            .collect()
+    }
+
+    /// Versions of a package reviewed with more than one digest, with reviewers of every digest
+    ///
+    /// Reviewers of the same version reviewed different code, so at least
+    /// one of them has reviewed something else than what's been published.
+    pub fn find_digest_conflicts(
+        &self,
+        source: &str,
+        name: &str,
+    ) -> BTreeMap<Version, BTreeMap<Digest, BTreeSet<Id>>> {
+        let mut by_version: BTreeMap<Version, BTreeMap<Digest, BTreeSet<Id>>> = BTreeMap::new();
+        for review in self.get_pkg_reviews_for_name(source, name) {
+            by_version
+                .entry(review.package.id.version.clone())
+                .or_default()
+                .entry(Digest::from_vec(review.package.digest.clone()))
+                .or_default()
+                .insert(review.from().id.clone());
+        }
+        by_version.retain(|_, by_digest| by_digest.len() > 1);
+        by_version
+    }
+
+    /// The most relevant review of a package with a given digest
+    ///
+    /// Only reviews by authors with effective trust of at least `min_level`
+    /// are considered, and distrusted authors are always ignored. Among these,
+    /// reviews are ordered by: author's effective trust level (highest first),
+    /// review date (newest first), author Id (lowest first).
+    pub fn best_review_for_digest(
+        &self,
+        digest: &Digest,
+        trust_set: &TrustSet,
+        min_level: TrustLevel,
+    ) -> Option<&review::Package> {
+        self.trusted_reviews_for_digest(digest, trust_set, min_level)
+            .min_by(|(a_level, a), (b_level, b)| {
+                b_level
+                    .cmp(a_level)
+                    .then_with(|| b.date_utc().cmp(&a.date_utc()))
+                    .then_with(|| a.from().id.cmp(&b.from().id))
+            })
+            .map(|(_, review)| review)
+    }
+
+    /// Number of reviews `best_review_for_digest` picks from
+    pub fn trusted_review_count_for_digest(
+        &self,
+        digest: &Digest,
+        trust_set: &TrustSet,
+        min_level: TrustLevel,
+    ) -> usize {
+        self.trusted_reviews_for_digest(digest, trust_set, min_level)
+            .count()
+    }
+
+    fn trusted_reviews_for_digest<'s: 't, 't>(
+        &'s self,
+        digest: &Digest,
+        trust_set: &'t TrustSet,
+        min_level: TrustLevel,
+    ) -> impl Iterator<Item = (TrustLevel, &'s review::Package)> + 't {
+        self.package_review_signatures_by_package_digest
+            .get(digest.as_slice())
+            .into_iter()
+            .flat_map(move |unique_reviews| {
+                unique_reviews
+                    .values()
+                    .map(move |signature| &*self.package_review_by_signature[&signature.value])
+            })
+            .filter(move |review| {
+                self.is_in_effect(review) && !trust_set.is_distrusted(&review.from().id)
+            })
+            .map(move |review| {
+                (
+                    trust_set.get_effective_trust_level(&review.from().id),
+                    review,
+                )
+            })
+            .filter(move |(level, _)| min_level <= *level)
+    }
+
+    /// Find package reviews of packages with a digest starting with a given prefix
+    ///
+    /// The prefix can be either hex, or base64 encoded (the way crev displays digests),
+    /// and must be at least `MIN_DIGEST_PREFIX_LEN` characters long. A prefix that is
+    /// valid in both encodings is read as hex, unless no digest matches it that way.
+    /// If the prefix matches more than one digest, `Error::AmbiguousDigestPrefix`
+    /// listing all the candidates is returned.
+    pub fn find_reviews_by_digest_prefix(&self, prefix: &str) -> Result<Vec<&review::Package>> {
+        if prefix.len() < MIN_DIGEST_PREFIX_LEN {
+            return Err(Error::DigestPrefixTooShort(MIN_DIGEST_PREFIX_LEN));
+        }
+        if !prefix.is_ascii() {
+            return Ok(vec![]);
+        }
+
+        let mut candidates = BTreeSet::new();
+
+        if prefix.chars().all(|c| c.is_ascii_hexdigit()) {
+            let prefix = prefix.to_ascii_lowercase();
+            let bytes = hex::decode(&prefix[..prefix.len() / 2 * 2]).expect("valid hex");
+            candidates.extend(
+                self.digests_with_byte_prefix(&bytes)
+                    .filter(|digest| hex::encode(digest).starts_with(&prefix)),
+            );
+        }
+
+        if candidates.is_empty() {
+            if let Ok(bytes) = crev_common::base64_decode(&prefix[..prefix.len() / 4 * 4]) {
+                candidates.extend(
+                    self.digests_with_byte_prefix(&bytes)
+                        .filter(|digest| crev_common::base64_encode(digest).starts_with(prefix)),
+                );
+            }
+        }
+
+        if candidates.len() > 1 {
+            return Err(Error::AmbiguousDigestPrefix(
+                candidates.into_iter().cloned().collect(),
+            ));
+        }
+
+        Ok(candidates
+            .into_iter()
+            .flat_map(|digest| &self.package_review_signatures_by_package_digest[digest])
+            .map(|(_, signature)| &*self.package_review_by_signature[&signature.value])
+            .filter(move |review| self.is_in_effect(review))
+            .collect())
+    }
+
+    fn digests_with_byte_prefix<'s>(&'s self, prefix: &[u8]) -> impl Iterator<Item = &'s Vec<u8>> {
+        let prefix = prefix.to_owned();
+        self.package_review_signatures_by_package_digest
+            .range(prefix.clone()..)
+            .map(|(digest, _)| digest)
+            .take_while(move |digest| digest.starts_with(&prefix))
+    }
+
+    /// Whether proofs of an Id with `url` fetched from `source` are
+    /// known to come from the Id itself
+    fn is_verified_fetch_source(&self, source: &FetchSource, url: &Url) -> bool {
+        match source {
+            FetchSource::LocalUser => true,
+            FetchSource::Url(fetched_url) => {
+                self.canonical_url(fetched_url) == self.canonical_url(url)
+            }
+        }
+    }
+
+    fn canonical_url(&self, url: &Url) -> String {
+        canonicalize_url(&url.url, self.ignore_url_scheme)
+    }
+
+    fn record_url_claim(&mut self, url: &Url, id: &Id) {
+        let canonical = self.canonical_url(url);
+        self.ids_by_url
+            .entry(canonical)
+            .or_default()
+            .insert(id.clone());
+    }
+
+    fn record_url_history(&mut self, id: &Id, url: &TimestampedUrl) {
+        let canonical = self.canonical_url(&url.value);
+        let history = self.url_history.entry(id.clone()).or_default();
+        match history.entry(canonical) {
+            btree_map::Entry::Occupied(mut e) => {
+                if (url.date, &url.value.url) < (e.get().date, &e.get().value.url) {
+                    e.insert(url.clone());
+                }
+            }
+            btree_map::Entry::Vacant(e) => {
+                e.insert(url.clone());
+            }
+        }
+    }
+
+    /// Record an untrusted mapping between a PublicId and a URL it declares
+    fn record_url_from_to_field(&mut self, date: &DateTime<Utc>, to: &crev_data::PublicId) {
+        if let Some(url) = &to.url {
+            self.record_url_claim(url, &to.id);
+            if let hash_map::Entry::Vacant(e) =
+                self.url_by_id_reported_by_others.entry(to.id.clone())
+            {
+                e.insert(TimestampedUrl {
+                    value: url.clone(),
+                    date: *date,
+                });
+                self.record_change(ChangeEvent::UrlRecorded {
+                    id: to.id.clone(),
+                    verified: false,
+                });
+            }
+        }
+    }
+
+    /// Record mapping between a PublicId and a URL it declares, and trust it's correct only if it's been fetched from the same URL
+    fn record_url_from_from_field(
+        &mut self,
+        date: &DateTime<Utc>,
+        from: &crev_data::PublicId,
+        fetched_from: &FetchSource,
+    ) {
+        if let Some(url) = &from.url {
+            let tu = TimestampedUrl {
+                value: url.clone(),
+                date: date.to_owned(),
+            };
+            let fetch_matches = self.is_verified_fetch_source(fetched_from, url);
+            self.record_url_claim(url, &from.id);
+            self.record_url_history(&from.id, &tu);
+            let mut old = None;
+            let (new_url, verified) = self
+                .url_by_id_self_reported
+                .entry(from.id.clone())
+                .and_modify(|e| {
+                    old = Some((e.0.value.clone(), e.1));
+                    e.0.update_to_more_recent(&tu);
+                    if fetch_matches {
+                        e.1 = true;
+                    }
+                })
+                .or_insert_with(|| (tu, fetch_matches));
+            let verified = *verified;
+            if old.as_ref() != Some(&(new_url.value.clone(), verified)) {
+                self.record_change(ChangeEvent::UrlRecorded {
+                    id: from.id.clone(),
+                    verified,
+                });
+            }
+        }
+    }
+
+    fn add_proof(
+        &mut self,
+        proof: &proof::Proof,
+        fetched_from: FetchSource,
+    ) -> Result<ProofContent> {
+        let content = ProofContent::parse(proof, self.store_unknown_proofs)?;
+        self.add_parsed_proof(proof, &content, fetched_from);
+        Ok(content)
+    }
+
+    /// The part of `add_proof` that modifies the indices
+    fn add_parsed_proof(
+        &mut self,
+        proof: &proof::Proof,
+        content: &ProofContent,
+        fetched_from: FetchSource,
+    ) {
+        let fetch_source_info = FetchSourceInfo::new(
+            fetched_from.clone(),
+            proof.date_utc(),
+            ProofKind::of(proof.kind()),
+            proof.author_id().clone(),
+        );
+        match content {
+            ProofContent::CodeReview(review) => {
+                self.add_code_review(review, proof.signature(), fetched_from)
+            }
+            ProofContent::PackageReview(review) => {
+                self.add_package_review(review, proof.signature(), fetched_from)
+            }
+            ProofContent::Trust(trust) => self.add_trust(trust, proof.signature(), fetched_from),
+            ProofContent::IdSupersession(supersession) => {
+                self.add_id_supersession(supersession, fetched_from)
+            }
+            ProofContent::Unknown => {
+                // URLs of Ids are the same in proofs of any kind
+                self.record_url_from_from_field(&proof.date_utc(), proof.from(), &fetched_from);
+                if !self
+                    .fetched_from_by_signature
+                    .contains_key(proof.signature())
+                {
+                    self.unknown_proofs.push((proof.clone(), fetched_from));
+                }
+            }
+        }
+        if self.keep_signed_proofs {
+            self.keep_signed_proof(proof, content);
+        }
+
+        self.record_fetch_source(proof.signature(), fetch_source_info);
+    }
+
+    /// Store the `proof` in `signed_proofs`, unless it was rejected
+    fn keep_signed_proof(&mut self, proof: &proof::Proof, content: &ProofContent) {
+        let accepted = match content {
+            // one with the same signature could have been kept instead
+            ProofContent::PackageReview(review) => matches!(
+                self.package_review_by_signature.get(proof.signature()),
+                Some(stored) if is_same_package_review(stored, review)
+            ),
+            // kept in `unknown_proofs` already
+            ProofContent::Unknown => false,
+            _ => true,
+        };
+        if accepted {
+            self.signed_proofs
+                .insert(proof.signature().to_owned(), proof.clone());
+        }
+    }
+
+    /// Remember where the proof with `signature` was fetched from
+    fn record_fetch_source(&mut self, signature: &str, fetch_source_info: FetchSourceInfo) {
+        // the same proof can be fetched from many places; first one wins,
+        // but others are remembered, in case the first one is removed
+        let is_new_source = match self.fetched_from_by_signature.entry(signature.to_owned()) {
+            hash_map::Entry::Occupied(mut e) => {
+                e.get_mut().add_source(fetch_source_info.source.clone())
+            }
+            hash_map::Entry::Vacant(e) => {
+                e.insert(fetch_source_info.clone());
+                true
+            }
+        };
+        if is_new_source {
+            self.repo_stats
+                .entry(fetch_source_info.source.name().to_owned())
+                .or_insert_with(|| RepoStats::new(fetch_source_info.date))
+                .record(&fetch_source_info);
+        }
+    }
+
+    /// Proofs fetched from the repo at `url`
+    pub fn get_repo_stats(&self, url: &Url) -> Option<&RepoStats> {
+        self.repo_stats.get(&url.url)
+    }
+
+    /// Proofs from user's own repos (`FetchSource::LocalUser`)
+    pub fn get_local_user_stats(&self) -> Option<&RepoStats> {
+        self.repo_stats.get(FetchSource::LocalUser.name())
+    }
+
+    /// Proofs of every repo, by `FetchSource::name`
+    pub fn all_repo_stats(&self) -> impl Iterator<Item = (&str, &RepoStats)> {
+        self.repo_stats
+            .iter()
+            .map(|(name, stats)| (name.as_str(), stats))
+    }
+
+    fn recalculate_repo_stats(&mut self) {
+        let mut repo_stats: BTreeMap<String, RepoStats> = BTreeMap::new();
+        for info in self.fetched_from_by_signature.values() {
+            for source in info.sources() {
+                repo_stats
+                    .entry(source.name().to_owned())
+                    .or_insert_with(|| RepoStats::new(info.date))
+                    .record(info);
+            }
+        }
+        self.repo_stats = repo_stats;
+    }
+
+    pub fn import_from_iter(&mut self, i: impl Iterator<Item = (proof::Proof, FetchSource)>) {
+        // ignore errors
+        self.import_from_iter_with_report(i);
+    }
+
+    /// Like `import_from_iter`, but returning which proofs were accepted, and why others weren't
+    pub fn import_from_iter_with_report(
+        &mut self,
+        i: impl Iterator<Item = (proof::Proof, FetchSource)>,
+    ) -> ImportReport {
+        let mut report = ImportReport::default();
+        for (proof, fetch_source) in i {
+            let result = self.add_proof(&proof, fetch_source.clone());
+            report.record(&proof, fetch_source, result);
+        }
+        report
+    }
+
+    /// Like `import_from_iter_with_report`, but reporting progress every
+    /// `DEFAULT_IMPORT_PROGRESS_INTERVAL` proofs
+    ///
+    /// See `import_with_progress_every`.
+    pub fn import_with_progress(
+        &mut self,
+        i: impl Iterator<Item = (proof::Proof, FetchSource)>,
+        progress: &mut dyn FnMut(ImportProgress) -> ControlFlow<()>,
+    ) -> ImportReport {
+        self.import_with_progress_every(i, DEFAULT_IMPORT_PROGRESS_INTERVAL, progress)
+    }
+
+    /// Like `import_from_iter_with_report`, but calling `progress` after every `interval` proofs
+    ///
+    /// `progress` is called once more after the last proof, unless it just was.
+    /// If it returns `ControlFlow::Break`, no more proofs are taken from `i`, and the
+    /// report of the ones imported so far is returned. Proofs are added one by one,
+    /// so the `ProofDB` is left just like if only these were imported.
+    pub fn import_with_progress_every(
+        &mut self,
+        i: impl Iterator<Item = (proof::Proof, FetchSource)>,
+        interval: usize,
+        progress: &mut dyn FnMut(ImportProgress) -> ControlFlow<()>,
+    ) -> ImportReport {
+        let interval = cmp::max(interval, 1);
+        let mut report = ImportReport::default();
+        let mut current = ImportProgress::default();
+        for (proof, fetch_source) in i {
+            let result = self.add_proof(&proof, fetch_source.clone());
+            current.processed += 1;
+            match result {
+                Ok(_) => current.accepted += 1,
+                Err(_) => current.rejected += 1,
+            }
+            current.last_kind = Some(proof.kind().to_owned());
+            report.record(&proof, fetch_source, result);
+
+            if current.processed % interval == 0 && progress(current.clone()).is_break() {
+                debug!("Import cancelled after {} proofs", current.processed);
+                return report;
+            }
+        }
+        if current.processed % interval != 0 {
+            let _ = progress(current);
+        }
+        report
+    }
+
+    /// Verify and parse `proofs`, to be added with `apply` later
+    ///
+    /// This is the slow part of importing, and it doesn't modify the `ProofDB`,
+    /// so it can be done while others are still querying it (e.g. under a read lock).
+    /// Only `apply` needs exclusive access. The batch should be applied to
+    /// the same `ProofDB` (or a clone of it).
+    pub fn stage_import(
+        &self,
+        proofs: impl Iterator<Item = (proof::Proof, FetchSource)>,
+    ) -> ImportBatch {
+        ImportBatch {
+            proofs: proofs
+                .map(|(proof, fetch_source)| {
+                    let content = ProofContent::parse(&proof, self.store_unknown_proofs);
+                    (proof, fetch_source, content)
+                })
+                .collect(),
+        }
+    }
+
+    /// Add proofs verified by `stage_import`
+    ///
+    /// The result is the same as `import_from_iter_with_report` of the proofs
+    /// would have, in the same order.
+    pub fn apply(&mut self, batch: ImportBatch) -> ImportReport {
+        let mut report = ImportReport::default();
+        for (proof, fetch_source, content) in batch.proofs {
+            if let Ok(content) = &content {
+                self.add_parsed_proof(&proof, content, fetch_source.clone());
+            }
+            report.record(&proof, fetch_source, content);
+        }
+        report
+    }
+
+    /// Like `import_from_iter_with_report`, but verifying and parsing the proofs in parallel
+    ///
+    /// Proofs are added in the order of their dates (and then signatures), so the result
+    /// doesn't depend on the order of `proofs`, and is the same as importing them one by one
+    /// in that order. Proofs that fail the verification are skipped, and reported.
+    #[cfg(feature = "rayon")]
+    pub fn import_par_from_vec(
+        &mut self,
+        proofs: Vec<(proof::Proof, FetchSource)>,
+    ) -> ImportReport {
+        use rayon::prelude::*;
+
+        let store_unknown_proofs = self.store_unknown_proofs;
+        let mut parsed: Vec<_> = proofs
+            .into_par_iter()
+            .map(|(proof, fetch_source)| {
+                let content = ProofContent::parse(&proof, store_unknown_proofs);
+                (proof, fetch_source, content)
+            })
+            .collect();
+        parsed.par_sort_by(|a, b| {
+            a.0.date_utc()
+                .cmp(&b.0.date_utc())
+                .then_with(|| a.0.signature().cmp(b.0.signature()))
+        });
+        self.apply(ImportBatch { proofs: parsed })
+    }
+
+    /// Like `import_from_iter`, but returning what has changed
+    ///
+    /// Proofs that don't change anything (e.g. ones that were already
+    /// imported before) don't generate any events.
+    pub fn import_from_iter_with_changes(
+        &mut self,
+        i: impl Iterator<Item = (proof::Proof, FetchSource)>,
+    ) -> Vec<ChangeEvent> {
+        self.change_events = Some(vec![]);
+        self.import_from_iter(i);
+        self.change_events.take().unwrap_or_default()
+    }
+
+    fn record_change(&mut self, event: ChangeEvent) {
+        if let Some(events) = self.change_events.as_mut() {
+            events.push(event);
+        }
+    }
+
+    /// Merge all the proof data from `other` into `self`
+    ///
+    /// The result is the same as if all the proofs were imported into one `ProofDB`:
+    /// the most recent information wins. Merging is commutative: `a.merge(b)`
+    /// answers all queries just like `b.merge(a)` would. Settings of `self`
+    /// (like `set_ignore_url_scheme`) are kept.
+    ///
+    /// Nothing is verified or parsed again, so it's much faster than importing
+    /// the proofs of `other` anew, e.g. to combine `ProofDB`s built in parallel.
+    /// (If many proofs are equally recent, which one wins can differ from
+    /// importing them in some particular order.) Package reviews with the same
+    /// signature, but different content, are resolved like on import of the proofs
+    /// of `other` after the ones of `self`, and reported in `integrity_warnings`.
+    pub fn merge(&mut self, mut other: ProofDB) {
+        self.insertion_counter += other.insertion_counter + 1;
+        self.trust_insertion_counter += other.trust_insertion_counter + 1;
+
+        for (id, (url, fetch_matches)) in std::mem::take(&mut other.url_by_id_self_reported) {
+            match self.url_by_id_self_reported.entry(id) {
+                hash_map::Entry::Occupied(mut e) => {
+                    let e = e.get_mut();
+                    e.0.merge_more_recent(url, url_key);
+                    e.1 |= fetch_matches;
+                }
+                hash_map::Entry::Vacant(e) => {
+                    e.insert((url, fetch_matches));
+                }
+            }
+        }
+        // after the URLs, since they decide which one of conflicting reviews is kept
+        self.resolve_merged_signature_conflicts(&mut other);
+
+        for (from, edges) in other.trust_id_to_id {
+            let map = self.trust_id_to_id.entry(from).or_default();
+            for (to, edge) in edges {
+                merge_entry(map.entry(to), edge, TrustEdge::clone);
+            }
+        }
+        for (to, froms) in other.trust_reported_by {
+            self.trust_reported_by.entry(to).or_default().extend(froms);
+        }
+        for (id, level) in other.self_trust {
+            merge_entry(self.self_trust.entry(id), level, |level| *level);
+        }
+        for (id, superseded_by) in other.id_superseded_by {
+            match self.id_superseded_by.entry(id) {
+                hash_map::Entry::Occupied(mut e) => {
+                    e.get_mut().merge_more_recent(superseded_by, Id::clone)
+                }
+                hash_map::Entry::Vacant(e) => {
+                    e.insert(superseded_by);
+                }
+            }
+        }
+        for (from, edges) in other.trust_history {
+            let map = self.trust_history.entry(from).or_default();
+            for (to, history) in edges {
+                let merged = map.entry(to).or_default();
+                merged.extend(history);
+                // for equal dates, keep the highest level, like `merge_more_recent` would
+                merged.sort_by(|a, b| a.date.cmp(&b.date).then(b.value.cmp(&a.value)));
+                merged.dedup_by_key(|entry| entry.date);
+            }
+        }
+
+        for (id, url) in other.url_by_id_reported_by_others {
+            match self.url_by_id_reported_by_others.entry(id) {
+                hash_map::Entry::Occupied(mut e) => e.get_mut().merge_more_recent(url, url_key),
+                hash_map::Entry::Vacant(e) => {
+                    e.insert(url);
+                }
+            }
+        }
+        for (url, ids) in other.ids_by_url {
+            self.ids_by_url.entry(url).or_default().extend(ids);
+        }
+        for (id, urls) in other.url_history {
+            let history = self.url_history.entry(id).or_default();
+            for (canonical, url) in urls {
+                match history.entry(canonical) {
+                    btree_map::Entry::Occupied(mut e) => {
+                        if (url.date, &url.value.url) < (e.get().date, &e.get().value.url) {
+                            e.insert(url);
+                        }
+                    }
+                    btree_map::Entry::Vacant(e) => {
+                        e.insert(url);
+                    }
+                }
+            }
+        }
+
+        self.interner.merge(other.interner);
+        for (signature, review) in other.package_review_by_signature {
+            self.package_review_by_signature
+                .entry(signature)
+                .or_insert(review);
+        }
+        for (digest, reviews) in other.package_review_signatures_by_package_digest {
+            let map = self
+                .package_review_signatures_by_package_digest
+                .entry(digest)
+                .or_default();
+            for (pkg_review_id, signature) in reviews {
+                merge_entry(map.entry(pkg_review_id), signature, SharedSignature::clone);
+            }
+        }
+        for (pkg_review_id, signature) in other.package_review_signatures_by_pkg_review_id {
+            match self
+                .package_review_signatures_by_pkg_review_id
+                .entry(pkg_review_id)
+            {
+                hash_map::Entry::Occupied(mut e) => e
+                    .get_mut()
+                    .merge_more_recent(signature, SharedSignature::clone),
+                hash_map::Entry::Vacant(e) => {
+                    e.insert(signature);
+                }
+            }
+        }
+        for (pkg_review_id, history) in other.package_review_history {
+            self.package_review_history
+                .entry(pkg_review_id)
+                .or_default()
+                .extend(history);
+        }
+        for (source, names) in other.package_reviews {
+            let names_map = self.package_reviews.entry(source).or_default();
+            for (name, versions) in names {
+                let versions_map = names_map.entry(name).or_default();
+                for (version, pkg_review_ids) in versions {
+                    versions_map
+                        .entry(version)
+                        .or_default()
+                        .extend(pkg_review_ids);
+                }
+            }
+        }
+        for (pkg_id, pkg_review_ids) in other.package_range_reviews {
+            self.package_range_reviews
+                .entry(pkg_id)
+                .or_default()
+                .extend(pkg_review_ids);
+        }
+        for (id, pkg_review_ids) in other.package_reviews_by_author {
+            self.package_reviews_by_author
+                .entry(id)
+                .or_default()
+                .extend(pkg_review_ids);
+        }
+        for (pkg_id, files) in other.code_review_files {
+            let files_map = self.code_review_files.entry(pkg_id).or_default();
+            for (path, reviews) in files {
+                let map = files_map.entry(path).or_default();
+                for (id, file_review) in reviews {
+                    merge_entry(map.entry(id), file_review, |f| f.signature.clone());
+                }
+            }
+        }
+        self.reindex_file_reviews_by_digest();
+        for (pkg_id, alternatives) in other.package_alternatives {
+            let map = self.package_alternatives.entry(pkg_id).or_default();
+            for (id, signature) in alternatives {
+                merge_entry(map.entry(id), signature, SharedSignature::clone);
+            }
+        }
+        self.alternative_sources.extend(other.alternative_sources);
+
+        // All of these are determined by the most recent review, so it's
+        // easier to recalculate them, than to merge
+        self.package_reviews_by_date.clear();
+        self.latest_package_reviews.clear();
+        for (pkg_review_id, signature) in &self.package_review_signatures_by_pkg_review_id {
+            let pkg_id = &pkg_review_id.package_version_id.id;
+            self.package_reviews_by_date
+                .entry(canonical_package_id(&pkg_id.source, &pkg_id.name))
+                .or_default()
+                .insert((cmp::Reverse(signature.date), pkg_review_id.clone()));
+            record_latest_review(
+                &mut self.latest_package_reviews,
+                pkg_review_id,
+                signature.date,
+            );
+        }
+        self.package_flags.clear();
+        for (pkg_id, alternatives) in &self.package_alternatives {
+            let flags = self.package_flags.entry(pkg_id.clone()).or_default();
+            for (id, signature) in alternatives {
+                let review = &self.package_review_by_signature[&signature.value];
+                flags.insert(
+                    id.clone(),
+                    TimestampedFlags {
+                        date: signature.date,
+                        value: review.flags.clone(),
+                    },
+                );
+            }
+        }
+
+        self.unknown_proofs.extend(other.unknown_proofs);
+        self.unknown_proofs
+            .sort_by(|a, b| a.0.signature().cmp(b.0.signature()));
+        self.unknown_proofs
+            .dedup_by(|a, b| a.0.signature() == b.0.signature());
+        for (signature, proof) in other.signed_proofs {
+            self.signed_proofs.entry(signature).or_insert(proof);
+        }
+
+        self.integrity_warnings.extend(other.integrity_warnings);
+
+        for (signature, mut info) in other.fetched_from_by_signature {
+            match self.fetched_from_by_signature.entry(signature) {
+                hash_map::Entry::Occupied(mut e) => {
+                    if info.source.preference_key() < e.get().source.preference_key() {
+                        std::mem::swap(e.get_mut(), &mut info);
+                    }
+                    let merged = e.get_mut();
+                    for source in info.sources() {
+                        merged.add_source(source.clone());
+                    }
+                }
+                hash_map::Entry::Vacant(e) => {
+                    e.insert(info);
+                }
+            }
+        }
+        self.recalculate_repo_stats();
+    }
+
+    /// Keep only one of the package reviews with the same signature in `self` and `other`
+    ///
+    /// Conflicts are resolved (and reported) just like they would be if the
+    /// reviews of `other` were imported after the ones of `self`: the discarded
+    /// review is removed from the indices of its db, so only one is merged.
+    fn resolve_merged_signature_conflicts(&mut self, other: &mut ProofDB) {
+        let conflicts: Vec<_> = other
+            .package_review_by_signature
+            .iter()
+            .filter_map(|(signature, incoming)| {
+                match self.package_review_by_signature.get(signature) {
+                    Some(stored) if !is_same_package_review(stored, incoming) => {
+                        Some((signature.clone(), stored.clone(), incoming.clone()))
+                    }
+                    _ => None,
+                }
+            })
+            .collect();
+
+        for (signature, stored, incoming) in conflicts {
+            let incoming_info = other.fetched_from_by_signature.remove(&*signature);
+            let replace = match &incoming_info {
+                Some(info) => {
+                    self.resolve_signature_conflict(&stored, &incoming, &signature, &info.source)
+                }
+                None => false,
+            };
+            let (discarding, discarded) = if replace {
+                (&mut *self, &stored)
+            } else {
+                (&mut *other, &incoming)
+            };
+            discarding.unindex_package_review(discarded, &signature);
+            discarding.package_review_by_signature.remove(&signature);
+            discarding.signed_proofs.remove(&*signature);
+            discarding.restore_package_reviews(
+                &std::iter::once(canonical_pkg_review_id(discarded)).collect(),
+            );
+
+            match incoming_info {
+                Some(info) if replace => {
+                    self.fetched_from_by_signature
+                        .insert(signature.to_string(), info);
+                }
+                // like `record_fetch_source` would, on import
+                Some(info) => {
+                    if let Some(stored_info) = self.fetched_from_by_signature.get_mut(&*signature) {
+                        for source in info.sources() {
+                            stored_info.add_source(source.clone());
+                        }
+                    }
+                }
+                None => {}
+            }
+        }
+    }
+
+    /// Remove all the data that is known only from proofs fetched from `url`
+    ///
+    /// Proofs that have been fetched from other places too stay. Package reviews,
+    /// that were superseded by the removed ones, take their place again, but
+    /// superseded trust edges and file reviews aren't kept, so they can't.
+    /// `get_trust_history` and `get_url_history` are left as they were.
+    /// Returns the number of removed proofs.
+    pub fn remove_proofs_fetched_from(&mut self, url: &Url) -> usize {
+        let ignore_url_scheme = self.ignore_url_scheme;
+        let canonical = self.canonical_url(url);
+        let is_removed_source = |source: &FetchSource| match source {
+            FetchSource::Url(fetched_url) => {
+                canonicalize_url(&fetched_url.url, ignore_url_scheme) == canonical
+            }
+            FetchSource::LocalUser => false,
+        };
+
+        let mut removed: HashSet<Signature> = HashSet::new();
+        self.fetched_from_by_signature.retain(|signature, info| {
+            info.other_sources
+                .retain(|source| !is_removed_source(source));
+            if !is_removed_source(&info.source) {
+                return true;
+            }
+            if info.other_sources.is_empty() {
+                removed.insert(signature.clone());
+                return false;
+            }
+            info.source = info.other_sources.remove(0);
+            true
+        });
+        self.recalculate_repo_stats();
+        if removed.is_empty() {
+            return 0;
+        }
+        self.insertion_counter += 1;
+        self.trust_insertion_counter += 1;
+
+        // Ids whose URL records might not be supported by any proof anymore
+        let mut authors: HashSet<Id> = HashSet::new();
+        let mut trusted: HashSet<Id> = HashSet::new();
+
+        let mut superseded_by_removed = HashSet::new();
+        for signature in &removed {
+            if let Some(review) = self.package_review_by_signature.remove(signature.as_str()) {
+                self.unindex_package_review(&review, signature);
+                authors.insert(review.from().id.clone());
+                superseded_by_removed.insert(canonical_pkg_review_id(&review));
+            }
+        }
+        self.restore_package_reviews(&superseded_by_removed);
+        self.interner.release_unused();
+
+        let mut removed_edges = vec![];
+        for (from, edges) in &mut self.trust_id_to_id {
+            edges.retain(|to, edge| {
+                if removed.contains(&edge.value.signature) {
+                    authors.insert(from.clone());
+                    trusted.insert(to.clone());
+                    removed_edges.push((from.clone(), to.clone()));
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+        self.trust_id_to_id.retain(|_, edges| !edges.is_empty());
+        for (from, to) in removed_edges {
+            if let hash_map::Entry::Occupied(mut e) = self.trust_reported_by.entry(to) {
+                e.get_mut().remove(&from);
+                if e.get().is_empty() {
+                    e.remove();
+                }
+            }
+        }
+
+        for files in self.code_review_files.values_mut() {
+            for by_author in files.values_mut() {
+                by_author.retain(|id, file_review| {
+                    if removed.contains(&file_review.value.signature) {
+                        authors.insert(id.clone());
+                        false
+                    } else {
+                        true
+                    }
+                });
+            }
+            files.retain(|_, by_author| !by_author.is_empty());
+        }
+        self.code_review_files.retain(|_, files| !files.is_empty());
+        self.reindex_file_reviews_by_digest();
+        self.reindex_alternative_sources();
+
+        self.signed_proofs
+            .retain(|signature, _| !removed.contains(signature));
+        self.unknown_proofs.retain(|(proof, _)| {
+            if removed.contains(proof.signature()) {
+                authors.insert(proof.author_id().clone());
+                false
+            } else {
+                true
+            }
+        });
+
+        for id in authors {
+            let signatures = self.signatures_by_author(&id);
+            if signatures.is_empty() {
+                if let Some((url, _)) = self.url_by_id_self_reported.remove(&id) {
+                    self.forget_url_claim(&id, &url.value);
+                }
+                continue;
+            }
+            let own_url = match self.url_by_id_self_reported.get(&id) {
+                Some((url, _)) => self.canonical_url(&url.value),
+                None => continue,
+            };
+            let verified = signatures
+                .iter()
+                .filter_map(|signature| self.fetched_from_by_signature.get(*signature))
+                .flat_map(FetchSourceInfo::sources)
+                .any(|source| match source {
+                    FetchSource::LocalUser => true,
+                    FetchSource::Url(fetched_url) => self.canonical_url(fetched_url) == own_url,
+                });
+            if let Some((_, fetch_matches)) = self.url_by_id_self_reported.get_mut(&id) {
+                *fetch_matches = verified;
+            }
+        }
+
+        let still_trusted: HashSet<Id> = self
+            .trust_id_to_id
+            .values()
+            .flat_map(BTreeMap::keys)
+            .filter(|id| trusted.contains(*id))
+            .cloned()
+            .collect();
+        for id in trusted.difference(&still_trusted) {
+            if let Some(url) = self.url_by_id_reported_by_others.remove(id) {
+                self.forget_url_claim(id, &url.value);
+            }
+        }
+
+        removed.len()
+    }
+
+    /// Signatures of all the proofs of `author` that are still in use
+    fn signatures_by_author(&self, author: &Id) -> Vec<&str> {
+        let trust = self
+            .trust_id_to_id
+            .get(author)
+            .into_iter()
+            .flat_map(BTreeMap::values)
+            .map(|edge| edge.value.signature.as_str());
+        let reviews = self
+            .package_reviews_by_author
+            .get(author)
+            .into_iter()
+            .flatten()
+            .filter_map(move |pkg_review_id| {
+                self.package_review_signatures_by_pkg_review_id
+                    .get(pkg_review_id)
+            })
+            .map(|signature| &*signature.value);
+        let code_reviews = self
+            .code_review_files
+            .values()
+            .flat_map(BTreeMap::values)
+            .filter_map(move |by_author| by_author.get(author))
+            .map(|file_review| file_review.value.signature.as_str());
+        let unknown = self
+            .unknown_proofs
+            .iter()
+            .filter(move |(proof, _)| proof.author_id() == author)
+            .map(|(proof, _)| proof.signature());
+        trust
+            .chain(reviews)
+            .chain(code_reviews)
+            .chain(unknown)
+            .collect()
+    }
+
+    /// Remove `id` from Ids claiming `url`, unless it's still claimed in the URL records
+    fn forget_url_claim(&mut self, id: &Id, url: &Url) {
+        let canonical = self.canonical_url(url);
+        let still_claimed = self
+            .url_by_id_self_reported
+            .get(id)
+            .map(|(url, _)| &url.value)
+            .into_iter()
+            .chain(
+                self.url_by_id_reported_by_others
+                    .get(id)
+                    .map(|url| &url.value),
+            )
+            .any(|url| self.canonical_url(url) == canonical);
+        if still_claimed {
+            return;
+        }
+        if let btree_map::Entry::Occupied(mut e) = self.ids_by_url.entry(canonical) {
+            e.get_mut().remove(id);
+            if e.get().is_empty() {
+                e.remove();
+            }
+        }
+    }
+
+    /// Get the direct trust relationships reported by `id`
+    ///
+    /// Every reported Id is returned only once, with the trust level and the date
+    /// of the most recent trust proof. If `since` is given, only relationships
+    /// (re-)asserted at or after that date are returned.
+    pub fn get_direct_trust_for_id<'s>(
+        &'s self,
+        id: &Id,
+        since: Option<DateTime<Utc>>,
+    ) -> impl Iterator<Item = DirectTrust<'s>> {
+        self.trust_id_to_id
+            .get(id)
+            .into_iter()
+            .flat_map(|map| map.iter())
+            .map(|(id, trust)| DirectTrust {
+                id,
+                level: trust.value.level,
+                date: trust.date,
+                expires: trust.value.expires,
+                comment: &trust.value.comment,
+            })
+            .filter(move |direct| !matches!(since, Some(since) if since > direct.date))
+    }
+
+    /// Trust level `from` reported for `to` in the most recent trust proof, with its date
+    ///
+    /// Unlike in the trust set, explicit `None` (e.g. to untrust someone trusted before)
+    /// and `Distrust` levels are returned too, while `None` is returned if `from`
+    /// never reported any trust level for `to`.
+    pub fn get_direct_trust(&self, from: &Id, to: &Id) -> Option<Timestamped<TrustLevel>> {
+        let edge = self.trust_id_to_id.get(from)?.get(to)?;
+        Some(Timestamped {
+            date: edge.date,
+            value: edge.value.level,
+        })
+    }
+
+    /// Every Id `from` reported any trust level for, like in `get_direct_trust`, ordered by Id
+    pub fn get_direct_trust_list(
+        &self,
+        from: &Id,
+    ) -> impl Iterator<Item = (&Id, Timestamped<TrustLevel>)> {
+        self.trust_id_to_id
+            .get(from)
+            .into_iter()
+            .flatten()
+            .map(|(to, edge)| {
+                (
+                    to,
+                    Timestamped {
+                        date: edge.date,
+                        value: edge.value.level,
+                    },
+                )
+            })
+    }
+
+    /// Every Id that reported any trust level for `to`, like in `get_direct_trust`, ordered by Id
+    pub fn get_reverse_trust<'a, 'b: 'a>(
+        &'a self,
+        to: &'b Id,
+    ) -> impl Iterator<Item = (&'a Id, Timestamped<TrustLevel>)> + 'a {
+        self.trust_reported_by
+            .get(to)
+            .into_iter()
+            .flatten()
+            .filter_map(move |from| Some((from, self.get_direct_trust(from, to)?)))
+    }
+
+    /// Trust reported by `id`, without the expired trust
+    fn get_trust_list_of_id(&self, id: &Id) -> impl Iterator<Item = (TrustLevel, &Id)> {
+        let now = self.now();
+        self.get_direct_trust_for_id(id, None)
+            .filter(move |direct| !is_expired_at(direct.expires, now))
+            .map(|direct| (direct.level, direct.id))
+    }
+
+    /// Like `get_trust_list_of_id`, but of the edges in the `view`
+    fn get_trust_list_of_id_in_view<'s>(
+        &'s self,
+        id: &Id,
+        view: &'s TrustEdgeView,
+    ) -> Vec<(TrustLevel, &'s Id)> {
+        let mut list: BTreeMap<&Id, TrustLevel> = match view.as_of {
+            None if view.scope == EdgeScope::All => self
+                .get_trust_list_of_id(id)
+                .map(|(level, id)| (id, level))
+                .collect(),
+            None => {
+                let now = self.now();
+                self.trust_id_to_id
+                    .get(id)
+                    .into_iter()
+                    .flatten()
+                    .filter(|(_, edge)| {
+                        edge.value.applies_to(&view.scope)
+                            && !is_expired_at(edge.value.expires, now)
+                    })
+                    .map(|(id, edge)| (id, edge.value.level))
+                    .collect()
+            }
+            Some(as_of) if self.keep_trust_history => self
+                .trust_history
+                .get(id)
+                .into_iter()
+                .flatten()
+                .filter_map(|(id, history)| Some((id, trust_level_at(history, as_of)?)))
+                .collect(),
+            Some(as_of) => self
+                .get_direct_trust_for_id(id, None)
+                .filter(|direct| direct.date <= as_of && !is_expired_at(direct.expires, as_of))
+                .map(|direct| (direct.id, direct.level))
+                .collect(),
+        };
+        for (to, level) in self.unsigned_trust_overrides_in_view(id, view) {
+            match self.unsigned_override_precedence {
+                UnsignedOverridePrecedence::OverrideWins => {
+                    list.insert(to, level);
+                }
+                UnsignedOverridePrecedence::ProofWins => {
+                    list.entry(to).or_insert(level);
+                }
+            }
+        }
+        if let Some(extra) = view.extra_edges.get(id) {
+            list.extend(extra.iter().map(|(id, level)| (id, *level)));
+        }
+        list.into_iter().map(|(id, level)| (level, id)).collect()
+    }
+
+    /// Unsigned trust overrides of `from`, in effect in the `view`
+    fn unsigned_trust_overrides_in_view<'s>(
+        &'s self,
+        from: &Id,
+        view: &TrustEdgeView,
+    ) -> impl Iterator<Item = (&'s Id, TrustLevel)> {
+        let as_of = view.as_of;
+        self.unsigned_trust_overrides
+            .get(from)
+            .into_iter()
+            .flatten()
+            .filter(move |(_, level)| !matches!(as_of, Some(as_of) if as_of < level.date))
+            .map(|(to, level)| (to, level.value))
+    }
+
+    fn unsigned_trust_override_in_view(
+        &self,
+        from: &Id,
+        to: &Id,
+        view: &TrustEdgeView,
+    ) -> Option<TrustLevel> {
+        self.unsigned_trust_overrides_in_view(from, view)
+            .find(|(id, _)| *id == to)
+            .map(|(_, level)| level)
+    }
+
+    /// Trust level `from` reported for `to`, in the `view`
+    fn get_trust_level_in_view(
+        &self,
+        from: &Id,
+        to: &Id,
+        view: &TrustEdgeView,
+    ) -> Option<TrustLevel> {
+        if let Some(level) = view.extra_edges.get(from).and_then(|map| map.get(to)) {
+            return Some(*level);
+        }
+        let unsigned = || self.unsigned_trust_override_in_view(from, to, view);
+        match self.unsigned_override_precedence {
+            UnsignedOverridePrecedence::OverrideWins => {
+                unsigned().or_else(|| self.get_proof_trust_level_in_view(from, to, view))
+            }
+            UnsignedOverridePrecedence::ProofWins => self
+                .get_proof_trust_level_in_view(from, to, view)
+                .or_else(unsigned),
+        }
+    }
+
+    /// Like `get_trust_level_in_view`, but only from proofs
+    fn get_proof_trust_level_in_view(
+        &self,
+        from: &Id,
+        to: &Id,
+        view: &TrustEdgeView,
+    ) -> Option<TrustLevel> {
+        match view.as_of {
+            None => self
+                .trust_id_to_id
+                .get(from)?
+                .get(to)
+                .filter(|trust| trust.value.applies_to(&view.scope))
+                .map(|trust| trust.value.level),
+            Some(as_of) if self.keep_trust_history => {
+                trust_level_at(self.trust_history.get(from)?.get(to)?, as_of)
+            }
+            Some(as_of) => self
+                .trust_id_to_id
+                .get(from)?
+                .get(to)
+                .filter(|trust| trust.date <= as_of)
+                .map(|trust| trust.value.level),
+        }
+    }
+
+    /// Proof in which `from` reported distrust for `to`, in the `view`
+    ///
+    /// Only known if it's the most recent trust proof of `from` for `to`.
+    fn get_distrust_proof_in_view(
+        &self,
+        from: &Id,
+        to: &Id,
+        view: &TrustEdgeView,
+    ) -> Option<DistrustProof> {
+        if view
+            .extra_edges
+            .get(from)
+            .and_then(|map| map.get(to))
+            .is_some()
+        {
+            return None;
+        }
+        if self.unsigned_override_precedence == UnsignedOverridePrecedence::OverrideWins
+            && self
+                .unsigned_trust_override_in_view(from, to, view)
+                .is_some()
+        {
+            return None;
+        }
+        let edge = self.trust_id_to_id.get(from)?.get(to)?;
+        let in_effect = match view.as_of {
+            Some(as_of) => edge.date <= as_of,
+            None => true,
+        };
+        if !in_effect || edge.value.level != TrustLevel::Distrust {
+            return None;
+        }
+        Some(DistrustProof {
+            signature: edge.value.signature.clone(),
+            date: edge.date,
+        })
+    }
+
+    pub fn calculate_trust_set(&self, for_id: &Id, params: &TrustDistanceParams) -> TrustSet {
+        self.calculate_trust_set_with(for_id, params, &mut ())
+    }
+
+    /// Like `calculate_trust_set`, but reusing the previous result if possible
+    ///
+    /// The most recent trust set of every root Id is kept, and calculated again
+    /// only if trust levels between any Ids changed since (including by trust
+    /// expiring), or `params` are different. With a `review_quality_cap`, it's
+    /// calculated again after any proof is added or removed, too.
+    pub fn calculate_trust_set_cached(
+        &self,
+        for_id: &Id,
+        params: &TrustDistanceParams,
+    ) -> sync::Arc<TrustSet> {
+        let now = self.now();
+        let is_valid = |cached: &CachedTrustSet| {
+            cached.trust_insertion_counter == self.trust_insertion_counter
+                && (params.review_quality_cap.is_none()
+                    || cached.insertion_counter == self.insertion_counter)
+                && &cached.params == params
+                && cached.calculated_at <= now
+                && match cached.next_expiration {
+                    Some(next_expiration) => now < next_expiration,
+                    None => true,
+                }
+        };
+        {
+            let read = self.cached_trust_sets.read().expect("lock to work");
+            if let Some(cached) = read.get(for_id).filter(|cached| is_valid(cached)) {
+                return cached.trust_set.clone();
+            }
+        }
+
+        let trust_set = sync::Arc::new(self.calculate_trust_set(for_id, params));
+        self.cached_trust_sets
+            .write()
+            .expect("lock to work")
+            .insert(
+                for_id.clone(),
+                CachedTrustSet {
+                    params: params.clone(),
+                    trust_insertion_counter: self.trust_insertion_counter,
+                    insertion_counter: self.insertion_counter,
+                    calculated_at: now,
+                    next_expiration: self.next_trust_expiration(now),
+                    trust_set: trust_set.clone(),
+                },
+            );
+        trust_set
+    }
+
+    /// The first expiration of any trust edge after `now`
+    fn next_trust_expiration(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.trust_id_to_id
+            .values()
+            .flat_map(BTreeMap::values)
+            .filter_map(|edge| edge.value.expires)
+            .filter(|expires| now < *expires)
+            .min()
+    }
+
+    /// The combined WoT of many `roots`, like the Ids of all the members of a team
+    ///
+    /// All the `roots` are fully trusted, and the traversal starts from all of them
+    /// at once, so an Id is trusted if any of the roots trusts it (with the highest
+    /// effective trust level and the shortest distance of all the ways to it).
+    /// Distrust is resolved just like within a WoT of a single root: one root
+    /// distrusting an Id bans it for all, unless someone with higher trust banned
+    /// the distrusting Id first. With no `roots` the trust set is empty.
+    pub fn calculate_trust_set_multi(
+        &self,
+        roots: &[Id],
+        params: &TrustDistanceParams,
+    ) -> TrustSet {
+        if roots.is_empty() {
+            return TrustSet::default();
+        }
+        self.calculate_trust_set_in_view(roots, params, &default(), &mut ())
+    }
+
+    /// Like `calculate_trust_set`, but with a custom `DistanceMetric`
+    pub fn calculate_trust_set_with_metric(
+        &self,
+        for_id: &Id,
+        metric: &dyn DistanceMetric,
+    ) -> TrustSet {
+        self.calculate_trust_set_with(for_id, metric, &mut ())
+    }
+
+    /// Like `calculate_trust_set`, but reporting progress to the `observer`
+    ///
+    /// If the `observer` cancels the calculation, the partial `TrustSet`
+    /// calculated so far is returned, with `is_complete()` being `false`.
+    pub fn calculate_trust_set_with(
+        &self,
+        for_id: &Id,
+        params: &dyn DistanceMetric,
+        observer: &mut dyn TrustSetObserver,
+    ) -> TrustSet {
+        self.calculate_trust_set_in_view(std::slice::from_ref(for_id), params, &default(), observer)
+    }
+
+    /// Calculate the trust set as it was at `as_of`
+    ///
+    /// Every trust edge has the trust level from the most recent proof
+    /// issued no later than `as_of`. Edges first reported after `as_of` are absent.
+    /// Unless trust history is kept (see `set_keep_trust_history`), only the most recent
+    /// proof of every edge is known, so edges re-reported after `as_of` are absent too,
+    /// and so are the ones expired by then (the trust history doesn't record expiration).
+    /// Reviews as they were at a point in time are returned by `get_pkg_reviews_for_version_at`.
+    pub fn calculate_trust_set_at(
+        &self,
+        for_id: &Id,
+        params: &dyn DistanceMetric,
+        as_of: DateTime<Utc>,
+    ) -> TrustSet {
+        let view = TrustEdgeView {
+            as_of: Some(as_of),
+            ..default()
+        };
+        self.calculate_trust_set_in_view(std::slice::from_ref(for_id), params, &view, &mut ())
+    }
+
+    /// Like `calculate_trust_set`, but with local `overrides` of effective trust levels
+    ///
+    /// Every overridden Id that is reached in the WoT gets exactly the given
+    /// effective trust level, no matter what others reported for it (distrust
+    /// included), and extends trust to others accordingly. With `None` the Id
+    /// is not trusted at all, and with `Distrust` it's banned, just like if the
+    /// root distrusted it. Overrides of Ids outside of the WoT don't bring them in.
+    /// An Id overridden to a higher level than it would get otherwise doesn't
+    /// extend more trust to others than it would without the override.
+    pub fn calculate_trust_set_with_overrides(
+        &self,
+        for_id: &Id,
+        params: &dyn DistanceMetric,
+        overrides: &HashMap<Id, TrustLevel>,
+    ) -> TrustSet {
+        let view = TrustEdgeView {
+            // the root is always trusted
+            overrides: overrides
+                .iter()
+                .filter(|(id, _)| *id != for_id)
+                .map(|(id, level)| (id.clone(), *level))
+                .collect(),
+            ..default()
+        };
+        self.calculate_trust_set_in_view(std::slice::from_ref(for_id), params, &view, &mut ())
+    }
+
+    /// Preview the effect of issuing new trust proofs from `root`
+    ///
+    /// `hypothetical` direct trust levels (including `Distrust`) override
+    /// the ones `root` currently reports. The database is not modified.
+    pub fn preview_trust_change(
+        &self,
+        root: &Id,
+        params: &dyn DistanceMetric,
+        hypothetical: &[(Id, TrustLevel)],
+    ) -> TrustSetDelta {
+        let mut view = TrustEdgeView::default();
+        view.extra_edges.insert(
+            root.clone(),
+            hypothetical.iter().cloned().collect::<BTreeMap<_, _>>(),
+        );
+
+        let before = self.calculate_trust_set_with(root, params, &mut ());
+        let after =
+            self.calculate_trust_set_in_view(std::slice::from_ref(root), params, &view, &mut ());
+
+        let mut delta = TrustSetDelta::default();
+        for (id, details) in &after.trusted {
+            match before.trusted.get(id) {
+                None => {
+                    delta
+                        .added
+                        .insert(id.clone(), details.effective_trust_level);
+                }
+                Some(prev) if prev.effective_trust_level != details.effective_trust_level => {
+                    delta.level_changed.insert(
+                        id.clone(),
+                        (prev.effective_trust_level, details.effective_trust_level),
+                    );
+                }
+                Some(_) => {}
+            }
+        }
+        for (id, details) in &before.trusted {
+            if !after.trusted.contains_key(id) {
+                delta
+                    .removed
+                    .insert(id.clone(), details.effective_trust_level);
+            }
+        }
+        delta.newly_distrusted = after
+            .distrusted
+            .keys()
+            .filter(|id| !before.distrusted.contains_key(id))
+            .cloned()
+            .collect();
+
+        for pkg_review_id in self.package_review_signatures_by_pkg_review_id.keys() {
+            if delta.added.contains_key(&pkg_review_id.from) {
+                delta.newly_covered_reviews += 1;
+            } else if delta.removed.contains_key(&pkg_review_id.from) {
+                delta.no_longer_covered_reviews += 1;
+            }
+        }
+
+        delta
+    }
+
+    /// Package versions of `source` whose number of trusted reviews changed between two trust sets
+    ///
+    /// Only the trust sets differ, so this shows the effect of the change of
+    /// the WoT alone, e.g. after fetching new trust proofs. Reviews count if their
+    /// authors are trusted (at least at `Low` level) and not distrusted.
+    pub fn diff_package_coverage(
+        &self,
+        old_ts: &TrustSet,
+        new_ts: &TrustSet,
+        source: &str,
+    ) -> CoverageDiff {
+        let (old_ts, new_ts) = (old_ts.for_source(source), new_ts.for_source(source));
+        let is_trusted = |trust_set: &TrustSet, id: &Id| {
+            !trust_set.is_distrusted(id)
+                && TrustLevel::Low <= trust_set.get_effective_trust_level(id)
+        };
+        let mut counts: BTreeMap<proof::PackageVersionId, (usize, usize)> = BTreeMap::new();
+        for review in self.get_pkg_reviews_for_source(source) {
+            let id = &review.from().id;
+            let (old, new) = (is_trusted(old_ts, id), is_trusted(new_ts, id));
+            if !old && !new {
+                continue;
+            }
+            let count = counts.entry(review.package.id.clone()).or_default();
+            count.0 += usize::from(old);
+            count.1 += usize::from(new);
+        }
+
+        let mut diff = CoverageDiff::default();
+        for (pkg, (old, new)) in counts {
+            match old.cmp(&new) {
+                cmp::Ordering::Less => {
+                    diff.gained.insert(pkg, (old, new));
+                }
+                cmp::Ordering::Greater => {
+                    diff.lost.insert(pkg, (old, new));
+                }
+                cmp::Ordering::Equal => {}
+            }
+        }
+        diff
+    }
+
+    /// Add a trust edge from `from` to `to`, without any proof
+    ///
+    /// Useful for experimenting with the WoT, or for modeling a local policy.
+    /// Overrides are kept apart from the proofs, and are used in all the trust
+    /// set calculations, together with the trust from proofs; which one wins
+    /// for the same edge is set with `set_unsigned_override_precedence`.
+    /// The previous override of the same edge is replaced.
+    pub fn add_unsigned_trust_override(
+        &mut self,
+        from: &Id,
+        to: &Id,
+        level: TrustLevel,
+        date: DateTime<Utc>,
+    ) {
+        if from == to {
+            debug!("Ignoring an override of trust of {} for itself", from);
+            return;
+        }
+        self.unsigned_trust_overrides
+            .entry(from.clone())
+            .or_default()
+            .insert(to.clone(), TimestampedTrustLevel { value: level, date });
+        self.trust_insertion_counter += 1;
+    }
+
+    /// All the edges added with `add_unsigned_trust_override`, ordered by `from` and `to`
+    pub fn list_unsigned_overrides(&self) -> Vec<UnsignedTrustOverride> {
+        self.unsigned_trust_overrides
+            .iter()
+            .flat_map(|(from, edges)| {
+                edges.iter().map(move |(to, level)| UnsignedTrustOverride {
+                    from: from.clone(),
+                    to: to.clone(),
+                    level: level.value,
+                    date: level.date,
+                })
+            })
+            .collect()
+    }
+
+    /// Remove all the edges added with `add_unsigned_trust_override`
+    pub fn clear_unsigned_overrides(&mut self) {
+        if !self.unsigned_trust_overrides.is_empty() {
+            self.unsigned_trust_overrides.clear();
+            self.trust_insertion_counter += 1;
+        }
+    }
+
+    pub fn set_unsigned_override_precedence(&mut self, precedence: UnsignedOverridePrecedence) {
+        if self.unsigned_override_precedence != precedence {
+            self.unsigned_override_precedence = precedence;
+            self.trust_insertion_counter += 1;
+        }
+    }
+
+    fn calculate_trust_set_in_view(
+        &self,
+        roots: &[Id],
+        params: &dyn DistanceMetric,
+        view: &TrustEdgeView,
+        observer: &mut dyn TrustSetObserver,
+    ) -> TrustSet {
+        let mut distrusted: BTreeMap<_, _> = view
+            .overrides
+            .iter()
+            .filter(|(_, level)| **level == TrustLevel::Distrust)
+            .map(|(id, _)| {
+                let mut details = DistrustedIdDetails::default();
+                details.reported_by.extend(roots.iter().cloned());
+                (id.clone(), details)
+            })
+            .collect();
+        let mut caps = match params.review_quality_cap() {
+            Some(review_quality) => self.review_quality_caps(roots, review_quality),
+            None => BTreeMap::new(),
+        };
+        // overridden levels are final
+        caps.retain(|id, _| !view.overrides.contains_key(id));
+        let mut restarts = 0;
+
+        // We keep retrying the whole thing, with more and more
+        // distrusted Ids, and (in corroboration mode, or with trust
+        // aggregation other than `Max`) more and more capped trust levels
+        let aggregation = params.trust_aggregation();
+        let supersession = params.supersession();
+        let supersession_groups = if supersession.redirect_trust || supersession.inherit_reviews {
+            self.supersession_groups()
+        } else {
+            BTreeMap::new()
+        };
+        loop {
+            let prev_distrusted_len = distrusted.len();
+            let mut trust_set =
+                self.calculate_trust_set_internal(roots, params, view, distrusted, &caps, observer);
+            if trust_set.incomplete {
+                return trust_set;
+            }
+            trust_set.distrust_superseded(&supersession_groups);
+
+            let restart = if trust_set.distrusted.len() > prev_distrusted_len {
+                true
+            } else if params.require_corroboration().is_some()
+                || aggregation != EffectiveTrustAggregation::Max
+            {
+                let mut new_caps = match params.require_corroboration() {
+                    Some(corroboration) => {
+                        trust_set.corroboration_caps(roots, corroboration, &caps)
+                    }
+                    None => caps.clone(),
+                };
+                new_caps = trust_set.aggregation_caps(roots, aggregation, &new_caps);
+                new_caps.retain(|id, _| !view.overrides.contains_key(id));
+                if new_caps != caps {
+                    debug!("Some trust levels got capped - restarting the WoT calculation");
+                    caps = new_caps;
+                    true
+                } else {
+                    false
+                }
+            } else {
+                false
+            };
+
+            if restart {
+                restarts += 1;
+                if observer.on_restart(restarts).is_break() {
+                    trust_set.incomplete = true;
+                    return trust_set;
+                }
+                distrusted = trust_set.distrusted;
+                continue;
+            }
+
+            trust_set.apply_caps(&caps);
+            if supersession.inherit_reviews {
+                trust_set.inherit_from_current_ids(&supersession_groups);
+            }
+            trust_set.distrust_conflicts = self.find_distrust_conflicts(&trust_set, view);
+            if view.scope == EdgeScope::All && view.as_of.is_none() {
+                self.calculate_scoped_trust_sets(roots, params, view, &mut trust_set);
+            }
+            return trust_set;
+        }
+    }
+
+    /// Fill `TrustSet::by_source` and `TrustSet::unlimited`, if any trust is
+    /// limited to some package sources
+    fn calculate_scoped_trust_sets(
+        &self,
+        roots: &[Id],
+        params: &dyn DistanceMetric,
+        view: &TrustEdgeView,
+        trust_set: &mut TrustSet,
+    ) {
+        let sources: BTreeSet<&String> = self
+            .trust_id_to_id
+            .values()
+            .flat_map(BTreeMap::values)
+            .flat_map(|edge| &edge.value.sources)
+            .collect();
+        if sources.is_empty() {
+            return;
+        }
+        let scoped = |scope| {
+            let view = TrustEdgeView {
+                scope,
+                ..view.clone()
+            };
+            self.calculate_trust_set_in_view(roots, params, &view, &mut ())
+        };
+        trust_set.by_source = sources
+            .into_iter()
+            .map(|source| (source.clone(), scoped(EdgeScope::Source(source.clone()))))
+            .collect();
+        trust_set.unlimited = Some(Box::new(scoped(EdgeScope::Unlimited)));
+    }
+
+    /// Trust level caps of Ids (other than `roots`) with low quality reviews
+    ///
+    /// See `ReviewQualityParams`.
+    fn review_quality_caps(
+        &self,
+        roots: &[Id],
+        params: &ReviewQualityParams,
+    ) -> BTreeMap<Id, TrustLevel> {
+        let mut levels_by_author: HashMap<&Id, (Vec<Level>, Vec<Level>)> = HashMap::new();
+        for signature in self.package_review_signatures_by_pkg_review_id.values() {
+            let package_review = &self.package_review_by_signature[&signature.value];
+            let review = match package_review.review() {
+                Some(review) if !package_review.retracted => review,
+                _ => continue,
+            };
+            let (thoroughness, understanding) = levels_by_author
+                .entry(&package_review.from().id)
+                .or_default();
+            thoroughness.push(review.thoroughness);
+            understanding.push(review.understanding);
+        }
+
+        fn median(mut levels: Vec<Level>) -> Level {
+            levels.sort();
+            levels[(levels.len() - 1) / 2]
+        }
+
+        levels_by_author
+            .into_iter()
+            .filter(|(id, _)| !roots.contains(id))
+            .filter_map(|(id, (thoroughness, understanding))| {
+                let low_quality = median(thoroughness) < params.min_thoroughness
+                    || median(understanding) < params.min_understanding;
+                if low_quality {
+                    Some((id.clone(), params.max_trust_level))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Pairs of Ids in the `trust_set` that distrust each other
+    fn find_distrust_conflicts(
+        &self,
+        trust_set: &TrustSet,
+        view: &TrustEdgeView,
+    ) -> BTreeSet<(Id, Id)> {
+        let in_set = |id: &Id| trust_set.is_trusted(id) || trust_set.is_distrusted(id);
+        let distrusts = |from: &Id, to: &Id| {
+            self.get_trust_level_in_view(from, to, view) == Some(TrustLevel::Distrust)
+        };
+
+        let mut conflicts = BTreeSet::new();
+        for id in trust_set.trusted.keys().chain(trust_set.distrusted.keys()) {
+            for (level, other) in self.get_trust_list_of_id_in_view(id, view) {
+                if level == TrustLevel::Distrust
+                    && id < other
+                    && in_set(other)
+                    && distrusts(other, id)
+                {
+                    conflicts.insert((id.clone(), other.clone()));
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// Calculate the effective trust levels for IDs inside a WoT.
+    ///
+    /// This is one of the most important functions in `crev-wot`.
+    fn calculate_trust_set_internal(
+        &self,
+        roots: &[Id],
+        params: &dyn DistanceMetric,
+        view: &TrustEdgeView,
+        distrusted: BTreeMap<Id, DistrustedIdDetails>,
+        caps: &BTreeMap<Id, TrustLevel>,
+        observer: &mut dyn TrustSetObserver,
+    ) -> TrustSet {
+        /// Node that is to be visited
+        ///
+        /// Ordered so that nodes are visited breadth-first with respect to
+        /// trust level: highest trust level first, then shortest distance.
+        #[derive(Eq, PartialEq, Clone, Debug)]
+        struct Visit {
+            /// Effective transitive trust level of the node
+            effective_trust_level: TrustLevel,
+            /// Distance from the root
+            distance: Distance,
+            /// Id we're visit
+            id: Id,
+        }
+
+        impl Ord for Visit {
+            fn cmp(&self, other: &Self) -> cmp::Ordering {
+                other
+                    .effective_trust_level
+                    .cmp(&self.effective_trust_level)
+                    .then_with(|| self.distance.cmp(&other.distance))
+                    .then_with(|| self.id.cmp(&other.id))
+            }
+        }
+
+        impl PartialOrd for Visit {
+            fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        let mut pending = BTreeSet::new();
+        let mut current_trust_set = TrustSet::default();
+        let initial_distrusted_len = distrusted.len();
+        current_trust_set.distrusted = distrusted;
+        let distrust_policy = params.distrust_policy();
+        current_trust_set.distrust_policy = distrust_policy;
+        let redirect_trust = params.supersession().redirect_trust;
+
+        for root in roots {
+            pending.insert(Visit {
+                effective_trust_level: TrustLevel::High,
+                distance: Distance::ZERO,
+                id: root.clone(),
+            });
+            current_trust_set.record_trusted_id(
+                root.clone(),
+                root.clone(),
+                Distance::ZERO,
+                TrustLevel::High,
+                ReportedTrust {
+                    effective_trust_level: TrustLevel::High,
+                    direct_trust_level: TrustLevel::High,
+                    distance: Distance::ZERO,
+                },
+            );
+        }
+        let mut previous_iter_trust_level = TrustLevel::High;
+
+        while let Some(current) = pending.iter().next().cloned() {
+            debug!("Traversing id: {:?}", current);
+            pending.remove(&current);
+
+            if current.effective_trust_level != previous_iter_trust_level {
+                debug!(
+                    "No more nodes with effective_trust_level of {}",
+                    previous_iter_trust_level
+                );
+                assert!(current.effective_trust_level < previous_iter_trust_level);
+                if initial_distrusted_len != current_trust_set.distrusted.len() {
+                    debug!("Some people got banned at the current trust level - restarting the WoT calculation");
+                    break;
+                }
+                previous_iter_trust_level = current.effective_trust_level;
+            }
+
+            let visit = TrustSetVisit {
+                id: &current.id,
+                effective_trust_level: current.effective_trust_level,
+                distance: current.distance,
+            };
+            if observer.on_visit(&visit).is_break() {
+                debug!("Trust set calculation cancelled");
+                current_trust_set.incomplete = true;
+                break;
+            }
+
+            for (direct_trust, reported_id) in self.get_trust_list_of_id_in_view(&current.id, view)
+            {
+                let candidate_id = if redirect_trust {
+                    self.resolve_id(reported_id)
+                } else {
+                    reported_id
+                };
+                if candidate_id == &current.id {
+                    // trust of an Id for its own successor
+                    continue;
+                }
+                debug!(
+                    "{} ({}) reports trust level for {}: {}",
+                    current.id, current.effective_trust_level, candidate_id, direct_trust
+                );
+
+                let override_level = view.overrides.get(candidate_id).copied();
+                let effective_trust_level = override_level
+                    .unwrap_or_else(|| std::cmp::min(direct_trust, current.effective_trust_level));
+                observer.on_edge(&TrustSetEdge {
+                    from: &current.id,
+                    to: candidate_id,
+                    direct_trust_level: direct_trust,
+                    effective_trust_level,
+                });
+
+                if current_trust_set.is_distrusted(candidate_id) {
+                    debug!("{} is distrusted", candidate_id);
+                    observer.on_prune(&current.id, candidate_id, PruneReason::Distrusted);
+                    continue;
+                }
+
+                // Note: lower trust node can ban higher trust node, but only
+                // if it wasn't banned by a higher trust node beforehand.
+                // However banning by the same trust level node, does not prevent
+                // the node from banning others.
+                if direct_trust == TrustLevel::Distrust && override_level.is_none() {
+                    let can_ban = match distrust_policy {
+                        DistrustPolicy::Anyone => true,
+                        DistrustPolicy::RequireLevel(level) => {
+                            current.effective_trust_level >= level
+                        }
+                        DistrustPolicy::RootOnly => roots.contains(&current.id),
+                    };
+                    if !can_ban {
+                        debug!(
+                            "Ignoring distrust for {}: {} can't ban others",
+                            candidate_id, current.id
+                        );
+                        observer.on_prune(&current.id, candidate_id, PruneReason::CannotDistrust);
+                        continue;
+                    }
+                    debug!("Adding {} to distrusted list", candidate_id);
+                    // We discard the result, because we actually want to make as much
+                    // progress as possible before restaring building the WoT, and
+                    // we will not visit any node that was marked as distrusted,
+                    // becuse we check it for every node to be visited
+                    let proof = self.get_distrust_proof_in_view(&current.id, reported_id, view);
+                    let _ = current_trust_set.record_distrusted_id(
+                        candidate_id.clone(),
+                        current.id.clone(),
+                        proof,
+                    );
+
+                    if observer.on_distrust(candidate_id, &current.id).is_break() {
+                        debug!("Trust set calculation cancelled");
+                        current_trust_set.incomplete = true;
+                        return current_trust_set;
+                    }
+                    continue;
+                }
+
+                // Note: we keep visiting nodes, even banned ones, just like they were originally
+                // reported
+                if override_level == Some(TrustLevel::None) {
+                    debug!("Not traversing {}: overridden", candidate_id);
+                    observer.on_prune(&current.id, candidate_id, PruneReason::Overridden);
+                    continue;
+                }
+                debug!(
+                    "Effective trust for {} {}",
+                    candidate_id, effective_trust_level
+                );
+
+                if effective_trust_level < TrustLevel::None {
+                    unreachable!(
+                        "this should not happen: candidate_effective_trust <= TrustLevel::None"
+                    );
+                }
+
+                let candidate_distance_from_current =
+                    if let Some(v) = params.distance_by_level(effective_trust_level) {
+                        v
+                    } else {
+                        debug!("Not traversing {}: trust too low", candidate_id);
+                        observer.on_prune(&current.id, candidate_id, PruneReason::TrustTooLow);
+                        continue;
+                    };
+
+                let candidate_total_distance = current.distance + candidate_distance_from_current;
+                let max_distance = params.max_distance();
+
+                debug!(
+                    "Distance of {} from {}: {}. Total distance from root: {}.",
+                    candidate_id,
+                    current.id,
+                    candidate_distance_from_current,
+                    candidate_total_distance
+                );
+
+                if candidate_total_distance > max_distance {
+                    debug!(
+                        "Total distance of {}: {} higher than max_distance: {}.",
+                        candidate_id, candidate_total_distance, max_distance
+                    );
+                    observer.on_prune(&current.id, candidate_id, PruneReason::TooFar);
+                    continue;
+                }
+
+                if current_trust_set.record_trusted_id(
+                    candidate_id.clone(),
+                    current.id.clone(),
+                    candidate_total_distance,
+                    effective_trust_level,
+                    ReportedTrust {
+                        effective_trust_level,
+                        direct_trust_level: direct_trust,
+                        distance: candidate_distance_from_current,
+                    },
+                ) {
+                    // A capped Id can't extend more trust than it has itself,
+                    // and an Id overridden to a higher level than the one being traversed
+                    // keeps it, but extends no more trust than it was reached with,
+                    // so that the levels are still visited from the highest down
+                    let effective_trust_level =
+                        caps.get(candidate_id).map_or(effective_trust_level, |cap| {
+                            std::cmp::min(*cap, effective_trust_level)
+                        });
+                    let effective_trust_level =
+                        std::cmp::min(effective_trust_level, current.effective_trust_level);
+                    let visit = Visit {
+                        effective_trust_level,
+                        distance: candidate_total_distance,
+                        id: candidate_id.to_owned(),
+                    };
+                    if pending.insert(visit.clone()) {
+                        debug!("{:?} inserted for visit", visit);
+                    } else {
+                        debug!("{:?} alreading pending", visit);
+                    }
                 }
             }
+        }
 
-            // Remove the reports that are already fixed
-            for id in &advisory.ids {
-                if let Some(mut issue_marker) = issue_reports_by_id.get_mut(id) {
-                    let issues = std::mem::replace(&mut issue_marker.issues, HashSet::new());
-                    issue_marker.issues = issues
-                        .into_iter()
-                        .filter(|pkg_review_id| {
-                            let signature = &self
-                                .package_review_signatures_by_pkg_review_id
-                                .get(pkg_review_id)
-                                .expect("review for this signature")
-                                .value;
-                            let issue_review = self
-                                .package_review_by_signature
-                                .get(signature)
-                                .expect("review for this pkg_review_id");
-                            !advisory.is_for_version_when_reported_in_version(
-                                &issue_review.package.id.version,
-                                &review.package.id.version,
-                            )
-                        })
-                        .collect();
+        current_trust_set
+    }
+
+    /// Signatures of the proofs needed to justify the verification of `packages`
+    ///
+    /// These are: reviews of the given package versions by Ids trusted by `root`,
+    /// their reviews of other versions reporting issues or advisories, and trust
+    /// proofs behind all the trust reported for these Ids on the way from `root`.
+    /// Importing only these proofs into an empty `ProofDB` gives the reviewers
+    /// the same effective trust levels, and so the same verification results.
+    pub fn select_proofs_for(
+        &self,
+        root: &Id,
+        params: &TrustDistanceParams,
+        packages: &[(Source, Name, Version)],
+    ) -> Vec<Signature> {
+        let trust_set = self.calculate_trust_set(root, params);
+        let mut signatures = BTreeSet::new();
+
+        let mut pending = vec![];
+        for (source, name, version) in packages {
+            for review in self.get_pkg_reviews_for_name(source, name) {
+                let from = &review.from().id;
+                let relevant = &review.package.id.version == version
+                    || !review.issues.is_empty()
+                    || !review.advisories.is_empty();
+                if !relevant || !trust_set.is_trusted(from) {
+                    continue;
+                }
+                let pkg_review_id: PkgVersionReviewId = review.into();
+                if let Some(signature) = self
+                    .package_review_signatures_by_pkg_review_id
+                    .get(&pkg_review_id)
+                {
+                    signatures.insert(signature.value.to_string());
                 }
+                pending.push(from.clone());
             }
         }
 
-        issue_reports_by_id
+        // walk back all the trust edges that lead to the reviewers
+        let mut visited = HashSet::new();
+        while let Some(id) = pending.pop() {
+            if !visited.insert(id.clone()) {
+                continue;
+            }
+            let reported_by = trust_set
+                .trusted
+                .get(&id)
+                .into_iter()
+                .flat_map(|details| details.reported_by.keys());
+            for reporter in reported_by {
+                if let Some(edge) = self
+                    .trust_id_to_id
+                    .get(reporter)
+                    .and_then(|edges| edges.get(&id))
+                {
+                    signatures.insert(edge.value.signature.clone());
+                }
+                pending.push(reporter.clone());
+            }
+        }
+
+        signatures.into_iter().collect()
+    }
+
+    /// All Ids that currently claim the given URL, or are reported to own it
+    ///
+    /// URLs are compared in their canonical form (see `canonicalize_url`).
+    /// Self-reported claims take precedence over reports by others,
+    /// just like in `lookup_url`.
+    pub fn ids_claiming_url(&self, url: &Url) -> Vec<(Id, UrlClaim)> {
+        let canonical = self.canonical_url(url);
+        self.ids_by_url
+            .get(&canonical)
             .into_iter()
-            .filter(|(_id, markers)| !markers.issues.is_empty() || !markers.advisories.is_empty())
+            .flatten()
+            .filter_map(|id| Some((id.clone(), self.current_url_claim(id, &canonical)?)))
             .collect()
     }
 
-    pub fn get_pkg_reviews_with_issues_for_version<'a, 'b, 'c: 'a>(
-        &'a self,
-        source: &'b str,
-        name: &'c str,
-        queried_version: &'c Version,
-        trust_set: &'c TrustSet,
-        trust_level_required: TrustLevel,
-    ) -> impl Iterator<Item = &proof::review::Package> {
-        self.get_pkg_reviews_with_issues_for_name(source, name, trust_set, trust_level_required)
-            .filter(move |review| {
-                !review.issues.is_empty()
-                    || review.advisories.iter().any(|advi| {
-                        advi.is_for_version_when_reported_in_version(
-                            &queried_version,
-                            &review.package.id.version,
-                        )
+    /// Ids claiming the given URL, the reverse of `lookup_url`
+    ///
+    /// URLs are compared in their canonical form (see `canonicalize_url`), so
+    /// trailing slashes and `.git` suffixes don't matter. Verified claims go first,
+    /// then self-reported, then reported by others; the most recent claims first.
+    pub fn lookup_id_by_url(&self, url: &Url) -> Vec<IdOfUrl<'_>> {
+        let canonical = self.canonical_url(url);
+        let mut claims: Vec<(&Id, UrlClaim)> = self
+            .ids_by_url
+            .get(&canonical)
+            .into_iter()
+            .flatten()
+            .filter_map(|id| Some((id, self.current_url_claim(id, &canonical)?)))
+            .collect();
+        let strength = |claim: &UrlClaim| match claim {
+            UrlClaim::FromSelfVerified(_) => 0,
+            UrlClaim::FromSelf(_) => 1,
+            UrlClaim::FromOthers(_) => 2,
+        };
+        claims.sort_by(|a, b| {
+            strength(&a.1)
+                .cmp(&strength(&b.1))
+                .then_with(|| b.1.date().cmp(&a.1.date()))
+                .then_with(|| a.0.cmp(b.0))
+        });
+        claims
+            .into_iter()
+            .map(|(id, claim)| match claim {
+                UrlClaim::FromSelfVerified(_) => IdOfUrl::FromSelfVerified(id),
+                UrlClaim::FromSelf(_) => IdOfUrl::FromSelf(id),
+                UrlClaim::FromOthers(_) => IdOfUrl::FromOthers(id),
+            })
+            .collect()
+    }
+
+    /// URLs claimed by more than one Id, in their own proofs
+    ///
+    /// This is either a key rotation, or an impersonation attempt.
+    /// Same as `find_url_conflicts`, but claims reported by others don't count.
+    pub fn url_conflicts(&self) -> Vec<UrlConflict> {
+        self.collect_url_conflicts(true)
+    }
+
+    /// URLs currently claimed by more than one Id, no matter who made the claims
+    ///
+    /// Unlike `url_conflicts`, claims reported by others count too, and the conflicts
+    /// come with the details of every claim, so it's possible to tell
+    /// an impersonation attempt from a key rotation. Ordered by canonical URL.
+    pub fn find_url_conflicts(&self) -> Vec<UrlConflict> {
+        self.collect_url_conflicts(false)
+    }
+
+    fn collect_url_conflicts(&self, self_reported_only: bool) -> Vec<UrlConflict> {
+        self.ids_by_url
+            .iter()
+            .filter_map(|(canonical, ids)| {
+                let mut claims: Vec<_> = ids
+                    .iter()
+                    .filter_map(|id| Some((id.clone(), self.current_url_claim(id, canonical)?)))
+                    .filter(|(_, claim)| {
+                        !self_reported_only || !matches!(claim, UrlClaim::FromOthers(_))
                     })
+                    .collect();
+                if claims.len() < 2 {
+                    return None;
+                }
+                claims.sort_by(|a, b| a.1.date().cmp(&b.1.date()).then_with(|| a.0.cmp(&b.0)));
+                let url = self.lookup_url(&claims[0].0).any_unverified()?.clone();
+                Some(UrlConflict { url, claims })
             })
+            .collect()
     }
 
-    pub fn get_pkg_reviews_with_issues_for_name<'a, 'b, 'c: 'a>(
-        &'a self,
-        source: &'b str,
-        name: &'c str,
-        trust_set: &'c TrustSet,
-        trust_level_required: TrustLevel,
-    ) -> impl Iterator<Item = &proof::review::Package> {
-        self.get_pkg_reviews_for_name(source, name)
-            .filter(move |review| {
-                let effective = trust_set.get_effective_trust_level(&review.from().id);
-                effective >= trust_level_required
+    /// All the URLs `id` has ever claimed in its own proofs, oldest first
+    ///
+    /// Every URL is listed once, with the date it was claimed first. Unlike `lookup_url`,
+    /// which returns only the most recent one, this shows an Id moving between repos.
+    pub fn get_url_history(&self, id: &Id) -> Vec<Timestamped<Url>> {
+        let mut history: Vec<_> = self
+            .url_history
+            .get(id)
+            .into_iter()
+            .flat_map(BTreeMap::values)
+            .cloned()
+            .collect();
+        history.sort_by(|a, b| {
+            a.date
+                .cmp(&b.date)
+                .then_with(|| a.value.url.cmp(&b.value.url))
+        });
+        history
+    }
+
+    fn current_url_claim(&self, id: &Id, canonical: &str) -> Option<UrlClaim> {
+        if let Some((url, fetch_matches)) = self.url_by_id_self_reported.get(id) {
+            // Id's own claim overrides whatever others say
+            return if self.canonical_url(&url.value) != canonical {
+                None
+            } else if *fetch_matches {
+                Some(UrlClaim::FromSelfVerified(url.date))
+            } else {
+                Some(UrlClaim::FromSelf(url.date))
+            };
+        }
+        self.url_by_id_reported_by_others
+            .get(id)
+            .filter(|url| self.canonical_url(&url.value) == canonical)
+            .map(|url| UrlClaim::FromOthers(url.date))
+    }
+
+    /// Finds which URL is the latest and claimed to belong to the given Id.
+    /// The result indicates how reliable information this is.
+    pub fn lookup_url(&self, id: &Id) -> UrlOfId<'_> {
+        self.url_by_id_self_reported
+            .get(id)
+            .map(|(url, fetch_matches)| {
+                if *fetch_matches {
+                    UrlOfId::FromSelfVerified(&url.value)
+                } else {
+                    UrlOfId::FromSelf(&url.value)
+                }
+            })
+            .or_else(|| {
+                self.url_by_id_reported_by_others
+                    .get(id)
+                    .map(|url| UrlOfId::FromOthers(&url.value))
             })
-            .filter(|review| !review.issues.is_empty() || !review.advisories.is_empty())
+            .unwrap_or(UrlOfId::None)
     }
 
-    pub fn get_pkg_reviews_with_issues_for_source<'a, 'b, 'c: 'a>(
-        &'a self,
-        source: &'b str,
-        trust_set: &'c TrustSet,
-        trust_level_required: TrustLevel,
-    ) -> impl Iterator<Item = &proof::review::Package> {
-        self.get_pkg_reviews_for_source(source)
-            .filter(move |review| {
-                let effective = trust_set.get_effective_trust_level(&review.from().id);
-                effective >= trust_level_required
+    /// Like `lookup_url`, for many Ids at once
+    ///
+    /// Ids without any known URL are not included in the result.
+    pub fn lookup_urls_bulk(&self, ids: &[Id]) -> HashMap<Id, OwnedUrlOfId> {
+        let wanted: HashSet<&Id> = ids.iter().collect();
+        let mut res: HashMap<Id, OwnedUrlOfId> = self
+            .url_by_id_self_reported
+            .iter()
+            .filter(|(id, _)| wanted.contains(id))
+            .map(|(id, (url, fetch_matches))| {
+                let verification = if *fetch_matches {
+                    Verification::SelfVerified
+                } else {
+                    Verification::SelfReported
+                };
+                (
+                    id.clone(),
+                    OwnedUrlOfId::new(url.value.clone(), verification),
+                )
             })
-            .filter(|review| !review.issues.is_empty() || !review.advisories.is_empty())
+            .collect();
+        for (id, url) in &self.url_by_id_reported_by_others {
+            if wanted.contains(id) && !res.contains_key(id) {
+                res.insert(
+                    id.clone(),
+                    OwnedUrlOfId::new(url.value.clone(), Verification::ReportedByOthers),
+                );
+            }
+        }
+        res
     }
 
-    pub fn unique_package_review_proof_count(&self) -> usize {
-        self.package_review_signatures_by_pkg_review_id.len()
+    /// URLs of all Ids known to the DB (see `all_known_ids`), as `lookup_url` would return them
+    pub fn all_ids_with_urls(&self) -> impl Iterator<Item = (&Id, UrlOfId<'_>)> {
+        let self_reported =
+            self.url_by_id_self_reported
+                .iter()
+                .map(|(id, (url, fetch_matches))| {
+                    if *fetch_matches {
+                        (id, UrlOfId::FromSelfVerified(&url.value))
+                    } else {
+                        (id, UrlOfId::FromSelf(&url.value))
+                    }
+                });
+        let from_others = self
+            .url_by_id_reported_by_others
+            .iter()
+            .filter(move |(id, _)| !self.url_by_id_self_reported.contains_key(id))
+            .map(|(id, url)| (id, UrlOfId::FromOthers(&url.value)));
+        self_reported.chain(from_others)
     }
 
-    pub fn unique_trust_proof_count(&self) -> usize {
-        self.trust_id_to_id
+    /// Ids with a URL containing `needle` (ignoring case), ordered by Id
+    ///
+    /// Both the URLs Ids report themselves and the ones reported by others are
+    /// searched. If both of them match, the self-reported one is returned.
+    pub fn find_ids_by_url_substring(&self, needle: &str) -> Vec<(Id, UrlOfId<'_>)> {
+        let needle = needle.to_lowercase();
+        let matches = |url: &Url| url.url.to_lowercase().contains(&needle);
+        let mut found: BTreeMap<&Id, UrlOfId<'_>> = self
+            .url_by_id_reported_by_others
             .iter()
-            .fold(0, |count, (_id, set)| count + set.len())
+            .filter(|(_, url)| matches(&url.value))
+            .map(|(id, url)| (id, UrlOfId::FromOthers(&url.value)))
+            .collect();
+        for (id, (url, fetch_matches)) in &self.url_by_id_self_reported {
+            if matches(&url.value) {
+                let url = if *fetch_matches {
+                    UrlOfId::FromSelfVerified(&url.value)
+                } else {
+                    UrlOfId::FromSelf(&url.value)
+                };
+                found.insert(id, url);
+            }
+        }
+        found
+            .into_iter()
+            .map(|(id, url)| (id.clone(), url))
+            .collect()
     }
+}
 
-    fn add_code_review(&mut self, review: &review::Code, fetched_from: FetchSource) {
-        let from = &review.from();
-        self.record_url_from_from_field(&review.date_utc(), &from, &fetched_from);
-        for _file in &review.files {
-            // not implemented right now; just ignore
+/// Which review of an author to pick in `ProofDB::get_latest_review_per_author`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LatestBy {
+    Date,
+    Version,
+}
+
+/// How versions of reviews are matched with the queried version
+///
+/// See `ProofDB::get_advisories_for_version_matching` and
+/// `ProofDB::get_open_issues_for_version_matching`.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum VersionMatchPolicy {
+    /// Build metadata is ignored, so `1.2.3+foo` is the same as `1.2.3`, while
+    /// pre-releases precede their release, so advisories reported in `1.2.3`
+    /// affect `1.2.3-rc.1`; just like semver ordering works
+    #[default]
+    IgnoreBuildMetadata,
+    /// Like `IgnoreBuildMetadata`, but reviews of a version with different build
+    /// metadata than the queried one (e.g. `1.2.3+foo` for `1.2.3`) are disregarded
+    Strict,
+    /// Pre-releases (and builds) are treated as the release they precede,
+    /// so `1.2.3-rc.1` is within the same advisory windows as `1.2.3`
+    PreReleaseAsRelease,
+}
+
+impl VersionMatchPolicy {
+    /// `version`, as it's compared with other versions
+    fn normalize(self, version: &Version) -> Cow<'_, Version> {
+        match self {
+            VersionMatchPolicy::PreReleaseAsRelease
+                if !version.pre.is_empty() || !version.build.is_empty() =>
+            {
+                Cow::Owned(Version::new(version.major, version.minor, version.patch))
+            }
+            _ => Cow::Borrowed(version),
         }
     }
 
-    fn add_package_review(
-        &mut self,
-        review: &review::Package,
-        signature: &str,
-        fetched_from: FetchSource,
-    ) {
-        self.insertion_counter += 1;
+    /// Can a review of `reviewed` say anything about `queried` at all
+    fn relates(self, reviewed: &Version, queried: &Version) -> bool {
+        match self {
+            VersionMatchPolicy::Strict => reviewed != queried || reviewed.build == queried.build,
+            _ => true,
+        }
+    }
 
-        let from = &review.from();
-        self.record_url_from_from_field(&review.date_utc(), &from, &fetched_from);
+    /// The lowest version that is the same as `version` once normalized
+    fn lowest_equal(self, version: &Version) -> Cow<'_, Version> {
+        match self {
+            VersionMatchPolicy::PreReleaseAsRelease => {
+                let mut lowest = Version::new(version.major, version.minor, version.patch);
+                lowest.pre = vec![semver::Identifier::Numeric(0)];
+                Cow::Owned(lowest)
+            }
+            _ => Cow::Borrowed(version),
+        }
+    }
+}
 
-        self.package_review_by_signature
-            .entry(signature.to_owned())
-            .or_insert_with(|| review.to_owned());
+/// Order of reviews in `ProofDB::get_package_reviews_for_package_sorted_ref`
+/// and `ProofDB::get_pkg_reviews_page`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ReviewSortOrder {
+    /// Oldest review first
+    DateAsc,
+    /// Newest review first
+    DateDesc,
+    /// Oldest version first; reviews of the same version oldest first
+    VersionThenDate,
+    /// Reviews of the most trusted authors first, then oldest first
+    ///
+    /// Without a trust set, all the authors are trusted the same.
+    TrustLevelDesc,
+}
 
-        let pkg_review_id = PkgVersionReviewId::from(review);
-        let timestamp_signature = TimestampedSignature::from((review.date(), signature.to_owned()));
-        let timestamp_flags = TimestampedFlags::from((review.date(), review.flags.clone()));
+/// Page of reviews returned by `ProofDB::get_pkg_reviews_page`
+#[derive(Debug, Clone)]
+pub struct ReviewPage<'a> {
+    pub reviews: Vec<&'a review::Package>,
+    /// Number of reviews on all the pages
+    pub total: usize,
+    /// There are more reviews after the ones on this page
+    pub has_more: bool,
+}
 
-        self.package_review_signatures_by_package_digest
-            .entry(review.package.digest.to_owned())
-            .or_default()
-            .entry(pkg_review_id.clone())
-            .and_modify(|s| s.update_to_more_recent(&timestamp_signature))
-            .or_insert_with(|| timestamp_signature.clone());
+impl<'a> ReviewPage<'a> {
+    /// Reviews from `offset` to `offset + limit` of all the `reviews`, counting them all
+    fn of(reviews: impl Iterator<Item = &'a review::Package>, offset: usize, limit: usize) -> Self {
+        let mut page = vec![];
+        let mut total = 0;
+        for review in reviews {
+            if offset <= total && page.len() < limit {
+                page.push(review);
+            }
+            total += 1;
+        }
+        Self {
+            has_more: offset.saturating_add(page.len()) < total,
+            reviews: page,
+            total,
+        }
+    }
+}
 
-        self.package_review_signatures_by_pkg_review_id
-            .entry(pkg_review_id.clone())
-            .and_modify(|s| s.update_to_more_recent(&timestamp_signature))
-            .or_insert_with(|| timestamp_signature.clone());
+/// Order of reviews in `ProofDB::get_pkg_reviews_for_name_page`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ReviewOrder {
+    /// Newest version first; reviews of the same version ordered by author Id
+    ByVersionDesc,
+    /// Newest review first; reviews with the same date ordered by author Id, then version
+    ByDateDesc,
+    /// By author Id; reviews of the same author ordered by version, newest first
+    ByAuthor,
+}
 
-        self.package_reviews
-            .entry(review.package.id.id.source.clone())
-            .or_default()
-            .entry(review.package.id.id.name.clone())
-            .or_default()
-            .entry(review.package.id.version.clone())
-            .or_default()
-            .insert(pkg_review_id);
+/// Entry counts of `ProofDB` indices, see `ProofDB::approximate_memory_usage`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryStats {
+    pub trust_edges: usize,
+    pub self_trust: usize,
+    pub id_supersessions: usize,
+    /// Entries in the trust history (it's empty, unless kept)
+    pub trust_history: usize,
+    pub urls_self_reported: usize,
+    pub url_history: usize,
+    pub urls_reported_by_others: usize,
+    pub url_claims: usize,
+    pub package_reviews: usize,
+    pub package_reviews_by_digest: usize,
+    pub package_reviews_by_pkg_review_id: usize,
+    pub package_review_history: usize,
+    pub package_reviews_by_version: usize,
+    pub package_reviews_by_date: usize,
+    pub package_range_reviews: usize,
+    pub package_reviews_by_author: usize,
+    pub latest_package_reviews: usize,
+    pub package_flags: usize,
+    pub code_review_files: usize,
+    pub file_reviews_by_digest: usize,
+    pub package_alternatives: usize,
+    pub alternative_sources: usize,
+    /// Alternative pairs in the derived cache (it's empty until first used)
+    pub derived_alternatives: usize,
+    /// Review comments in the search index (it's empty until first used)
+    pub derived_comment_index: usize,
+    /// Trust sets kept by `ProofDB::calculate_trust_set_cached`
+    pub cached_trust_sets: usize,
+    pub unknown_proofs: usize,
+    pub signed_proofs: usize,
+    pub fetch_sources: usize,
+    /// Repos in `ProofDB::all_repo_stats`
+    pub repo_stats: usize,
+}
 
-        self.package_alternatives
-            .entry(review.package.id.id.clone())
-            .or_default()
-            .entry(review.from().id.clone())
-            .and_modify(|a| a.update_to_more_recent(&timestamp_signature))
-            .or_insert_with(|| timestamp_signature);
+/// Difference between two trust sets, as returned by `ProofDB::preview_trust_change`
+#[derive(Debug, Clone, Default)]
+pub struct TrustSetDelta {
+    /// Ids that would become trusted, with their new effective trust level
+    pub added: BTreeMap<Id, TrustLevel>,
+    /// Ids that would no longer be trusted, with their old effective trust level
+    pub removed: BTreeMap<Id, TrustLevel>,
+    /// Ids that stay trusted, but with a different effective trust level (old, new)
+    pub level_changed: BTreeMap<Id, (TrustLevel, TrustLevel)>,
+    /// Ids that would become distrusted
+    pub newly_distrusted: BTreeSet<Id>,
+    /// Number of package reviews authored by `added` Ids
+    pub newly_covered_reviews: usize,
+    /// Number of package reviews authored by `removed` Ids
+    pub no_longer_covered_reviews: usize,
+}
 
-        self.package_flags
-            .entry(review.package.id.id.clone())
-            .or_default()
-            .entry(review.from().id.clone())
-            .and_modify(|f| f.update_to_more_recent(&timestamp_flags))
-            .or_insert_with(|| timestamp_flags);
-    }
+/// Chain of trust from the root of a `TrustSet` to an Id, see `TrustSet::get_trust_paths`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrustPath {
+    pub root: Id,
+    /// Ids on the way from the `root` (excluding it) to the target (the last one)
+    pub hops: Vec<TrustPathHop>,
+}
 
-    pub fn get_package_review_count(
-        &self,
-        source: &str,
-        name: Option<&str>,
-        version: Option<&Version>,
-    ) -> usize {
-        self.get_package_reviews_for_package(source, name, version)
-            .count()
+impl TrustPath {
+    /// The Id the path leads to
+    pub fn target(&self) -> &Id {
+        self.hops.last().map_or(&self.root, |hop| &hop.id)
     }
 
-    pub fn get_package_reviews_for_package<'a, 'b, 'c: 'a, 'd: 'a>(
-        &'a self,
-        source: &'b str,
-        name: Option<&'c str>,
-        version: Option<&'d Version>,
-    ) -> impl Iterator<Item = &'a proof::review::Package> + 'a {
-        match (name, version) {
-            (Some(ref name), Some(ref version)) => {
-                Box::new(self.get_pkg_reviews_for_version(source, name, version))
-                    as Box<dyn Iterator<Item = _>>
-            }
-            (Some(ref name), None) => Box::new(self.get_pkg_reviews_for_name(source, name)),
-            (None, None) => Box::new(self.get_pkg_reviews_for_source(source)),
-            (None, Some(_)) => panic!("Wrong usage"),
-        }
+    /// Total distance from the `root` to the target
+    pub fn distance(&self) -> Distance {
+        self.hops.last().map_or(Distance::ZERO, |hop| hop.distance)
     }
+}
 
-    pub fn get_package_reviews_for_package_sorted<'a, 'b, 'c: 'a, 'd: 'a>(
-        &'a self,
-        source: &'b str,
-        name: Option<&'c str>,
-        version: Option<&'d Version>,
-    ) -> Vec<proof::review::Package> {
-        let mut proofs: Vec<_> = self
-            .get_package_reviews_for_package(source, name, version)
-            .cloned()
-            .collect();
+/// A single step of a `TrustPath`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrustPathHop {
+    /// Id reached in this step
+    pub id: Id,
+    /// Trust level the previous Id on the path reported for `id`
+    pub direct_trust_level: TrustLevel,
+    /// Distance from the root, accumulated up to `id`
+    pub distance: Distance,
+}
+
+/// Who distrusted an Id, see `TrustSet::get_distrust_details`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DistrustReport {
+    /// Ids that reported distrust, with the proof they did it in
+    ///
+    /// The proof is not known for hypothetical trust (see `ProofDB::preview_trust_change`),
+    /// and for distrust that has since been changed (see `ProofDB::calculate_trust_set_at`).
+    pub reported_by: BTreeMap<Id, Option<DistrustProof>>,
+}
 
-        proofs.sort_by(|a, b| a.date_utc().cmp(&b.date_utc()));
+/// Trust proof reporting distrust
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DistrustProof {
+    pub signature: Signature,
+    pub date: DateTime<Utc>,
+}
 
-        proofs
-    }
+/// `TrustSet` with a deterministic order of everything, see `TrustSet::to_sorted`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SortedTrustSet {
+    pub trusted: BTreeMap<Id, SortedTrustedId>,
+    /// Distrusted Ids, with the Ids that reported them as such
+    pub distrusted: BTreeMap<Id, BTreeSet<Id>>,
+}
 
-    fn add_trust_raw(&mut self, from: &Id, to: &Id, date: DateTime<Utc>, trust: TrustLevel) {
-        let tl = TimestampedTrustLevel { value: trust, date };
-        self.trust_id_to_id
-            .entry(from.to_owned())
-            .or_insert_with(HashMap::new)
-            .entry(to.to_owned())
-            .and_modify(|e| e.update_to_more_recent(&tl))
-            .or_insert_with(|| tl);
-    }
+/// Details of a trusted Id in a `SortedTrustSet`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SortedTrustedId {
+    pub effective_trust_level: TrustLevel,
+    /// Distance from the root
+    pub distance: Distance,
+    /// Ids that reported trust for this Id, with effective trust levels
+    pub reported_by: BTreeMap<Id, TrustLevel>,
+}
 
-    fn add_trust(&mut self, trust: &proof::Trust, fetched_from: FetchSource) {
-        let from = &trust.from();
-        self.record_url_from_from_field(&trust.date_utc(), &from, &fetched_from);
-        for to in &trust.ids {
-            self.add_trust_raw(&from.id, &to.id, trust.date_utc(), trust.trust);
+impl SortedTrustSet {
+    /// What changed between `self` and a newer `other` trust set
+    pub fn diff(&self, other: &SortedTrustSet) -> TrustSetDiff {
+        let mut diff = TrustSetDiff::default();
+        for (id, new) in &other.trusted {
+            match self.trusted.get(id) {
+                None => {
+                    diff.added.insert(id.clone(), new.effective_trust_level);
+                }
+                Some(old) => {
+                    if old.effective_trust_level != new.effective_trust_level {
+                        diff.level_changed.insert(
+                            id.clone(),
+                            (old.effective_trust_level, new.effective_trust_level),
+                        );
+                    }
+                    if old.distance != new.distance {
+                        diff.distance_changed
+                            .insert(id.clone(), (old.distance, new.distance));
+                    }
+                }
+            }
         }
-        for to in &trust.ids {
-            // Others should not be making verified claims about this URL,
-            // regardless of where these proofs were fetched from, because only
-            // owner of the Id is authoritative.
-            self.record_url_from_to_field(&trust.date_utc(), &to)
+        for (id, old) in &self.trusted {
+            if !other.trusted.contains_key(id) {
+                diff.removed.insert(id.clone(), old.effective_trust_level);
+            }
         }
-    }
-
-    pub fn all_known_ids(&self) -> BTreeSet<Id> {
-        self.url_by_id_self_reported
+        diff.newly_distrusted = other
+            .distrusted
+            .iter()
+            .filter(|(id, _)| !self.distrusted.contains_key(id))
+            .map(|(id, reported_by)| (id.clone(), reported_by.clone()))
+            .collect();
+        diff.no_longer_distrusted = self
+            .distrusted
             .keys()
-            .chain(self.url_by_id_reported_by_others.keys())
+            .filter(|id| !other.distrusted.contains_key(id))
             .cloned()
-            .collect()
+            .collect();
+        diff
     }
+}
 
-    /// Get all Ids that authored a proof (with total count)
-    pub fn all_author_ids(&self) -> BTreeMap<Id, usize> {
-        let mut res = BTreeMap::new();
-        for (id, set) in &self.trust_id_to_id {
-            *res.entry(id.to_owned()).or_default() += set.len();
-        }
-
-        for uniq_rev in self.package_review_signatures_by_pkg_review_id.keys() {
-            *res.entry(uniq_rev.from.clone()).or_default() += 1;
-        }
+/// Difference between two trust sets, see `SortedTrustSet::diff`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrustSetDiff {
+    /// Ids that became trusted, with their effective trust level
+    pub added: BTreeMap<Id, TrustLevel>,
+    /// Ids that are no longer trusted, with their old effective trust level
+    pub removed: BTreeMap<Id, TrustLevel>,
+    /// Ids that stay trusted, but with a different effective trust level (old, new)
+    pub level_changed: BTreeMap<Id, (TrustLevel, TrustLevel)>,
+    /// Ids that stay trusted, but at a different distance from the root (old, new)
+    pub distance_changed: BTreeMap<Id, (Distance, Distance)>,
+    /// Ids that became distrusted, with the Ids that reported them as such
+    pub newly_distrusted: BTreeMap<Id, BTreeSet<Id>>,
+    pub no_longer_distrusted: BTreeSet<Id>,
+}
 
-        res
+impl TrustSetDiff {
+    pub fn is_empty(&self) -> bool {
+        self == &TrustSetDiff::default()
     }
+}
 
-    pub fn get_package_review_by_signature<'a>(
-        &'a self,
-        signature: &str,
-    ) -> Option<&'a review::Package> {
-        self.package_review_by_signature.get(signature)
-    }
+/// Package versions whose trusted reviews changed, see `ProofDB::diff_package_coverage`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoverageDiff {
+    /// Package versions with more trusted reviews, with their numbers (old, new)
+    pub gained: BTreeMap<proof::PackageVersionId, (usize, usize)>,
+    /// Package versions with fewer trusted reviews, with their numbers (old, new)
+    pub lost: BTreeMap<proof::PackageVersionId, (usize, usize)>,
+}
 
-    pub fn get_package_reviews_by_digest<'a>(
-        &'a self,
-        digest: &Digest,
-    ) -> impl Iterator<Item = review::Package> + 'a {
-        self.package_review_signatures_by_package_digest
-            .get(digest.as_slice())
-            .into_iter()
-            .flat_map(move |unique_reviews| {
-                unique_reviews
-                    .iter()
-                    .map(move |(_unique_review, signature)| {
-                        self.package_review_by_signature[&signature.value].clone()
-                    })
-            })
+impl CoverageDiff {
+    pub fn is_empty(&self) -> bool {
+        self.gained.is_empty() && self.lost.is_empty()
     }
+}
 
-    /// Record an untrusted mapping between a PublicId and a URL it declares
-    fn record_url_from_to_field(&mut self, date: &DateTime<Utc>, to: &crev_data::PublicId) {
-        if let Some(url) = &to.url {
-            self.url_by_id_reported_by_others
-                .entry(to.id.clone())
-                .or_insert_with(|| TimestampedUrl {
-                    value: url.clone(),
-                    date: *date,
-                });
-        }
-    }
+/// Change made to a `ProofDB` by importing a proof
+///
+/// See `ProofDB::import_from_iter_with_changes`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeEvent {
+    /// First review of a package version by `author`
+    PackageReviewAdded {
+        source: String,
+        name: String,
+        version: Version,
+        author: Id,
+    },
+    /// Newer review of a package version replaced a previous one by the same `author`
+    PackageReviewSuperseded {
+        source: String,
+        name: String,
+        version: Version,
+        author: Id,
+    },
+    /// Effective direct trust level of `from` for `to` changed
+    TrustEdgeChanged {
+        from: Id,
+        to: Id,
+        /// `None` if there was no such trust edge before
+        old_level: Option<TrustLevel>,
+        new_level: TrustLevel,
+    },
+    /// URL of an Id (or whether it's verified) changed
+    UrlRecorded { id: Id, verified: bool },
+}
 
-    /// Record mapping between a PublicId and a URL it declares, and trust it's correct only if it's been fetched from the same URL
-    fn record_url_from_from_field(
+enum ChangeKind {
+    Added,
+    Superseded,
+}
+
+/// Inconsistency in the imported proofs, see `ProofDB::integrity_warnings`
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IntegrityWarning {
+    /// Two proofs with the same signature, but different content
+    ///
+    /// Only one of them (`kept_from`) is used. A copy from user's own repos,
+    /// or from the repo the author reports as their own, is preferred;
+    /// otherwise the one imported first is kept.
+    ConflictingSignature {
+        signature: Signature,
+        kept_from: FetchSource,
+        discarded_from: FetchSource,
+    },
+}
+
+/// Outcome of `ProofDB::import_from_iter_with_report`
+///
+/// Every proof is counted in exactly one of `accepted`, `unknown` and `rejected`.
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    /// Number of imported proofs, by kind
+    pub accepted: BTreeMap<String, usize>,
+    /// Number of proofs of kinds this version doesn't understand, stored as they are, by kind
+    ///
+    /// See `ProofDB::set_store_unknown_proofs`; otherwise such proofs are `rejected`.
+    pub unknown: BTreeMap<String, usize>,
+    /// Number of the `accepted` trust proofs in which the author trusts itself
+    ///
+    /// Such trust is ignored, see `ProofDB::self_trust_ids`.
+    pub self_trust: usize,
+    /// Proofs that were not imported, with where they came from, and why
+    pub rejected: Vec<(FetchSource, Error)>,
+}
+
+impl ImportReport {
+    fn record(
         &mut self,
-        date: &DateTime<Utc>,
-        from: &crev_data::PublicId,
-        fetched_from: &FetchSource,
+        proof: &proof::Proof,
+        fetch_source: FetchSource,
+        result: Result<ProofContent>,
     ) {
-        if let Some(url) = &from.url {
-            let tu = TimestampedUrl {
-                value: url.clone(),
-                date: date.to_owned(),
-            };
-            let fetch_matches = match fetched_from {
-                FetchSource::LocalUser => true,
-                FetchSource::Url(fetched_url) if **fetched_url == *url => true,
-                _ => false,
-            };
-            self.url_by_id_self_reported
-                .entry(from.id.clone())
-                .and_modify(|e| {
-                    e.0.update_to_more_recent(&tu);
-                    if fetch_matches {
-                        e.1 = true;
+        match result {
+            Ok(ProofContent::Unknown) => {
+                *self.unknown.entry(proof.kind().to_owned()).or_default() += 1
+            }
+            Ok(content) => {
+                if let ProofContent::Trust(trust) = &content {
+                    if trust.ids.iter().any(|to| to.id == trust.from().id) {
+                        self.self_trust += 1;
                     }
-                })
-                .or_insert_with(|| (tu, fetch_matches));
+                }
+                *self.accepted.entry(proof.kind().to_owned()).or_default() += 1
+            }
+            Err(e) => {
+                debug!("Ignoring proof: {}", e);
+                self.rejected.push((fetch_source, e));
+            }
         }
     }
+}
 
-    fn add_proof(&mut self, proof: &proof::Proof, fetched_from: FetchSource) -> Result<()> {
-        proof
-            .verify()
-            .expect("All proofs were supposed to be valid here");
-        match proof.kind() {
-            proof::CodeReview::KIND => self.add_code_review(&proof.parse_content()?, fetched_from),
-            proof::PackageReview::KIND => {
-                self.add_package_review(&proof.parse_content()?, proof.signature(), fetched_from)
-            }
-            proof::Trust::KIND => self.add_trust(&proof.parse_content()?, fetched_from),
-            other => Err(Error::UnknownProofType(other.into()))?,
-        }
+/// How often `ProofDB::import_with_progress` reports progress, in proofs
+pub const DEFAULT_IMPORT_PROGRESS_INTERVAL: usize = 100;
+
+/// Progress of `ProofDB::import_with_progress`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportProgress {
+    /// Proofs taken from the iterator so far
+    pub processed: usize,
+    pub accepted: usize,
+    pub rejected: usize,
+    /// Kind of the most recently processed proof (accepted or not)
+    pub last_kind: Option<String>,
+}
 
-        Ok(())
-    }
+/// Proofs verified by `ProofDB::stage_import`, to be added with `ProofDB::apply`
+pub struct ImportBatch {
+    proofs: Vec<(proof::Proof, FetchSource, Result<ProofContent>)>,
+}
 
-    pub fn import_from_iter(&mut self, i: impl Iterator<Item = (proof::Proof, FetchSource)>) {
-        for (proof, fetch_source) in i {
-            // ignore errors
-            if let Err(e) = self.add_proof(&proof, fetch_source) {
-                debug!("Ignoring proof: {}", e);
-            }
-        }
+impl ImportBatch {
+    /// Number of proofs in the batch, including the ones that failed the verification
+    pub fn len(&self) -> usize {
+        self.proofs.len()
     }
 
-    fn get_trust_list_of_id(&self, id: &Id) -> impl Iterator<Item = (TrustLevel, &Id)> {
-        if let Some(map) = self.trust_id_to_id.get(id) {
-            Some(map.iter().map(|(id, trust)| (trust.value, id)))
-        } else {
-            None
-        }
-        .into_iter()
-        .flatten()
+    pub fn is_empty(&self) -> bool {
+        self.proofs.is_empty()
     }
+}
 
-    pub fn calculate_trust_set(&self, for_id: &Id, params: &TrustDistanceParams) -> TrustSet {
-        let mut distrusted = HashMap::new();
+/// Verified content of a proof, ready to be added to a `ProofDB`
+enum ProofContent {
+    CodeReview(Box<review::Code>),
+    PackageReview(Box<review::Package>),
+    Trust(proof::Trust),
+    IdSupersession(proof::IdSupersession),
+    /// Proof of a kind this version doesn't understand, to be stored as it is
+    Unknown,
+}
 
-        // We keep retrying the whole thing, with more and more
-        // distrusted Ids
-        loop {
-            let prev_distrusted_len = distrusted.len();
-            let trust_set = self.calculate_trust_set_internal(for_id, params, distrusted);
-            if trust_set.distrusted.len() <= prev_distrusted_len {
-                return trust_set;
+impl ProofContent {
+    /// Verify the signature and parse the content of `proof`
+    ///
+    /// Doesn't depend on the `ProofDB`, so it can be done in parallel.
+    fn parse(proof: &proof::Proof, store_unknown_proofs: bool) -> Result<Self> {
+        proof.verify()?;
+        Ok(match proof.kind() {
+            proof::CodeReview::KIND => ProofContent::CodeReview(Box::new(proof.parse_content()?)),
+            proof::PackageReview::KIND => {
+                ProofContent::PackageReview(Box::new(proof.parse_content()?))
             }
-            distrusted = trust_set.distrusted;
-        }
+            proof::Trust::KIND => ProofContent::Trust(proof.parse_content()?),
+            proof::IdSupersession::KIND => ProofContent::IdSupersession(proof.parse_content()?),
+            _ if store_unknown_proofs => ProofContent::Unknown,
+            other => return Err(Error::UnknownProofType(other.into())),
+        })
     }
+}
 
-    /// Calculate the effective trust levels for IDs inside a WoT.
+/// Review of a single file, from a code review proof
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FileReview {
+    /// Version of the package the file is from
+    pub version: Version,
+    pub digest: Vec<u8>,
+    pub digest_type: String,
+    pub review: review::Review,
+    /// Of the code review proof
+    pub signature: Signature,
+}
+
+/// Advisory of a package, see `ProofDB::advisories_affecting`
+/// and `ProofDB::get_open_advisories`
+#[derive(Debug, Clone)]
+pub struct AdvisorySummary<'a> {
+    pub ids: &'a [String],
+    pub severity: Level,
+    /// Older versions affected by the advisory
+    pub range: review::VersionRange,
+    /// Review the advisory is part of
+    pub review: &'a review::Package,
+    /// Id that reported the advisory
+    pub reporter: &'a Id,
+    /// Effective trust level of the `reporter`
+    pub effective_trust_level: TrustLevel,
+    /// Version the advisory was reported in, which is the one fixing the problem
+    pub fixed_in: &'a Version,
+    /// None of the `ids` is an open issue in the newest version reviewed by
+    /// the trust set, i.e. newer reviews agree that the problem got fixed
     ///
-    /// This is one of the most important functions in `crev-wot`.
-    fn calculate_trust_set_internal(
-        &self,
-        for_id: &Id,
-        params: &TrustDistanceParams,
-        distrusted: HashMap<Id, DistrustedIdDetails>,
-    ) -> TrustSet {
-        /// Node that is to be visited
-        ///
-        /// Order of field is important, since we use the `Ord` trait
-        /// to visit nodes breadth-first with respect to trust level
-        #[derive(PartialOrd, Ord, Eq, PartialEq, Clone, Debug)]
-        struct Visit {
-            /// Effective transitive trust level of the node
-            effective_trust_level: TrustLevel,
-            /// Distance from the root, in some abstract numerical unit
-            distance: u64,
-            /// Id we're visit
-            id: Id,
+    /// See `ProofDB::get_open_issues_for_version` for how advisories cancel issues.
+    pub fixed: bool,
+}
+
+impl<'a> AdvisorySummary<'a> {
+    fn new(
+        review: &'a review::Package,
+        advisory_index: usize,
+        trust_set: &TrustSet,
+        open_in_latest: Option<&BTreeMap<String, IssueDetails>>,
+    ) -> Self {
+        let advisory = &review.advisories[advisory_index];
+        let reporter = &review.from().id;
+        AdvisorySummary {
+            ids: &advisory.ids,
+            severity: advisory.severity,
+            range: advisory.range,
+            review,
+            reporter,
+            effective_trust_level: trust_set.get_effective_trust_level(reporter),
+            fixed_in: &review.package.id.version,
+            fixed: matches!(open_in_latest,
+                Some(open) if !advisory.ids.iter().any(|id| open.contains_key(id))),
         }
+    }
+}
 
-        let mut pending = BTreeSet::new();
-        let mut current_trust_set = TrustSet::default();
-        let initial_distrusted_len = distrusted.len();
-        current_trust_set.distrusted = distrusted;
+/// Node of the WoT visited during the trust set calculation
+#[derive(Debug, Copy, Clone)]
+pub struct TrustSetVisit<'a> {
+    pub id: &'a Id,
+    /// Effective transitive trust level of the node
+    pub effective_trust_level: TrustLevel,
+    /// Distance from the root
+    pub distance: Distance,
+}
 
-        pending.insert(Visit {
-            effective_trust_level: TrustLevel::High,
-            distance: 0,
-            id: for_id.clone(),
-        });
-        let mut previous_iter_trust_level = TrustLevel::High;
-        current_trust_set.record_trusted_id(for_id.clone(), for_id.clone(), 0, TrustLevel::High);
+/// Trust reported by one node of the WoT for another, considered during the trust set calculation
+#[derive(Debug, Copy, Clone)]
+pub struct TrustSetEdge<'a> {
+    pub from: &'a Id,
+    pub to: &'a Id,
+    /// Trust level `from` reported for `to`
+    pub direct_trust_level: TrustLevel,
+    /// Trust level `to` would get through this edge (after the overrides)
+    pub effective_trust_level: TrustLevel,
+}
 
-        while let Some(current) = pending.iter().next().cloned() {
-            debug!("Traversing id: {:?}", current);
-            pending.remove(&current);
+/// Why a trust edge was not followed during the trust set calculation
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PruneReason {
+    /// The target is already distrusted
+    Distrusted,
+    /// The distrust was ignored, because of the `DistrustPolicy`
+    CannotDistrust,
+    /// The target is overridden to `TrustLevel::None`
+    Overridden,
+    /// The `DistanceMetric` doesn't follow edges of this effective trust level
+    TrustTooLow,
+    /// The target would be further than `max_distance` from the root
+    TooFar,
+}
 
-            if current.effective_trust_level != previous_iter_trust_level {
-                debug!(
-                    "No more nodes with effective_trust_level of {}",
-                    previous_iter_trust_level
-                );
-                assert!(current.effective_trust_level < previous_iter_trust_level);
-                if initial_distrusted_len != current_trust_set.distrusted.len() {
-                    debug!("Some people got banned at the current trust level - restarting the WoT calculation");
-                    break;
-                }
-            } else {
-                previous_iter_trust_level = current.effective_trust_level;
-            }
+/// Progress reporting for `ProofDB::calculate_trust_set_with`
+///
+/// Returning `ControlFlow::Break` from `on_visit`, `on_distrust` or `on_restart`
+/// cancels the calculation. `on_edge` and `on_prune` are only notifications.
+/// `()` is a no-op observer.
+pub trait TrustSetObserver {
+    /// A node is being traversed
+    fn on_visit(&mut self, _visit: &TrustSetVisit<'_>) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
 
-            for (direct_trust, candidate_id) in self.get_trust_list_of_id(&&current.id) {
-                debug!(
-                    "{} ({}) reports trust level for {}: {}",
-                    current.id, current.effective_trust_level, candidate_id, direct_trust
-                );
+    /// `by` reported distrust for `id`
+    fn on_distrust(&mut self, _id: &Id, _by: &Id) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
 
-                if current_trust_set.is_distrusted(candidate_id) {
-                    debug!("{} is distrusted", candidate_id);
-                    continue;
-                }
+    /// Calculation is being restarted (`restarts` times so far), because
+    /// some Ids got distrusted or capped
+    fn on_restart(&mut self, _restarts: usize) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
 
-                // Note: lower trust node can ban higher trust node, but only
-                // if it wasn't banned by a higher trust node beforehand.
-                // However banning by the same trust level node, does not prevent
-                // the node from banning others.
-                if direct_trust == TrustLevel::Distrust {
-                    debug!("Adding {} to distrusted list", candidate_id);
-                    // We discard the result, because we actually want to make as much
-                    // progress as possible before restaring building the WoT, and
-                    // we will not visit any node that was marked as distrusted,
-                    // becuse we check it for every node to be visited
-                    let _ = current_trust_set
-                        .record_distrusted_id(candidate_id.clone(), current.id.clone());
+    /// Trust reported by the node being traversed is considered
+    ///
+    /// Followed by `on_prune` or `on_distrust` if the edge doesn't lead
+    /// to the target being trusted.
+    fn on_edge(&mut self, _edge: &TrustSetEdge<'_>) {}
 
-                    continue;
-                }
+    /// Trust reported by `from` for `to` is not followed
+    fn on_prune(&mut self, _from: &Id, _to: &Id, _reason: PruneReason) {}
+}
 
-                // Note: we keep visiting nodes, even banned ones, just like they were originally
-                // reported
-                let effective_trust_level =
-                    std::cmp::min(direct_trust, current.effective_trust_level);
-                debug!(
-                    "Effective trust for {} {}",
-                    candidate_id, effective_trust_level
-                );
+impl TrustSetObserver for () {}
 
-                if effective_trust_level == TrustLevel::None {
-                    continue;
-                } else if effective_trust_level < TrustLevel::None {
-                    unreachable!(
-                        "this should not happen: candidate_effective_trust <= TrustLevel::None"
-                    );
-                }
+/// Event of the trust set calculation, as recorded by `RecordingObserver`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrustSetEvent {
+    Visited {
+        id: Id,
+        effective_trust_level: TrustLevel,
+        distance: Distance,
+    },
+    EdgeConsidered {
+        from: Id,
+        to: Id,
+        direct_trust_level: TrustLevel,
+        effective_trust_level: TrustLevel,
+    },
+    Pruned {
+        from: Id,
+        to: Id,
+        reason: PruneReason,
+    },
+    Distrusted {
+        id: Id,
+        by: Id,
+    },
+    Restarted {
+        restarts: usize,
+    },
+}
 
-                let candidate_distance_from_current =
-                    if let Some(v) = params.distance_by_level(effective_trust_level) {
-                        v
-                    } else {
-                        debug!("Not traversing {}: trust too low", candidate_id);
-                        continue;
-                    };
+/// `TrustSetObserver` collecting all the events, to explain a trust set calculation
+///
+/// Meant for debugging and tests: large WoTs generate a lot of events.
+#[derive(Debug, Clone, Default)]
+pub struct RecordingObserver {
+    pub events: Vec<TrustSetEvent>,
+}
 
-                let candidate_total_distance = current.distance + candidate_distance_from_current;
+impl RecordingObserver {
+    pub fn new() -> Self {
+        Self::default()
+    }
 
-                debug!(
-                    "Distance of {} from {}: {}. Total distance from root: {}.",
-                    candidate_id,
-                    current.id,
-                    candidate_distance_from_current,
-                    candidate_total_distance
-                );
+    /// Reasons for which trust reported for `id` was not followed
+    pub fn prune_reasons_for<'a>(&'a self, id: &'a Id) -> impl Iterator<Item = PruneReason> + 'a {
+        self.events.iter().filter_map(move |event| match event {
+            TrustSetEvent::Pruned { to, reason, .. } if to == id => Some(*reason),
+            _ => None,
+        })
+    }
+}
+
+impl TrustSetObserver for RecordingObserver {
+    fn on_visit(&mut self, visit: &TrustSetVisit<'_>) -> ControlFlow<()> {
+        self.events.push(TrustSetEvent::Visited {
+            id: visit.id.clone(),
+            effective_trust_level: visit.effective_trust_level,
+            distance: visit.distance,
+        });
+        ControlFlow::Continue(())
+    }
+
+    fn on_distrust(&mut self, id: &Id, by: &Id) -> ControlFlow<()> {
+        self.events.push(TrustSetEvent::Distrusted {
+            id: id.clone(),
+            by: by.clone(),
+        });
+        ControlFlow::Continue(())
+    }
+
+    fn on_restart(&mut self, restarts: usize) -> ControlFlow<()> {
+        self.events.push(TrustSetEvent::Restarted { restarts });
+        ControlFlow::Continue(())
+    }
+
+    fn on_edge(&mut self, edge: &TrustSetEdge<'_>) {
+        self.events.push(TrustSetEvent::EdgeConsidered {
+            from: edge.from.clone(),
+            to: edge.to.clone(),
+            direct_trust_level: edge.direct_trust_level,
+            effective_trust_level: edge.effective_trust_level,
+        });
+    }
 
-                if candidate_total_distance > params.max_distance {
-                    debug!(
-                        "Total distance of {}: {} higher than max_distance: {}.",
-                        candidate_id, candidate_total_distance, params.max_distance
-                    );
-                    continue;
-                }
+    fn on_prune(&mut self, from: &Id, to: &Id, reason: PruneReason) {
+        self.events.push(TrustSetEvent::Pruned {
+            from: from.clone(),
+            to: to.clone(),
+            reason,
+        });
+    }
+}
 
-                if current_trust_set.record_trusted_id(
-                    candidate_id.clone(),
-                    current.id.clone(),
-                    candidate_total_distance,
-                    effective_trust_level,
-                ) {
-                    let visit = Visit {
-                        effective_trust_level,
-                        distance: candidate_total_distance,
-                        id: candidate_id.to_owned(),
-                    };
-                    if pending.insert(visit.clone()) {
-                        debug!("{:?} inserted for visit", visit);
-                    } else {
-                        debug!("{:?} alreading pending", visit);
-                    }
-                }
-            }
-        }
+/// Direct trust of one Id for another, as reported in trust proofs
+#[derive(Debug, Copy, Clone)]
+pub struct DirectTrust<'a> {
+    /// Id that the trust is reported for
+    pub id: &'a Id,
+    /// Trust level from the most recent trust proof
+    pub level: TrustLevel,
+    /// Date of the most recent trust proof
+    pub date: DateTime<Utc>,
+    /// Expiration date of the most recent trust proof, if any
+    pub expires: Option<DateTime<Utc>>,
+    comment: &'a str,
+}
 
-        current_trust_set
+impl<'a> DirectTrust<'a> {
+    /// Comment from the most recent trust proof (empty if it had none)
+    pub fn comment(&self) -> &'a str {
+        self.comment
     }
+}
 
-    /// Finds which URL is the latest and claimed to belong to the given Id.
-    /// The result indicates how reliable information this is.
-    pub fn lookup_url(&self, id: &Id) -> UrlOfId<'_> {
-        self.url_by_id_self_reported
-            .get(id)
-            .map(|(url, fetch_matches)| {
-                if *fetch_matches {
-                    UrlOfId::FromSelfVerified(&url.value)
-                } else {
-                    UrlOfId::FromSelf(&url.value)
-                }
-            })
-            .or_else(|| {
-                self.url_by_id_reported_by_others
-                    .get(id)
-                    .map(|url| UrlOfId::FromOthers(&url.value))
-            })
-            .unwrap_or(UrlOfId::None)
+/// How an Id is claimed to own a URL, and the date of the claim
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum UrlClaim {
+    /// Self-reported, and verified by fetching proofs from the URL
+    FromSelfVerified(DateTime<Utc>),
+    /// Self-reported
+    FromSelf(DateTime<Utc>),
+    /// Reported by someone else
+    FromOthers(DateTime<Utc>),
+}
+
+impl UrlClaim {
+    pub fn date(&self) -> DateTime<Utc> {
+        match self {
+            UrlClaim::FromSelfVerified(date)
+            | UrlClaim::FromSelf(date)
+            | UrlClaim::FromOthers(date) => *date,
+        }
     }
 }
 
+/// URL claimed by more than one Id, see `ProofDB::find_url_conflicts`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UrlConflict {
+    /// As claimed by the first Id of `claims`
+    pub url: Url,
+    /// Every Id currently claiming the URL, oldest claim first
+    pub claims: Vec<(Id, UrlClaim)>,
+}
+
 /// Result of URL lookup
 #[derive(Debug, Copy, Clone)]
 pub enum UrlOfId<'a> {
@@ -1237,61 +8043,353 @@ impl<'a> UrlOfId<'a> {
             _ => None,
         }
     }
+
+    /// `None` if the URL is unknown
+    pub fn into_owned(self) -> Option<OwnedUrlOfId> {
+        let (url, verification) = match self {
+            Self::FromSelfVerified(url) => (url, Verification::SelfVerified),
+            Self::FromSelf(url) => (url, Verification::SelfReported),
+            Self::FromOthers(url) => (url, Verification::ReportedByOthers),
+            Self::None => return None,
+        };
+        Some(OwnedUrlOfId::new(url.clone(), verification))
+    }
+}
+
+/// Result of the reverse URL lookup, see `ProofDB::lookup_id_by_url`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum IdOfUrl<'a> {
+    /// The Id claims the URL, and its proofs were fetched from it
+    FromSelfVerified(&'a Id),
+    /// The Id claims the URL (signed by this Id)
+    FromSelf(&'a Id),
+    /// Someone else reported the Id owns the URL (unverified)
+    FromOthers(&'a Id),
+}
+
+impl<'a> IdOfUrl<'a> {
+    pub fn id(self) -> &'a Id {
+        match self {
+            Self::FromSelfVerified(id) | Self::FromSelf(id) | Self::FromOthers(id) => id,
+        }
+    }
+
+    pub fn verification(self) -> Verification {
+        match self {
+            Self::FromSelfVerified(_) => Verification::SelfVerified,
+            Self::FromSelf(_) => Verification::SelfReported,
+            Self::FromOthers(_) => Verification::ReportedByOthers,
+        }
+    }
+}
+
+/// How reliable the URL of an Id is (see `UrlOfId`)
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Verification {
+    SelfVerified,
+    SelfReported,
+    ReportedByOthers,
+}
+
+/// Owned version of `UrlOfId`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnedUrlOfId {
+    pub url: Url,
+    pub verification: Verification,
+}
+
+impl OwnedUrlOfId {
+    pub fn new(url: Url, verification: Verification) -> Self {
+        Self { url, verification }
+    }
+
+    pub fn as_url_of_id(&self) -> UrlOfId<'_> {
+        match self.verification {
+            Verification::SelfVerified => UrlOfId::FromSelfVerified(&self.url),
+            Verification::SelfReported => UrlOfId::FromSelf(&self.url),
+            Verification::ReportedByOthers => UrlOfId::FromOthers(&self.url),
+        }
+    }
 }
 
 /// Details of a one Id that is trusted
 #[derive(Debug, Clone)]
 struct TrustedIdDetails {
     // distanc from the root of trust
-    distance: u64,
+    distance: Distance,
     // effective, global trust from the root of the WoT
     effective_trust_level: TrustLevel,
     /// People that reported trust for this id
-    reported_by: HashMap<Id, TrustLevel>,
+    reported_by: BTreeMap<Id, ReportedTrust>,
+}
+
+/// Trust reported for an Id by one other Id
+#[derive(Debug, Clone)]
+struct ReportedTrust {
+    /// Effective trust level, as reported by the reporter
+    effective_trust_level: TrustLevel,
+    /// Trust level from the reporter's proof
+    direct_trust_level: TrustLevel,
+    /// Distance between the reporter and the Id
+    distance: Distance,
 }
 
 /// Details of a one Id that is distrusted
 #[derive(Debug, Clone, Default)]
 struct DistrustedIdDetails {
     /// People that reported distrust for this id
-    reported_by: HashSet<Id>,
+    reported_by: BTreeSet<Id>,
+    /// Proofs some of them reported it in, if known
+    proofs: BTreeMap<Id, DistrustProof>,
 }
 
 #[derive(Default, Debug, Clone)]
 pub struct TrustSet {
-    trusted: HashMap<Id, TrustedIdDetails>,
-    distrusted: HashMap<Id, DistrustedIdDetails>,
+    trusted: BTreeMap<Id, TrustedIdDetails>,
+    distrusted: BTreeMap<Id, DistrustedIdDetails>,
+    distrust_conflicts: BTreeSet<(Id, Id)>,
+    /// Calculation was cancelled before finishing
+    incomplete: bool,
+    /// Trust sets of only the trust applying to packages of a source, for every
+    /// source some trust is limited to; empty if no trust is limited at all
+    by_source: BTreeMap<String, TrustSet>,
+    /// Trust set of only the trust not limited to any sources, if some trust is
+    unlimited: Option<Box<TrustSet>>,
+    /// Policy the set was calculated with
+    distrust_policy: DistrustPolicy,
 }
 
 impl TrustSet {
+    /// `false` if the calculation was cancelled, and this is only a partial result
+    pub fn is_complete(&self) -> bool {
+        !self.incomplete
+    }
+
+    /// Who could ban others in the calculation of this set
+    pub fn distrust_policy(&self) -> DistrustPolicy {
+        self.distrust_policy
+    }
+
+    /// Pairs of Ids (lower Id first) that distrust each other
+    ///
+    /// Only one of them can end up trusted - which one depends
+    /// on the shape of the WoT, so it's worth pointing out to the user.
+    pub fn distrust_conflicts(&self) -> impl Iterator<Item = &(Id, Id)> {
+        self.distrust_conflicts.iter()
+    }
+
     pub fn trusted_ids(&self) -> impl Iterator<Item = &Id> {
         self.trusted.keys()
     }
 
+    /// What changed between `self` and a newer `other` trust set
+    ///
+    /// Same as `SortedTrustSet::diff` of both.
+    pub fn diff(&self, other: &TrustSet) -> TrustSetDiff {
+        self.to_sorted().diff(&other.to_sorted())
+    }
+
+    /// All the data of the trust set, in a form that can be compared,
+    /// serialized and cached
+    pub fn to_sorted(&self) -> SortedTrustSet {
+        SortedTrustSet {
+            trusted: self
+                .trusted
+                .iter()
+                .map(|(id, details)| {
+                    (
+                        id.clone(),
+                        SortedTrustedId {
+                            effective_trust_level: details.effective_trust_level,
+                            distance: details.distance,
+                            reported_by: details
+                                .reported_by
+                                .iter()
+                                .map(|(id, reported)| (id.clone(), reported.effective_trust_level))
+                                .collect(),
+                        },
+                    )
+                })
+                .collect(),
+            distrusted: self
+                .distrusted
+                .iter()
+                .map(|(id, details)| (id.clone(), details.reported_by.clone()))
+                .collect(),
+        }
+    }
+
     pub fn is_trusted(&self, id: &Id) -> bool {
         self.trusted.contains_key(id)
     }
 
+    /// All the ways the trust (or distrust) flows from the root to `id`
+    ///
+    /// Shortest paths come first, paths of equal distance are ordered by Ids
+    /// along the way. For a distrusted `id`, the last hop of every path
+    /// is a `Distrust` one, from one of the Ids that reported the distrust.
+    /// The number of paths can grow very quickly in a dense WoT, so this
+    /// is meant for explaining the results, and not for regular use.
+    pub fn get_trust_paths(&self, id: &Id) -> Vec<TrustPath> {
+        let mut suffixes = vec![];
+        if self.trusted.contains_key(id) {
+            self.collect_trust_path_suffixes(id, &mut vec![], &mut suffixes);
+        } else if let Some(details) = self.distrusted.get(id) {
+            for reporter in &details.reported_by {
+                let hop = TrustPathHop {
+                    id: id.clone(),
+                    direct_trust_level: TrustLevel::Distrust,
+                    distance: Distance::ZERO,
+                };
+                self.collect_trust_path_suffixes(reporter, &mut vec![hop], &mut suffixes);
+            }
+        }
+
+        let mut paths: Vec<_> = suffixes
+            .into_iter()
+            .map(|(root, mut hops)| {
+                hops.reverse();
+                // accumulate the distances of the edges
+                let mut distance = Distance::ZERO;
+                for hop in &mut hops {
+                    distance = distance + hop.distance;
+                    hop.distance = distance;
+                }
+                TrustPath { root, hops }
+            })
+            .collect();
+        paths.sort_by(|a, b| {
+            a.distance().cmp(&b.distance()).then_with(|| {
+                a.hops
+                    .iter()
+                    .map(|hop| &hop.id)
+                    .cmp(b.hops.iter().map(|hop| &hop.id))
+            })
+        });
+        paths
+    }
+
+    /// Walk back from `id` to the root, through every Id that reported trust for it
+    ///
+    /// `suffix` holds the hops (target first) from `id` to the Id the paths are calculated for,
+    /// with distances of single edges.
+    fn collect_trust_path_suffixes(
+        &self,
+        id: &Id,
+        suffix: &mut Vec<TrustPathHop>,
+        out: &mut Vec<(Id, Vec<TrustPathHop>)>,
+    ) {
+        let details = if let Some(details) = self.trusted.get(id) {
+            details
+        } else {
+            return;
+        };
+        // roots are the only Ids reporting trust for themselves
+        if details.reported_by.contains_key(id) {
+            out.push((id.clone(), suffix.clone()));
+            return;
+        }
+        for (reporter, reported) in &details.reported_by {
+            // no cycles
+            if suffix.iter().any(|hop| &hop.id == reporter) {
+                continue;
+            }
+            suffix.push(TrustPathHop {
+                id: id.clone(),
+                direct_trust_level: reported.direct_trust_level,
+                distance: reported.distance,
+            });
+            self.collect_trust_path_suffixes(reporter, suffix, out);
+            suffix.pop();
+        }
+    }
+
     pub fn is_distrusted(&self, id: &Id) -> bool {
         self.distrusted.contains_key(id)
     }
 
+    pub fn distrusted_ids(&self) -> impl Iterator<Item = &Id> {
+        self.distrusted.keys()
+    }
+
+    /// Which trusted Ids distrusted `id`, if it is distrusted
+    pub fn get_distrust_details(&self, id: &Id) -> Option<DistrustReport> {
+        let details = self.distrusted.get(id)?;
+        Some(DistrustReport {
+            reported_by: details
+                .reported_by
+                .iter()
+                .map(|reporter| (reporter.clone(), details.proofs.get(reporter).cloned()))
+                .collect(),
+        })
+    }
+
     /// Record that an Id is reported as distrusted
     ///
     /// Return `true` if it was previously considered as trusted,
     /// and so that WoT traversal needs to be restarted
-    fn record_distrusted_id(&mut self, subject: Id, reported_by: Id) -> bool {
+    fn record_distrusted_id(
+        &mut self,
+        subject: Id,
+        reported_by: Id,
+        proof: Option<DistrustProof>,
+    ) -> bool {
         let res = self.trusted.remove(&subject).is_some();
 
-        self.distrusted
-            .entry(subject)
-            .or_default()
-            .reported_by
-            .insert(reported_by);
+        let details = self.distrusted.entry(subject).or_default();
+        if let Some(proof) = proof {
+            details.proofs.insert(reported_by.clone(), proof);
+        }
+        details.reported_by.insert(reported_by);
 
         res
     }
 
+    /// Distrust all the Ids in the same supersession group as any distrusted one
+    ///
+    /// See `ProofDB::supersession_groups`.
+    fn distrust_superseded(&mut self, groups: &BTreeMap<&Id, Vec<&Id>>) {
+        if groups.is_empty() {
+            return;
+        }
+        let group_of: HashMap<&Id, &Vec<&Id>> = groups
+            .values()
+            .flat_map(|group| group.iter().map(move |id| (*id, group)))
+            .collect();
+        let mut extra = vec![];
+        for (id, details) in &self.distrusted {
+            for other in group_of.get(id).into_iter().copied().flatten() {
+                if !self.distrusted.contains_key(*other) {
+                    extra.push(((*other).clone(), details.clone()));
+                }
+            }
+        }
+        for (id, details) in extra {
+            self.trusted.remove(&id);
+            self.distrusted.entry(id).or_insert(details);
+        }
+    }
+
+    /// Give every superseded Id the same trust as its current Id
+    fn inherit_from_current_ids(&mut self, groups: &BTreeMap<&Id, Vec<&Id>>) {
+        for (current, group) in groups {
+            let details = self.trusted.get(*current).cloned();
+            for old in group.iter().filter(|old| *old != current) {
+                if self.distrusted.contains_key(*old) {
+                    continue;
+                }
+                match &details {
+                    Some(details) => {
+                        self.trusted.insert((*old).clone(), details.clone());
+                    }
+                    None => {
+                        self.trusted.remove(*old);
+                    }
+                }
+            }
+        }
+    }
+
     /// Record that an Id is reported as trusted
     ///
     /// Returns `true` if this actually added or changed the `subject` details,
@@ -1300,18 +8398,17 @@ impl TrustSet {
         &mut self,
         subject: Id,
         reported_by: Id,
-        distance: u64,
+        distance: Distance,
         effective_trust_level: TrustLevel,
+        report: ReportedTrust,
     ) -> bool {
-        use std::collections::hash_map::Entry;
+        use std::collections::btree_map::Entry;
 
         assert!(effective_trust_level >= TrustLevel::None);
 
         match self.trusted.entry(subject) {
             Entry::Vacant(entry) => {
-                let reported_by = vec![(reported_by, effective_trust_level)]
-                    .into_iter()
-                    .collect();
+                let reported_by = vec![(reported_by, report)].into_iter().collect();
                 entry.insert(TrustedIdDetails {
                     distance,
                     effective_trust_level,
@@ -1332,13 +8429,13 @@ impl TrustSet {
                 }
                 match details.reported_by.entry(reported_by) {
                     Entry::Vacant(entry) => {
-                        entry.insert(effective_trust_level);
+                        entry.insert(report);
                         changed = true;
                     }
                     Entry::Occupied(mut entry) => {
-                        let level = entry.get_mut();
-                        if *level < effective_trust_level {
-                            *level = effective_trust_level;
+                        let reported = entry.get_mut();
+                        if reported.effective_trust_level < report.effective_trust_level {
+                            *reported = report;
                             changed = true;
                         }
                     }
@@ -1348,6 +8445,108 @@ impl TrustSet {
         }
     }
 
+    /// Calculate the trust level caps required by the corroboration mode
+    ///
+    /// An Id can only have `Medium` or `High` effective trust if enough
+    /// distinct trusted Ids reported it at (at least) that level. Caps are
+    /// never relaxed in comparison to `previous` ones, so repeating the calculation
+    /// always converges.
+    fn corroboration_caps(
+        &self,
+        roots: &[Id],
+        params: &CorroborationParams,
+        previous: &BTreeMap<Id, TrustLevel>,
+    ) -> BTreeMap<Id, TrustLevel> {
+        let mut caps = previous.clone();
+        for (id, details) in &self.trusted {
+            if roots.contains(id) {
+                continue;
+            }
+            let corroborated_level = [TrustLevel::High, TrustLevel::Medium]
+                .iter()
+                .cloned()
+                .find(|level| {
+                    details
+                        .reported_by
+                        .values()
+                        .filter(|reported| reported.effective_trust_level >= *level)
+                        .count()
+                        >= params.min_reporters
+                })
+                .unwrap_or(TrustLevel::Low);
+
+            if corroborated_level < details.effective_trust_level {
+                let cap = caps.entry(id.clone()).or_insert(corroborated_level);
+                *cap = std::cmp::min(*cap, corroborated_level);
+            }
+        }
+        caps
+    }
+
+    /// Trust level caps of Ids, according to the `aggregation` of the levels reported for them
+    ///
+    /// Just like in `corroboration_caps`, `previous` caps are never relaxed.
+    fn aggregation_caps(
+        &self,
+        roots: &[Id],
+        aggregation: EffectiveTrustAggregation,
+        previous: &BTreeMap<Id, TrustLevel>,
+    ) -> BTreeMap<Id, TrustLevel> {
+        let mut caps = previous.clone();
+        if aggregation == EffectiveTrustAggregation::Max {
+            return caps;
+        }
+        for (id, details) in &self.trusted {
+            if roots.contains(id) {
+                continue;
+            }
+            let mut reported: Vec<TrustLevel> = details
+                .reported_by
+                .values()
+                .map(|reported| reported.effective_trust_level)
+                .collect();
+            let aggregated = match aggregation {
+                EffectiveTrustAggregation::Max => continue,
+                EffectiveTrustAggregation::RequireQuorum { level, count } => {
+                    [TrustLevel::High, TrustLevel::Medium, TrustLevel::Low]
+                        .iter()
+                        .cloned()
+                        .filter(|quorum_level| level <= *quorum_level)
+                        .find(|quorum_level| {
+                            reported
+                                .iter()
+                                .filter(|reported| *reported >= quorum_level)
+                                .count()
+                                >= count
+                        })
+                        .unwrap_or(match level {
+                            TrustLevel::High => TrustLevel::Medium,
+                            _ => TrustLevel::Low,
+                        })
+                }
+                EffectiveTrustAggregation::Median => {
+                    reported.sort();
+                    reported[(reported.len() - 1) / 2]
+                }
+            };
+            let aggregated = cmp::max(aggregated, TrustLevel::Low);
+
+            if aggregated < details.effective_trust_level {
+                let cap = caps.entry(id.clone()).or_insert(aggregated);
+                *cap = std::cmp::min(*cap, aggregated);
+            }
+        }
+        caps
+    }
+
+    fn apply_caps(&mut self, caps: &BTreeMap<Id, TrustLevel>) {
+        for (id, cap) in caps {
+            if let Some(details) = self.trusted.get_mut(id) {
+                details.effective_trust_level = std::cmp::min(details.effective_trust_level, *cap);
+            }
+        }
+    }
+
     pub fn get_effective_trust_level(&self, id: &Id) -> TrustLevel {
         self.get_effective_trust_level_opt(id)
             .unwrap_or(TrustLevel::None)
@@ -1359,13 +8558,207 @@ impl TrustSet {
             .map(|details| details.effective_trust_level)
             .or_else(|| self.distrusted.get(id).map(|_| TrustLevel::Distrust))
     }
+
+    /// Trust set of only the trust that applies to packages from `source`
+    ///
+    /// Trust proofs can be limited to some package sources (see
+    /// `proof::Trust::sources`); if none are, this is the whole trust set.
+    pub fn for_source(&self, source: &str) -> &TrustSet {
+        match (self.by_source.get(source), &self.unlimited) {
+            (Some(scoped), _) => scoped,
+            (None, Some(unlimited)) => unlimited,
+            (None, None) => self,
+        }
+    }
+
+    /// Effective trust level of `id` for packages from `source`, see `for_source`
+    pub fn get_effective_trust_level_for_source(&self, id: &Id, source: &str) -> TrustLevel {
+        self.for_source(source).get_effective_trust_level(id)
+    }
+}
+
+/// Distance in the WoT, in some abstract numerical unit
+///
+/// Kept as a fixed-point number of thousandths of a unit,
+/// so fractional distances are possible, while still being totally ordered.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Distance(u64);
+
+impl Distance {
+    pub const ZERO: Distance = Distance(0);
+
+    const MILLIS_PER_UNIT: u64 = 1000;
+
+    pub fn from_units(units: u64) -> Self {
+        Distance(units.saturating_mul(Self::MILLIS_PER_UNIT))
+    }
+
+    pub fn from_millis(millis: u64) -> Self {
+        Distance(millis)
+    }
+
+    pub fn as_millis(self) -> u64 {
+        self.0
+    }
+
+    pub fn as_f64(self) -> f64 {
+        self.0 as f64 / Self::MILLIS_PER_UNIT as f64
+    }
+}
+
+impl ops::Add for Distance {
+    type Output = Distance;
+
+    fn add(self, other: Distance) -> Distance {
+        Distance(self.0.saturating_add(other.0))
+    }
+}
+
+impl fmt::Display for Distance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_f64())
+    }
+}
+
+/// How far from the root of the WoT the trust reaches
+///
+/// `TrustDistanceParams` is the default implementation.
+pub trait DistanceMetric {
+    /// Distance added by following an edge of a given effective trust level
+    ///
+    /// `None` means the edge is not traversed at all. `Distrust` edges
+    /// are never traversed, no matter what is returned for them.
+    fn distance_by_level(&self, level: TrustLevel) -> Option<Distance>;
+
+    /// Ids further than that from the root are not trusted
+    fn max_distance(&self) -> Distance;
+
+    /// See `TrustDistanceParams::require_corroboration`
+    fn require_corroboration(&self) -> Option<&CorroborationParams> {
+        None
+    }
+
+    /// See `TrustDistanceParams::review_quality_cap`
+    fn review_quality_cap(&self) -> Option<&ReviewQualityParams> {
+        None
+    }
+
+    /// See `TrustDistanceParams::distrust_policy`
+    fn distrust_policy(&self) -> DistrustPolicy {
+        DistrustPolicy::Anyone
+    }
+
+    /// See `TrustDistanceParams::trust_aggregation`
+    fn trust_aggregation(&self) -> EffectiveTrustAggregation {
+        EffectiveTrustAggregation::Max
+    }
+
+    /// See `TrustDistanceParams::supersession`
+    fn supersession(&self) -> SupersessionParams {
+        SupersessionParams::default()
+    }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TrustDistanceParams {
     pub max_distance: u64,
     pub high_trust_distance: u64,
     pub medium_trust_distance: u64,
     pub low_trust_distance: u64,
+    /// If set, `Medium` and `High` effective trust requires corroboration
+    /// from multiple trusted Ids
+    pub require_corroboration: Option<CorroborationParams>,
+    /// If set, effective trust of Ids writing low quality package reviews is capped
+    pub review_quality_cap: Option<ReviewQualityParams>,
+    /// Which trusted Ids can make others distrusted
+    pub distrust_policy: DistrustPolicy,
+    /// How the trust reported for an Id by many trusted Ids adds up
+    pub trust_aggregation: EffectiveTrustAggregation,
+    /// Whether superseded Ids are treated like their successors
+    pub supersession: SupersessionParams,
+}
+
+/// Who can ban others from the trust set, by reporting `Distrust` for them
+///
+/// Distrust reported by Ids not allowed to ban is ignored, just like
+/// if they didn't report any trust at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DistrustPolicy {
+    /// Any trusted Id
+    #[default]
+    Anyone,
+    /// Only Ids with at least this effective trust level
+    RequireLevel(TrustLevel),
+    /// Only the roots of the calculation
+    RootOnly,
+}
+
+/// How the effective trust level of an Id is derived from the levels
+/// reported for it by all the trusted Ids
+///
+/// Whatever the levels are, the effective trust level of an Id reached
+/// in the WoT is never lowered below `Low`, and roots are never affected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EffectiveTrustAggregation {
+    /// The highest of them
+    #[default]
+    Max,
+    /// Levels of `level` and higher need at least `count` Ids reporting them
+    ///
+    /// Otherwise the effective trust level is capped at the level below `level`.
+    RequireQuorum { level: TrustLevel, count: usize },
+    /// The median of them (the lower one of the two for an even number of them)
+    Median,
+}
+
+/// How Id supersession proofs (see `ProofDB::resolve_id`) affect the WoT calculation
+///
+/// Both are off by default, and supersession proofs are only recorded.
+/// With any of them on, distrust of an Id applies to all the Ids it superseded,
+/// or got superseded by, too.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SupersessionParams {
+    /// Trust reported for a superseded Id is reported for its current Id instead
+    pub redirect_trust: bool,
+    /// Superseded Ids get the effective trust level of their current Id
+    /// (or none, if it's not trusted), so their reviews count as if the current
+    /// Id wrote them in queries filtering reviews by trust
+    pub inherit_reviews: bool,
+}
+
+/// Parameters of the corroboration mode of the WoT calculation
+///
+/// In this mode an Id reaches effective `Medium` or `High` trust
+/// only if at least `min_reporters` distinct trusted Ids (including the root)
+/// report it at that level. Otherwise its effective trust is capped at `Low`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorroborationParams {
+    pub min_reporters: usize,
+}
+
+/// Parameters of capping the trust of Ids by the quality of their package reviews
+///
+/// Quality of an Id's reviews is the median `thoroughness` and `understanding`
+/// of all their most recent reviews of package versions. If any of these is lower
+/// than required, effective trust of the Id is capped at `max_trust_level`.
+/// Ids that didn't review anything are not affected, and the cap never
+/// raises anyone's trust.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReviewQualityParams {
+    pub min_thoroughness: Level,
+    pub min_understanding: Level,
+    pub max_trust_level: TrustLevel,
+}
+
+impl Default for ReviewQualityParams {
+    fn default() -> Self {
+        Self {
+            min_thoroughness: Level::Low,
+            min_understanding: Level::Low,
+            max_trust_level: TrustLevel::Low,
+        }
+    }
 }
 
 impl TrustDistanceParams {
@@ -1375,18 +8768,49 @@ impl TrustDistanceParams {
             high_trust_distance: 1,
             medium_trust_distance: 1,
             low_trust_distance: 1,
+            require_corroboration: None,
+            review_quality_cap: None,
+            distrust_policy: DistrustPolicy::Anyone,
+            trust_aggregation: EffectiveTrustAggregation::Max,
+            supersession: SupersessionParams::default(),
         }
     }
+}
 
-    fn distance_by_level(&self, level: TrustLevel) -> Option<u64> {
+impl DistanceMetric for TrustDistanceParams {
+    fn distance_by_level(&self, level: TrustLevel) -> Option<Distance> {
         use crev_data::proof::trust::TrustLevel::*;
-        Some(match level {
+        Some(Distance::from_units(match level {
             Distrust => return Option::None,
             None => return Option::None,
             Low => self.low_trust_distance,
             Medium => self.medium_trust_distance,
             High => self.high_trust_distance,
-        })
+        }))
+    }
+
+    fn max_distance(&self) -> Distance {
+        Distance::from_units(self.max_distance)
+    }
+
+    fn require_corroboration(&self) -> Option<&CorroborationParams> {
+        self.require_corroboration.as_ref()
+    }
+
+    fn review_quality_cap(&self) -> Option<&ReviewQualityParams> {
+        self.review_quality_cap.as_ref()
+    }
+
+    fn distrust_policy(&self) -> DistrustPolicy {
+        self.distrust_policy
+    }
+
+    fn trust_aggregation(&self) -> EffectiveTrustAggregation {
+        self.trust_aggregation
+    }
+
+    fn supersession(&self) -> SupersessionParams {
+        self.supersession
     }
 }
 
@@ -1397,6 +8821,11 @@ impl Default for TrustDistanceParams {
             high_trust_distance: 0,
             medium_trust_distance: 1,
             low_trust_distance: 5,
+            require_corroboration: None,
+            review_quality_cap: None,
+            distrust_policy: DistrustPolicy::Anyone,
+            trust_aggregation: EffectiveTrustAggregation::Max,
+            supersession: SupersessionParams::default(),
         }
     }
 }
@@ -0,0 +1,271 @@
+//! A reference score of a package version, with an explanation of it
+//!
+//! Tools deciding whether a package can be used tend to sum up the reviews
+//! and issues into a single number. `ProofDB::score_package` is one way of
+//! doing it, tunable with `ScoreWeights`, that keeps track of what every part
+//! of the score comes from.
+use crate::{FlagPolicy, FlagRule, ProofDB, TrustSet};
+use chrono::Duration;
+use crev_data::{
+    proof::{self, review::Rating, trust::TrustLevel, CommonOps},
+    Id, Level,
+};
+use std::collections::BTreeSet;
+
+/// How much every part of a score counts, see `ProofDB::score_package`
+///
+/// All the weights are meant to be non-negative; the ones of the
+/// negative parts (negative reviews, issues, flags) are subtracted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoreWeights {
+    /// Reviewers with a lower effective trust level are not taken into account
+    pub min_level: TrustLevel,
+    /// Per positive review, times the trust level of the reviewer (1 to 3),
+    /// and twice as much for `Strong` ones
+    pub positive_review: f64,
+    /// Per positive review, times the sum of its thoroughness and understanding (0 to 6)
+    pub thoroughness_understanding: f64,
+    /// Per negative review, times the trust level of the reviewer (1 to 3)
+    pub negative_review: f64,
+    /// Per open issue, times its severity (0 to 3)
+    pub open_issue: f64,
+    /// If the package is flagged unmaintained according to the `flag_rule`
+    pub unmaintained: f64,
+    pub flag_rule: FlagRule,
+    /// For a positive review made today, decreasing linearly
+    /// to nothing for one `newest_review_horizon` old
+    pub newest_review: f64,
+    pub newest_review_horizon: Duration,
+}
+
+impl Default for ScoreWeights {
+    fn default() -> Self {
+        Self {
+            min_level: TrustLevel::Low,
+            positive_review: 10.0,
+            thoroughness_understanding: 1.0,
+            negative_review: 20.0,
+            open_issue: 10.0,
+            unmaintained: 10.0,
+            flag_rule: FlagRule::default(),
+            newest_review: 5.0,
+            newest_review_horizon: Duration::days(365),
+        }
+    }
+}
+
+/// A part of a `PackageScore`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ScoreComponent {
+    PositiveReviews,
+    ThoroughnessUnderstanding,
+    NegativeReviews,
+    OpenIssues,
+    Unmaintained,
+    NewestReview,
+}
+
+/// How much a `ScoreComponent` contributed to a `PackageScore`, and why
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoreContribution {
+    pub component: ScoreComponent,
+    /// Negative for the negative parts
+    pub contribution: f64,
+    /// Ids of the reviewers (or issue reporters) the contribution comes from, sorted
+    pub evidence: Vec<Id>,
+}
+
+/// Score of a package version, see `ProofDB::score_package`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackageScore {
+    /// Sum of all the `breakdown` contributions
+    pub score: f64,
+    /// One entry for every `ScoreComponent`, in their order
+    pub breakdown: Vec<ScoreContribution>,
+}
+
+impl PackageScore {
+    pub fn contribution(&self, component: ScoreComponent) -> Option<&ScoreContribution> {
+        self.breakdown
+            .iter()
+            .find(|contribution| contribution.component == component)
+    }
+}
+
+fn trust_level_factor(level: TrustLevel) -> f64 {
+    match level {
+        TrustLevel::Distrust | TrustLevel::None => 0.,
+        TrustLevel::Low => 1.,
+        TrustLevel::Medium => 2.,
+        TrustLevel::High => 3.,
+    }
+}
+
+fn level_factor(level: Level) -> f64 {
+    match level {
+        Level::None => 0.,
+        Level::Low => 1.,
+        Level::Medium => 2.,
+        Level::High => 3.,
+    }
+}
+
+/// Contribution being summed up, with its evidence deduplicated and sorted
+struct Accumulator {
+    component: ScoreComponent,
+    contribution: f64,
+    evidence: BTreeSet<Id>,
+}
+
+impl Accumulator {
+    fn new(component: ScoreComponent) -> Self {
+        Self {
+            component,
+            contribution: 0.,
+            evidence: BTreeSet::new(),
+        }
+    }
+
+    fn add(&mut self, contribution: f64, id: &Id) {
+        self.contribution += contribution;
+        self.evidence.insert(id.clone());
+    }
+
+    fn finish(self) -> ScoreContribution {
+        ScoreContribution {
+            component: self.component,
+            contribution: self.contribution,
+            evidence: self.evidence.into_iter().collect(),
+        }
+    }
+}
+
+impl ProofDB {
+    /// Score of `pkg` according to the reviews of Ids trusted in `trust_set`
+    ///
+    /// Only the reviews of exactly `pkg` count, but open issues and flags
+    /// are the ones `get_open_issues_for_version` and `get_effective_flags`
+    /// report. The age of the newest review is relative to `ProofDB::now`,
+    /// and only positive reviews count for it, so a fresh negative review
+    /// never improves the score.
+    ///
+    /// The score only depends on the arguments and the proofs in the db (and
+    /// the clock), and never goes down by adding a positive review by another
+    /// trusted Id, nor up by another open issue.
+    pub fn score_package(
+        &self,
+        pkg: &proof::PackageVersionId,
+        trust_set: &TrustSet,
+        weights: &ScoreWeights,
+    ) -> PackageScore {
+        let source = pkg.id.source.as_str();
+        let source_trust_set = trust_set.for_source(source);
+
+        let mut positive = Accumulator::new(ScoreComponent::PositiveReviews);
+        let mut thoroughness = Accumulator::new(ScoreComponent::ThoroughnessUnderstanding);
+        let mut negative = Accumulator::new(ScoreComponent::NegativeReviews);
+        let mut newest = Accumulator::new(ScoreComponent::NewestReview);
+        let mut newest_positive = None;
+
+        for review in self.get_pkg_reviews_for_version(source, &pkg.id.name, &pkg.version) {
+            let author = &review.from().id;
+            let level = source_trust_set.get_effective_trust_level(author);
+            if source_trust_set.is_distrusted(author) || level < weights.min_level {
+                continue;
+            }
+            let trust_factor = trust_level_factor(level);
+            let details = review.review_possibly_none();
+            match details.rating {
+                Rating::Positive | Rating::Strong => {
+                    let rating_factor = if details.rating == Rating::Strong {
+                        2.
+                    } else {
+                        1.
+                    };
+                    positive.add(
+                        weights.positive_review * trust_factor * rating_factor,
+                        author,
+                    );
+                    thoroughness.add(
+                        weights.thoroughness_understanding
+                            * (level_factor(details.thoroughness)
+                                + level_factor(details.understanding)),
+                        author,
+                    );
+                    let date = review.date_utc();
+                    let is_newer = match newest_positive {
+                        Some((newest_date, _)) => newest_date < date,
+                        None => true,
+                    };
+                    if is_newer {
+                        newest_positive = Some((date, author));
+                    }
+                }
+                Rating::Negative => negative.add(-weights.negative_review * trust_factor, author),
+                Rating::Neutral => {}
+            }
+        }
+
+        if let Some((date, author)) = newest_positive {
+            let horizon = weights.newest_review_horizon.num_seconds();
+            if horizon > 0 {
+                let age = (self.now() - date).num_seconds().max(0);
+                let freshness = 1. - (age as f64 / horizon as f64).min(1.);
+                newest.add(weights.newest_review * freshness, author);
+            }
+        }
+
+        let mut issues = Accumulator::new(ScoreComponent::OpenIssues);
+        // `BTreeMap`, so always summed up in the same order
+        for details in self
+            .get_open_issues_for_version(
+                source,
+                &pkg.id.name,
+                &pkg.version,
+                trust_set,
+                weights.min_level,
+            )
+            .values()
+        {
+            issues.contribution -= weights.open_issue * level_factor(details.severity);
+            issues
+                .evidence
+                .extend(details.reporting_ids.iter().cloned());
+        }
+
+        let mut unmaintained = Accumulator::new(ScoreComponent::Unmaintained);
+        let flags = self.get_effective_flags(
+            &pkg.id,
+            trust_set,
+            &FlagPolicy {
+                min_level: weights.min_level,
+                rule: weights.flag_rule,
+            },
+        );
+        if flags.unmaintained.is_set {
+            unmaintained.contribution = -weights.unmaintained;
+            unmaintained
+                .evidence
+                .extend(flags.unmaintained.set_by.iter().cloned());
+        }
+
+        let breakdown: Vec<_> = vec![
+            positive,
+            thoroughness,
+            negative,
+            issues,
+            unmaintained,
+            newest,
+        ]
+        .into_iter()
+        .map(Accumulator::finish)
+        .collect();
+        PackageScore {
+            score: breakdown
+                .iter()
+                .map(|contribution| contribution.contribution)
+                .sum(),
+            breakdown,
+        }
+    }
+}
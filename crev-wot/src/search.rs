@@ -0,0 +1,175 @@
+//! Searching the comments of package reviews
+//!
+//! Comments are searched in a lowercase copy of all of them, built when first
+//! needed and rebuilt only after proofs are added or removed, just like the
+//! derived alternatives data.
+use crate::{ProofDB, SharedSignature, TrustSet};
+use crev_data::proof::{self, review, trust::TrustLevel, CommonOps};
+use std::{ops::Range, sync};
+
+/// A review with a comment matching a query, see `ProofDB::search_review_comments`
+#[derive(Debug, Clone)]
+pub struct ReviewMatch<'a> {
+    pub review: &'a review::Package,
+    pub package: &'a proof::PackageVersionId,
+    /// Byte ranges of the `review.comment` matching the query, sorted and not overlapping
+    pub ranges: Vec<Range<usize>>,
+}
+
+/// Lowercase comments of the most recent package reviews, ordered by package, then author
+#[derive(Default)]
+pub(crate) struct CommentIndex {
+    derived_recalculation_counter: usize,
+    comments: Vec<(SharedSignature, String)>,
+}
+
+impl CommentIndex {
+    pub(crate) fn new() -> Self {
+        Default::default()
+    }
+
+    pub(crate) fn wipe(&mut self) {
+        *self = Self::new();
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.comments.len()
+    }
+
+    pub(crate) fn shrink_to_fit(&mut self) {
+        self.comments.shrink_to_fit();
+    }
+}
+
+/// Merge overlapping (or adjacent) ranges
+fn merge_ranges(mut ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
+    ranges.sort_by_key(|range| (range.start, range.end));
+    let mut merged: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// Ranges of all the occurrences of every token in `haystack`,
+/// or `None` if any of the tokens doesn't occur in it
+fn match_tokens(haystack: &str, tokens: &[String]) -> Option<Vec<Range<usize>>> {
+    let mut ranges = vec![];
+    for token in tokens {
+        let found = ranges.len();
+        ranges.extend(
+            haystack
+                .match_indices(token.as_str())
+                .map(|(start, matched)| start..start + matched.len()),
+        );
+        if ranges.len() == found {
+            return None;
+        }
+    }
+    Some(merge_ranges(ranges))
+}
+
+impl ProofDB {
+    fn get_comment_index(&self) -> sync::RwLockReadGuard<'_, CommentIndex> {
+        {
+            let read = self.derived_comment_index.read().expect("lock to work");
+
+            if read.derived_recalculation_counter == self.insertion_counter {
+                return read;
+            }
+        }
+
+        {
+            let mut write = self.derived_comment_index.write().expect("lock to work");
+
+            write.wipe();
+
+            let mut comments: Vec<_> = self
+                .package_review_signatures_by_pkg_review_id
+                .iter()
+                .filter_map(|(pkg_review_id, signature)| {
+                    let review = &self.package_review_by_signature[&signature.value];
+                    if review.comment.is_empty() {
+                        None
+                    } else {
+                        Some((
+                            pkg_review_id,
+                            signature.value.clone(),
+                            review.comment.to_ascii_lowercase(),
+                        ))
+                    }
+                })
+                .collect();
+            comments.sort_by(|a, b| {
+                (&a.0.package_version_id, &a.0.from).cmp(&(&b.0.package_version_id, &b.0.from))
+            });
+            write.comments = comments
+                .into_iter()
+                .map(|(_, signature, comment)| (signature, comment))
+                .collect();
+
+            write.derived_recalculation_counter = self.insertion_counter;
+        }
+
+        self.derived_comment_index.read().expect("lock to work")
+    }
+
+    /// Most recent reviews with comments containing all the words of the `query`
+    ///
+    /// Matching ignores (ASCII) case, and every occurrence of every word is
+    /// reported, so it can be highlighted. Retracted and expired reviews are never
+    /// returned; with a `source`, only reviews of packages from it are. With a
+    /// `trust_set`, only reviews by Ids with at least `min_level` of effective
+    /// trust (for the package's source) are. Ordered by package, then author.
+    pub fn search_review_comments<'a>(
+        &'a self,
+        query: &str,
+        source: Option<&str>,
+        trust_set: Option<&TrustSet>,
+        min_level: TrustLevel,
+    ) -> Vec<ReviewMatch<'a>> {
+        let tokens: Vec<String> = query
+            .split_whitespace()
+            .map(str::to_ascii_lowercase)
+            .collect();
+        if tokens.is_empty() {
+            return vec![];
+        }
+
+        let index = self.get_comment_index();
+        let mut matches = vec![];
+        for (signature, comment) in &index.comments {
+            let review = &**self
+                .package_review_by_signature
+                .get(signature)
+                .expect("exists");
+            let package = &review.package.id;
+            if matches!(source, Some(source) if source != package.id.source) {
+                continue;
+            }
+            if let Some(trust_set) = trust_set {
+                let trust_set = trust_set.for_source(&package.id.source);
+                let id = &review.from().id;
+                if trust_set.is_distrusted(id)
+                    || trust_set.get_effective_trust_level(id) < min_level
+                {
+                    continue;
+                }
+            }
+            if !self.is_in_effect(review) {
+                continue;
+            }
+            if let Some(ranges) = match_tokens(comment, &tokens) {
+                matches.push(ReviewMatch {
+                    review,
+                    package,
+                    ranges,
+                });
+            }
+        }
+        matches
+    }
+}
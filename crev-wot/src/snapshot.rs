@@ -0,0 +1,169 @@
+//! Storing the whole `ProofDB`, to avoid importing all the proofs again
+//!
+//! Importing verifies every proof, which dominates the time of loading
+//! a large number of them. A snapshot contains the already verified and
+//! indexed data, and a digest of the proofs it was made of, so that it can
+//! be discarded once the proofs change.
+use crate::{Error, FetchSource, ProofDB, Result};
+use crev_data::proof::{self, CommonOps};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeSet, io};
+
+/// Version of the snapshot format; snapshots of other versions can't be read
+pub const SNAPSHOT_VERSION: u32 = 15;
+
+/// Beginning of every snapshot, see `ProofDB::read_snapshot_header`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotHeader {
+    pub version: u32,
+    /// Digest of the proofs the snapshot was made of, see `proof_set_digest`
+    pub proof_set_digest: Vec<u8>,
+}
+
+/// Digest identifying a set of proofs by their signatures
+///
+/// Order and duplicates don't matter. A snapshot is up to date, if its
+/// `SnapshotHeader::proof_set_digest` is the same as a digest of the proofs
+/// that would be imported otherwise. Only proofs that were successfully imported
+/// count, so a snapshot of a set with invalid proofs always looks stale.
+pub fn proof_set_digest<'a>(signatures: impl IntoIterator<Item = &'a str>) -> Vec<u8> {
+    let signatures: BTreeSet<_> = signatures.into_iter().collect();
+    let mut input = String::new();
+    for signature in signatures {
+        input.push_str(signature);
+        input.push('\n');
+    }
+    crev_common::blake2b256sum(input.as_bytes())
+}
+
+impl ProofDB {
+    /// `proof_set_digest` of all the proofs imported so far
+    pub fn proof_set_digest(&self) -> Vec<u8> {
+        proof_set_digest(self.fetched_from_by_signature.keys().map(String::as_str))
+    }
+
+    /// Write all the data, to be restored with `ProofDB::deserialize_snapshot`
+    ///
+    /// Lazily calculated data (like `calculate_trust_set_cached` results) is not included.
+    pub fn serialize_snapshot(&self, w: impl io::Write) -> Result<()> {
+        let mut serializer = serde_cbor::Serializer::new(serde_cbor::ser::IoWrite::new(w));
+        SnapshotHeader {
+            version: SNAPSHOT_VERSION,
+            proof_set_digest: self.proof_set_digest(),
+        }
+        .serialize(&mut serializer)?;
+        self.serialize(&mut serializer)?;
+        Ok(())
+    }
+
+    /// Read just the header of a snapshot, to check if it's worth reading whole
+    pub fn read_snapshot_header(r: impl io::Read) -> Result<SnapshotHeader> {
+        let mut deserializer = serde_cbor::Deserializer::from_reader(r);
+        Ok(SnapshotHeader::deserialize(&mut deserializer)?)
+    }
+
+    /// Restore a `ProofDB` written by `ProofDB::serialize_snapshot`
+    pub fn deserialize_snapshot(r: impl io::Read) -> Result<ProofDB> {
+        let mut deserializer = serde_cbor::Deserializer::from_reader(r);
+        let header = SnapshotHeader::deserialize(&mut deserializer)?;
+        if header.version != SNAPSHOT_VERSION {
+            return Err(Error::UnsupportedSnapshotVersion(header.version));
+        }
+        let mut db = ProofDB::deserialize(&mut deserializer)?;
+        deserializer.end()?;
+        db.share_pkg_version_review_ids();
+        Ok(db)
+    }
+}
+
+/// `proof::Proof` is stored as its parts, and parsed again when restored
+pub(crate) mod unknown_proofs {
+    use super::*;
+    use serde::{de::Error as _, Deserializer, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct StoredProof {
+        body: String,
+        signature: String,
+        /// Needed for legacy proofs, which don't have it in the body
+        kind: String,
+        fetched_from: FetchSource,
+    }
+
+    pub fn serialize<S: Serializer>(
+        proofs: &[(proof::Proof, FetchSource)],
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_seq(proofs.iter().map(|(proof, fetched_from)| StoredProof {
+            body: proof.body().to_owned(),
+            signature: proof.signature().to_owned(),
+            kind: proof.kind().to_owned(),
+            fetched_from: fetched_from.clone(),
+        }))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Vec<(proof::Proof, FetchSource)>, D::Error> {
+        Vec::<StoredProof>::deserialize(deserializer)?
+            .into_iter()
+            .map(|stored| {
+                let StoredProof {
+                    body,
+                    signature,
+                    kind,
+                    fetched_from,
+                } = stored;
+                proof::Proof::from_parts(body.clone(), signature.clone())
+                    .or_else(|_| proof::Proof::from_legacy_parts(body, signature, kind))
+                    .map(|proof| (proof, fetched_from))
+                    .map_err(D::Error::custom)
+            })
+            .collect()
+    }
+}
+
+/// Like `unknown_proofs`, but for proofs kept by signature, see `ProofDB::set_keep_signed_proofs`
+pub(crate) mod signed_proofs {
+    use super::*;
+    use crate::Signature;
+    use serde::{de::Error as _, Deserializer, Serializer};
+    use std::collections::HashMap;
+
+    #[derive(Serialize, Deserialize)]
+    struct StoredProof {
+        body: String,
+        signature: String,
+        kind: String,
+    }
+
+    pub fn serialize<S: Serializer>(
+        proofs: &HashMap<Signature, proof::Proof>,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_seq(proofs.values().map(|proof| StoredProof {
+            body: proof.body().to_owned(),
+            signature: proof.signature().to_owned(),
+            kind: proof.kind().to_owned(),
+        }))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<HashMap<Signature, proof::Proof>, D::Error> {
+        Vec::<StoredProof>::deserialize(deserializer)?
+            .into_iter()
+            .map(|stored| {
+                let StoredProof {
+                    body,
+                    signature,
+                    kind,
+                } = stored;
+                proof::Proof::from_parts(body.clone(), signature.clone())
+                    .or_else(|_| proof::Proof::from_legacy_parts(body, signature, kind))
+                    .map(|proof| (proof.signature().to_owned(), proof))
+                    .map_err(D::Error::custom)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,337 @@
+//! Building `ProofDB`s in tests, without signing any proofs
+//!
+//! Proofs added with `ProofDBBuilder` go through the same code paths as
+//! imported ones, except for the signature verification, so they can use
+//! fabricated Ids, dates and signatures. Available with the `testing` feature.
+use crate::{FetchSource, FetchSourceInfo, ProofDB, ProofKind};
+use chrono::{offset::Utc, DateTime, Duration, FixedOffset, TimeZone};
+use crev_data::{
+    proof::{self, review, trust::TrustLevel, CommonOps},
+    Id, PublicId, Url,
+};
+use std::{cmp, collections::BTreeSet};
+
+/// A public Id (with URL `https://id-<n>.test`), that nobody has the secret key of
+///
+/// The same `n` always gives the same Id.
+pub fn fabricated_id(n: u64) -> PublicId {
+    let mut bytes = vec![0; 32];
+    bytes[..8].copy_from_slice(&n.to_be_bytes());
+    PublicId::new(
+        Id::new_crev(bytes).expect("32 bytes"),
+        Url::new_git(format!("https://id-{}.test", n)),
+    )
+}
+
+/// Midnight of 2020-01-01, plus `days`
+///
+/// Unlike `crev_common::now()`, it makes the tests reproducible.
+pub fn fabricated_date(days: i64) -> DateTime<Utc> {
+    Utc.ymd(2020, 1, 1).and_hms(0, 0, 0) + Duration::days(days)
+}
+
+fn common(kind: &str, from: &PublicId, date: DateTime<Utc>) -> proof::Common {
+    proof::Common {
+        kind: Some(kind.into()),
+        version: -1,
+        date: date.with_timezone(&FixedOffset::east(0)),
+        from: from.clone(),
+    }
+}
+
+/// A package review of `source`/`name`/`version`, by `from`, to be completed and built
+///
+/// Digest of the package is derived from its name and version.
+pub fn package_review_builder(
+    from: &PublicId,
+    source: &str,
+    name: &str,
+    version: &str,
+    date: DateTime<Utc>,
+) -> review::PackageBuilder {
+    let mut builder = review::PackageBuilder::default();
+    builder
+        .common(common(proof::PackageReview::KIND, from, date))
+        .package(proof::PackageInfo {
+            id: proof::PackageVersionId::new(
+                source.into(),
+                name.into(),
+                semver::Version::parse(version).expect("valid version"),
+            ),
+            digest: crev_common::blake2b256sum(
+                format!("{}/{}/{}", source, name, version).as_bytes(),
+            ),
+            digest_type: proof::default_digest_type(),
+            revision: "".into(),
+            revision_type: proof::default_revision_type(),
+        });
+    builder
+}
+
+/// Builds a `ProofDB` from proofs that don't need to be signed
+///
+/// Each proof gets a unique fabricated signature, unless set with `signature`.
+pub struct ProofDBBuilder {
+    db: ProofDB,
+    fetched_from: FetchSource,
+    signature: Option<String>,
+    signature_counter: u64,
+}
+
+impl Default for ProofDBBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProofDBBuilder {
+    pub fn new() -> Self {
+        Self::from_db(ProofDB::new())
+    }
+
+    /// Add proofs to an existing `db` (e.g. one with non-default settings)
+    pub fn from_db(db: ProofDB) -> Self {
+        Self {
+            db,
+            fetched_from: FetchSource::LocalUser,
+            signature: None,
+            signature_counter: 0,
+        }
+    }
+
+    /// Where the proofs added from now on were fetched from (`LocalUser` by default)
+    pub fn fetched_from(&mut self, fetched_from: FetchSource) -> &mut Self {
+        self.fetched_from = fetched_from;
+        self
+    }
+
+    /// Signature of the next proof added, instead of a fabricated one
+    ///
+    /// Useful to test conflicting proofs with the same signature.
+    pub fn signature(&mut self, signature: &str) -> &mut Self {
+        self.signature = Some(signature.to_owned());
+        self
+    }
+
+    fn next_signature(&mut self) -> String {
+        self.signature.take().unwrap_or_else(|| {
+            self.signature_counter += 1;
+            format!("TEST-SIGNATURE-{}", self.signature_counter)
+        })
+    }
+
+    fn record_fetch_source(&mut self, signature: &str, kind: ProofKind, common: &proof::Common) {
+        let info = FetchSourceInfo::new(
+            self.fetched_from.clone(),
+            common.date.with_timezone(&Utc),
+            kind,
+            common.from.id.clone(),
+        );
+        self.db.record_fetch_source(signature, info);
+    }
+
+    /// `from` reports trust `level` for `to`
+    pub fn trust(
+        &mut self,
+        from: &PublicId,
+        to: &PublicId,
+        level: TrustLevel,
+        date: DateTime<Utc>,
+    ) -> &mut Self {
+        let trust = proof::Trust {
+            common: common(proof::Trust::KIND, from, date),
+            ids: vec![to.clone()],
+            trust: level,
+            comment: String::new(),
+            sources: vec![],
+            expires: None,
+        };
+        self.trust_proof(&trust)
+    }
+
+    /// Add a complete trust proof (e.g. for many Ids, or limited to some sources)
+    pub fn trust_proof(&mut self, trust: &proof::Trust) -> &mut Self {
+        let signature = self.next_signature();
+        self.db
+            .add_trust(trust, &signature, self.fetched_from.clone());
+        self.record_fetch_source(&signature, ProofKind::Trust, trust.common());
+        self
+    }
+
+    /// Add a package review, see `package_review_builder`
+    pub fn package_review(&mut self, review: &review::Package) -> &mut Self {
+        let signature = self.next_signature();
+        self.db
+            .add_package_review(review, &signature, self.fetched_from.clone());
+        self.record_fetch_source(&signature, ProofKind::PackageReview, review.common());
+        self
+    }
+
+    pub fn code_review(&mut self, review: &review::Code) -> &mut Self {
+        let signature = self.next_signature();
+        self.db
+            .add_code_review(review, &signature, self.fetched_from.clone());
+        self.record_fetch_source(&signature, ProofKind::CodeReview, review.common());
+        self
+    }
+
+    /// `old` declares it's been superseded by `new`
+    pub fn id_supersession(
+        &mut self,
+        old: &PublicId,
+        new: &PublicId,
+        date: DateTime<Utc>,
+    ) -> &mut Self {
+        let supersession = proof::IdSupersession {
+            common: common(proof::IdSupersession::KIND, old, date),
+            superseded_by: new.clone(),
+            comment: String::new(),
+        };
+        let signature = self.next_signature();
+        self.db
+            .add_id_supersession(&supersession, self.fetched_from.clone());
+        self.record_fetch_source(&signature, ProofKind::Other, supersession.common());
+        self
+    }
+
+    /// `id` declares its URL, as it would in the `from` field of any of its proofs
+    ///
+    /// The URL is verified if it's the same as the one set with `fetched_from`.
+    pub fn url(&mut self, id: &PublicId, date: DateTime<Utc>) -> &mut Self {
+        self.db
+            .record_url_from_from_field(&date, id, &self.fetched_from);
+        self
+    }
+
+    pub fn build(self) -> ProofDB {
+        self.db
+    }
+}
+
+/// Shape of the WoT generated by `synthetic_wot`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntheticWotParams {
+    /// Number of Ids, including the root
+    pub id_count: u64,
+    /// Number of distinct other Ids every Id reports trust for
+    pub trust_per_id: usize,
+    /// Number of distinct packages, each with a few versions
+    pub package_count: u64,
+    /// Number of distinct package versions every Id reviewed
+    pub reviews_per_id: usize,
+    /// Different seeds give different WoTs of the same shape
+    pub seed: u64,
+}
+
+impl Default for SyntheticWotParams {
+    fn default() -> Self {
+        Self {
+            id_count: 100,
+            trust_per_id: 5,
+            package_count: 50,
+            reviews_per_id: 10,
+            seed: 0,
+        }
+    }
+}
+
+/// WoT generated by `synthetic_wot`
+pub struct SyntheticWot {
+    pub db: ProofDB,
+    /// `ids[0]`
+    pub root: Id,
+    pub ids: Vec<PublicId>,
+}
+
+/// Versions `1.0.0`, `1.1.0`, ... of every package in a synthetic WoT
+const VERSIONS_PER_PACKAGE: u64 = 3;
+
+/// xorshift64*, good enough for generating test data
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        // splitmix64, so that similar seeds give very different states
+        let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        // the state must not be zero
+        Rng(cmp::max(z, 1))
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+}
+
+/// A reproducible WoT of fabricated Ids trusting each other and reviewing packages
+///
+/// Every Id trusts random others (mostly positively, sometimes distrusting them),
+/// and reviews random versions of packages named `pkg-<n>` from `https://crates.io`.
+/// The same `params` always give the same proofs.
+pub fn synthetic_wot(params: &SyntheticWotParams) -> SyntheticWot {
+    let mut rng = Rng::new(params.seed);
+    let ids: Vec<PublicId> = (0..params.id_count).map(fabricated_id).collect();
+    let mut builder = ProofDBBuilder::new();
+
+    let trust_per_id = cmp::min(params.trust_per_id, ids.len().saturating_sub(1));
+    for (i, from) in ids.iter().enumerate() {
+        let mut trusted = BTreeSet::new();
+        while trusted.len() < trust_per_id {
+            let to = rng.below(params.id_count) as usize;
+            if to == i || !trusted.insert(to) {
+                continue;
+            }
+            let level = match rng.below(20) {
+                0 => TrustLevel::Distrust,
+                1..=7 => TrustLevel::Low,
+                8..=15 => TrustLevel::Medium,
+                _ => TrustLevel::High,
+            };
+            let date = fabricated_date(rng.below(365) as i64);
+            builder.trust(from, &ids[to], level, date);
+        }
+    }
+
+    let reviews_per_id = cmp::min(
+        params.reviews_per_id,
+        params.package_count as usize * VERSIONS_PER_PACKAGE as usize,
+    );
+    for from in &ids {
+        let mut reviewed = BTreeSet::new();
+        while reviewed.len() < reviews_per_id {
+            let package = rng.below(params.package_count);
+            let minor = rng.below(VERSIONS_PER_PACKAGE);
+            if !reviewed.insert((package, minor)) {
+                continue;
+            }
+            let name = format!("pkg-{}", package);
+            let version = format!("1.{}.0", minor);
+            let date = fabricated_date(rng.below(365) as i64);
+            let review = match rng.below(10) {
+                0 => review::Review::new_negative(),
+                1..=2 => review::Review::new_none(),
+                _ => review::Review::new_positive(),
+            };
+            let review = package_review_builder(from, "https://crates.io", &name, &version, date)
+                .review(review)
+                .build()
+                .expect("complete review");
+            builder.package_review(&review);
+        }
+    }
+
+    SyntheticWot {
+        db: builder.build(),
+        root: fabricated_id(0).id,
+        ids,
+    }
+}
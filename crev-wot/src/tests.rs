@@ -0,0 +1,425 @@
+use super::*;
+
+use chrono::Duration;
+
+fn timestamped(days: i64, value: &str) -> Timestamped<String> {
+    let date = Utc::now() + Duration::days(days);
+    (&date, value.to_owned()).into()
+}
+
+#[test]
+fn update_to_more_recent_overwrites_on_equal_date() {
+    let old = timestamped(0, "old");
+    let mut current = old.clone();
+    let same_date = Timestamped {
+        date: old.date,
+        value: "new".to_owned(),
+    };
+
+    assert!(current.update_to_more_recent(&same_date));
+    assert_eq!(current, same_date);
+
+    // and back again
+    assert!(current.update_to_more_recent(&old));
+    assert_eq!(current, old);
+}
+
+#[test]
+fn update_to_more_recent_keeps_newer() {
+    let older = timestamped(-1, "older");
+    let newer = timestamped(1, "newer");
+
+    let mut current = newer.clone();
+    assert!(!current.update_to_more_recent(&older));
+    assert_eq!(current, newer);
+
+    let mut current = older;
+    assert!(current.update_to_more_recent(&newer));
+    assert_eq!(current.value(), "newer");
+}
+
+#[test]
+fn timestamped_accessors() {
+    let t = timestamped(0, "value");
+    assert_eq!(t.as_ref().value(), &"value");
+    assert_eq!(t.as_ref().date, t.date);
+
+    let mapped = t.clone().map(|v| v.len());
+    assert_eq!(mapped.date, t.date);
+    assert_eq!(*mapped.value(), 5);
+
+    assert_eq!(t.into_value(), "value");
+}
+
+fn pkg_version_review_id(source: &str, name: &str, version: &str) -> PkgVersionReviewId {
+    PkgVersionReviewId::new(
+        Id::new_crev(vec![7; 32]).unwrap(),
+        proof::PackageVersionId::new(source.into(), name.into(), Version::parse(version).unwrap()),
+    )
+}
+
+#[test]
+fn pkg_review_id_display_from_str_roundtrip() {
+    for id in &[
+        pkg_version_review_id("https://crates.io", "serde", "1.0.0"),
+        pkg_version_review_id("https://example.com/a/b/", "name", "0.1.0-alpha.1+build"),
+        pkg_version_review_id("local", "x", "2.0.0"),
+    ] {
+        let s = id.to_string();
+        assert_eq!(&s.parse::<PkgVersionReviewId>().unwrap(), id, "{}", s);
+
+        let pkg_review_id = id.to_pkg_review_id();
+        assert_eq!(pkg_review_id.from(), id.from());
+        assert_eq!(pkg_review_id.package_id(), &id.package_version_id().id);
+        let s = pkg_review_id.to_string();
+        assert_eq!(s.parse::<PkgReviewId>().unwrap(), pkg_review_id, "{}", s);
+    }
+
+    let id = pkg_version_review_id("https://crates.io", "serde", "1.0.0");
+    assert_eq!(
+        id.to_string(),
+        format!("{}/https://crates.io/serde/1.0.0", id.from())
+    );
+}
+
+#[test]
+fn pkg_review_id_from_str_errors() {
+    let id = Id::new_crev(vec![7; 32]).unwrap();
+    for s in &[
+        "".to_owned(),
+        "no-slashes".to_owned(),
+        format!("{}/source-only", id),
+        format!("{}//name", id),
+        format!("{}/https://crates.io/serde/not-a-version", id),
+        "not-an-id/https://crates.io/serde/1.0.0".to_owned(),
+    ] {
+        assert!(s.parse::<PkgVersionReviewId>().is_err(), "{}", s);
+    }
+}
+
+fn review_of_version(author: &crev_data::PublicId, version: &str) -> review::Package {
+    testing::package_review_builder(
+        author,
+        "SOURCE_ID",
+        "foo",
+        version,
+        testing::fabricated_date(0),
+    )
+    .build()
+    .unwrap()
+}
+
+/// Author with a URL, and the repo at that URL
+fn author_and_own_repo() -> (crev_data::PublicId, FetchSource) {
+    let author = crev_data::PublicId::new(testing::fabricated_id(1).id, Url::new_git("https://a"));
+    let own_repo = FetchSource::Url(sync::Arc::new(Url::new_git("https://a")));
+    (author, own_repo)
+}
+
+fn reviewed_versions(db: &ProofDB) -> Vec<String> {
+    db.get_pkg_reviews_for_name("SOURCE_ID", "foo")
+        .map(|review| review.package.id.version.to_string())
+        .collect()
+}
+
+#[test]
+fn conflicting_signature_prefers_authors_own_repo() {
+    let (author, own_repo) = author_and_own_repo();
+    let genuine = review_of_version(&author, "1.0.0");
+    let forged = review_of_version(&author, "2.0.0");
+    let mirror = FetchSource::Url(sync::Arc::new(Url::new_git("https://mirror")));
+
+    for genuine_first in &[true, false] {
+        let mut builder = testing::ProofDBBuilder::new();
+        let mut add = |review: &review::Package, fetched_from: &FetchSource| {
+            builder
+                .fetched_from(fetched_from.clone())
+                .signature("SIGNATURE")
+                .package_review(review);
+        };
+        if *genuine_first {
+            add(&genuine, &own_repo);
+            add(&forged, &mirror);
+        } else {
+            add(&forged, &mirror);
+            add(&genuine, &own_repo);
+        }
+        let db = builder.build();
+
+        assert_eq!(reviewed_versions(&db), vec!["1.0.0"]);
+        assert_eq!(
+            db.get_latest_review_per_author("SOURCE_ID", "foo", LatestBy::Version)
+                .map(|review| review.package.id.version.to_string())
+                .collect::<Vec<_>>(),
+            vec!["1.0.0"]
+        );
+        assert_eq!(
+            db.get_package_reviews_by_digest(&Digest::from_vec(genuine.package.digest.clone()))
+                .count(),
+            1
+        );
+        assert_eq!(db.proof_count_by_fetch_source()["https://a"].proof_count, 1);
+        assert_eq!(db.integrity_warnings().len(), 1);
+        match &db.integrity_warnings()[0] {
+            IntegrityWarning::ConflictingSignature {
+                signature,
+                kept_from,
+                discarded_from,
+            } => {
+                assert_eq!(signature, "SIGNATURE");
+                assert_eq!(kept_from.name(), "https://a");
+                assert_eq!(discarded_from.name(), "https://mirror");
+            }
+        }
+    }
+}
+
+#[test]
+fn conflicting_signature_keeps_first_without_preference() {
+    let (author, _) = author_and_own_repo();
+    let mirror = FetchSource::Url(sync::Arc::new(Url::new_git("https://mirror")));
+    let other_mirror = FetchSource::Url(sync::Arc::new(Url::new_git("https://other")));
+
+    for (first, second) in &[("1.0.0", "2.0.0"), ("2.0.0", "1.0.0")] {
+        let mut builder = testing::ProofDBBuilder::new();
+        builder
+            .fetched_from(mirror.clone())
+            .signature("SIGNATURE")
+            .package_review(&review_of_version(&author, first))
+            .fetched_from(other_mirror.clone())
+            .signature("SIGNATURE")
+            .package_review(&review_of_version(&author, second));
+        let db = builder.build();
+
+        assert_eq!(reviewed_versions(&db), vec![*first]);
+        assert_eq!(db.integrity_warnings().len(), 1);
+    }
+}
+
+#[test]
+fn conflicting_signature_merge_matches_import() {
+    let (author, own_repo) = author_and_own_repo();
+    let genuine = (review_of_version(&author, "1.0.0"), own_repo);
+    let forged = (
+        review_of_version(&author, "2.0.0"),
+        FetchSource::Url(sync::Arc::new(Url::new_git("https://mirror"))),
+    );
+    let db_of = |reviews: &[&(review::Package, FetchSource)]| {
+        let mut builder = testing::ProofDBBuilder::new();
+        for (review, fetched_from) in reviews {
+            builder
+                .fetched_from(fetched_from.clone())
+                .signature("SIGNATURE")
+                .package_review(review);
+        }
+        builder.build()
+    };
+
+    for (first, second) in &[(&genuine, &forged), (&forged, &genuine)] {
+        let imported = db_of(&[first, second]);
+        let mut merged = db_of(&[first]);
+        merged.merge(db_of(&[second]));
+
+        assert_eq!(reviewed_versions(&merged), vec!["1.0.0"]);
+        assert_eq!(reviewed_versions(&merged), reviewed_versions(&imported));
+        assert_eq!(
+            merged.proof_count_by_fetch_source()["https://a"].proof_count,
+            1
+        );
+        assert_eq!(merged.integrity_warnings().len(), 1);
+        assert_eq!(
+            merged
+                .get_fetch_source_of("SIGNATURE")
+                .map(FetchSource::name),
+            Some("https://a")
+        );
+    }
+}
+
+#[test]
+fn conflicting_signature_restores_superseded_review() {
+    let (author, own_repo) = author_and_own_repo();
+    let mirror = FetchSource::Url(sync::Arc::new(Url::new_git("https://mirror")));
+    let review_on = |version: &str, days| {
+        testing::package_review_builder(
+            &author,
+            "SOURCE_ID",
+            "foo",
+            version,
+            testing::fabricated_date(days),
+        )
+    };
+    let bar = proof::PackageId {
+        source: "SOURCE_ID".into(),
+        name: "bar".into(),
+    };
+    let earlier = review_on("1.0.0", 5)
+        .flags(proof::Flags { unmaintained: true })
+        .alternatives(vec![bar.clone()].into_iter().collect())
+        .build()
+        .unwrap();
+    // forged to supersede the earlier one, but the genuine one is older
+    let forged = review_on("1.0.0", 10).build().unwrap();
+    let genuine = review_on("0.9.0", 1).build().unwrap();
+
+    let db_of = |reviews: &[(&review::Package, &FetchSource, Option<&str>)]| {
+        let mut builder = testing::ProofDBBuilder::new();
+        for (review, fetched_from, signature) in reviews {
+            builder.fetched_from((*fetched_from).clone());
+            if let Some(signature) = signature {
+                builder.signature(signature);
+            }
+            builder.package_review(review);
+        }
+        builder.build()
+    };
+    let imported = db_of(&[
+        (&earlier, &own_repo, None),
+        (&forged, &mirror, Some("SIGNATURE")),
+        (&genuine, &own_repo, Some("SIGNATURE")),
+    ]);
+    let mut merged = db_of(&[
+        (&earlier, &own_repo, None),
+        (&forged, &mirror, Some("SIGNATURE")),
+    ]);
+    merged.merge(db_of(&[(&genuine, &own_repo, Some("SIGNATURE"))]));
+
+    let foo = proof::PackageId {
+        source: "SOURCE_ID".into(),
+        name: "foo".into(),
+    };
+    for db in &[imported, merged] {
+        assert_eq!(db.integrity_warnings().len(), 1);
+        assert_eq!(
+            db.get_pkg_reviews_for_version("SOURCE_ID", "foo", &Version::new(1, 0, 0))
+                .map(|review| review.date_utc())
+                .collect::<Vec<_>>(),
+            vec![testing::fabricated_date(5)]
+        );
+        assert_eq!(
+            db.get_pkg_flags_by_author(&author.id, &foo),
+            Some(&proof::Flags { unmaintained: true })
+        );
+        assert_eq!(
+            db.get_pkg_alternatives_by_author(&author.id, &foo),
+            vec![bar.clone()].into_iter().collect()
+        );
+    }
+}
+
+#[test]
+fn conflicting_signature_without_fetch_source() {
+    let (author, own_repo) = author_and_own_repo();
+    let mut db = ProofDB::new();
+    // added without recording where it came from, which imports never do
+    db.add_package_review(
+        &review_of_version(&author, "2.0.0"),
+        "SIGNATURE",
+        FetchSource::LocalUser,
+    );
+    db.add_package_review(&review_of_version(&author, "1.0.0"), "SIGNATURE", own_repo);
+
+    assert_eq!(reviewed_versions(&db), vec!["2.0.0"]);
+    assert_eq!(db.integrity_warnings().len(), 1);
+}
+
+#[test]
+fn synthetic_wot_is_reproducible() {
+    let params = testing::SyntheticWotParams {
+        id_count: 30,
+        ..default()
+    };
+    let trust_set_of = |wot: &testing::SyntheticWot| {
+        wot.db
+            .calculate_trust_set(&wot.root, &TrustDistanceParams::default())
+            .to_sorted()
+    };
+
+    let wot = testing::synthetic_wot(&params);
+    let same = testing::synthetic_wot(&params);
+    assert_eq!(wot.ids.len(), 30);
+    assert_eq!(wot.db.unique_trust_proof_count(), 30 * 5);
+    assert_eq!(wot.db.unique_package_review_proof_count(), 30 * 10);
+    assert!(
+        wot.db
+            .calculate_trust_set(&wot.root, &default())
+            .trusted_ids()
+            .count()
+            > 1
+    );
+    assert_eq!(trust_set_of(&wot), trust_set_of(&same));
+
+    let other = testing::synthetic_wot(&testing::SyntheticWotParams { seed: 1, ..params });
+    assert_ne!(trust_set_of(&wot), trust_set_of(&other));
+}
+
+#[test]
+fn builder_records_urls_and_fetch_sources() {
+    let a = testing::fabricated_id(1);
+    let b = testing::fabricated_id(2);
+    let a_repo = FetchSource::Url(sync::Arc::new(a.url.clone().unwrap()));
+
+    let mut builder = testing::ProofDBBuilder::new();
+    builder
+        .fetched_from(a_repo)
+        .trust(&a, &b, TrustLevel::High, testing::fabricated_date(0))
+        .fetched_from(FetchSource::LocalUser)
+        .url(&b, testing::fabricated_date(1));
+    let db = builder.build();
+
+    assert_eq!(db.unique_trust_proof_count(), 1);
+    assert_eq!(
+        db.lookup_url(&a.id).verified(),
+        Some(a.url.as_ref().unwrap())
+    );
+    assert_eq!(db.lookup_url(&b.id).from_self(), b.url.as_ref());
+    assert_eq!(
+        db.proof_count_by_fetch_source()["https://id-1.test"].proof_count,
+        1
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn restored_snapshot_shares_pkg_version_review_ids() {
+    let author = testing::fabricated_id(1);
+    let mut builder = testing::ProofDBBuilder::new();
+    builder
+        .package_review(&review_of_version(&author, "1.0.0"))
+        .package_review(&review_of_version(&author, "2.0.0"));
+    let mut snapshot = vec![];
+    builder.build().serialize_snapshot(&mut snapshot).unwrap();
+
+    let mut db = ProofDB::deserialize_snapshot(&snapshot[..]).unwrap();
+    let shared_id = |db: &ProofDB| db.interner.ids.iter().next().cloned().unwrap();
+    let author_id = shared_id(&db);
+    assert_eq!(db.interner.ids.len(), 1);
+    assert_eq!(db.interner.package_version_ids.len(), 2);
+    let by_author = &db.package_reviews_by_author[&author.id];
+    assert!(by_author
+        .iter()
+        .chain(db.package_review_signatures_by_pkg_review_id.keys())
+        .chain(db.package_review_history.keys())
+        .all(|id| sync::Arc::ptr_eq(&id.from, &author_id)));
+    for id in by_author {
+        let by_signature = db
+            .package_review_signatures_by_pkg_review_id
+            .get_key_value(id)
+            .unwrap()
+            .0;
+        assert!(sync::Arc::ptr_eq(
+            &id.package_version_id,
+            &by_signature.package_version_id
+        ));
+    }
+
+    // reviews added later share them too
+    db.add_package_review(
+        &review_of_version(&author, "3.0.0"),
+        "new signature",
+        FetchSource::LocalUser,
+    );
+    assert!(sync::Arc::ptr_eq(&shared_id(&db), &author_id));
+    assert_eq!(db.interner.ids.len(), 1);
+    assert_eq!(db.interner.package_version_ids.len(), 3);
+}
@@ -0,0 +1,167 @@
+//! History of an issue of a package, across its versions
+//!
+//! `ProofDB::get_open_issues_for_version` answers whether an issue is open
+//! in a given version. This tells the whole story instead: who reported it,
+//! in which versions, and which advisories fixed it.
+use crate::{ProofDB, TrustSet};
+use chrono::{offset::Utc, DateTime};
+use crev_data::{
+    proof::{review::VersionRange, CommonOps},
+    Id, Level,
+};
+use semver::Version;
+use std::collections::BTreeSet;
+
+/// Something that happened to an issue, see `ProofDB::get_issue_timeline`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IssueEvent {
+    /// The issue was reported in a review of `version`
+    IssueReported {
+        version: Version,
+        reporter: Id,
+        date: DateTime<Utc>,
+        severity: Level,
+    },
+    /// An advisory in a review of `fixed_in` says the issue is fixed in it
+    AdvisoryPublished {
+        /// All the issues the advisory is for, including this one
+        ids: Vec<String>,
+        affected_range: VersionRange,
+        fixed_in: Version,
+        reporter: Id,
+        date: DateTime<Utc>,
+        severity: Level,
+    },
+}
+
+impl IssueEvent {
+    pub fn date(&self) -> DateTime<Utc> {
+        match self {
+            IssueEvent::IssueReported { date, .. } | IssueEvent::AdvisoryPublished { date, .. } => {
+                *date
+            }
+        }
+    }
+
+    pub fn reporter(&self) -> &Id {
+        match self {
+            IssueEvent::IssueReported { reporter, .. }
+            | IssueEvent::AdvisoryPublished { reporter, .. } => reporter,
+        }
+    }
+
+    /// Order of events with the same date: reports before advisories,
+    /// then by version, then by reporter
+    fn sort_key(&self) -> (DateTime<Utc>, bool, &Version, &Id) {
+        match self {
+            IssueEvent::IssueReported {
+                version,
+                reporter,
+                date,
+                ..
+            } => (*date, false, version, reporter),
+            IssueEvent::AdvisoryPublished {
+                fixed_in,
+                reporter,
+                date,
+                ..
+            } => (*date, true, fixed_in, reporter),
+        }
+    }
+}
+
+/// All the events of an issue of a package, oldest first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IssueTimeline {
+    pub issue_id: String,
+    pub events: Vec<IssueEvent>,
+}
+
+impl IssueTimeline {
+    /// Reported for the first time, if ever
+    pub fn first_reported(&self) -> Option<&IssueEvent> {
+        self.events
+            .iter()
+            .find(|event| matches!(event, IssueEvent::IssueReported { .. }))
+    }
+
+    /// Lowest version any advisory says the issue is fixed in
+    pub fn fixed_in(&self) -> Option<&Version> {
+        self.events
+            .iter()
+            .filter_map(|event| match event {
+                IssueEvent::AdvisoryPublished { fixed_in, .. } => Some(fixed_in),
+                _ => None,
+            })
+            .min()
+    }
+}
+
+impl ProofDB {
+    /// Chronological history of the issue `issue_id` of a package
+    ///
+    /// Every issue report and every advisory mentioning the issue in the current
+    /// package reviews is an event. With a `trust_set`, only reviews by Ids trusted
+    /// in it (for the `source`) are taken into account.
+    pub fn get_issue_timeline(
+        &self,
+        source: &str,
+        name: &str,
+        issue_id: &str,
+        trust_set: Option<&TrustSet>,
+    ) -> IssueTimeline {
+        let trust_set = trust_set.map(|trust_set| trust_set.for_source(source));
+        let mut events = vec![];
+        for review in self.get_pkg_reviews_for_name(source, name) {
+            let reporter = &review.from().id;
+            if let Some(trust_set) = trust_set {
+                if !trust_set.is_trusted(reporter) {
+                    continue;
+                }
+            }
+            let version = &review.package.id.version;
+            for issue in review.issues.iter().filter(|issue| issue.id == issue_id) {
+                events.push(IssueEvent::IssueReported {
+                    version: version.clone(),
+                    reporter: reporter.clone(),
+                    date: review.date_utc(),
+                    severity: issue.severity,
+                });
+            }
+            for advisory in review
+                .advisories
+                .iter()
+                .filter(|advisory| advisory.ids.iter().any(|id| id == issue_id))
+            {
+                events.push(IssueEvent::AdvisoryPublished {
+                    ids: advisory.ids.clone(),
+                    affected_range: advisory.range,
+                    fixed_in: version.clone(),
+                    reporter: reporter.clone(),
+                    date: review.date_utc(),
+                    severity: advisory.severity,
+                });
+            }
+        }
+        events.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+
+        IssueTimeline {
+            issue_id: issue_id.to_owned(),
+            events,
+        }
+    }
+
+    /// Ids of all the issues reported, or fixed by advisories, in reviews of a package
+    pub fn list_issue_ids_for_package(&self, source: &str, name: &str) -> BTreeSet<String> {
+        self.get_pkg_reviews_for_name(source, name)
+            .flat_map(|review| {
+                review
+                    .issues
+                    .iter()
+                    .map(|issue| &issue.id)
+                    .chain(review.advisories.iter().flat_map(|advisory| &advisory.ids))
+            })
+            .cloned()
+            .collect()
+    }
+}
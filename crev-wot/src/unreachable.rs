@@ -0,0 +1,111 @@
+//! Explaining why Ids are not in the trust set
+//!
+//! The trust set calculation stops following the trust as soon as it can't
+//! reach further, so it can't tell why someone is missing. This traverses
+//! the WoT again, without any limits on the distance.
+use crate::{Distance, DistanceMetric, ProofDB, TrustDistanceParams, TrustEdgeView};
+use crev_data::{proof::trust::TrustLevel, Id};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Why an Id is not trusted, see `ProofDB::explain_unreachable`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnreachableIdReason {
+    /// Nobody reachable from the root reports any trust for the Id
+    NoTrustPath { id: Id },
+    /// The Id is reachable, but further from the root than `max_distance`
+    TooFar {
+        id: Id,
+        /// Shortest distance from the root
+        distance: Distance,
+        max_distance: Distance,
+    },
+    /// Trust for the Id is only reported as `None`, by these Ids
+    TrustTooLow { id: Id, reported_by: Vec<Id> },
+    /// The Id is distrusted by these Ids
+    Distrusted { id: Id, by: Vec<Id> },
+}
+
+impl UnreachableIdReason {
+    pub fn id(&self) -> &Id {
+        match self {
+            UnreachableIdReason::NoTrustPath { id }
+            | UnreachableIdReason::TooFar { id, .. }
+            | UnreachableIdReason::TrustTooLow { id, .. }
+            | UnreachableIdReason::Distrusted { id, .. } => id,
+        }
+    }
+}
+
+impl ProofDB {
+    /// Every known Id that is not trusted by `root`, with the reason, ordered by Id
+    pub fn explain_unreachable(
+        &self,
+        root: &Id,
+        params: &TrustDistanceParams,
+    ) -> Vec<UnreachableIdReason> {
+        let trust_set = self.calculate_trust_set(root, params);
+        let view = TrustEdgeView::default();
+
+        // shortest distance of every Id reachable from the root (through Ids
+        // that are not distrusted), for every effective trust level it can
+        // be reached with, since that decides how far it extends the trust
+        let mut best: BTreeMap<(&Id, TrustLevel), Distance> = BTreeMap::new();
+        let mut distances: BTreeMap<&Id, Distance> = BTreeMap::new();
+        let mut reported_none: BTreeMap<&Id, BTreeSet<&Id>> = BTreeMap::new();
+        let mut pending = BTreeSet::new();
+        pending.insert((Distance::ZERO, TrustLevel::High, root));
+        while let Some(current) = pending.iter().next().cloned() {
+            pending.remove(&current);
+            let (distance, level, id) = current;
+            if matches!(best.get(&(id, level)), Some(prev) if *prev <= distance) {
+                continue;
+            }
+            best.insert((id, level), distance);
+            let shortest = distances.entry(id).or_insert(distance);
+            *shortest = std::cmp::min(*shortest, distance);
+
+            for (direct, candidate) in self.get_trust_list_of_id_in_view(id, &view) {
+                if trust_set.is_distrusted(candidate) || direct == TrustLevel::Distrust {
+                    continue;
+                }
+                let effective = std::cmp::min(direct, level);
+                match params.distance_by_level(effective) {
+                    Some(step) => {
+                        pending.insert((distance + step, effective, candidate));
+                    }
+                    None => {
+                        reported_none.entry(candidate).or_default().insert(id);
+                    }
+                }
+            }
+        }
+
+        let mut known = self.all_known_ids();
+        for (from, edges) in &self.trust_id_to_id {
+            known.insert(from.clone());
+            known.extend(edges.keys().cloned());
+        }
+
+        known
+            .into_iter()
+            .filter(|id| !trust_set.is_trusted(id))
+            .map(|id| {
+                if let Some(details) = trust_set.distrusted.get(&id) {
+                    let by = details.reported_by.iter().cloned().collect();
+                    UnreachableIdReason::Distrusted { id, by }
+                } else if let Some(distance) = distances.get(&id) {
+                    UnreachableIdReason::TooFar {
+                        distance: *distance,
+                        max_distance: params.max_distance(),
+                        id,
+                    }
+                } else if let Some(reported_by) = reported_none.get(&id) {
+                    let reported_by = reported_by.iter().map(|id| (*id).clone()).collect();
+                    UnreachableIdReason::TrustTooLow { id, reported_by }
+                } else {
+                    UnreachableIdReason::NoTrustPath { id }
+                }
+            })
+            .collect()
+    }
+}
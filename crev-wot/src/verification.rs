@@ -0,0 +1,567 @@
+//! Checking whether a package version is sufficiently reviewed
+//!
+//! A package version is considered verified if enough Ids inside
+//! the given `TrustSet` positively reviewed it thoroughly enough,
+//! nobody trusted reviewed it negatively, and there are no known
+//! open issues that are too severe.
+use crate::{
+    canonicalize_package_name, PkgVersionReviewId, ProofDB, ReviewFilter, ReviewIgnoreList,
+    TrustSet, VersionMatchPolicy, VersionStatus,
+};
+use crev_data::{
+    proof::{self, review, review::Rating, trust::TrustLevel, CommonOps},
+    Digest, Id, Level,
+};
+use semver::Version;
+use std::{
+    cmp,
+    collections::{hash_map, BTreeSet, HashMap},
+    ops::Bound,
+};
+
+/// Verification requirements
+#[derive(Clone, Debug)]
+pub struct VerificationRequirements {
+    /// Number of positive reviews from distinct authors required
+    pub redundancy: usize,
+    /// Minimum effective trust level of the reviewer
+    pub trust_level: TrustLevel,
+    pub thoroughness: Level,
+    pub understanding: Level,
+    /// Open issues of severity higher than this fail the verification
+    pub max_issue_severity: Level,
+    /// Reviews that should not be taken into account at all
+    pub ignore_list: ReviewIgnoreList,
+    /// Reviews (including issue reports) the filter doesn't accept are disregarded too
+    pub review_filter: ReviewFilter,
+}
+
+impl Default for VerificationRequirements {
+    fn default() -> Self {
+        VerificationRequirements {
+            redundancy: 1,
+            trust_level: TrustLevel::Low,
+            thoroughness: Default::default(),
+            understanding: Default::default(),
+            max_issue_severity: Level::None,
+            ignore_list: ReviewIgnoreList::new(),
+            review_filter: ReviewFilter::default(),
+        }
+    }
+}
+
+/// Result of verification
+///
+/// If more than one failure applies, the first one listed here is returned.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum VerificationStatus {
+    /// Trusted reviewers reviewed the package version negatively
+    Distrusted {
+        by: BTreeSet<Id>,
+    },
+    /// Trusted reviewers reported issues that are not fixed in this version
+    OpenIssues {
+        ids: BTreeSet<String>,
+    },
+    /// Enough trusted reviews exist, but not for the digest that was checked
+    DigestMismatch,
+    /// Nobody trusted enough reviewed the package version at all
+    NoReviews,
+    /// Not enough reviews meeting the requirements
+    InsufficientReviews {
+        have: usize,
+        need: usize,
+    },
+    Verified,
+}
+
+impl VerificationStatus {
+    pub fn is_verified(&self) -> bool {
+        *self == VerificationStatus::Verified
+    }
+}
+
+/// `VerificationStatus` of a package version, and what's known about the version
+///
+/// See `ProofDB::verify_packages_annotated`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PackageVerification {
+    pub status: VerificationStatus,
+    /// According to the version oracle, see `ProofDB::set_version_oracle`
+    pub version_status: VersionStatus,
+}
+
+impl PackageVerification {
+    /// Verified, and neither yanked nor missing from the registry
+    pub fn is_verified(&self) -> bool {
+        self.status.is_verified() && !self.version_status.is_unavailable()
+    }
+}
+
+/// Summary of the trusted reviews of a package version, see `ProofDB::get_pkg_review_verdict`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Verdict {
+    /// Number of positive (or better) reviews
+    pub positive: usize,
+    pub neutral: usize,
+    pub negative: usize,
+    /// The negative review overriding all the positive ones, if any
+    pub veto: Option<PkgVersionReviewId>,
+}
+
+impl Verdict {
+    pub fn is_vetoed(&self) -> bool {
+        self.veto.is_some()
+    }
+
+    /// Reviewed positively, and not vetoed
+    pub fn is_endorsed(&self) -> bool {
+        !self.is_vetoed() && 0 < self.positive
+    }
+}
+
+/// Trusted reviews of a digest, see `ProofDB::verify_digests`
+#[derive(Clone, Debug, Default)]
+pub struct DigestVerification<'a> {
+    /// Reviews by trusted enough authors, with their effective trust levels
+    ///
+    /// Ordered like in `ProofDB::best_review_for_digest`: the most trusted
+    /// authors first, then the newest reviews, then by author Id.
+    pub reviews: Vec<(TrustLevel, &'a review::Package)>,
+    /// Highest effective trust level of the authors of `reviews`
+    pub max_trust_level: Option<TrustLevel>,
+    /// Those of `reviews` rating the package negatively
+    pub negative: Vec<(TrustLevel, &'a review::Package)>,
+}
+
+impl<'a> DigestVerification<'a> {
+    pub fn trusted_count(&self) -> usize {
+        self.reviews.len()
+    }
+
+    pub fn is_reviewed(&self) -> bool {
+        !self.reviews.is_empty()
+    }
+
+    /// The review `ProofDB::best_review_for_digest` would return
+    pub fn best_review(&self) -> Option<&'a review::Package> {
+        self.reviews.first().map(|(_, review)| *review)
+    }
+}
+
+/// Trusted reviews of a package version, see `ProofDB::get_review_coverage_range`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VersionCoverage {
+    pub version: Version,
+    pub position: CoveragePosition,
+    /// Anyone (trusted or not) reviewed the version
+    ///
+    /// Only ends of the range can be included without being known.
+    pub known: bool,
+    /// Number of authors trusted enough that reviewed the version
+    pub trusted_reviewers: usize,
+    /// Base versions of the trusted differential reviews of the version
+    ///
+    /// A differential review only reviews the changes since its base version.
+    pub diff_bases: BTreeSet<Version>,
+}
+
+impl VersionCoverage {
+    pub fn is_reviewed(&self) -> bool {
+        0 < self.trusted_reviewers
+    }
+}
+
+/// Where a `VersionCoverage` is with respect to the range it was requested for
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CoveragePosition {
+    /// The newest trusted reviewed version older than the range
+    Before,
+    InRange,
+    /// The oldest trusted reviewed version newer than the range
+    After,
+}
+
+impl ProofDB {
+    /// Check if package version meets the verification `reqs`
+    ///
+    /// If `digest` is given, only reviews of that exact digest
+    /// count towards the required number of reviews.
+    pub fn verify_package(
+        &self,
+        source: &str,
+        name: &str,
+        version: &Version,
+        digest: Option<&Digest>,
+        trust_set: &TrustSet,
+        reqs: &VerificationRequirements,
+    ) -> VerificationStatus {
+        self.verify_reviews(
+            self.get_pkg_reviews_for_version_filtered(source, name, version, &reqs.review_filter),
+            &[proof::PackageVersionId::new(
+                source.to_owned(),
+                name.to_owned(),
+                version.clone(),
+            )],
+            digest,
+            trust_set,
+            reqs,
+        )
+    }
+
+    /// Check if the package with a given `digest` meets the verification `reqs`
+    ///
+    /// Just like `verify_package`, for the package versions the reviews of
+    /// the `digest` name (see `lookup_package_version_by_digest`), but only
+    /// reviews of that exact digest are taken into account. These count even if
+    /// their authors reviewed another digest of the version since.
+    pub fn verify_package_digest(
+        &self,
+        digest: &Digest,
+        trust_set: &TrustSet,
+        reqs: &VerificationRequirements,
+    ) -> VerificationStatus {
+        self.verify_reviews(
+            self.get_package_reviews_by_digest(digest)
+                .filter(reqs.review_filter.predicate(self.now())),
+            &self.lookup_package_version_by_digest(digest),
+            Some(digest),
+            trust_set,
+            reqs,
+        )
+    }
+
+    /// `VerificationStatus` of `versions`, given their `reviews` the `reqs` accept
+    fn verify_reviews<'a>(
+        &self,
+        reviews: impl Iterator<Item = &'a review::Package>,
+        versions: &[proof::PackageVersionId],
+        digest: Option<&Digest>,
+        trust_set: &TrustSet,
+        reqs: &VerificationRequirements,
+    ) -> VerificationStatus {
+        let mut negative_by = BTreeSet::new();
+        let mut matching_digest = BTreeSet::new();
+        let mut other_digest = BTreeSet::new();
+        let mut reviewed_by_trusted = false;
+
+        for review in reviews {
+            let trust_set = trust_set.for_source(&review.package.id.id.source);
+            let from = &review.from().id;
+            if trust_set.is_distrusted(from)
+                || trust_set.get_effective_trust_level(from) < reqs.trust_level
+                || reqs.ignore_list.is_ignored(review)
+            {
+                continue;
+            }
+            reviewed_by_trusted = true;
+
+            let details = review.review_possibly_none();
+            if details.rating <= Rating::Negative {
+                negative_by.insert(from.clone());
+            } else if !details.is_none()
+                && Rating::Neutral <= details.rating
+                && reqs.thoroughness <= details.thoroughness
+                && reqs.understanding <= details.understanding
+            {
+                match digest {
+                    Some(digest) if digest.as_slice() != review.package.digest.as_slice() => {
+                        other_digest.insert(from.clone());
+                    }
+                    _ => {
+                        matching_digest.insert(from.clone());
+                    }
+                }
+            }
+        }
+
+        if !negative_by.is_empty() {
+            return VerificationStatus::Distrusted { by: negative_by };
+        }
+
+        let open_issues: BTreeSet<String> = versions
+            .iter()
+            .flat_map(|pkg| {
+                self.get_open_issues_for_version_with(
+                    &pkg.id.source,
+                    &pkg.id.name,
+                    &pkg.version,
+                    trust_set,
+                    reqs.trust_level,
+                    &reqs.ignore_list,
+                    &reqs.review_filter,
+                    VersionMatchPolicy::default(),
+                )
+            })
+            .filter(|(_, details)| reqs.max_issue_severity < details.severity)
+            .map(|(id, _)| id)
+            .collect();
+        if !open_issues.is_empty() {
+            return VerificationStatus::OpenIssues { ids: open_issues };
+        }
+
+        let have = matching_digest.len();
+        if have >= reqs.redundancy {
+            VerificationStatus::Verified
+        } else if have + other_digest.len() >= reqs.redundancy {
+            VerificationStatus::DigestMismatch
+        } else if !reviewed_by_trusted {
+            VerificationStatus::NoReviews
+        } else {
+            VerificationStatus::InsufficientReviews {
+                have,
+                need: reqs.redundancy,
+            }
+        }
+    }
+
+    /// `verify_package` for many package versions at once, regardless of digests
+    pub fn verify_packages(
+        &self,
+        pkgs: &[proof::PackageVersionId],
+        trust_set: &TrustSet,
+        reqs: &VerificationRequirements,
+    ) -> Vec<VerificationStatus> {
+        pkgs.iter()
+            .map(|pkg| {
+                self.verify_package(
+                    &pkg.id.source,
+                    &pkg.id.name,
+                    &pkg.version,
+                    None,
+                    trust_set,
+                    reqs,
+                )
+            })
+            .collect()
+    }
+
+    /// `verify_packages`, with the status of every version according to the version oracle
+    ///
+    /// Reviews of yanked versions, or ones that never existed, don't
+    /// make them any more usable, so they are never `is_verified`.
+    pub fn verify_packages_annotated(
+        &self,
+        pkgs: &[proof::PackageVersionId],
+        trust_set: &TrustSet,
+        reqs: &VerificationRequirements,
+    ) -> Vec<PackageVerification> {
+        pkgs.iter()
+            .zip(self.verify_packages(pkgs, trust_set, reqs))
+            .map(|(pkg, status)| PackageVerification {
+                status,
+                version_status: self.get_version_status(&pkg.id.source, &pkg.id.name, &pkg.version),
+            })
+            .collect()
+    }
+
+    /// Counts of positive, neutral and negative reviews of `version` in the `trust_set`
+    ///
+    /// Only reviews by authors with effective trust of at least `min_level`
+    /// count. The negative review by the most trusted author vetoes the package,
+    /// if that author is trusted at least at `veto_level`, and at least as much
+    /// as any author of a positive review: when in doubt, the package is not endorsed.
+    pub fn get_pkg_review_verdict(
+        &self,
+        source: &str,
+        name: &str,
+        version: &Version,
+        trust_set: &TrustSet,
+        min_level: TrustLevel,
+        veto_level: TrustLevel,
+    ) -> Verdict {
+        let trust_set = trust_set.for_source(source);
+        let mut verdict = Verdict::default();
+        let mut max_positive_level = None;
+        let mut strongest_negative = None;
+        for review in self.get_pkg_reviews_for_version(source, name, version) {
+            let id = &review.from().id;
+            let level = trust_set.get_effective_trust_level(id);
+            if trust_set.is_distrusted(id) || level < min_level {
+                continue;
+            }
+            let rating = review.review_possibly_none().rating;
+            if Rating::Positive <= rating {
+                verdict.positive += 1;
+                max_positive_level = cmp::max(max_positive_level, Some(level));
+            } else if rating == Rating::Neutral {
+                verdict.neutral += 1;
+            } else {
+                verdict.negative += 1;
+                let key = (level, review.date_utc(), cmp::Reverse(id));
+                if matches!(&strongest_negative, Some((strongest, _)) if *strongest >= key) {
+                    continue;
+                }
+                strongest_negative = Some((key, review));
+            }
+        }
+        verdict.veto = strongest_negative
+            .filter(|((level, _, _), _)| veto_level <= *level && Some(*level) >= max_positive_level)
+            .map(|(_, review)| review.into());
+        verdict
+    }
+
+    /// Trusted reviews of every one of `digests`, e.g. of all the dependencies
+    ///
+    /// Just like in `best_review_for_digest`, only reviews by authors with
+    /// effective trust of at least `min_level` count, and distrusted ones never do.
+    /// Digests nobody trusted reviewed are included too, without any reviews.
+    /// Faster than `best_review_for_digest` and `trusted_review_count_for_digest`
+    /// for every digest, since the reviews of each one are only looked up once.
+    /// Trust limited to sources (see `TrustSet::for_source`) is taken into account.
+    pub fn verify_digests<'a>(
+        &'a self,
+        digests: impl IntoIterator<Item = &'a Digest>,
+        trust_set: &TrustSet,
+        min_level: TrustLevel,
+    ) -> HashMap<Digest, DigestVerification<'a>> {
+        let trust_level_of = |review: &review::Package| {
+            let trust_set = trust_set.for_source(&review.package.id.id.source);
+            let id = &review.from().id;
+            if trust_set.is_distrusted(id) {
+                return None;
+            }
+            Some(trust_set.get_effective_trust_level(id)).filter(|l| min_level <= *l)
+        };
+
+        let mut verifications = HashMap::new();
+        for digest in digests {
+            let entry = match verifications.entry(digest.clone()) {
+                hash_map::Entry::Occupied(_) => continue,
+                hash_map::Entry::Vacant(entry) => entry,
+            };
+            let signatures = match self
+                .package_review_signatures_by_package_digest
+                .get(digest.as_slice())
+            {
+                Some(signatures) => signatures,
+                None => {
+                    entry.insert(DigestVerification::default());
+                    continue;
+                }
+            };
+            let mut reviews = Vec::with_capacity(signatures.len());
+            for signature in signatures.values() {
+                let review = &*self.package_review_by_signature[&signature.value];
+                if !self.is_in_effect(review) {
+                    continue;
+                }
+                if let Some(level) = trust_level_of(review) {
+                    reviews.push((level, review));
+                }
+            }
+            reviews.sort_by_cached_key(|&(level, review)| {
+                (
+                    cmp::Reverse(level),
+                    cmp::Reverse(review.date_utc()),
+                    &review.from().id,
+                )
+            });
+            entry.insert(DigestVerification {
+                max_trust_level: reviews.first().map(|(level, _)| *level),
+                negative: reviews
+                    .iter()
+                    .filter(|(_, review)| review.review_possibly_none().rating <= Rating::Negative)
+                    .cloned()
+                    .collect(),
+                reviews,
+            });
+        }
+        verifications
+    }
+    /// Trusted reviews of every version of a package from `from` to `to` (inclusive)
+    ///
+    /// Useful to find out which versions still need to be reviewed before
+    /// upgrading. Only reviews by authors with effective trust of at least
+    /// `min_level` count. Versions nobody reviewed are not known, so they
+    /// are not included, except for `from` and `to` (with `known` unset).
+    /// If there are any, the closest trusted reviewed versions around the range
+    /// are included too. Ordered by version; empty if `to` is older than `from`.
+    pub fn get_review_coverage_range(
+        &self,
+        source: &str,
+        name: &str,
+        from: &Version,
+        to: &Version,
+        trust_set: &TrustSet,
+        min_level: TrustLevel,
+    ) -> Vec<VersionCoverage> {
+        if to < from {
+            return vec![];
+        }
+        let trust_set = trust_set.for_source(source);
+        let by_version = self
+            .package_reviews
+            .get(source)
+            .and_then(|map| map.get(&canonicalize_package_name(source, name)));
+        let coverage =
+            |version: &Version, pkg_review_ids: &BTreeSet<PkgVersionReviewId>, position| {
+                let mut coverage = VersionCoverage {
+                    version: version.clone(),
+                    position,
+                    known: false,
+                    trusted_reviewers: 0,
+                    diff_bases: BTreeSet::new(),
+                };
+                for pkg_review_id in pkg_review_ids {
+                    let review = match self.get_pkg_review_by_pkg_review_id(pkg_review_id) {
+                        Some(review) if self.is_in_effect(review) => review,
+                        _ => continue,
+                    };
+                    coverage.known = true;
+                    let id = &review.from().id;
+                    if trust_set.is_distrusted(id)
+                        || trust_set.get_effective_trust_level(id) < min_level
+                    {
+                        continue;
+                    }
+                    coverage.trusted_reviewers += 1;
+                    if let Some(diff_base) = &review.diff_base {
+                        coverage.diff_bases.insert(diff_base.id.version.clone());
+                    }
+                }
+                coverage
+            };
+        let unknown = |version: &Version| VersionCoverage {
+            version: version.clone(),
+            position: CoveragePosition::InRange,
+            known: false,
+            trusted_reviewers: 0,
+            diff_bases: BTreeSet::new(),
+        };
+
+        let by_version = match by_version {
+            Some(by_version) => by_version,
+            None if from == to => return vec![unknown(from)],
+            None => return vec![unknown(from), unknown(to)],
+        };
+        let mut coverages = vec![];
+        if let Some(before) = by_version
+            .range(..from)
+            .rev()
+            .map(|(version, ids)| coverage(version, ids, CoveragePosition::Before))
+            .find(VersionCoverage::is_reviewed)
+        {
+            coverages.push(before);
+        }
+        if !by_version.contains_key(from) {
+            coverages.push(unknown(from));
+        }
+        coverages.extend(
+            by_version
+                .range(from..=to)
+                .map(|(version, ids)| coverage(version, ids, CoveragePosition::InRange)),
+        );
+        if from != to && !by_version.contains_key(to) {
+            coverages.push(unknown(to));
+        }
+        if let Some(after) = by_version
+            .range((Bound::Excluded(to), Bound::Unbounded))
+            .map(|(version, ids)| coverage(version, ids, CoveragePosition::After))
+            .find(VersionCoverage::is_reviewed)
+        {
+            coverages.push(after);
+        }
+        coverages
+    }
+}
@@ -0,0 +1,105 @@
+//! Memory used by the indices of `ProofDB`, for a large synthetic set of reviews
+//!
+//! This is a separate test binary, so that the allocations can be counted
+//! without other tests running in parallel. Besides what the `ProofDB` keeps,
+//! the peak of the allocations during the import is checked, as that's what
+//! decides whether a large WoT fits in memory at all.
+//!
+//! Ignored by default, as it takes a while, run with `cargo test --release -p crev-wot --test memory -- --ignored`
+use crev_data::{proof, proof::ContentExt, UnlockedId};
+use crev_wot::{FetchSource, ProofDB};
+use semver::Version;
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+struct CountingAlloc;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let allocated = ALLOCATED.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+        let mut peak = PEAK.load(Ordering::SeqCst);
+        while allocated > peak {
+            match PEAK.compare_exchange_weak(peak, allocated, Ordering::SeqCst, Ordering::SeqCst) {
+                Ok(_) => break,
+                Err(current) => peak = current,
+            }
+        }
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::SeqCst);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+const AUTHORS: usize = 500;
+const PACKAGES: usize = 200;
+const VERSIONS: usize = 4;
+
+fn reviews() -> Vec<(proof::Proof, FetchSource)> {
+    let ids: Vec<_> = (0..AUTHORS)
+        .map(|i| UnlockedId::generate_for_git_url(&format!("https://example.com/{}", i)))
+        .collect();
+    let mut proofs = vec![];
+    for (i, id) in ids.iter().enumerate() {
+        for package in 0..PACKAGES {
+            let version = Version::new(1, ((i + package) % VERSIONS) as u64, 0);
+            let package_info = proof::PackageInfo {
+                id: proof::PackageVersionId::new(
+                    "https://crates.io".into(),
+                    format!("package-{}", package),
+                    version,
+                ),
+                digest: vec![(package % 256) as u8; 32],
+                digest_type: proof::default_digest_type(),
+                revision: "".into(),
+                revision_type: proof::default_revision_type(),
+            };
+            let review = proof::review::PackageBuilder::default()
+                .from(id.id.to_owned())
+                .package(package_info)
+                .build()
+                .unwrap();
+            proofs.push((review.sign_by(id).unwrap(), FetchSource::LocalUser));
+        }
+    }
+    proofs
+}
+
+#[test]
+#[ignore]
+fn memory_per_review() {
+    let proofs = reviews();
+    let review_count = proofs.len();
+
+    let before = ALLOCATED.load(Ordering::SeqCst);
+    PEAK.store(before, Ordering::SeqCst);
+    let mut db = ProofDB::new();
+    db.import_from_iter(proofs.into_iter());
+    db.shrink_to_fit();
+    assert_eq!(db.unique_package_review_proof_count(), review_count);
+    let peak_per_review = (PEAK.load(Ordering::SeqCst) - before) / review_count;
+
+    let with_db = ALLOCATED.load(Ordering::SeqCst);
+    drop(db);
+    let per_review = (with_db - ALLOCATED.load(Ordering::SeqCst)) / review_count;
+    // it used to be over 5000, before the indices shared the copies
+    // of Ids, package versions and signatures; it's about 2350 now
+    assert!(per_review < 2600, "{} bytes per review", per_review);
+    // on top of the proofs being imported, which are dropped as they are added;
+    // about 1800 now
+    assert!(
+        peak_per_review < 2000,
+        "{} bytes per review at peak",
+        peak_per_review
+    );
+}